@@ -0,0 +1,32 @@
+-- Church-owned songbooks and song catalog editors
+
+-- Owning church of a local songbook (NULL for the shared catalog)
+ALTER TABLE songbooks ADD COLUMN IF NOT EXISTS church_id UUID REFERENCES churches(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_songbooks_church ON songbooks(church_id) WHERE church_id IS NOT NULL;
+
+-- Users allowed to edit the shared song catalog
+CREATE TABLE IF NOT EXISTS song_editors (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Whether a songbook is visible to a viewer (NULL for anonymous requests):
+-- public songbooks to everyone, private church songbooks to church members,
+-- private catalog songbooks to song editors
+CREATE OR REPLACE FUNCTION songbook_visible(is_public BOOLEAN, church_id UUID, viewer UUID)
+RETURNS BOOLEAN AS $$
+    SELECT is_public
+        OR (church_id IS NOT NULL AND EXISTS (
+            SELECT 1 FROM public.memberships m
+            WHERE m.church_id = songbook_visible.church_id
+              AND m.user_id = viewer
+              AND m.role <> 'guest'
+        ))
+        OR (church_id IS NULL AND EXISTS (
+            SELECT 1 FROM public.song_editors e WHERE e.user_id = viewer
+        ))
+$$ LANGUAGE sql STABLE;
+
+-- Edition names are unique within a songbook
+CREATE UNIQUE INDEX IF NOT EXISTS idx_songbook_editions_name ON songbook_editions(songbook_id, edition_name);
@@ -0,0 +1,14 @@
+-- Edition-specific song numbering
+--
+-- songs.number is the numbering of the current edition. Older editions that
+-- number songs differently list their numbers here; editions without rows
+-- share the current numbering.
+CREATE TABLE IF NOT EXISTS song_edition_numbers (
+    edition_id UUID NOT NULL REFERENCES songbook_editions(id) ON DELETE CASCADE,
+    song_id UUID NOT NULL REFERENCES songs(id) ON DELETE CASCADE,
+    number INTEGER NOT NULL CHECK (number > 0),
+    PRIMARY KEY (edition_id, number),
+    UNIQUE (edition_id, song_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_song_edition_numbers_song ON song_edition_numbers(song_id);
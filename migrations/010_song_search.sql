@@ -0,0 +1,48 @@
+-- Typo-tolerant and transliterated song search
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Search key: lowercase Latin transliteration with ё/е folding, so that
+-- Cyrillic text and Latin-keyboard input ("blagodat") compare equal.
+-- Common Latin spelling variants (kh/h, ts/c, j/y, ...) are folded too.
+CREATE OR REPLACE FUNCTION song_search_key(value TEXT)
+RETURNS TEXT AS $$
+DECLARE
+    key TEXT := lower(coalesce(value, ''));
+    pair TEXT[];
+BEGIN
+    key := translate(key, 'абвгдеёзийклмнопрстуфхцыэъь', 'abvgdeeziyklmnoprstufhcye');
+
+    FOREACH pair SLICE 1 IN ARRAY ARRAY[
+        ['ж', 'zh'], ['ч', 'ch'], ['ш', 'sh'], ['щ', 'sh'], ['ю', 'yu'], ['я', 'ya'],
+        ['shch', 'sh'], ['sch', 'sh'], ['kh', 'h'], ['ts', 'c'], ['j', 'y'], ['w', 'v'], ['x', 'ks']
+    ] LOOP
+        key := replace(key, pair[1], pair[2]);
+    END LOOP;
+
+    RETURN trim(regexp_replace(key, '[^a-z0-9]+', ' ', 'g'));
+END;
+$$ LANGUAGE plpgsql IMMUTABLE PARALLEL SAFE;
+
+ALTER TABLE songs ADD COLUMN IF NOT EXISTS search_key TEXT GENERATED ALWAYS AS (
+    song_search_key(title) || ' ' || song_search_key(title_alt) || ' ' || song_search_key(first_line)
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_songs_search_key ON songs USING GIN(search_key gin_trgm_ops);
+
+-- Keep has_chords in sync with content for the has_chords filter
+CREATE OR REPLACE FUNCTION update_song_has_chords()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.has_chords := NEW.content ~ '\[[A-G][#b]?[^/\]]*\]';
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS update_songs_has_chords ON songs;
+CREATE TRIGGER update_songs_has_chords
+    BEFORE INSERT OR UPDATE OF content ON songs
+    FOR EACH ROW
+    EXECUTE FUNCTION update_song_has_chords();
+
+UPDATE songs SET has_chords = content ~ '\[[A-G][#b]?[^/\]]*\]';
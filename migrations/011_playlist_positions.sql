@@ -0,0 +1,14 @@
+-- Playlist item positions
+--
+-- Reordering rewrites the positions of a whole playlist in one statement,
+-- which temporarily duplicates positions. The unique constraint stays, but is
+-- checked at commit time when a transaction defers it.
+ALTER TABLE song_playlist_items
+    DROP CONSTRAINT IF EXISTS song_playlist_items_playlist_id_position_key;
+
+ALTER TABLE song_playlist_items
+    ADD CONSTRAINT song_playlist_items_playlist_id_position_key
+    UNIQUE (playlist_id, position) DEFERRABLE INITIALLY IMMEDIATE;
+
+ALTER TABLE song_playlist_items
+    ADD CONSTRAINT song_playlist_items_position_positive CHECK (position > 0);
@@ -0,0 +1,45 @@
+-- Church setlists: playlists with church_id shared by the worship team
+
+-- Members who plan church setlists (pastors and admins may always edit)
+CREATE TABLE IF NOT EXISTS church_worship_team (
+    church_id UUID NOT NULL REFERENCES churches(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (church_id, user_id)
+);
+
+-- Public read-only share links
+ALTER TABLE song_playlists ADD COLUMN IF NOT EXISTS share_token VARCHAR(64) UNIQUE;
+
+CREATE INDEX IF NOT EXISTS idx_playlists_church_event
+    ON song_playlists(church_id, event_date) WHERE church_id IS NOT NULL;
+
+-- Whether a user may edit the setlists of a church
+CREATE OR REPLACE FUNCTION church_setlist_editor(church_id UUID, viewer UUID)
+RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+            SELECT 1 FROM public.church_worship_team t
+            WHERE t.church_id = church_setlist_editor.church_id AND t.user_id = viewer
+        )
+        OR EXISTS (
+            SELECT 1 FROM public.memberships m
+            WHERE m.church_id = church_setlist_editor.church_id
+              AND m.user_id = viewer
+              AND m.role IN ('pastor', 'admin')
+        )
+$$ LANGUAGE sql STABLE;
+
+-- Whether a playlist is visible to a viewer (NULL for anonymous requests):
+-- public playlists to everyone, church setlists to church members, the rest
+-- to the owner only
+CREATE OR REPLACE FUNCTION playlist_visible(owner UUID, church_id UUID, is_public BOOLEAN, viewer UUID)
+RETURNS BOOLEAN AS $$
+    SELECT is_public
+        OR owner = viewer
+        OR (church_id IS NOT NULL AND EXISTS (
+            SELECT 1 FROM public.memberships m
+            WHERE m.church_id = playlist_visible.church_id
+              AND m.user_id = viewer
+              AND m.role <> 'guest'
+        ))
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,31 @@
+-- Live worship presentation sessions
+--
+-- One row per playlist being presented. The leader updates the row; every
+-- server replica LISTENs on `worship_live` and pushes the new slide to its
+-- connected followers.
+CREATE TABLE IF NOT EXISTS live_sessions (
+    playlist_id UUID PRIMARY KEY REFERENCES song_playlists(id) ON DELETE CASCADE,
+    leader_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    song_id UUID REFERENCES songs(id) ON DELETE SET NULL,
+    section INTEGER NOT NULL DEFAULT 0 CHECK (section >= 0),
+    transpose SMALLINT NOT NULL DEFAULT 0 CHECK (transpose BETWEEN -12 AND 12),
+    blank BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION notify_live_session()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('worship_live', json_build_object(
+        'playlist_id', COALESCE(NEW.playlist_id, OLD.playlist_id),
+        'ended', TG_OP = 'DELETE'
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS notify_live_sessions ON live_sessions;
+CREATE TRIGGER notify_live_sessions
+    AFTER INSERT OR UPDATE OR DELETE ON live_sessions
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_live_session();
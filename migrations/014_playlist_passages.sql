@@ -0,0 +1,17 @@
+-- Bible passages read during a service, placed between setlist songs
+
+CREATE TABLE IF NOT EXISTS song_playlist_passages (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    playlist_id UUID NOT NULL REFERENCES song_playlists(id) ON DELETE CASCADE,
+    -- Item the passage follows; NULL opens the service
+    after_item_id UUID REFERENCES song_playlist_items(id) ON DELETE SET NULL,
+    book_id SMALLINT NOT NULL REFERENCES bible_books(id),
+    chapter SMALLINT NOT NULL CHECK (chapter > 0),
+    verse_start SMALLINT NOT NULL CHECK (verse_start > 0),
+    verse_end SMALLINT NOT NULL CHECK (verse_end >= verse_start),
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_playlist_passages_playlist
+    ON song_playlist_passages(playlist_id, created_at);
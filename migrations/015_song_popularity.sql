@@ -0,0 +1,76 @@
+-- Time-windowed song popularity
+--
+-- `songs.views_count` stays a lifetime counter but now only grows with
+-- deduplicated views: one per viewer (user ID or hashed client address) per
+-- song per day. Rolling aggregates over the events and over held church
+-- services live in the `song_popularity` materialized view, which the server
+-- refreshes periodically.
+CREATE TABLE IF NOT EXISTS song_view_events (
+    song_id UUID NOT NULL REFERENCES songs(id) ON DELETE CASCADE,
+    viewer VARCHAR(64) NOT NULL,
+    viewed_on DATE NOT NULL DEFAULT CURRENT_DATE,
+    viewed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (song_id, viewer, viewed_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_song_view_events_day ON song_view_events(viewed_on);
+
+-- Seed the windows with the views signed-in users already made
+INSERT INTO song_view_events (song_id, viewer, viewed_on, viewed_at)
+SELECT song_id, user_id::text, viewed_at::date, MIN(viewed_at)
+FROM user_song_history
+WHERE viewed_at >= CURRENT_DATE - 30
+GROUP BY song_id, user_id, viewed_at::date
+ON CONFLICT DO NOTHING;
+
+-- Views and services over the last 7 and 30 days (today included) and the
+-- current calendar month. A service counts once per setlist, and weighs as
+-- much as five views in the trending score: activity of the last week minus
+-- the weekly average of the three weeks before it.
+CREATE MATERIALIZED VIEW IF NOT EXISTS song_popularity AS
+WITH views AS (
+    SELECT
+        song_id,
+        COUNT(*) FILTER (WHERE viewed_on > CURRENT_DATE - 7) AS views_7d,
+        COUNT(*) AS views_30d
+    FROM song_view_events
+    WHERE viewed_on > CURRENT_DATE - 30
+    GROUP BY song_id
+),
+sung AS (
+    SELECT
+        i.song_id,
+        COUNT(DISTINCT p.id) FILTER (WHERE p.event_date > CURRENT_DATE - 7) AS sung_7d,
+        COUNT(DISTINCT p.id) FILTER (WHERE p.event_date > CURRENT_DATE - 30) AS sung_30d,
+        COUNT(DISTINCT p.id) FILTER (
+            WHERE p.event_date >= date_trunc('month', CURRENT_DATE)::date
+        ) AS sung_month
+    FROM song_playlist_items i
+    JOIN song_playlists p ON p.id = i.playlist_id
+    WHERE p.church_id IS NOT NULL
+      AND p.event_date <= CURRENT_DATE
+      AND p.event_date >= LEAST(CURRENT_DATE - 29, date_trunc('month', CURRENT_DATE)::date)
+    GROUP BY i.song_id
+)
+SELECT
+    s.id AS song_id,
+    COALESCE(v.views_7d, 0)::int AS views_7d,
+    COALESCE(v.views_30d, 0)::int AS views_30d,
+    COALESCE(g.sung_7d, 0)::int AS sung_7d,
+    COALESCE(g.sung_30d, 0)::int AS sung_30d,
+    COALESCE(g.sung_month, 0)::int AS sung_month,
+    (
+        COALESCE(v.views_7d, 0) + 5 * COALESCE(g.sung_7d, 0)
+        - (
+            COALESCE(v.views_30d - v.views_7d, 0)
+            + 5 * COALESCE(g.sung_30d - g.sung_7d, 0)
+        ) * 7.0 / 23.0
+    )::float8 AS trending_score,
+    NOW() AS refreshed_at
+FROM songs s
+LEFT JOIN views v ON v.song_id = s.id
+LEFT JOIN sung g ON g.song_id = s.id
+WHERE v.song_id IS NOT NULL OR g.song_id IS NOT NULL;
+
+-- Required by REFRESH MATERIALIZED VIEW CONCURRENTLY
+CREATE UNIQUE INDEX IF NOT EXISTS idx_song_popularity_song ON song_popularity(song_id);
@@ -0,0 +1,18 @@
+-- Scripture passages a song is based on
+
+CREATE TABLE IF NOT EXISTS song_scripture_refs (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    song_id UUID NOT NULL REFERENCES songs(id) ON DELETE CASCADE,
+    book_id SMALLINT NOT NULL REFERENCES bible_books(id),
+    chapter SMALLINT NOT NULL CHECK (chapter > 0),
+    verse_start SMALLINT NOT NULL CHECK (verse_start > 0),
+    verse_end SMALLINT NOT NULL CHECK (verse_end >= verse_start),
+    note TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (song_id, book_id, chapter, verse_start, verse_end)
+);
+
+-- Songs for a chapter, looked up while preparing a sermon
+CREATE INDEX IF NOT EXISTS idx_song_scripture_chapter
+    ON song_scripture_refs(book_id, chapter, verse_start);
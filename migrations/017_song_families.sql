@@ -0,0 +1,22 @@
+-- Families of related songs: an original with its translations and the
+-- versions of the same hymn printed in different songbooks
+
+CREATE TABLE IF NOT EXISTS song_families (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS song_family_members (
+    song_id UUID PRIMARY KEY REFERENCES songs(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL REFERENCES song_families(id) ON DELETE CASCADE,
+    language VARCHAR(10) NOT NULL DEFAULT 'ru',
+    is_original BOOLEAN NOT NULL DEFAULT false,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_song_family_members_family
+    ON song_family_members(family_id);
+
+-- A family has at most one original
+CREATE UNIQUE INDEX IF NOT EXISTS idx_song_family_members_original
+    ON song_family_members(family_id) WHERE is_original;
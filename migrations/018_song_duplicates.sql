@@ -0,0 +1,9 @@
+-- Lyrics normalized for duplicate detection: transliterated, lowercased and
+-- stripped of punctuation, so songs differing only by punctuation, case or
+-- chords (already absent from content_plain) compare equal
+
+ALTER TABLE songs ADD COLUMN IF NOT EXISTS content_key TEXT
+    GENERATED ALWAYS AS (song_search_key(content_plain)) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_songs_content_key
+    ON songs USING gin (content_key gin_trgm_ops);
@@ -0,0 +1,72 @@
+-- Songs suggested by members, reviewed by song editors before they are
+-- published into a songbook
+
+DO $$ BEGIN
+    CREATE TYPE song_submission_status AS ENUM (
+        'draft', 'pending', 'approved', 'rejected', 'published'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE TABLE IF NOT EXISTS song_submissions (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    submitter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status song_submission_status NOT NULL DEFAULT 'draft',
+    title VARCHAR(300) NOT NULL,
+    author_lyrics VARCHAR(200),
+    author_music VARCHAR(200),
+    translator VARCHAR(200),
+    original_key VARCHAR(10),
+    content TEXT NOT NULL,
+    source_url VARCHAR(500),
+    -- Message from the submitter to the reviewers
+    note TEXT,
+    review_comment TEXT,
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    song_id UUID REFERENCES songs(id) ON DELETE SET NULL,
+    submitted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_song_submissions_submitter
+    ON song_submissions(submitter_id, created_at DESC);
+
+-- Review queue, oldest submission first
+CREATE INDEX IF NOT EXISTS idx_song_submissions_status
+    ON song_submissions(status, submitted_at);
+
+-- Notifications kept for users and pushed to delivery workers
+CREATE TABLE IF NOT EXISTS user_notifications (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT,
+    link VARCHAR(500),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_notifications_user
+    ON user_notifications(user_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION notify_user_notification()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('user_notification', json_build_object(
+        'id', NEW.id,
+        'user_id', NEW.user_id,
+        'kind', NEW.kind
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS notify_user_notifications ON user_notifications;
+CREATE TRIGGER notify_user_notifications
+    AFTER INSERT ON user_notifications
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_user_notification();
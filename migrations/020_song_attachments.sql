@@ -0,0 +1,35 @@
+-- Recordings, sheet music, rehearsal tracks and links attached to songs
+
+DO $$ BEGIN
+    CREATE TYPE song_attachment_kind AS ENUM (
+        'recording', 'sheet_music', 'rehearsal_track', 'link'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+CREATE TABLE IF NOT EXISTS song_attachments (
+    id UUID PRIMARY KEY DEFAULT uuidv7(),
+    song_id UUID NOT NULL REFERENCES songs(id) ON DELETE CASCADE,
+    kind song_attachment_kind NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    -- Key of the file in the attachment storage; NULL for links
+    storage_key VARCHAR(300) UNIQUE,
+    file_name VARCHAR(255),
+    content_type VARCHAR(100),
+    size_bytes BIGINT,
+    -- Address of link attachments
+    url VARCHAR(500),
+    uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (
+        CASE WHEN kind = 'link'
+            THEN url IS NOT NULL AND storage_key IS NULL
+            ELSE storage_key IS NOT NULL AND content_type IS NOT NULL
+                AND size_bytes IS NOT NULL
+        END
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_song_attachments_song
+    ON song_attachments(song_id, created_at);
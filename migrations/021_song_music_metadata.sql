@@ -0,0 +1,7 @@
+-- Blank keys and time signatures become NULL
+--
+-- Keys (C, F#, Bb, Am, F#m) and time signatures (4/4, 6/8) are validated
+-- and brought to canonical form when songs are written. Older invalid values
+-- are left for editors to fix from the key mismatch report.
+UPDATE songs SET original_key = NULL WHERE TRIM(original_key) = '';
+UPDATE songs SET time_signature = NULL WHERE TRIM(time_signature) = '';
@@ -0,0 +1,10 @@
+-- Word index lookups for phrase, proximity and prefix search
+--
+-- Phrases and NEAR queries join the index to itself on verse and position;
+-- prefix queries (благодат*) need a pattern index, as the plain index on
+-- `word` cannot serve LIKE under a non-C collation.
+CREATE INDEX IF NOT EXISTS idx_word_index_verse_position
+    ON bible_word_index(verse_id, position);
+
+CREATE INDEX IF NOT EXISTS idx_word_index_word_pattern
+    ON bible_word_index(word text_pattern_ops);
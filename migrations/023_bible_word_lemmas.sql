@@ -0,0 +1,35 @@
+-- Lemmas of indexed words for the symphony (concordance)
+--
+-- Every indexed word carries a lemma so that the symphony finds all forms of
+-- a word: любовь, любви and любовью share one. The lemma is the Snowball
+-- stem of the word, or of its dictionary form from `bible_lemmas` when the
+-- stemmer cannot relate the forms (человек / люди, идти / шёл).
+
+CREATE TABLE IF NOT EXISTS bible_lemmas (
+    word VARCHAR(100) PRIMARY KEY,
+    lemma VARCHAR(100) NOT NULL
+);
+
+CREATE OR REPLACE FUNCTION bible_lemma(w TEXT) RETURNS TEXT
+LANGUAGE sql STABLE AS $$
+    WITH base AS (
+        SELECT COALESCE((SELECT lemma FROM public.bible_lemmas WHERE word = w), w) AS word
+    )
+    -- Stop words have no stem and stay as they are
+    SELECT COALESCE((ts_lexize('russian_stem', base.word))[1], base.word) FROM base
+$$;
+
+ALTER TABLE bible_word_index ADD COLUMN IF NOT EXISTS lemma VARCHAR(100);
+
+UPDATE public.bible_word_index SET lemma = public.bible_lemma(word) WHERE lemma IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_word_index_lemma ON bible_word_index(lemma);
+
+-- Verses whose words are in the index, with the text and index version they
+-- were indexed from, so that the index can be rebuilt incrementally
+CREATE TABLE IF NOT EXISTS bible_index_state (
+    verse_id INTEGER PRIMARY KEY REFERENCES bible_verses(id) ON DELETE CASCADE,
+    text_hash TEXT NOT NULL,
+    version SMALLINT NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,31 @@
+-- Strong's lexicon and original-language tags of translated words
+--
+-- Tags are keyed by verse reference and word position rather than verse id,
+-- so they survive reloading the translation; positions count words the way
+-- the word index does.
+
+CREATE TABLE IF NOT EXISTS bible_strongs (
+    number VARCHAR(7) PRIMARY KEY CHECK (number ~ '^[HG][1-9][0-9]{0,4}[a-z]?$'),
+    lemma TEXT NOT NULL,
+    transliteration TEXT,
+    pronunciation TEXT,
+    definition TEXT NOT NULL,
+    usage TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- No foreign key to bible_strongs: tagged texts may be imported before the
+-- lexicon and may use numbers it lacks
+CREATE TABLE IF NOT EXISTS bible_word_tags (
+    id SERIAL PRIMARY KEY,
+    book_id SMALLINT NOT NULL REFERENCES bible_books(id),
+    chapter SMALLINT NOT NULL,
+    verse SMALLINT NOT NULL,
+    position SMALLINT NOT NULL,
+    word VARCHAR(100) NOT NULL,
+    strongs VARCHAR(7) NOT NULL,
+    morphology VARCHAR(32),
+    UNIQUE (book_id, chapter, verse, position, strongs)
+);
+
+CREATE INDEX IF NOT EXISTS idx_word_tags_strongs ON bible_word_tags(strongs);
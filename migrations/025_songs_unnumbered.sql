@@ -0,0 +1,11 @@
+-- Songs outside any songbook
+--
+-- `UNIQUE NULLS NOT DISTINCT (songbook_id, number)` treated every song
+-- without a songbook and number as the same (NULL, NULL) key, so only one
+-- such song could exist. Numbers stay unique within a songbook; songs
+-- without a songbook or without a number are not constrained.
+ALTER TABLE songs DROP CONSTRAINT IF EXISTS songs_songbook_id_number_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_songs_songbook_number
+    ON songs(songbook_id, number)
+    WHERE songbook_id IS NOT NULL AND number IS NOT NULL;
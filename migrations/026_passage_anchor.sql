@@ -0,0 +1,26 @@
+-- Keep Bible passages in place when the setlist item they follow is removed
+--
+-- A passage used to fall back to the start of the service (`after_item_id`
+-- NULL); now it moves to the item before the removed one. Passages of a
+-- playlist being deleted are left to the cascade.
+CREATE OR REPLACE FUNCTION reanchor_playlist_passages()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE public.song_playlist_passages
+    SET after_item_id = (
+        SELECT id FROM public.song_playlist_items
+        WHERE playlist_id = OLD.playlist_id AND position < OLD.position
+        ORDER BY position DESC
+        LIMIT 1
+    )
+    WHERE after_item_id = OLD.id
+        AND EXISTS (SELECT 1 FROM public.song_playlists WHERE id = OLD.playlist_id);
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS reanchor_playlist_passages ON song_playlist_items;
+CREATE TRIGGER reanchor_playlist_passages
+    BEFORE DELETE ON song_playlist_items
+    FOR EACH ROW
+    EXECUTE FUNCTION reanchor_playlist_passages();
@@ -0,0 +1,31 @@
+-- Reindex every Bible word for positional search
+--
+-- The first word index skipped words shorter than three letters and numbered
+-- positions among the kept words only, so phrases with short words (и, в,
+-- не) and NEAR distances did not match the text. Positions now count every
+-- word the way `word_offsets` does, which is also how the incremental rebuild
+-- indexes verses (index version 2).
+TRUNCATE bible_word_index;
+
+INSERT INTO bible_word_index (word, lemma, verse_id, position)
+SELECT
+    t.word,
+    bible_lemma(t.word),
+    t.verse_id,
+    ROW_NUMBER() OVER (PARTITION BY t.verse_id ORDER BY t.ordinality)::smallint
+FROM (
+    SELECT
+        v.id as verse_id,
+        LOWER(REGEXP_REPLACE(s.word, '[^а-яА-ЯёЁa-zA-Z0-9]', '', 'g')) as word,
+        s.ordinality
+    FROM bible_verses v,
+         LATERAL REGEXP_SPLIT_TO_TABLE(v.text, '\s+') WITH ORDINALITY AS s(word, ordinality)
+) t
+WHERE t.word <> '';
+
+INSERT INTO bible_index_state (verse_id, text_hash, version)
+SELECT id, md5(text), 2 FROM bible_verses
+ON CONFLICT (verse_id) DO UPDATE SET
+    text_hash = EXCLUDED.text_hash,
+    version = EXCLUDED.version,
+    indexed_at = NOW();
@@ -0,0 +1,171 @@
+use masterror::AppResult;
+use sqlx::PgPool;
+
+/// Version of the word index layout. Verses indexed with another version are
+/// reindexed on the next rebuild.
+pub const INDEX_VERSION: i16 = 2;
+
+/// Verses reindexed per statement
+const REINDEX_BATCH: usize = 2000;
+
+/// PostgreSQL word index of Bible verses used by positional search and the
+/// symphony
+pub struct PgBibleIndex {
+    pool: PgPool
+}
+
+impl PgBibleIndex {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Reindex verses that are new, changed since they were indexed or
+    /// indexed by an older [`INDEX_VERSION`]; `full` reindexes every verse
+    pub async fn rebuild(&self, full: bool) -> AppResult<IndexStats> {
+        let stale: Vec<i32> = sqlx::query_scalar(
+            r#"
+            SELECT v.id
+            FROM bible_verses v
+            LEFT JOIN bible_index_state s ON s.verse_id = v.id
+            WHERE $1
+               OR s.verse_id IS NULL
+               OR s.text_hash <> md5(v.text)
+               OR s.version <> $2
+            ORDER BY v.id
+            "#
+        )
+        .bind(full)
+        .bind(INDEX_VERSION)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = IndexStats::default();
+        for batch in stale.chunks(REINDEX_BATCH) {
+            stats.words_indexed += self.reindex(batch).await?;
+            stats.verses_indexed += batch.len() as u64;
+            tracing::info!("Indexed {} of {} verses", stats.verses_indexed, stale.len());
+        }
+        Ok(stats)
+    }
+
+    /// Replace the index rows of the given verses, returning the number of
+    /// words indexed.
+    ///
+    /// Words are split on whitespace and stripped of punctuation the way
+    /// [`crate::domain::word_offsets`] counts them.
+    async fn reindex(&self, verse_ids: &[i32]) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM bible_word_index WHERE verse_id = ANY($1)")
+            .bind(verse_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        let words = sqlx::query(
+            r#"
+            INSERT INTO bible_word_index (word, lemma, verse_id, position)
+            SELECT
+                t.word,
+                bible_lemma(t.word),
+                t.verse_id,
+                ROW_NUMBER() OVER (PARTITION BY t.verse_id ORDER BY t.ordinality)::smallint
+            FROM (
+                SELECT
+                    v.id as verse_id,
+                    LOWER(REGEXP_REPLACE(s.word, '[^а-яА-ЯёЁa-zA-Z0-9]', '', 'g')) as word,
+                    s.ordinality
+                FROM bible_verses v,
+                     LATERAL REGEXP_SPLIT_TO_TABLE(v.text, '\s+') WITH ORDINALITY AS s(word, ordinality)
+                WHERE v.id = ANY($1)
+            ) t
+            WHERE t.word <> ''
+            "#
+        )
+        .bind(verse_ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query(
+            r#"
+            INSERT INTO bible_index_state (verse_id, text_hash, version)
+            SELECT id, md5(text), $2 FROM bible_verses WHERE id = ANY($1)
+            ON CONFLICT (verse_id) DO UPDATE SET
+                text_hash = EXCLUDED.text_hash,
+                version = EXCLUDED.version,
+                indexed_at = NOW()
+            "#
+        )
+        .bind(verse_ids)
+        .bind(INDEX_VERSION)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(words)
+    }
+
+    /// Add or replace dictionary forms of words, as `(word, lemma)` pairs.
+    /// Call [`Self::refresh_lemmas`] afterwards to apply them to the index.
+    pub async fn load_lemmas(&self, pairs: &[(String, String)]) -> AppResult<u64> {
+        let (words, lemmas): (Vec<String>, Vec<String>) = pairs
+            .iter()
+            .map(|(word, lemma)| (word.to_lowercase(), lemma.to_lowercase()))
+            .unzip();
+
+        let loaded = sqlx::query(
+            r#"
+            INSERT INTO bible_lemmas (word, lemma)
+            SELECT * FROM UNNEST($1::text[], $2::text[])
+            ON CONFLICT (word) DO UPDATE SET lemma = EXCLUDED.lemma
+            "#
+        )
+        .bind(&words)
+        .bind(&lemmas)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(loaded)
+    }
+
+    /// Recompute lemmas of indexed words whose lemma changed, e.g. after
+    /// [`Self::load_lemmas`], returning the number of rows updated
+    pub async fn refresh_lemmas(&self) -> AppResult<u64> {
+        let updated = sqlx::query(
+            r#"
+            WITH forms AS (
+                SELECT word, bible_lemma(word) as lemma
+                FROM (SELECT DISTINCT word FROM bible_word_index) w
+            )
+            UPDATE bible_word_index i SET lemma = f.lemma
+            FROM forms f
+            WHERE i.word = f.word AND i.lemma IS DISTINCT FROM f.lemma
+            "#
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(updated)
+    }
+}
+
+/// Statistics of a word index rebuild
+#[derive(Debug, Default)]
+pub struct IndexStats {
+    pub verses_indexed: u64,
+    pub words_indexed:  u64
+}
+
+impl std::fmt::Display for IndexStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Indexed {} words in {} verses",
+            self.words_indexed, self.verses_indexed
+        )
+    }
+}
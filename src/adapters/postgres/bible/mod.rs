@@ -1,7 +1,11 @@
+mod index;
 mod reading;
 mod repository;
 mod search;
+mod strongs;
 
+pub use index::*;
 pub use reading::*;
 pub use repository::*;
 pub use search::*;
+pub use strongs::*;
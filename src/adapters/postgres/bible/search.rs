@@ -1,6 +1,12 @@
 use masterror::AppResult;
 use revelation_bible::{SearchResult, Verse, ports::BibleSearch};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, postgres::PgArguments, query::QueryAs};
+
+use crate::domain::{
+    BibleSearchMode, BibleSearchPage, BibleSearchParams, BookFrequency, BookHits, HIGHLIGHT_START,
+    HIGHLIGHT_STOP, IndexTerm, MAX_BIBLE_SEARCH_LIMIT, PositionalQuery, Symphony, VerseRef,
+    WordForm, highlight_offsets, index_word, word_offsets
+};
 
 /// PostgreSQL implementation of BibleSearch
 pub struct PgBibleSearch {
@@ -13,91 +19,459 @@ impl PgBibleSearch {
             pool
         }
     }
-}
 
-impl BibleSearch for PgBibleSearch {
-    async fn search(&self, query: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
-        let results = sqlx::query!(
+    /// Page of verses matching the query with the total hit count and hits
+    /// per book. Full-text results come best ranked first, positional ones
+    /// in canonical order.
+    pub async fn search_page(&self, params: &BibleSearchParams) -> AppResult<BibleSearchPage> {
+        let (results, books) = match params.mode {
+            BibleSearchMode::Fulltext => {
+                let terms = SearchTerms::Text(&params.query);
+                (
+                    self.fulltext_results(params).await?,
+                    self.book_hits(FULLTEXT_HITS, terms, params).await?
+                )
+            }
+            BibleSearchMode::Positional => {
+                let query = PositionalQuery::parse(&params.query)?;
+                let hits = positional_hits(&query);
+                let patterns = term_patterns(&query);
+                let terms = SearchTerms::Patterns(&patterns);
+                (
+                    self.positional_results(&hits, terms, params).await?,
+                    self.book_hits(&hits, terms, params).await?
+                )
+            }
+        };
+
+        Ok(BibleSearchPage {
+            total: books.iter().map(|f| f.matching).sum(),
+            limit: params.limit,
+            offset: params.offset,
+            results,
+            books: books
+                .into_iter()
+                .map(|f| BookHits {
+                    book_id:   f.book_id,
+                    book_name: f.book_name,
+                    hits:      f.hits
+                })
+                .collect()
+        })
+    }
+
+    async fn fulltext_results(&self, params: &BibleSearchParams) -> AppResult<Vec<SearchResult>> {
+        let headline_options =
+            format!("HighlightAll=true, StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_STOP}");
+
+        let rows = sqlx::query_as::<_, SearchRow>(&format!(
             r#"
+            WITH page AS (
+                SELECT v.id, ts_rank(v.text_search, q.query) as rank
+                FROM bible_verses v
+                JOIN bible_books b ON b.id = v.book_id,
+                     plainto_tsquery('russian', $1) q(query)
+                WHERE v.text_search @@ q.query AND {SEARCH_FILTERS}
+                ORDER BY rank DESC, v.book_id, v.chapter, v.verse
+                LIMIT $6 OFFSET $7
+            )
             SELECT
                 v.id, v.book_id, v.chapter, v.verse, v.text,
                 b.name_ru as book_name,
-                ts_headline('russian', v.text, plainto_tsquery('russian', $1)) as headline
-            FROM bible_verses v
+                ts_headline('russian', v.text, plainto_tsquery('russian', $1), $8) as headline
+            FROM page p
+            JOIN bible_verses v ON v.id = p.id
             JOIN bible_books b ON b.id = v.book_id
-            WHERE v.text_search @@ plainto_tsquery('russian', $1)
-            ORDER BY ts_rank(v.text_search, plainto_tsquery('russian', $1)) DESC
-            LIMIT $2
-            "#,
-            query,
-            limit
-        )
+            ORDER BY p.rank DESC, v.book_id, v.chapter, v.verse
+            "#
+        ))
+        .bind(&params.query)
+        .bind(params.testament)
+        .bind(params.book_id)
+        .bind(params.chapter_from)
+        .bind(params.chapter_to)
+        .bind(params.limit)
+        .bind(params.offset)
+        .bind(&headline_options)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(results
-            .into_iter()
-            .map(|r| SearchResult {
-                verse:      Verse {
-                    id:      r.id,
-                    book_id: r.book_id,
-                    chapter: r.chapter,
-                    verse:   r.verse,
-                    text:    r.text
-                },
-                book_name:  r.book_name,
-                highlights: Vec::new()
-            })
-            .collect())
+        Ok(rows.into_iter().map(SearchRow::into_result).collect())
     }
 
-    async fn symphony(&self, word: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
-        let results = sqlx::query!(
+    async fn positional_results(
+        &self,
+        hits: &str,
+        terms: SearchTerms<'_>,
+        params: &BibleSearchParams
+    ) -> AppResult<Vec<SearchResult>> {
+        let sql = format!(
             r#"
+            WITH hits AS ({hits})
             SELECT
                 v.id, v.book_id, v.chapter, v.verse, v.text,
-                b.name_ru as book_name
+                b.name_ru as book_name, h.positions
+            FROM hits h
+            JOIN bible_verses v ON v.id = h.verse_id
+            JOIN bible_books b ON b.id = v.book_id
+            WHERE {SEARCH_FILTERS}
+            ORDER BY v.book_id, v.chapter, v.verse
+            LIMIT $6 OFFSET $7
+            "#
+        );
+        let rows = terms
+            .bind(sqlx::query_as::<_, PositionalRow>(&sql))
+            .bind(params.testament)
+            .bind(params.book_id)
+            .bind(params.chapter_from)
+            .bind(params.chapter_to)
+            .bind(params.limit)
+            .bind(params.offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(PositionalRow::into_result).collect())
+    }
+
+    /// Every form of a word with its frequency per book and the verses it
+    /// occurs in, up to `limit`
+    pub async fn word_symphony(&self, word: &str, limit: i64) -> AppResult<Symphony> {
+        let normalized = index_word(word);
+        let lemma: String = sqlx::query_scalar("SELECT bible_lemma($1)")
+            .bind(&normalized)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let forms = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT word, COUNT(*)
+            FROM bible_word_index
+            WHERE lemma = $1
+            GROUP BY word
+            ORDER BY COUNT(*) DESC, word
+            "#
+        )
+        .bind(&lemma)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let books = sqlx::query_as::<_, BookFrequencyRow>(
+            r#"
+            SELECT
+                v.book_id, b.name_ru as book_name,
+                COUNT(*) as count,
+                COUNT(DISTINCT v.id) as verses,
+                MIN(v.chapter * 1000 + v.verse) as first,
+                MAX(v.chapter * 1000 + v.verse) as last
             FROM bible_word_index w
             JOIN bible_verses v ON v.id = w.verse_id
             JOIN bible_books b ON b.id = v.book_id
-            WHERE w.word = lower($1)
+            WHERE w.lemma = $1
+            GROUP BY v.book_id, b.name_ru
+            ORDER BY v.book_id
+            "#
+        )
+        .bind(&lemma)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let books: Vec<BookFrequency> = books
+            .into_iter()
+            .map(BookFrequencyRow::into_frequency)
+            .collect();
+        Ok(Symphony {
+            word: word.to_string(),
+            total_count: books.iter().map(|b| b.count).sum(),
+            verse_count: books.iter().map(|b| b.verses).sum(),
+            first: books.first().map(|b| b.first),
+            last: books.last().map(|b| b.last),
+            forms: forms
+                .into_iter()
+                .map(|(form, count)| WordForm {
+                    form,
+                    count
+                })
+                .collect(),
+            books,
+            verses: self.lemma_verses(&normalized, limit).await?,
+            lemma
+        })
+    }
+
+    /// Verses holding any form of a word, in canonical order, with the forms
+    /// highlighted
+    async fn lemma_verses(&self, word: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
+        let rows = sqlx::query_as::<_, PositionalRow>(
+            r#"
+            WITH hits AS (
+                SELECT verse_id, array_agg(position ORDER BY position) as positions
+                FROM bible_word_index
+                WHERE lemma = bible_lemma($1)
+                GROUP BY verse_id
+            )
+            SELECT
+                v.id, v.book_id, v.chapter, v.verse, v.text,
+                b.name_ru as book_name, h.positions
+            FROM hits h
+            JOIN bible_verses v ON v.id = h.verse_id
+            JOIN bible_books b ON b.id = v.book_id
             ORDER BY v.book_id, v.chapter, v.verse
             LIMIT $2
-            "#,
-            word,
-            limit
+            "#
         )
+        .bind(word)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(results
-            .into_iter()
-            .map(|r| SearchResult {
-                verse:      Verse {
-                    id:      r.id,
-                    book_id: r.book_id,
-                    chapter: r.chapter,
-                    verse:   r.verse,
-                    text:    r.text
-                },
-                book_name:  r.book_name,
-                highlights: Vec::new()
-            })
-            .collect())
+        Ok(rows.into_iter().map(PositionalRow::into_result).collect())
+    }
+
+    /// Hits per book within the testament filter and, counting only the
+    /// verses inside the book and chapter filters, the hits making up the
+    /// total
+    async fn book_hits(
+        &self,
+        hits: &str,
+        terms: SearchTerms<'_>,
+        params: &BibleSearchParams
+    ) -> AppResult<Vec<BookHitsRow>> {
+        let sql = format!(
+            r#"
+            WITH hits AS ({hits})
+            SELECT
+                v.book_id, b.name_ru as book_name,
+                COUNT(*) as hits,
+                COUNT(*) FILTER (WHERE {SEARCH_FILTERS}) as matching
+            FROM hits h
+            JOIN bible_verses v ON v.id = h.verse_id
+            JOIN bible_books b ON b.id = v.book_id
+            WHERE ($2::testament IS NULL OR b.testament = $2)
+            GROUP BY v.book_id, b.name_ru
+            ORDER BY v.book_id
+            "#
+        );
+        let books = terms
+            .bind(sqlx::query_as::<_, BookHitsRow>(&sql))
+            .bind(params.testament)
+            .bind(params.book_id)
+            .bind(params.chapter_from)
+            .bind(params.chapter_to)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(books)
+    }
+}
+
+/// Verses matching a full-text query bound as `$1`
+const FULLTEXT_HITS: &str = r#"
+    SELECT id as verse_id FROM bible_verses
+    WHERE text_search @@ plainto_tsquery('russian', $1)
+"#;
+
+/// Verses matching a positional query with the matched word positions; the
+/// term patterns are bound as a `$1` array
+fn positional_hits(query: &PositionalQuery) -> String {
+    let terms = query.terms();
+    let condition = |i: usize| match terms[i] {
+        IndexTerm::Word(_) => format!("w{i}.word = ($1::text[])[{}]", i + 1),
+        IndexTerm::Prefix(_) => format!("w{i}.word LIKE ($1::text[])[{}]", i + 1)
+    };
+
+    let mut joins = String::new();
+    for i in 1..terms.len() {
+        let placement = match query {
+            PositionalQuery::Phrase(_) => format!("w{i}.position = w0.position + {i}"),
+            PositionalQuery::Near {
+                distance, ..
+            } => format!(
+                "w{i}.position BETWEEN w0.position - {distance} AND w0.position + {distance} \
+                 AND w{i}.position <> w0.position"
+            )
+        };
+        joins.push_str(&format!(
+            " JOIN bible_word_index w{i} ON w{i}.verse_id = w0.verse_id AND {placement} AND {}",
+            condition(i)
+        ));
+    }
+    let positions: Vec<String> = (0..terms.len()).map(|i| format!("w{i}.position")).collect();
+
+    format!(
+        r#"
+        SELECT m.verse_id, array_agg(DISTINCT p ORDER BY p) as positions
+        FROM (
+            SELECT w0.verse_id, ARRAY[{positions}] as positions
+            FROM bible_word_index w0{joins}
+            WHERE {first}
+        ) m, unnest(m.positions) p
+        GROUP BY m.verse_id
+        "#,
+        positions = positions.join(", "),
+        first = condition(0)
+    )
+}
+
+/// Values for the `$1` array of [`positional_hits`]: words as they are,
+/// prefixes as `LIKE` patterns. Index words hold only letters and digits, so
+/// nothing needs escaping.
+fn term_patterns(query: &PositionalQuery) -> Vec<String> {
+    query
+        .terms()
+        .into_iter()
+        .map(|term| match term {
+            IndexTerm::Word(word) => word.clone(),
+            IndexTerm::Prefix(prefix) => format!("{prefix}%")
+        })
+        .collect()
+}
+
+/// What a search binds as `$1`
+#[derive(Clone, Copy)]
+enum SearchTerms<'a> {
+    Text(&'a str),
+    Patterns(&'a [String])
+}
+
+impl<'a> SearchTerms<'a> {
+    fn bind<O>(
+        self,
+        query: QueryAs<'a, Postgres, O, PgArguments>
+    ) -> QueryAs<'a, Postgres, O, PgArguments> {
+        match self {
+            Self::Text(text) => query.bind(text),
+            Self::Patterns(patterns) => query.bind(patterns)
+        }
+    }
+}
+
+/// Testament, book and chapter range filters over `v` and `b`, bound as
+/// `$2` to `$5`
+const SEARCH_FILTERS: &str = r#"
+    ($2::testament IS NULL OR b.testament = $2)
+    AND ($3::smallint IS NULL OR v.book_id = $3)
+    AND ($4::smallint IS NULL OR v.chapter >= $4)
+    AND ($5::smallint IS NULL OR v.chapter <= $5)
+"#;
+
+#[derive(sqlx::FromRow)]
+struct SearchRow {
+    id:        i32,
+    book_id:   i16,
+    chapter:   i16,
+    verse:     i16,
+    text:      String,
+    book_name: String,
+    headline:  String
+}
+
+impl SearchRow {
+    fn into_result(self) -> SearchResult {
+        SearchResult {
+            highlights: highlight_offsets(&self.headline),
+            verse:      Verse {
+                id:      self.id,
+                book_id: self.book_id,
+                chapter: self.chapter,
+                verse:   self.verse,
+                text:    self.text
+            },
+            book_name:  self.book_name
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(super) struct PositionalRow {
+    id:        i32,
+    book_id:   i16,
+    chapter:   i16,
+    verse:     i16,
+    text:      String,
+    book_name: String,
+    positions: Vec<i16>
+}
+
+impl PositionalRow {
+    pub(super) fn into_result(self) -> SearchResult {
+        SearchResult {
+            highlights: word_offsets(&self.text, &self.positions),
+            verse:      Verse {
+                id:      self.id,
+                book_id: self.book_id,
+                chapter: self.chapter,
+                verse:   self.verse,
+                text:    self.text
+            },
+            book_name:  self.book_name
+        }
+    }
+}
+
+/// Chapter and verse packed as `chapter * 1000 + verse`, so that the first
+/// and last verse of a book are a plain `MIN` and `MAX`
+#[derive(sqlx::FromRow)]
+struct BookFrequencyRow {
+    book_id:   i16,
+    book_name: String,
+    count:     i64,
+    verses:    i64,
+    first:     i32,
+    last:      i32
+}
+
+impl BookFrequencyRow {
+    fn into_frequency(self) -> BookFrequency {
+        let place = |packed: i32| VerseRef {
+            book_id: self.book_id,
+            chapter: (packed / 1000) as i16,
+            verse:   (packed % 1000) as i16
+        };
+        BookFrequency {
+            first:     place(self.first),
+            last:      place(self.last),
+            book_id:   self.book_id,
+            book_name: self.book_name,
+            count:     self.count,
+            verses:    self.verses
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct BookHitsRow {
+    book_id:   i16,
+    book_name: String,
+    hits:      i64,
+    matching:  i64
+}
+
+impl BibleSearch for PgBibleSearch {
+    async fn search(&self, query: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
+        let params = BibleSearchParams {
+            query: query.to_string(),
+            limit: limit.clamp(1, MAX_BIBLE_SEARCH_LIMIT),
+            ..Default::default()
+        };
+        Ok(self.search_page(&params).await?.results)
+    }
+
+    async fn symphony(&self, word: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
+        self.lemma_verses(&index_word(word), limit).await
     }
 
     async fn word_count(&self, word: &str) -> AppResult<i64> {
-        let result = sqlx::query_scalar!(
+        let count = sqlx::query_scalar(
             r#"
-            SELECT COUNT(*) as "count!"
+            SELECT COUNT(*)
             FROM bible_word_index
-            WHERE word = lower($1)
-            "#,
-            word
+            WHERE lemma = bible_lemma($1)
+            "#
         )
+        .bind(index_word(word))
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(result)
+        Ok(count)
     }
 }
@@ -0,0 +1,260 @@
+use masterror::AppResult;
+use revelation_bible::SearchResult;
+use sqlx::PgPool;
+
+use super::search::PositionalRow;
+use crate::domain::{StrongsEntry, StrongsNumber, WordForm, WordTag};
+
+/// PostgreSQL storage of the Strong's lexicon and word tags
+pub struct PgStrongs {
+    pool: PgPool
+}
+
+impl PgStrongs {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    pub async fn get_entry(&self, number: &StrongsNumber) -> AppResult<Option<StrongsEntry>> {
+        let row = sqlx::query_as::<_, StrongsEntryRow>(
+            r#"
+            SELECT number, lemma, transliteration, pronunciation, definition, usage
+            FROM bible_strongs
+            WHERE number = $1
+            "#
+        )
+        .bind(number.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| StrongsEntry {
+            number:          row.number,
+            language:        number.language(),
+            lemma:           row.lemma,
+            transliteration: row.transliteration,
+            pronunciation:   row.pronunciation,
+            definition:      row.definition,
+            usage:           row.usage
+        }))
+    }
+
+    /// Add or replace lexicon entries, returning the number written
+    pub async fn upsert_entries(&self, entries: &[StrongsEntry]) -> AppResult<u64> {
+        let mut numbers = Vec::with_capacity(entries.len());
+        let mut lemmas = Vec::with_capacity(entries.len());
+        let mut transliterations = Vec::with_capacity(entries.len());
+        let mut pronunciations = Vec::with_capacity(entries.len());
+        let mut definitions = Vec::with_capacity(entries.len());
+        let mut usages = Vec::with_capacity(entries.len());
+        for entry in entries {
+            numbers.push(entry.number.as_str());
+            lemmas.push(entry.lemma.as_str());
+            transliterations.push(entry.transliteration.as_deref());
+            pronunciations.push(entry.pronunciation.as_deref());
+            definitions.push(entry.definition.as_str());
+            usages.push(entry.usage.as_deref());
+        }
+
+        let written = sqlx::query(
+            r#"
+            INSERT INTO bible_strongs
+                (number, lemma, transliteration, pronunciation, definition, usage)
+            SELECT * FROM UNNEST(
+                $1::varchar[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[]
+            )
+            ON CONFLICT (number) DO UPDATE SET
+                lemma = EXCLUDED.lemma,
+                transliteration = EXCLUDED.transliteration,
+                pronunciation = EXCLUDED.pronunciation,
+                definition = EXCLUDED.definition,
+                usage = EXCLUDED.usage,
+                updated_at = NOW()
+            "#
+        )
+        .bind(&numbers)
+        .bind(&lemmas)
+        .bind(&transliterations)
+        .bind(&pronunciations)
+        .bind(&definitions)
+        .bind(&usages)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(written)
+    }
+
+    /// Replace the word tags of a verse, returning the number written
+    pub async fn replace_verse_tags(
+        &self,
+        book_id: i16,
+        chapter: i16,
+        verse: i16,
+        tags: &[WordTag]
+    ) -> AppResult<u64> {
+        let positions: Vec<i16> = tags.iter().map(|t| t.position).collect();
+        let words: Vec<&str> = tags.iter().map(|t| t.word.as_str()).collect();
+        let numbers: Vec<String> = tags.iter().map(|t| t.strongs.to_string()).collect();
+        let morphologies: Vec<Option<&str>> =
+            tags.iter().map(|t| t.morphology.as_deref()).collect();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "DELETE FROM bible_word_tags WHERE book_id = $1 AND chapter = $2 AND verse = $3"
+        )
+        .bind(book_id)
+        .bind(chapter)
+        .bind(verse)
+        .execute(&mut *tx)
+        .await?;
+
+        let written = sqlx::query(
+            r#"
+            INSERT INTO bible_word_tags
+                (book_id, chapter, verse, position, word, strongs, morphology)
+            SELECT $1, $2, $3, t.* FROM UNNEST(
+                $4::smallint[], $5::varchar[], $6::varchar[], $7::varchar[]
+            ) t
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(book_id)
+        .bind(chapter)
+        .bind(verse)
+        .bind(&positions)
+        .bind(&words)
+        .bind(&numbers)
+        .bind(&morphologies)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(written)
+    }
+
+    /// Tagged words and verses with a number
+    pub async fn count_occurrences(&self, number: &StrongsNumber) -> AppResult<(i64, i64)> {
+        let counts = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*), COUNT(DISTINCT (book_id, chapter, verse))
+            FROM bible_word_tags
+            WHERE strongs = $1
+            "#
+        )
+        .bind(number.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Translated words tagged with a number, most frequent first
+    pub async fn renderings(&self, number: &StrongsNumber) -> AppResult<Vec<WordForm>> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT lower(word), COUNT(*)
+            FROM bible_word_tags
+            WHERE strongs = $1
+            GROUP BY lower(word)
+            ORDER BY COUNT(*) DESC, lower(word)
+            "#
+        )
+        .bind(number.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(form, count)| WordForm {
+                form,
+                count
+            })
+            .collect())
+    }
+
+    /// Verses with words tagged with a number, in canonical order, with the
+    /// tagged words highlighted
+    pub async fn occurrences(
+        &self,
+        number: &StrongsNumber,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<SearchResult>> {
+        let rows = sqlx::query_as::<_, PositionalRow>(
+            r#"
+            WITH hits AS (
+                SELECT
+                    book_id, chapter, verse,
+                    array_agg(DISTINCT position ORDER BY position) as positions
+                FROM bible_word_tags
+                WHERE strongs = $1
+                GROUP BY book_id, chapter, verse
+            )
+            SELECT
+                v.id, v.book_id, v.chapter, v.verse, v.text,
+                b.name_ru as book_name, h.positions
+            FROM hits h
+            JOIN bible_verses v
+                ON v.book_id = h.book_id AND v.chapter = h.chapter AND v.verse = h.verse
+            JOIN bible_books b ON b.id = v.book_id
+            ORDER BY v.book_id, v.chapter, v.verse
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(number.to_string())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(PositionalRow::into_result).collect())
+    }
+
+    /// Word tags of a chapter with the lexicon words behind them, in verse
+    /// and word order
+    pub async fn chapter_tags(&self, book_id: i16, chapter: i16) -> AppResult<Vec<ChapterTagRow>> {
+        let rows = sqlx::query_as::<_, ChapterTagRow>(
+            r#"
+            SELECT
+                t.verse, t.position, t.word, t.strongs, t.morphology,
+                s.lemma, s.transliteration
+            FROM bible_word_tags t
+            LEFT JOIN bible_strongs s ON s.number = t.strongs
+            WHERE t.book_id = $1 AND t.chapter = $2
+            ORDER BY t.verse, t.position, t.id
+            "#
+        )
+        .bind(book_id)
+        .bind(chapter)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StrongsEntryRow {
+    number:          String,
+    lemma:           String,
+    transliteration: Option<String>,
+    pronunciation:   Option<String>,
+    definition:      String,
+    usage:           Option<String>
+}
+
+/// Word tag of a chapter verse
+#[derive(Debug, sqlx::FromRow)]
+pub struct ChapterTagRow {
+    pub verse:           i16,
+    pub position:        i16,
+    pub word:            String,
+    pub strongs:         String,
+    pub morphology:      Option<String>,
+    pub lemma:           Option<String>,
+    pub transliteration: Option<String>
+}
@@ -1,6 +1,36 @@
-use masterror::AppResult;
+use chrono::{DateTime, Utc};
+use masterror::prelude::*;
 use revelation_user::{TelegramRecipient, ports::NotificationRepository};
 use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::{NewNotification, UserNotification};
+
+/// Row type for user notifications
+#[derive(sqlx::FromRow)]
+struct UserNotificationRow {
+    id:         Uuid,
+    kind:       String,
+    title:      String,
+    body:       Option<String>,
+    link:       Option<String>,
+    created_at: DateTime<Utc>,
+    read_at:    Option<DateTime<Utc>>
+}
+
+impl From<UserNotificationRow> for UserNotification {
+    fn from(row: UserNotificationRow) -> Self {
+        Self {
+            id:         row.id,
+            kind:       row.kind,
+            title:      row.title,
+            body:       row.body,
+            link:       row.link,
+            created_at: row.created_at,
+            read_at:    row.read_at
+        }
+    }
+}
 
 /// PostgreSQL implementation of NotificationRepository
 pub struct PgNotificationRepository {
@@ -13,6 +43,69 @@ impl PgNotificationRepository {
             pool
         }
     }
+
+    /// Store a notification; a trigger announces it on `user_notification`
+    pub async fn create_notification(&self, notification: &NewNotification) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO user_notifications (user_id, kind, title, body, link)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#
+        )
+        .bind(notification.user_id)
+        .bind(notification.kind.as_str())
+        .bind(&notification.title)
+        .bind(&notification.body)
+        .bind(&notification.link)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Notifications of a user, newest first
+    pub async fn list_notifications(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        limit: i64
+    ) -> AppResult<Vec<UserNotification>> {
+        let notifications = sqlx::query_as::<_, UserNotificationRow>(
+            r#"
+            SELECT id, kind, title, body, link, created_at, read_at
+            FROM user_notifications
+            WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(notifications.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn mark_read(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_notifications SET read_at = COALESCE(read_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            "#
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Notification not found"));
+        }
+        Ok(())
+    }
 }
 
 impl NotificationRepository for PgNotificationRepository {
@@ -0,0 +1,113 @@
+use masterror::AppResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Church roles allowed to manage church songbooks
+const SONGBOOK_MANAGER_ROLES: &[&str] = &["pastor", "admin"];
+
+/// Church roles allowed to manage the worship team
+const WORSHIP_TEAM_MANAGER_ROLES: &[&str] = &["pastor", "admin"];
+
+/// PostgreSQL permission checks for songbook management
+pub struct PgSongAccess {
+    pool: PgPool
+}
+
+impl PgSongAccess {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Whether the user may edit the shared song catalog
+    pub async fn is_song_editor(&self, user_id: Uuid) -> AppResult<bool> {
+        let is_editor = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM song_editors WHERE user_id = $1)"
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(is_editor)
+    }
+
+    /// Whether the user holds one of the given roles in the church
+    pub async fn has_church_role(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid,
+        roles: &[&str]
+    ) -> AppResult<bool> {
+        let has_role = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM memberships
+                WHERE user_id = $1 AND church_id = $2 AND role::text = ANY($3)
+            )
+            "#
+        )
+        .bind(user_id)
+        .bind(church_id)
+        .bind(roles)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(has_role)
+    }
+
+    /// Whether the user is a member of the church in any role
+    pub async fn is_church_member(&self, user_id: Uuid, church_id: Uuid) -> AppResult<bool> {
+        let is_member = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM memberships WHERE user_id = $1 AND church_id = $2)"
+        )
+        .bind(user_id)
+        .bind(church_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(is_member)
+    }
+
+    /// Whether the user may manage songbooks owned by `church_id`, or the
+    /// shared catalog when `church_id` is `None`
+    pub async fn can_manage_songbooks(
+        &self,
+        user_id: Uuid,
+        church_id: Option<Uuid>
+    ) -> AppResult<bool> {
+        match church_id {
+            Some(church_id) => {
+                self.has_church_role(user_id, church_id, SONGBOOK_MANAGER_ROLES)
+                    .await
+            }
+            None => self.is_song_editor(user_id).await
+        }
+    }
+
+    /// Whether the user may manage the worship team of the church
+    pub async fn can_manage_worship_team(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid
+    ) -> AppResult<bool> {
+        self.has_church_role(user_id, church_id, WORSHIP_TEAM_MANAGER_ROLES)
+            .await
+    }
+
+    /// Whether the user may edit setlists of the church: worship team
+    /// members, pastors and admins
+    pub async fn can_edit_church_setlists(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid
+    ) -> AppResult<bool> {
+        let allowed = sqlx::query_scalar::<_, bool>("SELECT church_setlist_editor($1, $2)")
+            .bind(church_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(allowed)
+    }
+}
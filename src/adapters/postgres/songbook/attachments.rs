@@ -0,0 +1,144 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::SongAttachmentRow;
+use crate::{
+    adapters::storage::StoredFile,
+    domain::{AddAttachmentLink, AttachmentKind, SongAttachment, UploadAttachment}
+};
+
+const ATTACHMENT_COLUMNS: &str = r#"
+    id, song_id, kind, title, file_name, content_type, size_bytes, url, created_at
+"#;
+
+/// PostgreSQL song attachment metadata
+pub struct PgSongAttachments {
+    pool: PgPool
+}
+
+impl PgSongAttachments {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Attachments of a song in the order they were added
+    pub async fn list_attachments(&self, song_id: Uuid) -> AppResult<Vec<SongAttachment>> {
+        let attachments = sqlx::query_as::<_, SongAttachmentRow>(&format!(
+            "SELECT {ATTACHMENT_COLUMNS} FROM song_attachments WHERE song_id = $1 ORDER BY created_at"
+        ))
+        .bind(song_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attachments.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn get_attachment(&self, id: Uuid) -> AppResult<SongAttachment> {
+        let attachment = sqlx::query_as::<_, SongAttachmentRow>(&format!(
+            "SELECT {ATTACHMENT_COLUMNS} FROM song_attachments WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Attachment not found"))?;
+
+        Ok(attachment.into())
+    }
+
+    /// Storage key of a file attachment, `None` for links
+    pub async fn storage_key(&self, id: Uuid) -> AppResult<Option<String>> {
+        let key = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT storage_key FROM song_attachments WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Attachment not found"))?;
+
+        Ok(key)
+    }
+
+    /// Storage keys of all files attached to a song
+    pub async fn song_storage_keys(&self, song_id: Uuid) -> AppResult<Vec<String>> {
+        let keys = sqlx::query_scalar::<_, String>(
+            "SELECT storage_key FROM song_attachments WHERE song_id = $1 AND storage_key IS NOT NULL"
+        )
+        .bind(song_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    pub async fn create_file(
+        &self,
+        id: Uuid,
+        song_id: Uuid,
+        upload: &UploadAttachment,
+        file: &StoredFile,
+        uploaded_by: Uuid
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO song_attachments (
+                id, song_id, kind, title, storage_key, file_name, content_type,
+                size_bytes, uploaded_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#
+        )
+        .bind(id)
+        .bind(song_id)
+        .bind(upload.kind)
+        .bind(upload.title.trim())
+        .bind(&file.key)
+        .bind(upload.base_file_name())
+        .bind(&file.content_type)
+        .bind(file.size as i64)
+        .bind(uploaded_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_link(
+        &self,
+        song_id: Uuid,
+        link: &AddAttachmentLink,
+        uploaded_by: Uuid
+    ) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO song_attachments (song_id, kind, title, url, uploaded_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#
+        )
+        .bind(song_id)
+        .bind(AttachmentKind::Link)
+        .bind(link.title.trim())
+        .bind(link.url.trim())
+        .bind(uploaded_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Delete an attachment and return the storage key of its file
+    pub async fn delete_attachment(&self, id: Uuid) -> AppResult<Option<String>> {
+        let key = sqlx::query_scalar::<_, Option<String>>(
+            "DELETE FROM song_attachments WHERE id = $1 RETURNING storage_key"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Attachment not found"))?;
+
+        Ok(key)
+    }
+}
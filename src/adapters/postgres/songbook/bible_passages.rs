@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use masterror::prelude::*;
+use revelation_bible::Verse;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::PassageVerseRow;
+
+/// Table storing Bible passages as `book_id, chapter, verse_start,
+/// verse_end`
+#[derive(Debug, Clone, Copy)]
+pub(super) enum PassageTable {
+    /// Passages a song is based on, owned by a song
+    SongScripture,
+    /// Passages read during a service, owned by a playlist
+    PlaylistPassages
+}
+
+impl PassageTable {
+    /// Table name and owner column
+    fn names(self) -> (&'static str, &'static str) {
+        match self {
+            Self::SongScripture => ("song_scripture_refs", "song_id"),
+            Self::PlaylistPassages => ("song_playlist_passages", "playlist_id")
+        }
+    }
+}
+
+/// Fail unless every verse of the range exists
+pub(super) async fn ensure_passage_exists(
+    pool: &PgPool,
+    book_id: i16,
+    chapter: i16,
+    verse_start: i16,
+    verse_end: i16
+) -> AppResult<()> {
+    let found = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM bible_verses
+        WHERE book_id = $1 AND chapter = $2 AND verse BETWEEN $3 AND $4
+        "#
+    )
+    .bind(book_id)
+    .bind(chapter)
+    .bind(verse_start)
+    .bind(verse_end)
+    .fetch_one(pool)
+    .await?;
+
+    if found != i64::from(verse_end - verse_start + 1) {
+        return Err(AppError::validation("Passage is not in the Bible"));
+    }
+    Ok(())
+}
+
+/// Verses of every passage of an owner, keyed by passage ID, in verse order
+pub(super) async fn passage_verses(
+    pool: &PgPool,
+    table: PassageTable,
+    owner_id: Uuid
+) -> AppResult<HashMap<Uuid, Vec<Verse>>> {
+    let (table, owner) = table.names();
+    let rows = sqlx::query_as::<_, PassageVerseRow>(&format!(
+        r#"
+        SELECT p.id as passage_id, v.id, v.book_id, v.chapter, v.verse, v.text
+        FROM {table} p
+        JOIN bible_verses v
+            ON v.book_id = p.book_id
+            AND v.chapter = p.chapter
+            AND v.verse BETWEEN p.verse_start AND p.verse_end
+        WHERE p.{owner} = $1
+        ORDER BY v.verse
+        "#
+    ))
+    .bind(owner_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut verses: HashMap<Uuid, Vec<Verse>> = HashMap::new();
+    for row in rows {
+        verses.entry(row.passage_id).or_default().push(Verse {
+            id:      row.id,
+            book_id: row.book_id,
+            chapter: row.chapter,
+            verse:   row.verse,
+            text:    row.text
+        });
+    }
+    Ok(verses)
+}
@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use masterror::prelude::*;
+use revelation_songbook::SongSummary;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::rows::{DuplicatePairRow, SongSummaryRow};
+use crate::domain::{DuplicateSongs, SongMerge};
+
+/// Weight of the lyrics similarity in the duplicate score; titles weigh 0.2
+/// and an identical first line 0.1
+const CONTENT_WEIGHT: f64 = 0.7;
+
+/// PostgreSQL duplicate song detection and merging
+pub struct PgSongDuplicates {
+    pool: PgPool
+}
+
+impl PgSongDuplicates {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Song pairs scoring at least `min_score`, most similar first.
+    ///
+    /// Lyrics are compared through `songs.content_key`, the transliterated
+    /// `content_plain` without punctuation. Only pairs whose lyrics alone can
+    /// still reach `min_score` are scored, which lets the trigram index
+    /// prune the self-join. Songs already related in a family are skipped.
+    pub async fn find_duplicates(
+        &self,
+        songbook_id: Option<Uuid>,
+        min_score: f64,
+        limit: i64
+    ) -> AppResult<Vec<DuplicateSongs>> {
+        let content_threshold =
+            ((min_score - (1.0 - CONTENT_WEIGHT)) / CONTENT_WEIGHT).clamp(0.1, 1.0);
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT set_config('pg_trgm.similarity_threshold', $1, true)")
+            .bind(content_threshold.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let pairs = sqlx::query_as::<_, DuplicatePairRow>(
+            r#"
+            WITH pairs AS (
+                SELECT
+                    a.id AS a_id, b.id AS b_id,
+                    similarity(a.content_key, b.content_key)::float8 AS content_similarity,
+                    similarity(song_search_key(a.title), song_search_key(b.title))::float8
+                        AS title_similarity,
+                    song_search_key(a.first_line) = song_search_key(b.first_line)
+                        AS same_first_line,
+                    a.songbook_id IS NOT DISTINCT FROM b.songbook_id AS same_songbook,
+                    (a.number IS NOT NULL, a.has_chords, a.favorites_count, a.views_count,
+                        b.created_at)
+                        >= (b.number IS NOT NULL, b.has_chords, b.favorites_count, b.views_count,
+                        a.created_at)
+                        AS keep_a
+                FROM songs a
+                JOIN songs b ON a.id < b.id AND a.content_key % b.content_key
+                WHERE ($1::uuid IS NULL OR a.songbook_id = $1 OR b.songbook_id = $1)
+                    AND NOT EXISTS (
+                        SELECT 1
+                        FROM song_family_members fa
+                        JOIN song_family_members fb ON fa.family_id = fb.family_id
+                        WHERE fa.song_id = a.id AND fb.song_id = b.id
+                    )
+            ),
+            scored AS (
+                SELECT
+                    *,
+                    $4 * content_similarity
+                        + 0.2 * title_similarity
+                        + CASE WHEN same_first_line THEN 0.1 ELSE 0 END AS score
+                FROM pairs
+            )
+            SELECT
+                CASE WHEN keep_a THEN a_id ELSE b_id END AS keep_id,
+                CASE WHEN keep_a THEN b_id ELSE a_id END AS merge_id,
+                score, content_similarity, title_similarity, same_first_line, same_songbook
+            FROM scored
+            WHERE score >= $2
+            ORDER BY score DESC, keep_id, merge_id
+            LIMIT $3
+            "#
+        )
+        .bind(songbook_id)
+        .bind(min_score)
+        .bind(limit)
+        .bind(CONTENT_WEIGHT)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let ids: Vec<Uuid> = pairs.iter().flat_map(|p| [p.keep_id, p.merge_id]).collect();
+        let songs = self.summaries(&ids).await?;
+
+        let duplicates = pairs
+            .into_iter()
+            .filter_map(|pair| {
+                Some(DuplicateSongs {
+                    keep:               songs.get(&pair.keep_id)?.clone(),
+                    merge:              songs.get(&pair.merge_id)?.clone(),
+                    score:              pair.score,
+                    content_similarity: pair.content_similarity,
+                    title_similarity:   pair.title_similarity,
+                    same_first_line:    pair.same_first_line,
+                    same_songbook:      pair.same_songbook
+                })
+            })
+            .collect();
+
+        Ok(duplicates)
+    }
+
+    async fn summaries(&self, ids: &[Uuid]) -> AppResult<HashMap<Uuid, SongSummary>> {
+        let songs = sqlx::query_as::<_, SongSummaryRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count, false as is_favorite,
+                ARRAY(
+                    SELECT sc.category FROM song_categories sc WHERE sc.song_id = s.id
+                ) as categories
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            WHERE s.id = ANY($1)
+            "#
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|row| (row.id, row.into())).collect())
+    }
+
+    /// Move everything that refers to `duplicate` onto `keep`, then delete
+    /// `duplicate`.
+    ///
+    /// Favorites, history, playlist items, view events, live sessions and
+    /// attachments follow the surviving song, as do its tags, categories and
+    /// Scripture links; rows `keep` already has are dropped.
+    /// Songbooks of two songs, in argument order
+    pub async fn songbooks_of(
+        &self,
+        keep: Uuid,
+        duplicate: Uuid
+    ) -> AppResult<(Option<Uuid>, Option<Uuid>)> {
+        let rows = sqlx::query_as::<_, (Uuid, Option<Uuid>)>(
+            "SELECT id, songbook_id FROM songs WHERE id = $1 OR id = $2"
+        )
+        .bind(keep)
+        .bind(duplicate)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let songbook_of = |id: Uuid| {
+            rows.iter()
+                .find(|(song, _)| *song == id)
+                .map(|(_, songbook)| *songbook)
+                .ok_or_else(|| AppError::not_found("Song not found"))
+        };
+        Ok((songbook_of(keep)?, songbook_of(duplicate)?))
+    }
+
+    pub async fn merge(&self, keep: Uuid, duplicate: Uuid) -> AppResult<SongMerge> {
+        let mut tx = self.pool.begin().await?;
+
+        let found = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM (SELECT 1 FROM songs WHERE id = $1 OR id = $2 FOR UPDATE) s"
+        )
+        .bind(keep)
+        .bind(duplicate)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if found != 2 {
+            return Err(AppError::not_found("Song not found"));
+        }
+
+        let favorites_moved = sqlx::query(
+            r#"
+            INSERT INTO user_favorite_songs (user_id, song_id, created_at)
+            SELECT user_id, $1, created_at FROM user_favorite_songs WHERE song_id = $2
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(keep)
+        .bind(duplicate)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let history_moved =
+            sqlx::query("UPDATE user_song_history SET song_id = $1 WHERE song_id = $2")
+                .bind(keep)
+                .bind(duplicate)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+        let playlist_items_moved =
+            sqlx::query("UPDATE song_playlist_items SET song_id = $1 WHERE song_id = $2")
+                .bind(keep)
+                .bind(duplicate)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+
+        sqlx::query("UPDATE live_sessions SET song_id = $1 WHERE song_id = $2")
+            .bind(keep)
+            .bind(duplicate)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE song_edition_numbers e SET song_id = $1
+            WHERE e.song_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM song_edition_numbers k
+                    WHERE k.edition_id = e.edition_id AND k.song_id = $1
+                )
+            "#
+        )
+        .bind(keep)
+        .bind(duplicate)
+        .execute(&mut *tx)
+        .await?;
+
+        merge_family_membership(&mut tx, keep, duplicate).await?;
+
+        for statement in [
+            "UPDATE song_attachments SET song_id = $1 WHERE song_id = $2",
+            r#"
+            INSERT INTO song_view_events (song_id, viewer, viewed_on, viewed_at)
+            SELECT $1, viewer, viewed_on, viewed_at FROM song_view_events WHERE song_id = $2
+            ON CONFLICT DO NOTHING
+            "#,
+            r#"
+            INSERT INTO song_tag_assignments (song_id, tag_id)
+            SELECT $1, tag_id FROM song_tag_assignments WHERE song_id = $2
+            ON CONFLICT DO NOTHING
+            "#,
+            r#"
+            INSERT INTO song_categories (song_id, category)
+            SELECT $1, category FROM song_categories WHERE song_id = $2
+            ON CONFLICT DO NOTHING
+            "#,
+            r#"
+            INSERT INTO song_scripture_refs
+                (song_id, book_id, chapter, verse_start, verse_end, note, created_by)
+            SELECT $1, book_id, chapter, verse_start, verse_end, note, created_by
+            FROM song_scripture_refs WHERE song_id = $2
+            ON CONFLICT DO NOTHING
+            "#,
+            r#"
+            UPDATE songs SET
+                views_count = views_count + (SELECT views_count FROM songs WHERE id = $2)
+            WHERE id = $1
+            "#
+        ] {
+            sqlx::query(statement)
+                .bind(keep)
+                .bind(duplicate)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let (songbook_id, number) = sqlx::query_as::<_, (Option<Uuid>, Option<i32>)>(
+            "DELETE FROM songs WHERE id = $1 RETURNING songbook_id, number"
+        )
+        .bind(duplicate)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // The kept song takes the duplicate's number when it has none
+        sqlx::query(
+            r#"
+            UPDATE songs SET number = $2
+            WHERE id = $1 AND number IS NULL AND songbook_id IS NOT DISTINCT FROM $3
+            "#
+        )
+        .bind(keep)
+        .bind(number)
+        .bind(songbook_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(SongMerge {
+            keep_id: keep,
+            merged_id: duplicate,
+            favorites_moved,
+            history_moved,
+            playlist_items_moved
+        })
+    }
+}
+
+/// Hand the family membership of `duplicate` to `keep`.
+///
+/// A kept song outside any family takes the duplicate's place; one in the
+/// same family inherits its original flag. A family the duplicate leaves
+/// with a single song is dissolved.
+async fn merge_family_membership(
+    conn: &mut PgConnection,
+    keep: Uuid,
+    duplicate: Uuid
+) -> AppResult<()> {
+    let taken_over = sqlx::query(
+        r#"
+        UPDATE song_family_members SET song_id = $1
+        WHERE song_id = $2
+            AND NOT EXISTS (SELECT 1 FROM song_family_members WHERE song_id = $1)
+        "#
+    )
+    .bind(keep)
+    .bind(duplicate)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    if taken_over > 0 {
+        return Ok(());
+    }
+
+    let Some((family_id, was_original)) = sqlx::query_as::<_, (Uuid, bool)>(
+        "DELETE FROM song_family_members WHERE song_id = $1 RETURNING family_id, is_original"
+    )
+    .bind(duplicate)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    if was_original {
+        sqlx::query(
+            "UPDATE song_family_members SET is_original = true \
+             WHERE song_id = $1 AND family_id = $2"
+        )
+        .bind(keep)
+        .bind(family_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"
+        DELETE FROM song_families f
+        WHERE f.id = $1
+            AND (SELECT COUNT(*) FROM song_family_members m WHERE m.family_id = f.id) < 2
+        "#
+    )
+    .bind(family_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::FamilySongRow;
+use crate::domain::{FamilySong, LinkSongFamily};
+
+/// PostgreSQL song families
+pub struct PgSongFamilies {
+    pool: PgPool
+}
+
+impl PgSongFamilies {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    pub async fn family_of(&self, song_id: Uuid) -> AppResult<Option<Uuid>> {
+        let family_id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT family_id FROM song_family_members WHERE song_id = $1"
+        )
+        .bind(song_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(family_id)
+    }
+
+    /// Visible members of a family, the original first
+    pub async fn family_members(
+        &self,
+        family_id: Uuid,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<FamilySong>> {
+        let members = sqlx::query_as::<_, FamilySongRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY(
+                    SELECT sc.category FROM song_categories sc WHERE sc.song_id = s.id
+                ) as categories,
+                m.language, m.is_original
+            FROM song_family_members m
+            JOIN songs s ON s.id = m.song_id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            WHERE m.family_id = $1
+                AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2))
+            ORDER BY m.is_original DESC, m.language, sb.code NULLS LAST, s.number, s.title
+            "#
+        )
+        .bind(family_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(members.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Put a song into the family of `link.related_to`, returning the family.
+    ///
+    /// Whichever of the two songs already has a family lends it to the
+    /// other; with two families, the song's family is merged into the
+    /// related one, which keeps its original if it has one.
+    ///
+    /// Both songs are locked in ID order first, so concurrent links of the
+    /// same songs run one after another and the later one joins the family
+    /// the earlier one created.
+    pub async fn link(&self, song_id: Uuid, link: &LinkSongFamily) -> AppResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+
+        let locked = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM songs WHERE id = $1 OR id = $2 ORDER BY id FOR NO KEY UPDATE"
+        )
+        .bind(song_id)
+        .bind(link.related_to)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if locked.len() != 2 {
+            return Err(AppError::not_found("Song not found"));
+        }
+
+        let families = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            SELECT song_id, family_id FROM song_family_members
+            WHERE song_id = $1 OR song_id = $2
+            FOR UPDATE
+            "#
+        )
+        .bind(song_id)
+        .bind(link.related_to)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let family_of = |id: Uuid| {
+            families
+                .iter()
+                .find(|(song, _)| *song == id)
+                .map(|(_, family)| *family)
+        };
+        let song_family = family_of(song_id);
+        let related_family = family_of(link.related_to);
+
+        let family_id = match related_family.or(song_family) {
+            Some(family_id) => family_id,
+            None => {
+                sqlx::query_scalar::<_, Uuid>(
+                    "INSERT INTO song_families DEFAULT VALUES RETURNING id"
+                )
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+
+        if related_family.is_none() {
+            sqlx::query(
+                r#"
+                INSERT INTO song_family_members (song_id, family_id, language)
+                SELECT s.id, $2, COALESCE(sb.language, 'ru')
+                FROM songs s
+                LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+                WHERE s.id = $1
+                "#
+            )
+            .bind(link.related_to)
+            .bind(family_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(merged) = song_family.filter(|f| *f != family_id) {
+            sqlx::query(
+                r#"
+                UPDATE song_family_members SET
+                    family_id = $2,
+                    is_original = is_original AND NOT EXISTS (
+                        SELECT 1 FROM song_family_members
+                        WHERE family_id = $2 AND is_original
+                    )
+                WHERE family_id = $1
+                "#
+            )
+            .bind(merged)
+            .bind(family_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM song_families WHERE id = $1")
+                .bind(merged)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if link.is_original {
+            sqlx::query(
+                r#"
+                UPDATE song_family_members SET is_original = false
+                WHERE family_id = $1 AND song_id <> $2 AND is_original
+                "#
+            )
+            .bind(family_id)
+            .bind(song_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO song_family_members (song_id, family_id, language, is_original)
+            SELECT s.id, $2, COALESCE($3, sb.language, 'ru'), $4
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            WHERE s.id = $1
+            ON CONFLICT (song_id) DO UPDATE SET
+                family_id = EXCLUDED.family_id,
+                language = COALESCE($3, song_family_members.language),
+                is_original = EXCLUDED.is_original
+            "#
+        )
+        .bind(song_id)
+        .bind(family_id)
+        .bind(&link.language)
+        .bind(link.is_original)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(family_id)
+    }
+
+    /// Take a song out of its family; a family left with a single song is
+    /// dissolved
+    pub async fn unlink(&self, song_id: Uuid) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let family_id = sqlx::query_scalar::<_, Uuid>(
+            "DELETE FROM song_family_members WHERE song_id = $1 RETURNING family_id"
+        )
+        .bind(song_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Song is not in a family"))?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM song_families f
+            WHERE f.id = $1
+                AND (SELECT COUNT(*) FROM song_family_members m WHERE m.family_id = f.id) < 2
+            "#
+        )
+        .bind(family_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::LiveSessionRow;
+use crate::domain::{LiveSession, LiveState};
+
+/// PostgreSQL live presentation sessions.
+///
+/// Every change fires `NOTIFY worship_live` (see `013_live_sessions.sql`).
+pub struct PgLiveSessions {
+    pool: PgPool
+}
+
+impl PgLiveSessions {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Current session state with the song being shown
+    pub async fn get_session(&self, playlist_id: Uuid) -> AppResult<Option<LiveSession>> {
+        let session = sqlx::query_as::<_, LiveSessionRow>(
+            r#"
+            SELECT
+                ls.playlist_id, ls.leader_id, ls.song_id, ls.section, ls.transpose, ls.blank,
+                ls.updated_at, s.title, s.content, s.original_key
+            FROM live_sessions ls
+            LEFT JOIN songs s ON s.id = ls.song_id
+            WHERE ls.playlist_id = $1
+            "#
+        )
+        .bind(playlist_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(session.map(|r| r.into()))
+    }
+
+    /// Change the session state, starting a session when there is none.
+    ///
+    /// The playlist row stays locked until the new state is written, so
+    /// concurrent leader commands apply one after another instead of
+    /// overwriting each other.
+    pub async fn update_session(
+        &self,
+        playlist_id: Uuid,
+        leader_id: Uuid,
+        change: impl FnOnce(LiveState) -> LiveState
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM song_playlists WHERE id = $1 FOR NO KEY UPDATE"
+        )
+        .bind(playlist_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist not found"))?;
+
+        let current = sqlx::query_as::<_, (Option<Uuid>, i32, i16, bool)>(
+            "SELECT song_id, section, transpose, blank FROM live_sessions WHERE playlist_id = $1"
+        )
+        .bind(playlist_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|(song_id, section, transpose, blank)| LiveState {
+            song_id,
+            section,
+            transpose,
+            blank
+        })
+        .unwrap_or_default();
+        let next = change(current);
+
+        sqlx::query(
+            r#"
+            INSERT INTO live_sessions (playlist_id, leader_id, song_id, section, transpose, blank)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (playlist_id) DO UPDATE SET
+                leader_id = EXCLUDED.leader_id,
+                song_id = EXCLUDED.song_id,
+                section = EXCLUDED.section,
+                transpose = EXCLUDED.transpose,
+                blank = EXCLUDED.blank,
+                updated_at = NOW()
+            "#
+        )
+        .bind(playlist_id)
+        .bind(leader_id)
+        .bind(next.song_id)
+        .bind(next.section)
+        .bind(next.transpose)
+        .bind(next.blank)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn end_session(&self, playlist_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM live_sessions WHERE playlist_id = $1")
+            .bind(playlist_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("No live session for this playlist"));
+        }
+        Ok(())
+    }
+
+    /// Song and transposition of a playlist item
+    pub async fn find_item(
+        &self,
+        playlist_id: Uuid,
+        item_id: Uuid
+    ) -> AppResult<Option<(Uuid, i16)>> {
+        let item = sqlx::query_as::<_, (Uuid, i16)>(
+            r#"
+            SELECT song_id, transpose_semitones FROM song_playlist_items
+            WHERE playlist_id = $1 AND id = $2
+            "#
+        )
+        .bind(playlist_id)
+        .bind(item_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(item)
+    }
+
+    /// Transposition of the first playlist item with the song
+    pub async fn find_song_item(
+        &self,
+        playlist_id: Uuid,
+        song_id: Uuid
+    ) -> AppResult<Option<i16>> {
+        let transpose = sqlx::query_scalar::<_, i16>(
+            r#"
+            SELECT transpose_semitones FROM song_playlist_items
+            WHERE playlist_id = $1 AND song_id = $2
+            ORDER BY position
+            LIMIT 1
+            "#
+        )
+        .bind(playlist_id)
+        .bind(song_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(transpose)
+    }
+}
@@ -1,18 +1,45 @@
+mod access;
+mod attachments;
+mod bible_passages;
+mod duplicates;
+mod families;
 mod favorites;
 mod history;
+mod live;
+mod passages;
 mod playlist;
+mod popularity;
+mod recommendations;
+mod reports;
 mod rows;
+mod scripture;
 mod search;
 mod song_read;
 mod song_write;
 mod songbook_read;
+mod songbook_write;
+mod submissions;
 mod tags;
+mod worship_team;
 
+pub use access::*;
+pub use attachments::*;
+pub use duplicates::*;
+pub use families::*;
 pub use favorites::*;
 pub use history::*;
+pub use live::*;
+pub use passages::*;
 pub use playlist::*;
+pub use popularity::*;
+pub use recommendations::*;
+pub use reports::*;
+pub use scripture::*;
 pub use search::*;
 pub use song_read::*;
 pub use song_write::*;
 pub use songbook_read::*;
+pub use songbook_write::*;
+pub use submissions::*;
 pub use tags::*;
+pub use worship_team::*;
@@ -0,0 +1,106 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    bible_passages::{PassageTable, ensure_passage_exists, passage_verses},
+    rows::PlaylistPassageRow
+};
+use crate::domain::{AddPlaylistPassage, PlaylistPassage};
+
+/// PostgreSQL Bible passages of playlists
+pub struct PgPlaylistPassages {
+    pool: PgPool
+}
+
+impl PgPlaylistPassages {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Passages of a playlist with their verses, in the order they were added
+    pub async fn list_passages(&self, playlist_id: Uuid) -> AppResult<Vec<PlaylistPassage>> {
+        let rows = sqlx::query_as::<_, PlaylistPassageRow>(
+            r#"
+            SELECT
+                p.id, p.after_item_id, p.book_id, b.name_ru as book_name, b.abbreviation,
+                p.chapter, p.verse_start, p.verse_end, p.notes
+            FROM song_playlist_passages p
+            JOIN bible_books b ON b.id = p.book_id
+            WHERE p.playlist_id = $1
+            ORDER BY p.created_at, p.id
+            "#
+        )
+        .bind(playlist_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut verses =
+            passage_verses(&self.pool, PassageTable::PlaylistPassages, playlist_id).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let mut passage = PlaylistPassage::from(row);
+                passage.verses = verses.remove(&passage.id).unwrap_or_default();
+                passage
+            })
+            .collect())
+    }
+
+    /// Add a passage; every verse of the range must exist
+    pub async fn add_passage(
+        &self,
+        playlist_id: Uuid,
+        passage: &AddPlaylistPassage
+    ) -> AppResult<Uuid> {
+        let verse_end = passage.verse_end.unwrap_or(passage.verse_start);
+        ensure_passage_exists(
+            &self.pool,
+            passage.book_id,
+            passage.chapter,
+            passage.verse_start,
+            verse_end
+        )
+        .await?;
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO song_playlist_passages
+                (playlist_id, after_item_id, book_id, chapter, verse_start, verse_end, notes)
+            SELECT $1, $2, $3, $4, $5, $6, $7
+            WHERE $2::uuid IS NULL OR EXISTS (
+                SELECT 1 FROM song_playlist_items WHERE id = $2 AND playlist_id = $1
+            )
+            RETURNING id
+            "#
+        )
+        .bind(playlist_id)
+        .bind(passage.after_item_id)
+        .bind(passage.book_id)
+        .bind(passage.chapter)
+        .bind(passage.verse_start)
+        .bind(verse_end)
+        .bind(&passage.notes)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        id.ok_or_else(|| AppError::validation("Item is not in this playlist"))
+    }
+
+    pub async fn remove_passage(&self, playlist_id: Uuid, passage_id: Uuid) -> AppResult<()> {
+        let result =
+            sqlx::query("DELETE FROM song_playlist_passages WHERE id = $1 AND playlist_id = $2")
+                .bind(passage_id)
+                .bind(playlist_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Passage not found"));
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,21 @@
-use masterror::AppResult;
+use chrono::NaiveDate;
+use masterror::prelude::*;
 use revelation_songbook::{
     AddToPlaylist, CreatePlaylist, PlaylistItem, SongCategory, SongPlaylist, SongSummary,
     ports::PlaylistRepository
 };
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+use super::rows::PlaylistRow;
+use crate::domain::{DuplicatePlaylist, UpdatePlaylist, UpdatePlaylistItem};
+
+const PLAYLIST_COLUMNS: &str = r#"
+    p.id, p.user_id, p.church_id, p.name, p.description, p.is_public, p.event_date,
+    (SELECT COUNT(*) FROM song_playlist_items WHERE playlist_id = p.id)::int as songs_count,
+    p.created_at, p.updated_at
+"#;
+
 /// PostgreSQL implementation of PlaylistRepository
 pub struct PgPlaylistRepository {
     pool: PgPool
@@ -17,6 +27,323 @@ impl PgPlaylistRepository {
             pool
         }
     }
+
+    /// Owner and church of the playlist
+    pub async fn get_playlist_access(&self, id: Uuid) -> AppResult<(Uuid, Option<Uuid>)> {
+        sqlx::query_as::<_, (Uuid, Option<Uuid>)>(
+            "SELECT user_id, church_id FROM song_playlists WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist not found"))
+    }
+
+    /// Playlist if visible to the viewer (see `playlist_visible`)
+    pub async fn get_visible_playlist(
+        &self,
+        id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<SongPlaylist> {
+        let playlist = sqlx::query_as::<_, PlaylistRow>(&format!(
+            r#"
+            SELECT {PLAYLIST_COLUMNS}
+            FROM song_playlists p
+            WHERE p.id = $1 AND playlist_visible(p.user_id, p.church_id, p.is_public, $2)
+            "#
+        ))
+        .bind(id)
+        .bind(viewer)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist not found"))?;
+
+        Ok(playlist.into())
+    }
+
+    /// Playlist behind a public share link
+    pub async fn get_shared_playlist(&self, token: &str) -> AppResult<SongPlaylist> {
+        let playlist = sqlx::query_as::<_, PlaylistRow>(&format!(
+            "SELECT {PLAYLIST_COLUMNS} FROM song_playlists p WHERE p.share_token = $1"
+        ))
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Shared playlist not found"))?;
+
+        Ok(playlist.into())
+    }
+
+    /// Church setlists with an event date in `from..=to` visible to the viewer
+    pub async fn list_church_setlists(
+        &self,
+        church_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<SongPlaylist>> {
+        let setlists = sqlx::query_as::<_, PlaylistRow>(&format!(
+            r#"
+            SELECT {PLAYLIST_COLUMNS}
+            FROM song_playlists p
+            WHERE p.church_id = $1
+              AND p.event_date BETWEEN $2 AND $3
+              AND playlist_visible(p.user_id, p.church_id, p.is_public, $4)
+            ORDER BY p.event_date, p.created_at
+            "#
+        ))
+        .bind(church_id)
+        .bind(from)
+        .bind(to)
+        .bind(viewer)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(setlists.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Items of a playlist; `viewer` only marks favorites
+    pub async fn list_items(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<PlaylistItem>> {
+        let items = sqlx::query_as::<_, PlaylistItemRow>(
+            r#"
+            SELECT
+                pi.id, pi.position, pi.transpose_semitones, pi.notes,
+                s.id as song_id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
+            FROM song_playlist_items pi
+            JOIN songs s ON pi.song_id = s.id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN song_categories sc ON s.id = sc.song_id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            WHERE pi.playlist_id = $1
+            GROUP BY pi.id, s.id, sb.code, uf.user_id
+            ORDER BY pi.position
+            "#,
+        )
+        .bind(playlist_id)
+        .bind(viewer)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(items.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Current share token of the playlist
+    pub async fn get_share_token(&self, id: Uuid) -> AppResult<Option<String>> {
+        let token = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT share_token FROM song_playlists WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist not found"))?;
+
+        Ok(token)
+    }
+
+    /// Set or revoke (`None`) the share token of the playlist
+    pub async fn set_share_token(&self, id: Uuid, token: Option<&str>) -> AppResult<()> {
+        let result = sqlx::query("UPDATE song_playlists SET share_token = $2 WHERE id = $1")
+            .bind(id)
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Playlist not found"));
+        }
+        Ok(())
+    }
+
+    pub async fn update_playlist(&self, id: Uuid, playlist: &UpdatePlaylist) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE song_playlists SET
+                name = COALESCE($2, name),
+                description = COALESCE($3, description),
+                is_public = COALESCE($4, is_public),
+                event_date = COALESCE($5, event_date)
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(&playlist.name)
+        .bind(&playlist.description)
+        .bind(playlist.is_public)
+        .bind(playlist.event_date)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Playlist not found"));
+        }
+        Ok(())
+    }
+
+    /// Update item settings and optionally move it to a new position
+    pub async fn update_playlist_item(
+        &self,
+        playlist_id: Uuid,
+        item_id: Uuid,
+        item: &UpdatePlaylistItem
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let mut order = lock_playlist(&mut tx, playlist_id).await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE song_playlist_items SET
+                transpose_semitones = COALESCE($3, transpose_semitones),
+                notes = COALESCE($4, notes)
+            WHERE id = $1 AND playlist_id = $2
+            "#
+        )
+        .bind(item_id)
+        .bind(playlist_id)
+        .bind(item.transpose_semitones)
+        .bind(&item.notes)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Playlist item not found"));
+        }
+
+        if let Some(position) = item.position {
+            let current = order.iter().position(|id| *id == item_id);
+            if let Some(current) = current {
+                let id = order.remove(current);
+                let target = (position as usize - 1).min(order.len());
+                order.insert(target, id);
+                write_positions(&mut tx, playlist_id, &order).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Reorder the playlist to match `item_ids`, which must list every item
+    /// exactly once
+    pub async fn reorder_playlist(&self, playlist_id: Uuid, item_ids: &[Uuid]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let mut current = lock_playlist(&mut tx, playlist_id).await?;
+
+        let mut requested = item_ids.to_vec();
+        current.sort();
+        requested.sort();
+        if current != requested {
+            return Err(AppError::validation(
+                "Reorder must list every playlist item exactly once"
+            ));
+        }
+
+        write_positions(&mut tx, playlist_id, item_ids).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Copy a playlist the user can see, including items and their settings
+    pub async fn duplicate_playlist(
+        &self,
+        template_id: Uuid,
+        user_id: Uuid,
+        copy: &DuplicatePlaylist
+    ) -> AppResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO song_playlists (user_id, church_id, name, description, is_public, event_date)
+            SELECT $2, COALESCE($4, p.church_id), COALESCE($3, p.name), p.description, false, $5
+            FROM song_playlists p
+            WHERE p.id = $1 AND playlist_visible(p.user_id, p.church_id, p.is_public, $2)
+            RETURNING id
+            "#
+        )
+        .bind(template_id)
+        .bind(user_id)
+        .bind(&copy.name)
+        .bind(copy.church_id)
+        .bind(copy.event_date)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist not found"))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO song_playlist_items (playlist_id, song_id, position, transpose_semitones, notes)
+            SELECT $1, song_id, ROW_NUMBER() OVER (ORDER BY position)::smallint, transpose_semitones, notes
+            FROM song_playlist_items
+            WHERE playlist_id = $2
+            "#
+        )
+        .bind(id)
+        .bind(template_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(id)
+    }
+}
+
+/// Lock the playlist row for the rest of the transaction and return its
+/// item ids in order.
+///
+/// Every change to item positions goes through this lock, so concurrent
+/// edits of the same playlist are serialized instead of racing on the
+/// `(playlist_id, position)` constraint.
+async fn lock_playlist(conn: &mut PgConnection, playlist_id: Uuid) -> AppResult<Vec<Uuid>> {
+    sqlx::query_scalar::<_, Uuid>(
+        "UPDATE song_playlists SET updated_at = NOW() WHERE id = $1 RETURNING id"
+    )
+    .bind(playlist_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::not_found("Playlist not found"))?;
+
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        "SELECT id FROM song_playlist_items WHERE playlist_id = $1 ORDER BY position"
+    )
+    .bind(playlist_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(ids)
+}
+
+/// Renumber items 1..n in the given order
+async fn write_positions(
+    conn: &mut PgConnection,
+    playlist_id: Uuid,
+    item_ids: &[Uuid]
+) -> AppResult<()> {
+    sqlx::query("SET CONSTRAINTS song_playlist_items_playlist_id_position_key DEFERRED")
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE song_playlist_items pi SET position = o.position::smallint
+        FROM UNNEST($2::uuid[]) WITH ORDINALITY AS o(id, position)
+        WHERE pi.id = o.id AND pi.playlist_id = $1
+        "#
+    )
+    .bind(playlist_id)
+    .bind(item_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
 }
 
 #[derive(sqlx::FromRow)]
@@ -112,23 +439,7 @@ impl PlaylistRepository for PgPlaylistRepository {
     }
 
     async fn get_playlist(&self, id: Uuid, user_id: Uuid) -> AppResult<SongPlaylist> {
-        let playlist = sqlx::query_as!(
-            SongPlaylist,
-            r#"
-            SELECT
-                p.id, p.user_id, p.church_id, p.name, p.description, p.is_public, p.event_date,
-                (SELECT COUNT(*) FROM song_playlist_items WHERE playlist_id = p.id)::int as "songs_count!",
-                p.created_at, p.updated_at
-            FROM song_playlists p
-            WHERE p.id = $1 AND (p.user_id = $2 OR p.is_public = true)
-            "#,
-            id,
-            user_id
-        )
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(playlist)
+        self.get_visible_playlist(id, Some(user_id)).await
     }
 
     async fn get_playlist_items(
@@ -136,39 +447,18 @@ impl PlaylistRepository for PgPlaylistRepository {
         playlist_id: Uuid,
         user_id: Uuid
     ) -> AppResult<Vec<PlaylistItem>> {
-        let items = sqlx::query_as::<_, PlaylistItemRow>(
-            r#"
-            SELECT
-                pi.id, pi.position, pi.transpose_semitones, pi.notes,
-                s.id as song_id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
-                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
-                s.views_count, s.favorites_count,
-                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
-                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
-            FROM song_playlist_items pi
-            JOIN songs s ON pi.song_id = s.id
-            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
-            LEFT JOIN song_categories sc ON s.id = sc.song_id
-            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
-            WHERE pi.playlist_id = $1
-            GROUP BY pi.id, s.id, sb.code, uf.user_id
-            ORDER BY pi.position
-            "#,
-        )
-        .bind(playlist_id)
-        .bind(user_id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        Ok(items.into_iter().map(|r| r.into()).collect())
+        self.list_items(playlist_id, Some(user_id)).await
     }
 
     async fn add_to_playlist(&self, playlist_id: Uuid, item: AddToPlaylist) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        lock_playlist(&mut tx, playlist_id).await?;
+
         let next_pos = sqlx::query_scalar::<_, i16>(
-            "SELECT COALESCE(MAX(position), 0) + 1 FROM song_playlist_items WHERE playlist_id = $1"
+            "SELECT (COALESCE(MAX(position), 0) + 1)::smallint FROM song_playlist_items WHERE playlist_id = $1"
         )
         .bind(playlist_id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         sqlx::query(
@@ -182,9 +472,10 @@ impl PlaylistRepository for PgPlaylistRepository {
         .bind(next_pos)
         .bind(item.transpose_semitones.unwrap_or(0))
         .bind(&item.notes)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
@@ -0,0 +1,112 @@
+use masterror::AppResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::PopularSongRow;
+use crate::domain::{PopularSong, PopularityOrder};
+
+/// View events older than this no longer count towards any window
+const VIEW_RETENTION_DAYS: i32 = 30;
+
+/// `song_popularity` column a popularity ordering sorts by
+fn popularity_column(order: PopularityOrder) -> &'static str {
+    match order {
+        PopularityOrder::Popular7d => "views_7d",
+        PopularityOrder::Popular30d => "views_30d",
+        PopularityOrder::Trending => "trending_score",
+        PopularityOrder::MostSungMonth => "sung_month"
+    }
+}
+
+/// PostgreSQL view events and rolling popularity aggregates
+pub struct PgSongPopularity {
+    pool: PgPool
+}
+
+impl PgSongPopularity {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Count a view unless the viewer already viewed the song today.
+    ///
+    /// Returns whether the view was counted.
+    pub async fn record_view(&self, song_id: Uuid, viewer: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            WITH counted AS (
+                INSERT INTO song_view_events (song_id, viewer)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                RETURNING song_id
+            )
+            UPDATE songs SET views_count = views_count + 1
+            WHERE id IN (SELECT song_id FROM counted)
+            "#
+        )
+        .bind(song_id)
+        .bind(viewer)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Recompute the aggregates and drop view events outside every window
+    pub async fn refresh(&self) -> AppResult<()> {
+        sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY song_popularity")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("DELETE FROM song_view_events WHERE viewed_on <= CURRENT_DATE - $1")
+            .bind(VIEW_RETENTION_DAYS)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Songs visible to the viewer with a positive value of the ordering's
+    /// aggregate, highest first
+    pub async fn list_popular(
+        &self,
+        order: PopularityOrder,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<PopularSong>> {
+        let column = popularity_column(order);
+        let songs = sqlx::query_as::<_, PopularSongRow>(&format!(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY(
+                    SELECT sc.category FROM song_categories sc WHERE sc.song_id = s.id
+                ) as categories,
+                sp.views_7d, sp.views_30d, sp.sung_7d, sp.sung_30d, sp.sung_month,
+                sp.trending_score, sp.refreshed_at
+            FROM song_popularity sp
+            JOIN songs s ON s.id = sp.song_id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $1
+            WHERE sp.{column} > 0
+                AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $1))
+                AND ($2::uuid IS NULL OR s.songbook_id = $2)
+            ORDER BY sp.{column} DESC, sp.views_30d DESC, s.title
+            LIMIT $3
+            "#
+        ))
+        .bind(user_id)
+        .bind(songbook_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+}
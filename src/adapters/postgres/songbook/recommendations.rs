@@ -0,0 +1,153 @@
+use masterror::AppResult;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::SimilarSongRow;
+use crate::domain::SimilarSong;
+
+/// Largest tempo difference, in BPM, that still counts as a similar tempo
+const TEMPO_RANGE: i32 = 10;
+
+/// PostgreSQL song recommendations
+pub struct PgSongRecommendations {
+    pool: PgPool
+}
+
+impl PgSongRecommendations {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Songs most like the seed songs, excluding the seeds themselves.
+    ///
+    /// Each seed contributes 3 points per shared tag, 2 per shared category,
+    /// 1.5 for the same key and 1 for a tempo within [`TEMPO_RANGE`]. Playlists
+    /// and user histories holding both songs add logarithmically, so a few
+    /// popular pairings do not drown the catalog signals.
+    pub async fn similar_to(
+        &self,
+        seeds: &[Uuid],
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SimilarSong>> {
+        let songs = sqlx::query_as::<_, SimilarSongRow>(
+            r#"
+            WITH seeds AS (
+                SELECT id, original_key, tempo FROM songs WHERE id = ANY($1)
+            ),
+            categories AS (
+                SELECT o.song_id, COUNT(*)::int AS n
+                FROM song_categories t
+                JOIN song_categories o ON o.category = t.category
+                WHERE t.song_id = ANY($1) AND o.song_id <> ALL($1)
+                GROUP BY o.song_id
+            ),
+            tags AS (
+                SELECT o.song_id, COUNT(*)::int AS n
+                FROM song_tag_assignments t
+                JOIN song_tag_assignments o ON o.tag_id = t.tag_id
+                WHERE t.song_id = ANY($1) AND o.song_id <> ALL($1)
+                GROUP BY o.song_id
+            ),
+            sung AS (
+                SELECT o.song_id, COUNT(DISTINCT o.playlist_id)::int AS n
+                FROM song_playlist_items t
+                JOIN song_playlist_items o ON o.playlist_id = t.playlist_id
+                WHERE t.song_id = ANY($1) AND o.song_id <> ALL($1)
+                GROUP BY o.song_id
+            ),
+            viewed AS (
+                SELECT o.song_id, COUNT(DISTINCT o.user_id)::int AS n
+                FROM (
+                    SELECT DISTINCT user_id FROM user_song_history WHERE song_id = ANY($1)
+                ) t
+                JOIN user_song_history o ON o.user_id = t.user_id
+                WHERE o.song_id <> ALL($1)
+                GROUP BY o.song_id
+            ),
+            musical AS (
+                SELECT
+                    s.id AS song_id,
+                    COUNT(*) FILTER (WHERE s.original_key = seed.original_key)::int AS keys,
+                    COUNT(*) FILTER (WHERE ABS(s.tempo - seed.tempo) <= $4)::int AS tempos
+                FROM songs s
+                JOIN seeds seed
+                    ON s.original_key = seed.original_key OR ABS(s.tempo - seed.tempo) <= $4
+                WHERE s.id <> ALL($1)
+                GROUP BY s.id
+            ),
+            scored AS (
+                SELECT
+                    c.song_id,
+                    COALESCE(cat.n, 0) AS shared_categories,
+                    COALESCE(tag.n, 0) AS shared_tags,
+                    COALESCE(m.keys, 0) AS keys,
+                    COALESCE(m.tempos, 0) AS tempos,
+                    COALESCE(sung.n, 0) AS sung_together,
+                    COALESCE(viewed.n, 0) AS viewed_together
+                FROM (
+                    SELECT song_id FROM categories
+                    UNION SELECT song_id FROM tags
+                    UNION SELECT song_id FROM sung
+                    UNION SELECT song_id FROM viewed
+                    UNION SELECT song_id FROM musical
+                ) c
+                LEFT JOIN categories cat ON cat.song_id = c.song_id
+                LEFT JOIN tags tag ON tag.song_id = c.song_id
+                LEFT JOIN musical m ON m.song_id = c.song_id
+                LEFT JOIN sung ON sung.song_id = c.song_id
+                LEFT JOIN viewed ON viewed.song_id = c.song_id
+            )
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY(
+                    SELECT sc.category FROM song_categories sc WHERE sc.song_id = s.id
+                ) as categories,
+                (
+                    3.0 * x.shared_tags
+                    + 2.0 * x.shared_categories
+                    + 1.5 * x.keys
+                    + 1.0 * x.tempos
+                    + 2.0 * LN(1 + x.sung_together)
+                    + 1.0 * LN(1 + x.viewed_together)
+                )::float8 AS score,
+                x.shared_categories, x.shared_tags,
+                x.keys > 0 AS same_key,
+                x.tempos > 0 AS similar_tempo,
+                x.sung_together, x.viewed_together
+            FROM scored x
+            JOIN songs s ON s.id = x.song_id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            WHERE sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2)
+            ORDER BY score DESC, s.views_count DESC, s.title
+            LIMIT $3
+            "#
+        )
+        .bind(seeds)
+        .bind(user_id)
+        .bind(limit)
+        .bind(TEMPO_RANGE)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Favorite songs of a user, the seeds of their recommendations
+    pub async fn favorite_ids(&self, user_id: Uuid) -> AppResult<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT song_id FROM user_favorite_songs WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::NaiveDate;
+use masterror::AppResult;
+use revelation_songbook::SongSummary;
+use sqlx::PgPool;
+use tokio_stream::{Stream, StreamExt};
+use uuid::Uuid;
+
+use super::rows::{SongContentRow, SongSummaryRow, SongUsageRow};
+use crate::domain::SongUsage;
+
+/// PostgreSQL song reports for licensing and catalog maintenance
+pub struct PgSongReports {
+    pool: PgPool
+}
+
+impl PgSongReports {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Songs performed in church setlists with an event date in `from..=to`
+    pub async fn song_usage(
+        &self,
+        church_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate
+    ) -> AppResult<Vec<SongUsage>> {
+        let rows = sqlx::query_as::<_, SongUsageRow>(
+            r#"
+            SELECT
+                s.id as song_id, s.title, sb.code as songbook_code, s.number,
+                s.author_lyrics, s.author_music, s.translator, s.copyright,
+                COUNT(*) as times_used,
+                COUNT(DISTINCT p.id) as services,
+                MIN(p.event_date) as first_used,
+                MAX(p.event_date) as last_used
+            FROM song_playlists p
+            JOIN song_playlist_items i ON i.playlist_id = p.id
+            JOIN songs s ON s.id = i.song_id
+            LEFT JOIN songbooks sb ON sb.id = s.songbook_id
+            WHERE p.church_id = $1 AND p.event_date BETWEEN $2 AND $3
+            GROUP BY s.id, sb.code
+            ORDER BY times_used DESC, s.title
+            "#
+        )
+        .bind(church_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Songs without copyright information, by songbook and number
+    pub async fn missing_copyright(
+        &self,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<SongSummary>> {
+        let songs = sqlx::query_as::<_, SongSummaryRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count, false as is_favorite,
+                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN song_categories sc ON s.id = sc.song_id
+            WHERE NULLIF(TRIM(s.copyright), '') IS NULL
+              AND ($1::uuid IS NULL OR s.songbook_id = $1)
+            GROUP BY s.id, sb.code
+            ORDER BY sb.code NULLS LAST, s.number NULLS LAST, s.title
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(songbook_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Songs with chords together with their content, for checking keys
+    /// against the chords.
+    ///
+    /// Rows are streamed in catalog order, so callers needing only a page
+    /// can stop reading once they have it.
+    pub fn songs_with_chords(
+        &self,
+        songbook_id: Option<Uuid>
+    ) -> impl Stream<Item = AppResult<(SongSummary, String)>> + Send + '_ {
+        sqlx::query_as::<_, SongContentRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count, false as is_favorite,
+                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories,
+                s.content
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN song_categories sc ON s.id = sc.song_id
+            WHERE s.has_chords AND ($1::uuid IS NULL OR s.songbook_id = $1)
+            GROUP BY s.id, sb.code
+            ORDER BY sb.code NULLS LAST, s.number NULLS LAST, s.title
+            "#
+        )
+        .bind(songbook_id)
+        .fetch(&self.pool)
+        .map(|row| {
+            let row = row?;
+            Ok((row.song.into(), row.content))
+        })
+    }
+}
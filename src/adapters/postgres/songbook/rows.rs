@@ -1,6 +1,15 @@
-use revelation_songbook::{SongCategory, SongSearchResult, SongSummary};
+use chrono::{DateTime, NaiveDate, Utc};
+use revelation_songbook::{
+    SongCategory, SongPlaylist, SongSearchResult, SongSummary, SongTag, Songbook, SongbookEdition
+};
 use uuid::Uuid;
 
+use crate::domain::{
+    AttachmentKind, FamilySong, LiveSession, PlaylistPassage, PopularSong, ScriptureSong,
+    ScriptureSuggestion, SimilarSong, SongAttachment, SongPopularity, SongScripture,
+    SongSubmission, SongSuggestion, SongUsage, SubmissionStatus, WorshipTeamMember
+};
+
 /// Row type for song summary queries
 #[derive(sqlx::FromRow)]
 pub struct SongSummaryRow {
@@ -39,6 +48,155 @@ impl From<SongSummaryRow> for SongSummary {
     }
 }
 
+/// Row type for a song summary with the song's ChordPro content
+#[derive(sqlx::FromRow)]
+pub struct SongContentRow {
+    #[sqlx(flatten)]
+    pub song:    SongSummaryRow,
+    pub content: String
+}
+
+/// Row type for popularity lists: a song summary with its aggregates
+#[derive(sqlx::FromRow)]
+pub struct PopularSongRow {
+    #[sqlx(flatten)]
+    pub song:           SongSummaryRow,
+    pub views_7d:       i32,
+    pub views_30d:      i32,
+    pub sung_7d:        i32,
+    pub sung_30d:       i32,
+    pub sung_month:     i32,
+    pub trending_score: f64,
+    pub refreshed_at:   DateTime<Utc>
+}
+
+impl From<PopularSongRow> for PopularSong {
+    fn from(row: PopularSongRow) -> Self {
+        Self {
+            song:         row.song.into(),
+            popularity:   SongPopularity {
+                views_7d:       row.views_7d,
+                views_30d:      row.views_30d,
+                sung_7d:        row.sung_7d,
+                sung_30d:       row.sung_30d,
+                sung_month:     row.sung_month,
+                trending_score: row.trending_score
+            },
+            refreshed_at: row.refreshed_at
+        }
+    }
+}
+
+/// Row type for recommendations: a song summary with its similarity
+#[derive(sqlx::FromRow)]
+pub struct SimilarSongRow {
+    #[sqlx(flatten)]
+    pub song:              SongSummaryRow,
+    pub score:             f64,
+    pub shared_categories: i32,
+    pub shared_tags:       i32,
+    pub same_key:          bool,
+    pub similar_tempo:     bool,
+    pub sung_together:     i32,
+    pub viewed_together:   i32
+}
+
+impl From<SimilarSongRow> for SimilarSong {
+    fn from(row: SimilarSongRow) -> Self {
+        Self {
+            song:              row.song.into(),
+            score:             row.score,
+            shared_categories: row.shared_categories,
+            shared_tags:       row.shared_tags,
+            same_key:          row.same_key,
+            similar_tempo:     row.similar_tempo,
+            sung_together:     row.sung_together,
+            viewed_together:   row.viewed_together
+        }
+    }
+}
+
+/// Row type for song family members
+#[derive(sqlx::FromRow)]
+pub struct FamilySongRow {
+    #[sqlx(flatten)]
+    pub song:        SongSummaryRow,
+    pub language:    String,
+    pub is_original: bool
+}
+
+impl From<FamilySongRow> for FamilySong {
+    fn from(row: FamilySongRow) -> Self {
+        Self {
+            song:        row.song.into(),
+            language:    row.language,
+            is_original: row.is_original
+        }
+    }
+}
+
+/// Row type for likely duplicate song pairs
+#[derive(sqlx::FromRow)]
+pub struct DuplicatePairRow {
+    pub keep_id:            Uuid,
+    pub merge_id:           Uuid,
+    pub score:              f64,
+    pub content_similarity: f64,
+    pub title_similarity:   f64,
+    pub same_first_line:    bool,
+    pub same_songbook:      bool
+}
+
+/// Row type for song submissions
+#[derive(sqlx::FromRow)]
+pub struct SongSubmissionRow {
+    pub id:             Uuid,
+    pub submitter_id:   Uuid,
+    pub submitter_name: Option<String>,
+    pub status:         SubmissionStatus,
+    pub title:          String,
+    pub author_lyrics:  Option<String>,
+    pub author_music:   Option<String>,
+    pub translator:     Option<String>,
+    pub original_key:   Option<String>,
+    pub content:        String,
+    pub source_url:     Option<String>,
+    pub note:           Option<String>,
+    pub review_comment: Option<String>,
+    pub reviewed_by:    Option<Uuid>,
+    pub reviewed_at:    Option<DateTime<Utc>>,
+    pub song_id:        Option<Uuid>,
+    pub submitted_at:   Option<DateTime<Utc>>,
+    pub created_at:     DateTime<Utc>,
+    pub updated_at:     DateTime<Utc>
+}
+
+impl From<SongSubmissionRow> for SongSubmission {
+    fn from(row: SongSubmissionRow) -> Self {
+        Self {
+            id:             row.id,
+            submitter_id:   row.submitter_id,
+            submitter_name: row.submitter_name,
+            status:         row.status,
+            title:          row.title,
+            author_lyrics:  row.author_lyrics,
+            author_music:   row.author_music,
+            translator:     row.translator,
+            original_key:   row.original_key,
+            content:        row.content,
+            source_url:     row.source_url,
+            note:           row.note,
+            review_comment: row.review_comment,
+            reviewed_by:    row.reviewed_by,
+            reviewed_at:    row.reviewed_at,
+            song_id:        row.song_id,
+            submitted_at:   row.submitted_at,
+            created_at:     row.created_at,
+            updated_at:     row.updated_at
+        }
+    }
+}
+
 /// Row type for search queries
 #[derive(sqlx::FromRow)]
 pub struct SongSearchRow {
@@ -84,3 +242,434 @@ impl From<SongSearchRow> for SongSearchResult {
         }
     }
 }
+
+/// Row type for tag queries
+#[derive(sqlx::FromRow)]
+pub struct SongTagRow {
+    pub id:          Uuid,
+    pub name:        String,
+    pub name_ru:     String,
+    pub usage_count: i32
+}
+
+impl From<SongTagRow> for SongTag {
+    fn from(row: SongTagRow) -> Self {
+        Self {
+            id:          row.id,
+            name:        row.name,
+            name_ru:     row.name_ru,
+            usage_count: row.usage_count
+        }
+    }
+}
+
+/// Row type for songbook queries
+#[derive(sqlx::FromRow)]
+pub struct SongbookRow {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub name_ru: String,
+    pub description: Option<String>,
+    pub cover_url: Option<String>,
+    pub songs_count: i32,
+    pub songs_with_chords_count: i32,
+    pub is_public: bool,
+    pub year_first_published: Option<i16>,
+    pub year_latest_edition: Option<i16>,
+    pub edition_name: Option<String>,
+    pub total_songs_in_print: Option<i32>,
+    pub publisher: Option<String>,
+    pub editor: Option<String>,
+    pub isbn: Option<String>,
+    pub language: Option<String>,
+    pub country: Option<String>,
+    pub denomination: Option<String>,
+    pub website_url: Option<String>,
+    pub purchase_url: Option<String>,
+    pub history: Option<String>,
+    pub notes: Option<String>
+}
+
+impl From<SongbookRow> for Songbook {
+    fn from(row: SongbookRow) -> Self {
+        Self {
+            id: row.id,
+            code: row.code,
+            name: row.name,
+            name_ru: row.name_ru,
+            description: row.description,
+            cover_url: row.cover_url,
+            songs_count: row.songs_count,
+            songs_with_chords_count: row.songs_with_chords_count,
+            is_public: row.is_public,
+            year_first_published: row.year_first_published,
+            year_latest_edition: row.year_latest_edition,
+            edition_name: row.edition_name,
+            total_songs_in_print: row.total_songs_in_print,
+            publisher: row.publisher,
+            editor: row.editor,
+            isbn: row.isbn,
+            language: row.language,
+            country: row.country,
+            denomination: row.denomination,
+            website_url: row.website_url,
+            purchase_url: row.purchase_url,
+            history: row.history,
+            notes: row.notes
+        }
+    }
+}
+
+/// Row type for songbook edition queries
+#[derive(sqlx::FromRow)]
+pub struct SongbookEditionRow {
+    pub id:             Uuid,
+    pub songbook_id:    Uuid,
+    pub edition_name:   String,
+    pub year_published: i16,
+    pub songs_count:    i32,
+    pub publisher:      Option<String>,
+    pub isbn:           Option<String>,
+    pub notes:          Option<String>
+}
+
+impl From<SongbookEditionRow> for SongbookEdition {
+    fn from(row: SongbookEditionRow) -> Self {
+        Self {
+            id:             row.id,
+            songbook_id:    row.songbook_id,
+            edition_name:   row.edition_name,
+            year_published: row.year_published,
+            songs_count:    row.songs_count,
+            publisher:      row.publisher,
+            isbn:           row.isbn,
+            notes:          row.notes
+        }
+    }
+}
+
+/// Row type for song autocomplete queries
+#[derive(sqlx::FromRow)]
+pub struct SongSuggestionRow {
+    pub id:            Uuid,
+    pub title:         String,
+    pub first_line:    String,
+    pub songbook_code: Option<String>,
+    pub number:        Option<i32>
+}
+
+impl From<SongSuggestionRow> for SongSuggestion {
+    fn from(row: SongSuggestionRow) -> Self {
+        Self {
+            id:            row.id,
+            title:         row.title,
+            first_line:    row.first_line,
+            songbook_code: row.songbook_code,
+            number:        row.number
+        }
+    }
+}
+
+/// Row type for playlist queries
+#[derive(sqlx::FromRow)]
+pub struct PlaylistRow {
+    pub id:          Uuid,
+    pub user_id:     Uuid,
+    pub church_id:   Option<Uuid>,
+    pub name:        String,
+    pub description: Option<String>,
+    pub is_public:   bool,
+    pub event_date:  Option<NaiveDate>,
+    pub songs_count: i32,
+    pub created_at:  DateTime<Utc>,
+    pub updated_at:  DateTime<Utc>
+}
+
+impl From<PlaylistRow> for SongPlaylist {
+    fn from(row: PlaylistRow) -> Self {
+        Self {
+            id:          row.id,
+            user_id:     row.user_id,
+            church_id:   row.church_id,
+            name:        row.name,
+            description: row.description,
+            is_public:   row.is_public,
+            event_date:  row.event_date,
+            songs_count: row.songs_count,
+            created_at:  row.created_at,
+            updated_at:  row.updated_at
+        }
+    }
+}
+
+/// Row type for worship team queries
+#[derive(sqlx::FromRow)]
+pub struct WorshipTeamMemberRow {
+    pub user_id:  Uuid,
+    pub name:     Option<String>,
+    pub added_at: DateTime<Utc>
+}
+
+impl From<WorshipTeamMemberRow> for WorshipTeamMember {
+    fn from(row: WorshipTeamMemberRow) -> Self {
+        Self {
+            user_id:  row.user_id,
+            name:     row.name,
+            added_at: row.added_at
+        }
+    }
+}
+
+/// Row type for playlist passage queries, joined with the book
+#[derive(sqlx::FromRow)]
+pub struct PlaylistPassageRow {
+    pub id:            Uuid,
+    pub after_item_id: Option<Uuid>,
+    pub book_id:       i16,
+    pub book_name:     String,
+    pub abbreviation:  String,
+    pub chapter:       i16,
+    pub verse_start:   i16,
+    pub verse_end:     i16,
+    pub notes:         Option<String>
+}
+
+/// Reference such as `Ин 3:16-18`, or `Ин 3:16` for a single verse
+pub fn passage_reference(abbreviation: &str, chapter: i16, start: i16, end: i16) -> String {
+    if end > start {
+        format!("{abbreviation} {chapter}:{start}-{end}")
+    } else {
+        format!("{abbreviation} {chapter}:{start}")
+    }
+}
+
+impl From<PlaylistPassageRow> for PlaylistPassage {
+    fn from(row: PlaylistPassageRow) -> Self {
+        let reference = passage_reference(
+            &row.abbreviation,
+            row.chapter,
+            row.verse_start,
+            row.verse_end
+        );
+
+        Self {
+            id: row.id,
+            after_item_id: row.after_item_id,
+            book_id: row.book_id,
+            book_name: row.book_name,
+            chapter: row.chapter,
+            verse_start: row.verse_start,
+            verse_end: row.verse_end,
+            reference,
+            notes: row.notes,
+            verses: Vec::new()
+        }
+    }
+}
+
+/// Row type for verses of playlist and song passages
+#[derive(sqlx::FromRow)]
+pub struct PassageVerseRow {
+    pub passage_id: Uuid,
+    pub id:         i32,
+    pub book_id:    i16,
+    pub chapter:    i16,
+    pub verse:      i16,
+    pub text:       String
+}
+
+/// Row type for song Scripture links
+#[derive(sqlx::FromRow)]
+pub struct SongScriptureRow {
+    pub id:           Uuid,
+    pub song_id:      Uuid,
+    pub book_id:      i16,
+    pub book_name:    String,
+    pub abbreviation: String,
+    pub chapter:      i16,
+    pub verse_start:  i16,
+    pub verse_end:    i16,
+    pub note:         Option<String>
+}
+
+impl From<SongScriptureRow> for SongScripture {
+    fn from(row: SongScriptureRow) -> Self {
+        Self {
+            id:          row.id,
+            song_id:     row.song_id,
+            book_id:     row.book_id,
+            book_name:   row.book_name,
+            chapter:     row.chapter,
+            verse_start: row.verse_start,
+            verse_end:   row.verse_end,
+            reference:   passage_reference(
+                &row.abbreviation,
+                row.chapter,
+                row.verse_start,
+                row.verse_end
+            ),
+            note:        row.note,
+            verses:      Vec::new()
+        }
+    }
+}
+
+/// Row type for songs linked to a chapter
+#[derive(sqlx::FromRow)]
+pub struct ScriptureSongRow {
+    #[sqlx(flatten)]
+    pub song:         SongSummaryRow,
+    pub scripture_id: Uuid,
+    pub abbreviation: String,
+    pub chapter:      i16,
+    pub verse_start:  i16,
+    pub verse_end:    i16,
+    pub note:         Option<String>
+}
+
+impl From<ScriptureSongRow> for ScriptureSong {
+    fn from(row: ScriptureSongRow) -> Self {
+        Self {
+            song:         row.song.into(),
+            scripture_id: row.scripture_id,
+            verse_start:  row.verse_start,
+            verse_end:    row.verse_end,
+            reference:    passage_reference(
+                &row.abbreviation,
+                row.chapter,
+                row.verse_start,
+                row.verse_end
+            ),
+            note:         row.note
+        }
+    }
+}
+
+/// Row type for Scripture suggestions
+#[derive(sqlx::FromRow)]
+pub struct ScriptureSuggestionRow {
+    pub book_id:      i16,
+    pub book_name:    String,
+    pub abbreviation: String,
+    pub chapter:      i16,
+    pub verse:        i16,
+    pub text:         String,
+    pub shared_words: i32,
+    pub score:        f64
+}
+
+impl From<ScriptureSuggestionRow> for ScriptureSuggestion {
+    fn from(row: ScriptureSuggestionRow) -> Self {
+        Self {
+            book_id:      row.book_id,
+            book_name:    row.book_name,
+            chapter:      row.chapter,
+            verse:        row.verse,
+            reference:    passage_reference(&row.abbreviation, row.chapter, row.verse, row.verse),
+            text:         row.text,
+            shared_words: row.shared_words,
+            score:        row.score
+        }
+    }
+}
+
+/// Row type for song usage reports
+#[derive(sqlx::FromRow)]
+pub struct SongUsageRow {
+    pub song_id:       Uuid,
+    pub title:         String,
+    pub songbook_code: Option<String>,
+    pub number:        Option<i32>,
+    pub author_lyrics: Option<String>,
+    pub author_music:  Option<String>,
+    pub translator:    Option<String>,
+    pub copyright:     Option<String>,
+    pub times_used:    i64,
+    pub services:      i64,
+    pub first_used:    NaiveDate,
+    pub last_used:     NaiveDate
+}
+
+impl From<SongUsageRow> for SongUsage {
+    fn from(row: SongUsageRow) -> Self {
+        Self {
+            song_id:       row.song_id,
+            title:         row.title,
+            songbook_code: row.songbook_code,
+            number:        row.number,
+            author_lyrics: row.author_lyrics,
+            author_music:  row.author_music,
+            translator:    row.translator,
+            copyright:     row.copyright,
+            times_used:    row.times_used,
+            services:      row.services,
+            first_used:    row.first_used,
+            last_used:     row.last_used
+        }
+    }
+}
+
+/// Row type for live session queries, joined with the current song
+#[derive(sqlx::FromRow)]
+pub struct LiveSessionRow {
+    pub playlist_id:  Uuid,
+    pub leader_id:    Uuid,
+    pub song_id:      Option<Uuid>,
+    pub section:      i32,
+    pub transpose:    i16,
+    pub blank:        bool,
+    pub updated_at:   DateTime<Utc>,
+    pub title:        Option<String>,
+    pub content:      Option<String>,
+    pub original_key: Option<String>
+}
+
+impl From<LiveSessionRow> for LiveSession {
+    fn from(row: LiveSessionRow) -> Self {
+        Self {
+            playlist_id:  row.playlist_id,
+            leader_id:    row.leader_id,
+            song_id:      row.song_id,
+            section:      row.section,
+            transpose:    row.transpose,
+            blank:        row.blank,
+            updated_at:   row.updated_at,
+            title:        row.title,
+            content:      row.content,
+            original_key: row.original_key
+        }
+    }
+}
+
+/// Row type for song attachment queries
+#[derive(sqlx::FromRow)]
+pub struct SongAttachmentRow {
+    pub id:           Uuid,
+    pub song_id:      Uuid,
+    pub kind:         AttachmentKind,
+    pub title:        String,
+    pub file_name:    Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes:   Option<i64>,
+    pub url:          Option<String>,
+    pub created_at:   DateTime<Utc>
+}
+
+impl From<SongAttachmentRow> for SongAttachment {
+    fn from(row: SongAttachmentRow) -> Self {
+        Self {
+            id:           row.id,
+            song_id:      row.song_id,
+            kind:         row.kind,
+            title:        row.title,
+            file_name:    row.file_name,
+            content_type: row.content_type,
+            size_bytes:   row.size_bytes,
+            url:          row
+                .url
+                .unwrap_or_else(|| format!("/api/songs/attachments/{}", row.id)),
+            created_at:   row.created_at
+        }
+    }
+}
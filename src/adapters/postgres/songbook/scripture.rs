@@ -0,0 +1,211 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    bible_passages::{PassageTable, ensure_passage_exists, passage_verses},
+    rows::{ScriptureSongRow, ScriptureSuggestionRow, SongScriptureRow},
+    songbook_write::unique_violation
+};
+use crate::domain::{AddSongScripture, ScriptureSong, ScriptureSuggestion, SongScripture};
+
+/// Fewest word stems a verse must share with the lyrics to be suggested
+const MIN_SHARED_WORDS: i32 = 3;
+
+/// PostgreSQL links between songs and Scripture passages
+pub struct PgSongScripture {
+    pool: PgPool
+}
+
+impl PgSongScripture {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Passages of a song with their verses, in canonical order
+    pub async fn list_song_scripture(&self, song_id: Uuid) -> AppResult<Vec<SongScripture>> {
+        let rows = sqlx::query_as::<_, SongScriptureRow>(
+            r#"
+            SELECT
+                r.id, r.song_id, r.book_id, b.name_ru as book_name, b.abbreviation,
+                r.chapter, r.verse_start, r.verse_end, r.note
+            FROM song_scripture_refs r
+            JOIN bible_books b ON b.id = r.book_id
+            WHERE r.song_id = $1
+            ORDER BY r.book_id, r.chapter, r.verse_start, r.verse_end
+            "#
+        )
+        .bind(song_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut verses = passage_verses(&self.pool, PassageTable::SongScripture, song_id).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let mut passage = SongScripture::from(row);
+                passage.verses = verses.remove(&passage.id).unwrap_or_default();
+                passage
+            })
+            .collect())
+    }
+
+    /// Link a passage to a song; every verse of the range must exist
+    pub async fn add_scripture(
+        &self,
+        song_id: Uuid,
+        passage: &AddSongScripture,
+        user_id: Uuid
+    ) -> AppResult<Uuid> {
+        let verse_end = passage.verse_end.unwrap_or(passage.verse_start);
+        ensure_passage_exists(
+            &self.pool,
+            passage.book_id,
+            passage.chapter,
+            passage.verse_start,
+            verse_end
+        )
+        .await?;
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO song_scripture_refs
+                (song_id, book_id, chapter, verse_start, verse_end, note, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#
+        )
+        .bind(song_id)
+        .bind(passage.book_id)
+        .bind(passage.chapter)
+        .bind(passage.verse_start)
+        .bind(verse_end)
+        .bind(&passage.note)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => match db.constraint() {
+                Some("song_scripture_refs_song_id_fkey") => AppError::not_found("Song not found"),
+                Some("song_scripture_refs_created_by_fkey") => {
+                    AppError::not_found("User not found")
+                }
+                _ => AppError::validation("Passage is not in the Bible")
+            },
+            _ => unique_violation(e, "Passage is already linked to this song")
+        })?;
+
+        Ok(id)
+    }
+
+    pub async fn remove_scripture(&self, song_id: Uuid, scripture_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM song_scripture_refs WHERE id = $1 AND song_id = $2")
+            .bind(scripture_id)
+            .bind(song_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Passage not found"));
+        }
+        Ok(())
+    }
+
+    /// Songs linked to any passage of a chapter, in verse order
+    pub async fn chapter_songs(
+        &self,
+        book_id: i16,
+        chapter: i16,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<ScriptureSong>> {
+        let songs = sqlx::query_as::<_, ScriptureSongRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY(
+                    SELECT sc.category FROM song_categories sc WHERE sc.song_id = s.id
+                ) as categories,
+                r.id as scripture_id, b.abbreviation, r.chapter,
+                r.verse_start, r.verse_end, r.note
+            FROM song_scripture_refs r
+            JOIN bible_books b ON b.id = r.book_id
+            JOIN songs s ON s.id = r.song_id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $3
+            WHERE r.book_id = $1 AND r.chapter = $2
+                AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $3))
+            ORDER BY r.verse_start, r.verse_end, s.title
+            "#
+        )
+        .bind(book_id)
+        .bind(chapter)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Verses sharing the most word stems with the lyrics of a song.
+    ///
+    /// Lyrics and verses are both stemmed with the `russian` text search
+    /// configuration, which also drops stop words. A verse scores the square
+    /// of its shared stems over its own stem count, so short verses quoted
+    /// nearly whole rank above long ones that merely mention a few words.
+    /// Verses already linked to the song are left out.
+    pub async fn suggest(&self, song_id: Uuid, limit: i64) -> AppResult<Vec<ScriptureSuggestion>> {
+        let suggestions = sqlx::query_as::<_, ScriptureSuggestionRow>(
+            r#"
+            WITH lyrics AS (
+                SELECT tsvector_to_array(to_tsvector('russian', content_plain)) AS words
+                FROM songs WHERE id = $1
+            ),
+            query AS (
+                SELECT to_tsquery('simple', string_agg(quote_literal(w), ' | ')) AS q
+                FROM lyrics, unnest(lyrics.words) w
+            ),
+            candidates AS (
+                SELECT
+                    v.book_id, v.chapter, v.verse, v.text,
+                    cardinality(tsvector_to_array(v.text_search)) AS total,
+                    (
+                        SELECT COUNT(*)::int
+                        FROM unnest(tsvector_to_array(v.text_search)) w
+                        WHERE w = ANY(lyrics.words)
+                    ) AS shared_words
+                FROM bible_verses v, query, lyrics
+                WHERE v.text_search @@ query.q
+                    AND NOT EXISTS (
+                        SELECT 1 FROM song_scripture_refs r
+                        WHERE r.song_id = $1
+                            AND r.book_id = v.book_id
+                            AND r.chapter = v.chapter
+                            AND v.verse BETWEEN r.verse_start AND r.verse_end
+                    )
+            )
+            SELECT
+                c.book_id, b.name_ru as book_name, b.abbreviation,
+                c.chapter, c.verse, c.text, c.shared_words,
+                (c.shared_words * c.shared_words)::float8 / GREATEST(c.total, 1) AS score
+            FROM candidates c
+            JOIN bible_books b ON b.id = c.book_id
+            WHERE c.shared_words >= $3
+            ORDER BY score DESC, c.shared_words DESC, c.book_id, c.chapter, c.verse
+            LIMIT $2
+            "#
+        )
+        .bind(song_id)
+        .bind(limit)
+        .bind(MIN_SHARED_WORDS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(suggestions.into_iter().map(|r| r.into()).collect())
+    }
+}
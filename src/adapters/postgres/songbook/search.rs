@@ -3,7 +3,11 @@ use revelation_songbook::{SongCategory, SongSearchResult, SongSummary, ports::So
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use super::rows::{SongSearchRow, SongSummaryRow};
+use super::rows::{SongSearchRow, SongSuggestionRow, SongSummaryRow};
+use crate::domain::{SongSearchParams, SongSuggestion};
+
+/// Minimum trigram word similarity for typo-tolerant title matches
+const FUZZY_THRESHOLD: &str = "0.4";
 
 /// PostgreSQL implementation of SongSearch
 pub struct PgSongSearch {
@@ -16,49 +20,151 @@ impl PgSongSearch {
             pool
         }
     }
-}
 
-impl SongSearch for PgSongSearch {
-    async fn search_songs(
+    /// Filtered search over lyrics (full-text) and titles (typo-tolerant,
+    /// transliterated).
+    ///
+    /// Unless `params.all_versions` is set, only the best match of each song
+    /// family is returned, so a hymn printed in several songbooks or
+    /// translated appears once.
+    ///
+    /// Titles are compared through `songs.search_key`, a Latin
+    /// transliteration maintained by the database, so the similarity
+    /// threshold is set for the duration of the query transaction.
+    pub async fn search(
         &self,
-        query: &str,
-        limit: i64,
+        params: &SongSearchParams,
         user_id: Option<Uuid>
     ) -> AppResult<Vec<SongSearchResult>> {
-        let limit = limit.min(100);
+        let mut tags = params.tag_ids.clone();
+        tags.sort();
+        tags.dedup();
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)")
+            .bind(FUZZY_THRESHOLD)
+            .execute(&mut *tx)
+            .await?;
 
         let results = sqlx::query_as::<_, SongSearchRow>(
             r#"
-            SELECT
-                s.id, s.songbook_id, sb.code as songbook_code, sb.name_ru as songbook_name,
-                s.number, s.title, s.author_lyrics, s.first_line, s.original_key, s.has_chords,
-                s.views_count, s.favorites_count,
-                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
-                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories,
-                ts_rank(s.content_search, websearch_to_tsquery('russian', $1)) as rank,
-                ts_headline('russian', s.content_plain, websearch_to_tsquery('russian', $1),
-                    'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=15') as highlight
+            WITH q AS (
+                SELECT websearch_to_tsquery('russian', $1) AS tsq, song_search_key($1) AS key
+            ),
+            matches AS (
+                SELECT
+                    s.id, s.songbook_id, sb.code as songbook_code, sb.name_ru as songbook_name,
+                    s.number, s.title, s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                    s.views_count, s.favorites_count,
+                    CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                    ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories,
+                    GREATEST(
+                        ts_rank(s.content_search, q.tsq),
+                        word_similarity(q.key, s.search_key)
+                    )::real as rank,
+                    ts_headline('russian', s.content_plain, q.tsq,
+                        'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=15') as highlight,
+                    CASE WHEN song_search_key(s.title) LIKE q.key || '%' THEN 0 ELSE 1 END
+                        as title_match,
+                    CASE WHEN $9 THEN s.id ELSE COALESCE(fm.family_id, s.id) END as family_key
+                FROM songs s
+                CROSS JOIN q
+                LEFT JOIN song_family_members fm ON s.id = fm.song_id
+                LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+                LEFT JOIN song_categories sc ON s.id = sc.song_id
+                LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+                WHERE (s.content_search @@ q.tsq
+                   OR (q.key <> '' AND (q.key <% s.search_key OR s.search_key LIKE '%' || q.key || '%')))
+                  AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2))
+                  AND ($3::uuid IS NULL OR s.songbook_id = $3)
+                  AND ($4::song_category IS NULL
+                       OR EXISTS (SELECT 1 FROM song_categories c WHERE c.song_id = s.id AND c.category = $4))
+                  AND (cardinality($5::uuid[]) = 0
+                       OR (SELECT COUNT(*) FROM song_tag_assignments t
+                           WHERE t.song_id = s.id AND t.tag_id = ANY($5)) = cardinality($5::uuid[]))
+                  AND ($6::text IS NULL OR s.original_key = $6)
+                  AND ($7::bool IS NULL OR s.has_chords = $7)
+                GROUP BY s.id, sb.code, sb.name_ru, uf.user_id, q.tsq, q.key, fm.family_id
+            ),
+            best AS (
+                SELECT DISTINCT ON (family_key) *
+                FROM matches
+                ORDER BY family_key, title_match, rank DESC, views_count DESC
+            )
+            SELECT * FROM best
+            ORDER BY title_match, rank DESC, views_count DESC
+            LIMIT $8
+            "#
+        )
+        .bind(&params.query)
+        .bind(user_id)
+        .bind(params.songbook_id)
+        .bind(params.category)
+        .bind(&tags)
+        .bind(&params.key)
+        .bind(params.has_chords)
+        .bind(params.limit.clamp(1, 100))
+        .bind(params.all_versions)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(results.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Title autocomplete: prefix matches first, then typo-tolerant ones
+    pub async fn suggest(
+        &self,
+        prefix: &str,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSuggestion>> {
+        let suggestions = sqlx::query_as::<_, SongSuggestionRow>(
+            r#"
+            WITH q AS (SELECT song_search_key($1) AS key)
+            SELECT s.id, s.title, s.first_line, sb.code as songbook_code, s.number
             FROM songs s
+            CROSS JOIN q
             LEFT JOIN songbooks sb ON s.songbook_id = sb.id
-            LEFT JOIN song_categories sc ON s.id = sc.song_id
-            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
-            WHERE s.content_search @@ websearch_to_tsquery('russian', $1)
-               OR s.title ILIKE '%' || $1 || '%'
-               OR s.first_line ILIKE '%' || $1 || '%'
-            GROUP BY s.id, sb.code, sb.name_ru, uf.user_id
+            WHERE q.key <> ''
+              AND (s.search_key LIKE q.key || '%'
+                   OR s.search_key LIKE '% ' || q.key || '%'
+                   OR q.key <% s.search_key)
+              AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2))
+              AND ($3::uuid IS NULL OR s.songbook_id = $3)
             ORDER BY
-                CASE WHEN s.title ILIKE $1 || '%' THEN 0 ELSE 1 END,
-                rank DESC, s.views_count DESC
-            LIMIT $3
-            "#,
+                CASE WHEN song_search_key(s.title) LIKE q.key || '%' THEN 0 ELSE 1 END,
+                word_similarity(q.key, s.search_key) DESC,
+                s.views_count DESC
+            LIMIT $4
+            "#
         )
-        .bind(query)
+        .bind(prefix)
         .bind(user_id)
-        .bind(limit)
+        .bind(songbook_id)
+        .bind(limit.clamp(1, 20))
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(results.into_iter().map(|r| r.into()).collect())
+        Ok(suggestions.into_iter().map(|r| r.into()).collect())
+    }
+}
+
+impl SongSearch for PgSongSearch {
+    async fn search_songs(
+        &self,
+        query: &str,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSearchResult>> {
+        let params = SongSearchParams {
+            query: query.to_string(),
+            limit,
+            ..Default::default()
+        };
+        self.search(&params, user_id).await
     }
 
     async fn list_by_category(
@@ -80,6 +186,7 @@ impl SongSearch for PgSongSearch {
             LEFT JOIN songbooks sb ON s.songbook_id = sb.id
             LEFT JOIN song_categories sc2 ON s.id = sc2.song_id
             LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            WHERE sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2)
             GROUP BY s.id, sb.code, uf.user_id
             ORDER BY s.views_count DESC
             LIMIT $3
@@ -1,11 +1,14 @@
-use masterror::AppResult;
+use std::collections::HashMap;
+
+use masterror::{AppError, AppResult};
 use revelation_songbook::{
     Song, SongCategory, SongFilters, SongSortBy, SongSummary, SongTag, ports::SongRead
 };
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use super::rows::SongSummaryRow;
+use super::{PgSongPopularity, rows::SongSummaryRow};
+use crate::domain::{PopularityOrder, SongOrder};
 
 /// PostgreSQL implementation of SongRead
 pub struct PgSongRead {
@@ -18,6 +21,410 @@ impl PgSongRead {
             pool
         }
     }
+
+    /// Load a song without counting a view or writing history, whatever its
+    /// songbook visibility
+    pub async fn load_song(&self, id: Uuid, user_id: Option<Uuid>) -> AppResult<Song> {
+        let row = sqlx::query_as::<_, SongRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, s.number, s.title, s.title_alt,
+                s.author_lyrics, s.author_music, s.translator, s.year_written,
+                s.copyright, s.original_key, s.tempo, s.time_signature,
+                s.content, s.first_line, s.views_count, s.favorites_count,
+                sb.code as songbook_code,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                COALESCE(uh.transpose_semitones, 0)::smallint as user_transpose
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            LEFT JOIN (
+                SELECT DISTINCT ON (song_id) song_id, transpose_semitones
+                FROM user_song_history WHERE user_id = $2
+                ORDER BY song_id, viewed_at DESC
+            ) uh ON s.id = uh.song_id
+            WHERE s.id = $1
+            "#
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let categories = sqlx::query_scalar::<_, SongCategory>(
+            "SELECT category FROM song_categories WHERE song_id = $1"
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tags = sqlx::query_as!(
+            SongTag,
+            r#"
+            SELECT t.id, t.name, t.name_ru, t.usage_count
+            FROM song_tags t
+            JOIN song_tag_assignments sta ON t.id = sta.tag_id
+            WHERE sta.song_id = $1
+            "#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(row.into_song(categories, tags))
+    }
+
+    /// Fail with not found unless the song is outside any songbook or its
+    /// songbook is visible to the viewer
+    pub async fn ensure_visible(&self, id: Uuid, viewer: Option<Uuid>) -> AppResult<()> {
+        let visible = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2)
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            WHERE s.id = $1
+            "#
+        )
+        .bind(id)
+        .bind(viewer)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match visible {
+            Some(true) => Ok(()),
+            _ => Err(AppError::not_found("Song not found"))
+        }
+    }
+
+    /// Load a song the viewer may see, without counting a view
+    pub async fn load_visible_song(&self, id: Uuid, user_id: Option<Uuid>) -> AppResult<Song> {
+        self.ensure_visible(id, user_id).await?;
+        self.load_song(id, user_id).await
+    }
+
+    /// Load the songs the viewer may see among `ids` in three queries, keyed
+    /// by id; hidden or missing songs are left out
+    pub async fn load_visible_songs(
+        &self,
+        ids: &[Uuid],
+        user_id: Option<Uuid>
+    ) -> AppResult<HashMap<Uuid, Song>> {
+        let rows = sqlx::query_as::<_, SongRow>(
+            r#"
+            SELECT
+                s.id, s.songbook_id, s.number, s.title, s.title_alt,
+                s.author_lyrics, s.author_music, s.translator, s.year_written,
+                s.copyright, s.original_key, s.tempo, s.time_signature,
+                s.content, s.first_line, s.views_count, s.favorites_count,
+                sb.code as songbook_code,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                COALESCE(uh.transpose_semitones, 0)::smallint as user_transpose
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
+            LEFT JOIN (
+                SELECT DISTINCT ON (song_id) song_id, transpose_semitones
+                FROM user_song_history WHERE user_id = $2
+                ORDER BY song_id, viewed_at DESC
+            ) uh ON s.id = uh.song_id
+            WHERE s.id = ANY($1)
+                AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $2))
+            "#
+        )
+        .bind(ids)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let found: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+
+        let mut categories: HashMap<Uuid, Vec<SongCategory>> = HashMap::new();
+        for (song_id, category) in sqlx::query_as::<_, (Uuid, SongCategory)>(
+            "SELECT song_id, category FROM song_categories WHERE song_id = ANY($1)"
+        )
+        .bind(&found)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            categories.entry(song_id).or_default().push(category);
+        }
+
+        let mut tags: HashMap<Uuid, Vec<SongTag>> = HashMap::new();
+        for row in sqlx::query_as::<_, SongTagAssignmentRow>(
+            r#"
+            SELECT sta.song_id, t.id, t.name, t.name_ru, t.usage_count
+            FROM song_tags t
+            JOIN song_tag_assignments sta ON t.id = sta.tag_id
+            WHERE sta.song_id = ANY($1)
+            "#
+        )
+        .bind(&found)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            tags.entry(row.song_id).or_default().push(SongTag {
+                id:          row.id,
+                name:        row.name,
+                name_ru:     row.name_ru,
+                usage_count: row.usage_count
+            });
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let id = row.id;
+                let song = row.into_song(
+                    categories.remove(&id).unwrap_or_default(),
+                    tags.remove(&id).unwrap_or_default()
+                );
+                (id, song)
+            })
+            .collect())
+    }
+
+    /// Find a song by its number within a songbook
+    pub async fn find_song_id(&self, songbook_id: Uuid, number: i32) -> AppResult<Option<Uuid>> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM songs WHERE songbook_id = $1 AND number = $2"
+        )
+        .bind(songbook_id)
+        .bind(number)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Songbook of a song (`None` for songs outside any songbook)
+    pub async fn get_song_songbook(&self, id: Uuid) -> AppResult<Option<Uuid>> {
+        let songbook_id =
+            sqlx::query_scalar::<_, Option<Uuid>>("SELECT songbook_id FROM songs WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::not_found("Song not found"))?;
+
+        Ok(songbook_id)
+    }
+
+    /// Find a song by its number in a songbook edition.
+    ///
+    /// Editions without their own numbering use the current `songs.number`.
+    pub async fn find_song_id_in_edition(
+        &self,
+        songbook_id: Uuid,
+        edition_id: Uuid,
+        number: i32
+    ) -> AppResult<Option<Uuid>> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT s.id
+            FROM songs s
+            LEFT JOIN song_edition_numbers n ON n.song_id = s.id AND n.edition_id = $2
+            WHERE s.songbook_id = $1
+                AND CASE
+                    WHEN EXISTS (SELECT 1 FROM song_edition_numbers WHERE edition_id = $2)
+                    THEN n.number = $3
+                    ELSE s.number = $3
+                END
+            "#
+        )
+        .bind(songbook_id)
+        .bind(edition_id)
+        .bind(number)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Songs numbered `from..=to` in a songbook, optionally using the
+    /// numbering of an edition; `number` in the result is the edition number
+    pub async fn list_songs_by_number(
+        &self,
+        songbook_id: Uuid,
+        from: i32,
+        to: i32,
+        edition_id: Option<Uuid>,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        let songs = sqlx::query_as::<_, SongSummaryRow>(
+            r#"
+            WITH numbered AS (
+                SELECT
+                    s.id,
+                    CASE
+                        WHEN $2::uuid IS NOT NULL
+                            AND EXISTS (SELECT 1 FROM song_edition_numbers WHERE edition_id = $2)
+                        THEN n.number
+                        ELSE s.number
+                    END as number
+                FROM songs s
+                LEFT JOIN song_edition_numbers n ON n.song_id = s.id AND n.edition_id = $2
+                WHERE s.songbook_id = $1
+            )
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, nb.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
+            FROM numbered nb
+            JOIN songs s ON s.id = nb.id
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN song_categories sc ON s.id = sc.song_id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $5
+            WHERE nb.number BETWEEN $3 AND $4
+            GROUP BY s.id, sb.code, nb.number, uf.user_id
+            ORDER BY nb.number
+            "#
+        )
+        .bind(songbook_id)
+        .bind(edition_id)
+        .bind(from)
+        .bind(to)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Load a song for display: count a view once per viewer and day and
+    /// write the signed-in user's history. Songs in songbooks hidden from
+    /// the user are not found.
+    pub async fn view_song(
+        &self,
+        id: Uuid,
+        user_id: Option<Uuid>,
+        viewer: Option<&str>
+    ) -> AppResult<Song> {
+        let song = self.load_visible_song(id, user_id).await?;
+
+        if let Some(viewer) = viewer {
+            PgSongPopularity::new(self.pool.clone())
+                .record_view(id, viewer)
+                .await?;
+        }
+
+        if let Some(uid) = user_id {
+            sqlx::query(
+                "INSERT INTO user_song_history (user_id, song_id, viewed_at) VALUES ($1, $2, NOW())",
+            )
+            .bind(uid)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(song)
+    }
+
+    /// Songs matching the filters and carrying every tag of `tag_ids` (and
+    /// `filters.tag_id`) in the given order; `filters.sort_by` is ignored
+    pub async fn list_songs_ordered(
+        &self,
+        filters: &SongFilters,
+        tag_ids: &[Uuid],
+        order: SongOrder,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        let limit = filters.limit.unwrap_or(50).min(100);
+        let offset = filters.offset.unwrap_or(0);
+
+        let mut tags: Vec<Uuid> = tag_ids.iter().copied().chain(filters.tag_id).collect();
+        tags.sort();
+        tags.dedup();
+
+        let order_by = match order {
+            SongOrder::Catalog(SongSortBy::Title) => "s.title ASC",
+            SongOrder::Catalog(SongSortBy::Number) => "s.number ASC NULLS LAST, s.title ASC",
+            SongOrder::Catalog(SongSortBy::ViewsDesc) => "s.views_count DESC, s.title ASC",
+            SongOrder::Catalog(SongSortBy::FavoritesDesc) => "s.favorites_count DESC, s.title ASC",
+            SongOrder::Catalog(SongSortBy::RecentlyAdded) => "s.created_at DESC",
+            SongOrder::Catalog(SongSortBy::HasChordsFirst) => "s.has_chords DESC, s.title ASC",
+            SongOrder::Catalog(SongSortBy::NoChordsFirst) => "s.has_chords ASC, s.title ASC",
+            // Songs without recent activity have no aggregates and come last
+            SongOrder::Popularity(PopularityOrder::Popular7d) => {
+                "sp.views_7d DESC NULLS LAST, s.views_count DESC, s.title ASC"
+            }
+            SongOrder::Popularity(PopularityOrder::Popular30d) => {
+                "sp.views_30d DESC NULLS LAST, s.views_count DESC, s.title ASC"
+            }
+            SongOrder::Popularity(PopularityOrder::Trending) => {
+                "sp.trending_score DESC NULLS LAST, s.views_count DESC, s.title ASC"
+            }
+            SongOrder::Popularity(PopularityOrder::MostSungMonth) => {
+                "sp.sung_month DESC NULLS LAST, s.views_count DESC, s.title ASC"
+            }
+        };
+        let songs = sqlx::query_as::<_, SongSummaryRow>(&format!(
+            r#"
+            SELECT
+                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
+                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
+                s.views_count, s.favorites_count,
+                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
+                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
+            FROM songs s
+            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
+            LEFT JOIN song_categories sc ON s.id = sc.song_id
+            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $1
+            LEFT JOIN song_popularity sp ON sp.song_id = s.id
+            WHERE 1=1
+                AND (sb.id IS NULL OR songbook_visible(sb.is_public, sb.church_id, $1))
+                AND ($2::uuid IS NULL OR s.songbook_id = $2)
+                AND ($3::song_category IS NULL OR sc.category = $3)
+                AND (cardinality($4::uuid[]) = 0 OR (
+                    SELECT COUNT(*) FROM song_tag_assignments sta
+                    WHERE sta.song_id = s.id AND sta.tag_id = ANY($4)
+                ) = cardinality($4::uuid[]))
+                AND ($5::text IS NULL OR s.original_key = $5)
+            GROUP BY s.id, sb.code, uf.user_id,
+                sp.views_7d, sp.views_30d, sp.trending_score, sp.sung_month
+            ORDER BY {}
+            LIMIT $6 OFFSET $7
+            "#,
+            order_by
+        ))
+        .bind(user_id)
+        .bind(filters.songbook_id)
+        .bind(filters.category)
+        .bind(&tags)
+        .bind(&filters.key)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// IDs of all songs, optionally limited to one songbook, in songbook order
+    pub async fn list_song_ids(&self, songbook_id: Option<Uuid>) -> AppResult<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM songs
+            WHERE $1::uuid IS NULL OR songbook_id = $1
+            ORDER BY songbook_id, number NULLS LAST, title
+            "#
+        )
+        .bind(songbook_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SongTagAssignmentRow {
+    song_id:     Uuid,
+    id:          Uuid,
+    name:        String,
+    name_ru:     String,
+    usage_count: i32
 }
 
 #[derive(sqlx::FromRow)]
@@ -79,120 +486,13 @@ impl SongRead for PgSongRead {
         filters: &SongFilters,
         user_id: Option<Uuid>
     ) -> AppResult<Vec<SongSummary>> {
-        let limit = filters.limit.unwrap_or(50).min(100);
-        let offset = filters.offset.unwrap_or(0);
-
-        let order_by = match filters.sort_by.unwrap_or_default() {
-            SongSortBy::Title => "s.title ASC",
-            SongSortBy::Number => "s.number ASC NULLS LAST, s.title ASC",
-            SongSortBy::ViewsDesc => "s.views_count DESC, s.title ASC",
-            SongSortBy::FavoritesDesc => "s.favorites_count DESC, s.title ASC",
-            SongSortBy::RecentlyAdded => "s.created_at DESC",
-            SongSortBy::HasChordsFirst => "s.has_chords DESC, s.title ASC",
-            SongSortBy::NoChordsFirst => "s.has_chords ASC, s.title ASC"
-        };
-
-        let songs = sqlx::query_as::<_, SongSummaryRow>(&format!(
-            r#"
-            SELECT
-                s.id, s.songbook_id, sb.code as songbook_code, s.number, s.title,
-                s.author_lyrics, s.first_line, s.original_key, s.has_chords,
-                s.views_count, s.favorites_count,
-                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
-                ARRAY_AGG(DISTINCT sc.category) FILTER (WHERE sc.category IS NOT NULL) as categories
-            FROM songs s
-            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
-            LEFT JOIN song_categories sc ON s.id = sc.song_id
-            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $1
-            WHERE 1=1
-                AND ($2::uuid IS NULL OR s.songbook_id = $2)
-                AND ($3::song_category IS NULL OR sc.category = $3)
-                AND ($4::uuid IS NULL OR EXISTS (
-                    SELECT 1 FROM song_tag_assignments sta WHERE sta.song_id = s.id AND sta.tag_id = $4
-                ))
-                AND ($5::text IS NULL OR s.original_key = $5)
-            GROUP BY s.id, sb.code, uf.user_id
-            ORDER BY {}
-            LIMIT $6 OFFSET $7
-            "#,
-            order_by
-        ))
-        .bind(user_id)
-        .bind(filters.songbook_id)
-        .bind(filters.category)
-        .bind(filters.tag_id)
-        .bind(&filters.key)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?;
-
-        Ok(songs.into_iter().map(|r| r.into()).collect())
+        let order = filters.sort_by.unwrap_or_default().into();
+        self.list_songs_ordered(filters, &[], order, user_id).await
     }
 
     async fn get_song(&self, id: Uuid, user_id: Option<Uuid>) -> AppResult<Song> {
-        let row = sqlx::query_as::<_, SongRow>(
-            r#"
-            SELECT
-                s.id, s.songbook_id, s.number, s.title, s.title_alt,
-                s.author_lyrics, s.author_music, s.translator, s.year_written,
-                s.copyright, s.original_key, s.tempo, s.time_signature,
-                s.content, s.first_line, s.views_count, s.favorites_count,
-                sb.code as songbook_code,
-                CASE WHEN uf.user_id IS NOT NULL THEN true ELSE false END as is_favorite,
-                COALESCE(uh.transpose_semitones, 0)::smallint as user_transpose
-            FROM songs s
-            LEFT JOIN songbooks sb ON s.songbook_id = sb.id
-            LEFT JOIN user_favorite_songs uf ON s.id = uf.song_id AND uf.user_id = $2
-            LEFT JOIN (
-                SELECT DISTINCT ON (song_id) song_id, transpose_semitones
-                FROM user_song_history WHERE user_id = $2
-                ORDER BY song_id, viewed_at DESC
-            ) uh ON s.id = uh.song_id
-            WHERE s.id = $1
-            "#
-        )
-        .bind(id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        let categories = sqlx::query_scalar::<_, SongCategory>(
-            "SELECT category FROM song_categories WHERE song_id = $1"
-        )
-        .bind(id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let tags = sqlx::query_as!(
-            SongTag,
-            r#"
-            SELECT t.id, t.name, t.name_ru, t.usage_count
-            FROM song_tags t
-            JOIN song_tag_assignments sta ON t.id = sta.tag_id
-            WHERE sta.song_id = $1
-            "#,
-            id
-        )
-        .fetch_all(&self.pool)
-        .await?;
-
-        sqlx::query("UPDATE songs SET views_count = views_count + 1 WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
-
-        if let Some(uid) = user_id {
-            sqlx::query(
-                "INSERT INTO user_song_history (user_id, song_id, viewed_at) VALUES ($1, $2, NOW())",
-            )
-            .bind(uid)
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
-        }
-
-        Ok(row.into_song(categories, tags))
+        let viewer = user_id.map(|id| id.to_string());
+        self.view_song(id, user_id, viewer.as_deref()).await
     }
 
     async fn get_song_by_number(
@@ -201,13 +501,12 @@ impl SongRead for PgSongRead {
         number: i32,
         user_id: Option<Uuid>
     ) -> AppResult<Song> {
-        let id = sqlx::query_scalar::<_, Uuid>(
-            "SELECT id FROM songs WHERE songbook_id = $1 AND number = $2"
-        )
-        .bind(songbook_id)
-        .bind(number)
-        .fetch_one(&self.pool)
-        .await?;
+        let id = self
+            .find_song_id(songbook_id, number)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("No song number {number} in this songbook"))
+            })?;
 
         self.get_song(id, user_id).await
     }
@@ -1,9 +1,6 @@
 use masterror::AppResult;
-use revelation_songbook::{
-    CreateSong, Song, UpdateSong,
-    ports::{SongRead, SongWrite}
-};
-use sqlx::PgPool;
+use revelation_songbook::{CreateSong, Song, UpdateSong, ports::SongWrite};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use super::song_read::PgSongRead;
@@ -19,24 +16,27 @@ impl PgSongWrite {
             pool
         }
     }
-}
 
-impl SongWrite for PgSongWrite {
-    async fn create_song(&self, song: CreateSong) -> AppResult<Song> {
+    /// Overwrite all song fields in one transaction, keeping tags and
+    /// replacing categories only when some are given
+    pub async fn replace_song(&self, id: Uuid, song: &CreateSong) -> AppResult<()> {
         let content_plain = strip_chords(&song.content);
         let first_line = extract_first_line(&song.content);
 
-        let id = sqlx::query_scalar::<_, Uuid>(
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
             r#"
-            INSERT INTO songs (
-                songbook_id, number, title, title_alt, author_lyrics, author_music,
-                translator, year_written, copyright, original_key, tempo, time_signature,
-                content, content_plain, first_line, source_url
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
-            RETURNING id
+            UPDATE songs SET
+                songbook_id = $2, number = $3, title = $4, title_alt = $5,
+                author_lyrics = $6, author_music = $7, translator = $8, year_written = $9,
+                copyright = $10, original_key = $11, tempo = $12, time_signature = $13,
+                content = $14, content_plain = $15, first_line = $16,
+                source_url = COALESCE($17, source_url), updated_at = NOW()
+            WHERE id = $1
             "#
         )
+        .bind(id)
         .bind(song.songbook_id)
         .bind(song.number)
         .bind(&song.title)
@@ -53,26 +53,49 @@ impl SongWrite for PgSongWrite {
         .bind(&content_plain)
         .bind(&first_line)
         .bind(&song.source_url)
-        .fetch_one(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        for category in &song.categories {
-            sqlx::query("INSERT INTO song_categories (song_id, category) VALUES ($1, $2)")
+        if !song.categories.is_empty() {
+            sqlx::query("DELETE FROM song_categories WHERE song_id = $1")
                 .bind(id)
-                .bind(category)
-                .execute(&self.pool)
+                .execute(&mut *tx)
                 .await?;
-        }
 
-        for tag_id in &song.tag_ids {
-            sqlx::query("INSERT INTO song_tag_assignments (song_id, tag_id) VALUES ($1, $2)")
-                .bind(id)
-                .bind(tag_id)
-                .execute(&self.pool)
-                .await?;
+            for category in &song.categories {
+                sqlx::query("INSERT INTO song_categories (song_id, category) VALUES ($1, $2)")
+                    .bind(id)
+                    .bind(category)
+                    .execute(&mut *tx)
+                    .await?;
+            }
         }
 
-        PgSongRead::new(self.pool.clone()).get_song(id, None).await
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Set the key of a song that has none; returns whether it was set
+    pub async fn fill_original_key(&self, id: Uuid, key: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE songs SET original_key = $2 WHERE id = $1 AND original_key IS NULL"
+        )
+        .bind(id)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl SongWrite for PgSongWrite {
+    async fn create_song(&self, song: CreateSong) -> AppResult<Song> {
+        let mut tx = self.pool.begin().await?;
+        let id = insert_song(&mut tx, &song).await?;
+        tx.commit().await?;
+
+        PgSongRead::new(self.pool.clone()).load_song(id, None).await
     }
 
     async fn update_song(&self, id: Uuid, song: UpdateSong) -> AppResult<Song> {
@@ -127,7 +150,7 @@ impl SongWrite for PgSongWrite {
             }
         }
 
-        PgSongRead::new(self.pool.clone()).get_song(id, None).await
+        PgSongRead::new(self.pool.clone()).load_song(id, None).await
     }
 
     async fn delete_song(&self, id: Uuid) -> AppResult<()> {
@@ -140,6 +163,60 @@ impl SongWrite for PgSongWrite {
     }
 }
 
+/// Insert a song with its categories and tags, returning its ID
+pub(super) async fn insert_song(conn: &mut PgConnection, song: &CreateSong) -> AppResult<Uuid> {
+    let content_plain = strip_chords(&song.content);
+    let first_line = extract_first_line(&song.content);
+
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO songs (
+            songbook_id, number, title, title_alt, author_lyrics, author_music,
+            translator, year_written, copyright, original_key, tempo, time_signature,
+            content, content_plain, first_line, source_url
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        RETURNING id
+        "#
+    )
+    .bind(song.songbook_id)
+    .bind(song.number)
+    .bind(&song.title)
+    .bind(&song.title_alt)
+    .bind(&song.author_lyrics)
+    .bind(&song.author_music)
+    .bind(&song.translator)
+    .bind(song.year_written)
+    .bind(&song.copyright)
+    .bind(&song.original_key)
+    .bind(song.tempo)
+    .bind(&song.time_signature)
+    .bind(&song.content)
+    .bind(&content_plain)
+    .bind(&first_line)
+    .bind(&song.source_url)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    for category in &song.categories {
+        sqlx::query("INSERT INTO song_categories (song_id, category) VALUES ($1, $2)")
+            .bind(id)
+            .bind(category)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    for tag_id in &song.tag_ids {
+        sqlx::query("INSERT INTO song_tag_assignments (song_id, tag_id) VALUES ($1, $2)")
+            .bind(id)
+            .bind(tag_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(id)
+}
+
 /// Strip chords from ChordPro format
 fn strip_chords(content: &str) -> String {
     let mut result = String::new();
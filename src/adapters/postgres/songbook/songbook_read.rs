@@ -1,8 +1,21 @@
-use masterror::AppResult;
+use masterror::prelude::*;
 use revelation_songbook::{Songbook, SongbookEdition, ports::SongbookRead};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use super::rows::SongbookRow;
+
+/// Songbook columns shared by songbook queries
+const SONGBOOK_COLUMNS: &str = r#"
+    sb.id, sb.code, sb.name, sb.name_ru, sb.description, sb.cover_url,
+    sb.songs_count,
+    COALESCE((SELECT COUNT(*) FROM songs s WHERE s.songbook_id = sb.id AND s.has_chords = true), 0)::int as songs_with_chords_count,
+    sb.is_public,
+    sb.year_first_published, sb.year_latest_edition, sb.edition_name, sb.total_songs_in_print,
+    sb.publisher, sb.editor, sb.isbn, sb.language, sb.country, sb.denomination,
+    sb.website_url, sb.purchase_url, sb.history, sb.notes
+"#;
+
 /// PostgreSQL implementation of SongbookRead
 pub struct PgSongbookRead {
     pool: PgPool
@@ -14,6 +27,97 @@ impl PgSongbookRead {
             pool
         }
     }
+
+    /// Find a songbook by code or by its English or Russian name
+    pub async fn find_songbook_id(&self, name: &str) -> AppResult<Option<Uuid>> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM songbooks
+            WHERE lower(code) = lower($1) OR lower(name) = lower($1) OR lower(name_ru) = lower($1)
+            ORDER BY (lower(code) = lower($1)) DESC
+            LIMIT 1
+            "#
+        )
+        .bind(name.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Songbooks visible to the viewer: public ones and private songbooks
+    /// of the viewer's churches
+    pub async fn list_visible_songbooks(&self, viewer: Option<Uuid>) -> AppResult<Vec<Songbook>> {
+        let songbooks = sqlx::query_as::<_, SongbookRow>(&format!(
+            r#"
+            SELECT {SONGBOOK_COLUMNS}
+            FROM songbooks sb
+            WHERE songbook_visible(sb.is_public, sb.church_id, $1)
+            ORDER BY sb.church_id NULLS FIRST, sb.name_ru
+            "#
+        ))
+        .bind(viewer)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(songbooks.into_iter().map(Into::into).collect())
+    }
+
+    /// Songbook by ID or code if visible to the viewer
+    pub async fn get_visible_songbook(
+        &self,
+        id_or_code: &str,
+        viewer: Option<Uuid>
+    ) -> AppResult<Songbook> {
+        let id = Uuid::parse_str(id_or_code).ok();
+
+        let songbook = sqlx::query_as::<_, SongbookRow>(&format!(
+            r#"
+            SELECT {SONGBOOK_COLUMNS}
+            FROM songbooks sb
+            WHERE (sb.id = $1 OR ($1::uuid IS NULL AND sb.code = $2))
+                AND songbook_visible(sb.is_public, sb.church_id, $3)
+            "#
+        ))
+        .bind(id)
+        .bind(id_or_code)
+        .bind(viewer)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        songbook
+            .map(Into::into)
+            .ok_or_else(|| AppError::not_found("Songbook not found"))
+    }
+
+    /// Find a songbook edition by ID or name
+    pub async fn find_edition_id(&self, songbook_id: Uuid, edition: &str) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM songbook_editions
+            WHERE songbook_id = $1 AND (id = $2 OR lower(edition_name) = lower($3))
+            "#
+        )
+        .bind(songbook_id)
+        .bind(Uuid::parse_str(edition).ok())
+        .bind(edition.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        id.ok_or_else(|| AppError::not_found("Edition not found"))
+    }
+
+    /// Owning church of a songbook (`None` for the shared catalog)
+    pub async fn get_songbook_church(&self, id: Uuid) -> AppResult<Option<Uuid>> {
+        let church_id =
+            sqlx::query_scalar::<_, Option<Uuid>>("SELECT church_id FROM songbooks WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::not_found("Songbook not found"))?;
+
+        Ok(church_id)
+    }
 }
 
 impl SongbookRead for PgSongbookRead {
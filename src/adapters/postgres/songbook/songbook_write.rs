@@ -0,0 +1,292 @@
+use masterror::prelude::*;
+use revelation_songbook::SongbookEdition;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::SongbookEditionRow;
+use crate::domain::{
+    CreateSongbook, CreateSongbookEdition, EditionNumber, UpdateSongbook, UpdateSongbookEdition
+};
+
+/// PostgreSQL songbook and edition management
+pub struct PgSongbookWrite {
+    pool: PgPool
+}
+
+impl PgSongbookWrite {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    pub async fn create_songbook(&self, songbook: &CreateSongbook) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO songbooks (
+                code, name, name_ru, description, cover_url, is_public, church_id,
+                year_first_published, year_latest_edition, edition_name, total_songs_in_print,
+                publisher, editor, isbn, language, country, denomination,
+                website_url, purchase_url, history, notes
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11,
+                $12, $13, $14, COALESCE($15, 'ru'), $16, $17, $18, $19, $20, $21
+            )
+            RETURNING id
+            "#
+        )
+        .bind(&songbook.code)
+        .bind(&songbook.name)
+        .bind(&songbook.name_ru)
+        .bind(&songbook.description)
+        .bind(&songbook.cover_url)
+        .bind(songbook.is_public)
+        .bind(songbook.church_id)
+        .bind(songbook.year_first_published)
+        .bind(songbook.year_latest_edition)
+        .bind(&songbook.edition_name)
+        .bind(songbook.total_songs_in_print)
+        .bind(&songbook.publisher)
+        .bind(&songbook.editor)
+        .bind(&songbook.isbn)
+        .bind(&songbook.language)
+        .bind(&songbook.country)
+        .bind(&songbook.denomination)
+        .bind(&songbook.website_url)
+        .bind(&songbook.purchase_url)
+        .bind(&songbook.history)
+        .bind(&songbook.notes)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Songbook code is already taken"))?;
+
+        Ok(id)
+    }
+
+    pub async fn update_songbook(&self, id: Uuid, songbook: &UpdateSongbook) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE songbooks SET
+                code = COALESCE($2, code),
+                name = COALESCE($3, name),
+                name_ru = COALESCE($4, name_ru),
+                description = COALESCE($5, description),
+                cover_url = COALESCE($6, cover_url),
+                is_public = COALESCE($7, is_public),
+                year_first_published = COALESCE($8, year_first_published),
+                year_latest_edition = COALESCE($9, year_latest_edition),
+                edition_name = COALESCE($10, edition_name),
+                total_songs_in_print = COALESCE($11, total_songs_in_print),
+                publisher = COALESCE($12, publisher),
+                editor = COALESCE($13, editor),
+                isbn = COALESCE($14, isbn),
+                language = COALESCE($15, language),
+                country = COALESCE($16, country),
+                denomination = COALESCE($17, denomination),
+                website_url = COALESCE($18, website_url),
+                purchase_url = COALESCE($19, purchase_url),
+                history = COALESCE($20, history),
+                notes = COALESCE($21, notes)
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(&songbook.code)
+        .bind(&songbook.name)
+        .bind(&songbook.name_ru)
+        .bind(&songbook.description)
+        .bind(&songbook.cover_url)
+        .bind(songbook.is_public)
+        .bind(songbook.year_first_published)
+        .bind(songbook.year_latest_edition)
+        .bind(&songbook.edition_name)
+        .bind(songbook.total_songs_in_print)
+        .bind(&songbook.publisher)
+        .bind(&songbook.editor)
+        .bind(&songbook.isbn)
+        .bind(&songbook.language)
+        .bind(&songbook.country)
+        .bind(&songbook.denomination)
+        .bind(&songbook.website_url)
+        .bind(&songbook.purchase_url)
+        .bind(&songbook.history)
+        .bind(&songbook.notes)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Songbook code is already taken"))?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Songbook not found"));
+        }
+        Ok(())
+    }
+
+    /// Delete an empty songbook.
+    ///
+    /// Songbooks that still contain songs are rejected: detaching numbered
+    /// songs would collide in the `(songbook_id, number)` constraint.
+    pub async fn delete_songbook(&self, id: Uuid) -> AppResult<()> {
+        let songs =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM songs WHERE songbook_id = $1")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        if songs > 0 {
+            return Err(AppError::conflict(format!(
+                "Songbook still contains {songs} songs"
+            )));
+        }
+
+        let result = sqlx::query("DELETE FROM songbooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Songbook not found"));
+        }
+        Ok(())
+    }
+
+    pub async fn create_edition(
+        &self,
+        songbook_id: Uuid,
+        edition: &CreateSongbookEdition
+    ) -> AppResult<SongbookEdition> {
+        let edition = sqlx::query_as::<_, SongbookEditionRow>(
+            r#"
+            INSERT INTO songbook_editions (
+                songbook_id, edition_name, year_published, songs_count, publisher, isbn, notes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, songbook_id, edition_name, year_published, songs_count, publisher, isbn, notes
+            "#
+        )
+        .bind(songbook_id)
+        .bind(&edition.edition_name)
+        .bind(edition.year_published)
+        .bind(edition.songs_count)
+        .bind(&edition.publisher)
+        .bind(&edition.isbn)
+        .bind(&edition.notes)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Edition with this name already exists"))?;
+
+        Ok(edition.into())
+    }
+
+    pub async fn update_edition(
+        &self,
+        songbook_id: Uuid,
+        id: Uuid,
+        edition: &UpdateSongbookEdition
+    ) -> AppResult<SongbookEdition> {
+        let edition = sqlx::query_as::<_, SongbookEditionRow>(
+            r#"
+            UPDATE songbook_editions SET
+                edition_name = COALESCE($3, edition_name),
+                year_published = COALESCE($4, year_published),
+                songs_count = COALESCE($5, songs_count),
+                publisher = COALESCE($6, publisher),
+                isbn = COALESCE($7, isbn),
+                notes = COALESCE($8, notes)
+            WHERE id = $1 AND songbook_id = $2
+            RETURNING id, songbook_id, edition_name, year_published, songs_count, publisher, isbn, notes
+            "#
+        )
+        .bind(id)
+        .bind(songbook_id)
+        .bind(&edition.edition_name)
+        .bind(edition.year_published)
+        .bind(edition.songs_count)
+        .bind(&edition.publisher)
+        .bind(&edition.isbn)
+        .bind(&edition.notes)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Edition with this name already exists"))?
+        .ok_or_else(|| AppError::not_found("Edition not found"))?;
+
+        Ok(edition.into())
+    }
+
+    pub async fn delete_edition(&self, songbook_id: Uuid, id: Uuid) -> AppResult<()> {
+        let result =
+            sqlx::query("DELETE FROM songbook_editions WHERE id = $1 AND songbook_id = $2")
+                .bind(id)
+                .bind(songbook_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Edition not found"));
+        }
+        Ok(())
+    }
+
+    /// Replace the numbering of an edition with `(edition number, song)` pairs
+    pub async fn set_edition_numbers(
+        &self,
+        songbook_id: Uuid,
+        edition_id: Uuid,
+        numbers: &[EditionNumber]
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM songbook_editions WHERE id = $1 AND songbook_id = $2)"
+        )
+        .bind(edition_id)
+        .bind(songbook_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !exists {
+            return Err(AppError::not_found("Edition not found"));
+        }
+
+        sqlx::query("DELETE FROM song_edition_numbers WHERE edition_id = $1")
+            .bind(edition_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let edition_numbers: Vec<i32> = numbers.iter().map(|n| n.number).collect();
+        let song_ids: Vec<Uuid> = numbers.iter().map(|n| n.song_id).collect();
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO song_edition_numbers (edition_id, number, song_id)
+            SELECT $1, n.number, n.song_id
+            FROM UNNEST($2::int[], $3::uuid[]) AS n(number, song_id)
+            JOIN songs s ON s.id = n.song_id AND s.songbook_id = $4
+            "#
+        )
+        .bind(edition_id)
+        .bind(&edition_numbers)
+        .bind(&song_ids)
+        .bind(songbook_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| unique_violation(e, "Duplicate number or song in edition numbering"))?;
+
+        if inserted.rows_affected() as usize != numbers.len() {
+            return Err(AppError::validation(
+                "Edition numbering references songs outside of the songbook"
+            ));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Map unique constraint violations to a conflict error
+pub(super) fn unique_violation(error: sqlx::Error, message: &'static str) -> AppError {
+    match &error {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::conflict(message),
+        _ => error.into()
+    }
+}
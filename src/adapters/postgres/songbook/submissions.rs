@@ -0,0 +1,261 @@
+use masterror::prelude::*;
+use revelation_songbook::CreateSong;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{rows::SongSubmissionRow, song_write::insert_song};
+use crate::domain::{
+    CreateSongSubmission, SongSubmission, SubmissionStatus, UpdateSongSubmission
+};
+
+const SUBMISSION_COLUMNS: &str = r#"
+    s.id, s.submitter_id, u.name as submitter_name, s.status, s.title,
+    s.author_lyrics, s.author_music, s.translator, s.original_key, s.content,
+    s.source_url, s.note, s.review_comment, s.reviewed_by, s.reviewed_at,
+    s.song_id, s.submitted_at, s.created_at, s.updated_at
+"#;
+
+/// PostgreSQL song submissions
+pub struct PgSongSubmissions {
+    pool: PgPool
+}
+
+impl PgSongSubmissions {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    pub async fn get_submission(&self, id: Uuid) -> AppResult<SongSubmission> {
+        let submission = sqlx::query_as::<_, SongSubmissionRow>(&format!(
+            r#"
+            SELECT {SUBMISSION_COLUMNS}
+            FROM song_submissions s
+            JOIN users u ON u.id = s.submitter_id
+            WHERE s.id = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Submission not found"))?;
+
+        Ok(submission.into())
+    }
+
+    /// Submissions of a user, newest first
+    pub async fn list_by_submitter(&self, user_id: Uuid) -> AppResult<Vec<SongSubmission>> {
+        let submissions = sqlx::query_as::<_, SongSubmissionRow>(&format!(
+            r#"
+            SELECT {SUBMISSION_COLUMNS}
+            FROM song_submissions s
+            JOIN users u ON u.id = s.submitter_id
+            WHERE s.submitter_id = $1
+            ORDER BY s.created_at DESC
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(submissions.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Submissions in a status, the longest waiting first
+    pub async fn list_by_status(
+        &self,
+        status: SubmissionStatus,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<SongSubmission>> {
+        let submissions = sqlx::query_as::<_, SongSubmissionRow>(&format!(
+            r#"
+            SELECT {SUBMISSION_COLUMNS}
+            FROM song_submissions s
+            JOIN users u ON u.id = s.submitter_id
+            WHERE s.status = $1
+            ORDER BY s.submitted_at NULLS LAST, s.created_at
+            LIMIT $2 OFFSET $3
+            "#
+        ))
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(submissions.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn create_submission(
+        &self,
+        submitter_id: Uuid,
+        submission: &CreateSongSubmission
+    ) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO song_submissions (
+                submitter_id, title, author_lyrics, author_music, translator,
+                original_key, content, source_url, note
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#
+        )
+        .bind(submitter_id)
+        .bind(submission.title.trim())
+        .bind(&submission.author_lyrics)
+        .bind(&submission.author_music)
+        .bind(&submission.translator)
+        .bind(&submission.original_key)
+        .bind(&submission.content)
+        .bind(&submission.source_url)
+        .bind(&submission.note)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Change a draft or rejected submission, which returns it to draft
+    pub async fn update_submission(
+        &self,
+        id: Uuid,
+        submission: &UpdateSongSubmission
+    ) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE song_submissions SET
+                title = COALESCE($2, title),
+                author_lyrics = COALESCE($3, author_lyrics),
+                author_music = COALESCE($4, author_music),
+                translator = COALESCE($5, translator),
+                original_key = COALESCE($6, original_key),
+                content = COALESCE($7, content),
+                source_url = COALESCE($8, source_url),
+                note = COALESCE($9, note),
+                status = 'draft',
+                updated_at = NOW()
+            WHERE id = $1 AND status IN ('draft', 'rejected')
+            "#
+        )
+        .bind(id)
+        .bind(submission.title.as_deref().map(str::trim))
+        .bind(&submission.author_lyrics)
+        .bind(&submission.author_music)
+        .bind(&submission.translator)
+        .bind(&submission.original_key)
+        .bind(&submission.content)
+        .bind(&submission.source_url)
+        .bind(&submission.note)
+        .execute(&self.pool)
+        .await?;
+
+        expect_one(result.rows_affected())
+    }
+
+    /// Send a draft to the review queue
+    pub async fn submit(&self, id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE song_submissions SET
+                status = 'pending', submitted_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = 'draft'
+            "#
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        expect_one(result.rows_affected())
+    }
+
+    /// Approve or reject a pending submission
+    pub async fn review(
+        &self,
+        id: Uuid,
+        reviewer_id: Uuid,
+        status: SubmissionStatus,
+        comment: Option<&str>
+    ) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE song_submissions SET
+                status = $2, review_comment = $3, reviewed_by = $4,
+                reviewed_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            "#
+        )
+        .bind(id)
+        .bind(status)
+        .bind(comment)
+        .bind(reviewer_id)
+        .execute(&self.pool)
+        .await?;
+
+        expect_one(result.rows_affected())
+    }
+
+    /// Turn an approved submission into a catalog song, returning the song.
+    ///
+    /// The submission stays locked until the song is created, so two editors
+    /// publishing it at once create one song and the other gets a conflict.
+    pub async fn publish(&self, id: Uuid, song: &CreateSong) -> AppResult<Uuid> {
+        let mut tx = self.pool.begin().await?;
+
+        let status = sqlx::query_scalar::<_, SubmissionStatus>(
+            "SELECT status FROM song_submissions WHERE id = $1 FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Submission not found"))?;
+
+        if status != SubmissionStatus::Approved {
+            return Err(AppError::conflict(
+                "Only approved submissions can be published"
+            ));
+        }
+
+        let song_id = insert_song(&mut tx, song).await?;
+
+        sqlx::query(
+            r#"
+            UPDATE song_submissions SET
+                status = 'published', song_id = $2, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .bind(song_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(song_id)
+    }
+
+    /// Delete a draft or rejected submission
+    pub async fn delete_submission(&self, id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            "DELETE FROM song_submissions WHERE id = $1 AND status IN ('draft', 'rejected')"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        expect_one(result.rows_affected())
+    }
+}
+
+/// Status-guarded updates touch nothing when another request moved the
+/// submission on first
+fn expect_one(rows_affected: u64) -> AppResult<()> {
+    if rows_affected == 0 {
+        return Err(AppError::conflict(
+            "Submission has changed; reload it and try again"
+        ));
+    }
+    Ok(())
+}
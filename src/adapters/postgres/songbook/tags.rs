@@ -1,6 +1,10 @@
-use masterror::AppResult;
+use masterror::{AppError, AppResult};
 use revelation_songbook::{SongTag, ports::SongTags};
 use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{rows::SongTagRow, songbook_write::unique_violation};
+use crate::domain::{CreateSongTag, UpdateSongTag};
 
 /// PostgreSQL implementation of SongTags
 pub struct PgSongTags {
@@ -13,6 +17,166 @@ impl PgSongTags {
             pool
         }
     }
+
+    pub async fn get_tag(&self, id: Uuid) -> AppResult<SongTag> {
+        let tag = sqlx::query_as::<_, SongTagRow>(
+            "SELECT id, name, name_ru, usage_count FROM song_tags WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Tag not found"))?;
+
+        Ok(tag.into())
+    }
+
+    /// Tags of a song, most used first
+    pub async fn song_tags(&self, song_id: Uuid) -> AppResult<Vec<SongTag>> {
+        let tags = sqlx::query_as::<_, SongTagRow>(
+            r#"
+            SELECT t.id, t.name, t.name_ru, t.usage_count
+            FROM song_tags t
+            JOIN song_tag_assignments sta ON t.id = sta.tag_id
+            WHERE sta.song_id = $1
+            ORDER BY t.usage_count DESC, t.name_ru
+            "#
+        )
+        .bind(song_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tags.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Resolve tag names or IDs, rejecting unknown ones
+    pub async fn resolve_tags(&self, names: &[String]) -> AppResult<Vec<Uuid>> {
+        let found = sqlx::query_as::<_, (Uuid, String)>(
+            "SELECT id, name FROM song_tags WHERE id::text = ANY($1) OR name = ANY($1)"
+        )
+        .bind(names)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut ids = Vec::with_capacity(names.len());
+        for name in names {
+            let tag = found
+                .iter()
+                .find(|(id, tag)| tag == name || id.to_string() == *name)
+                .ok_or_else(|| AppError::validation(format!("Unknown tag: {name}")))?;
+            ids.push(tag.0);
+        }
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    pub async fn create_tag(&self, tag: &CreateSongTag) -> AppResult<SongTag> {
+        let tag = sqlx::query_as::<_, SongTagRow>(
+            r#"
+            INSERT INTO song_tags (name, name_ru)
+            VALUES ($1, $2)
+            RETURNING id, name, name_ru, usage_count
+            "#
+        )
+        .bind(&tag.name)
+        .bind(tag.name_ru.trim())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Tag name already exists"))?;
+
+        Ok(tag.into())
+    }
+
+    pub async fn update_tag(&self, id: Uuid, tag: &UpdateSongTag) -> AppResult<SongTag> {
+        let tag = sqlx::query_as::<_, SongTagRow>(
+            r#"
+            UPDATE song_tags SET
+                name = COALESCE($2, name),
+                name_ru = COALESCE($3, name_ru)
+            WHERE id = $1
+            RETURNING id, name, name_ru, usage_count
+            "#
+        )
+        .bind(id)
+        .bind(&tag.name)
+        .bind(tag.name_ru.as_deref().map(str::trim))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| unique_violation(e, "Tag name already exists"))?
+        .ok_or_else(|| AppError::not_found("Tag not found"))?;
+
+        Ok(tag.into())
+    }
+
+    /// Give every song of `source` the `target` tag, then delete `source`
+    pub async fn merge_tags(&self, source: Uuid, target: Uuid) -> AppResult<SongTag> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO song_tag_assignments (song_id, tag_id)
+            SELECT song_id, $2 FROM song_tag_assignments WHERE tag_id = $1
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(source)
+        .bind(target)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM song_tags WHERE id = $1")
+            .bind(source)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        self.get_tag(target).await
+    }
+
+    pub async fn delete_tag(&self, id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM song_tags WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Tag not found"));
+        }
+        Ok(())
+    }
+
+    /// Tag a song; tagging it again is a no-op
+    pub async fn add_song_tag(&self, song_id: Uuid, tag_id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO song_tag_assignments (song_id, tag_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(song_id)
+        .bind(tag_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+                AppError::not_found("Song or tag not found")
+            }
+            _ => e.into()
+        })?;
+
+        Ok(())
+    }
+
+    pub async fn remove_song_tag(&self, song_id: Uuid, tag_id: Uuid) -> AppResult<()> {
+        sqlx::query("DELETE FROM song_tag_assignments WHERE song_id = $1 AND tag_id = $2")
+            .bind(song_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
 }
 
 impl SongTags for PgSongTags {
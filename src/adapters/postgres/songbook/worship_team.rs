@@ -0,0 +1,83 @@
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::rows::WorshipTeamMemberRow;
+use crate::domain::WorshipTeamMember;
+
+/// PostgreSQL church worship team membership
+pub struct PgWorshipTeam {
+    pool: PgPool
+}
+
+impl PgWorshipTeam {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    pub async fn list_members(&self, church_id: Uuid) -> AppResult<Vec<WorshipTeamMember>> {
+        let members = sqlx::query_as::<_, WorshipTeamMemberRow>(
+            r#"
+            SELECT t.user_id, u.name, t.added_at
+            FROM church_worship_team t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.church_id = $1
+            ORDER BY t.added_at
+            "#
+        )
+        .bind(church_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(members.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Add a church member to the worship team; guests cannot join
+    pub async fn add_member(&self, church_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO church_worship_team (church_id, user_id)
+            SELECT church_id, user_id FROM memberships
+            WHERE church_id = $1 AND user_id = $2 AND role <> 'guest'
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(church_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            let already = sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM church_worship_team WHERE church_id = $1 AND user_id = $2)"
+            )
+            .bind(church_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if !already {
+                return Err(AppError::validation(
+                    "Only church members can join the worship team"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn remove_member(&self, church_id: Uuid, user_id: Uuid) -> AppResult<()> {
+        let result =
+            sqlx::query("DELETE FROM church_worship_team WHERE church_id = $1 AND user_id = $2")
+                .bind(church_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Worship team member not found"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    io,
+    path::{Component, Path, PathBuf}
+};
+
+use async_trait::async_trait;
+use masterror::prelude::*;
+use tokio::{
+    fs::{self, File},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom}
+};
+use tokio_stream::StreamExt;
+use tokio_util::io::ReaderStream;
+
+use super::{AttachmentStorage, ByteStream};
+use crate::domain::ByteRange;
+
+/// Environment variable with the directory of locally stored attachments
+pub const ATTACHMENTS_DIR_ENV: &str = "SONG_ATTACHMENTS_DIR";
+
+/// Attachment files in a directory of the server's filesystem.
+///
+/// Every replica must see the same directory, e.g. a shared volume; files
+/// written to a container's own filesystem are lost with the container.
+pub struct LocalAttachmentStorage {
+    root: PathBuf
+}
+
+impl LocalAttachmentStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into()
+        }
+    }
+
+    /// Storage in the directory named by `SONG_ATTACHMENTS_DIR`, created
+    /// if missing
+    pub fn from_env() -> AppResult<Self> {
+        let root = std::env::var(ATTACHMENTS_DIR_ENV)
+            .ok()
+            .filter(|root| !root.is_empty())
+            .ok_or_else(|| AppError::internal(format!("{ATTACHMENTS_DIR_ENV} must be set")))?;
+        std::fs::create_dir_all(&root).map_err(|error| {
+            AppError::internal(format!(
+                "Cannot create attachment directory {root}: {error}"
+            ))
+        })?;
+        Ok(Self::new(root))
+    }
+
+    /// Keys are relative paths; anything that could leave the root is refused
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let relative = Path::new(key);
+        let is_plain = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if key.is_empty() || !is_plain {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid attachment key {key}")
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl AttachmentStorage for LocalAttachmentStorage {
+    async fn put(&self, key: &str, mut data: ByteStream) -> io::Result<u64> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // Written next to the target and renamed, so readers never see a
+        // partial file
+        let partial = path.with_extension("part");
+        let written = async {
+            let mut file = File::create(&partial).await?;
+            let mut size = 0;
+            while let Some(chunk) = data.next().await {
+                let chunk = chunk?;
+                file.write_all(&chunk).await?;
+                size += chunk.len() as u64;
+            }
+            file.sync_all().await?;
+            Ok::<_, io::Error>(size)
+        }
+        .await;
+
+        match written {
+            Ok(size) => {
+                fs::rename(&partial, &path).await?;
+                Ok(size)
+            }
+            Err(error) => {
+                let _ = fs::remove_file(&partial).await;
+                Err(error)
+            }
+        }
+    }
+
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> io::Result<ByteStream> {
+        let mut file = File::open(self.path(key)?).await?;
+        let stream: ByteStream = match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start)).await?;
+                Box::pin(ReaderStream::new(file.take(range.size())))
+            }
+            None => Box::pin(ReaderStream::new(file))
+        };
+        Ok(stream)
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(())
+        }
+    }
+}
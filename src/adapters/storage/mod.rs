@@ -0,0 +1,40 @@
+//! Storage of song attachment files.
+//!
+//! Files are addressed by keys such as `songs/{song_id}/{attachment_id}`;
+//! their metadata lives in PostgreSQL.
+
+mod local;
+
+use std::{io, pin::Pin};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+pub use local::*;
+use tokio_stream::Stream;
+
+use crate::domain::ByteRange;
+
+/// File contents read or written in chunks
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+/// File kept in the attachment storage
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub key:          String,
+    pub content_type: String,
+    pub size:         u64
+}
+
+/// Backend holding attachment files
+#[async_trait]
+pub trait AttachmentStorage: Send + Sync {
+    /// Store a file under `key`, replacing any previous one, and return its
+    /// size. Nothing is kept when the stream fails.
+    async fn put(&self, key: &str, data: ByteStream) -> io::Result<u64>;
+
+    /// Read a file, or the inclusive `range` of it
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> io::Result<ByteStream>;
+
+    /// Remove a file; missing files are not an error
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
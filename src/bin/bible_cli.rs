@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use masterror::prelude::*;
-use revelation_server::loader::BibleLoader;
+use revelation_server::{BibleService, StrongsLoader, loader::BibleLoader};
 use sqlx::postgres::PgPoolOptions;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -24,6 +24,29 @@ enum Commands {
         #[arg(short, long)]
         file: PathBuf
     },
+    /// Rebuild the word index of verses added or changed since the last run
+    Index {
+        /// Reindex every verse
+        #[arg(long)]
+        full: bool
+    },
+    /// Load dictionary forms of words and apply them to the word index
+    Lemmas {
+        /// Path to a tab-separated file of `word<TAB>lemma` lines
+        #[arg(short, long)]
+        file: PathBuf
+    },
+    /// Import Strong's lexicons and texts tagged with Strong's numbers
+    ImportStrongs {
+        /// Lexicon JSON keyed by number (Open Scriptures Strong's dictionary
+        /// format); repeat for the Hebrew and Greek lexicons
+        #[arg(short, long)]
+        lexicon: Vec<PathBuf>,
+        /// Tab-separated `book_id<TAB>chapter<TAB>verse<TAB>text` file with
+        /// tags like `слово<H1234>` after the words
+        #[arg(short, long)]
+        tagged:  Vec<PathBuf>
+    },
     /// Show statistics about loaded data
     Stats
 }
@@ -58,6 +81,55 @@ async fn main() -> AppResult<()> {
 
             tracing::info!("{}", stats);
         }
+        Commands::Index {
+            full
+        } => {
+            let stats = BibleService::new(pool).rebuild_word_index(full).await?;
+            tracing::info!("{}", stats);
+        }
+        Commands::Lemmas {
+            file
+        } => {
+            let content = std::fs::read_to_string(&file)
+                .map_err(|e| AppError::internal(format!("Failed to read file: {e}")))?;
+            let pairs: Vec<(String, String)> = content
+                .lines()
+                .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+                .filter_map(|line| {
+                    let (word, lemma) = line.split_once('\t')?;
+                    Some((word.trim().to_string(), lemma.trim().to_string()))
+                })
+                .collect();
+
+            let updated = BibleService::new(pool).load_lemmas(&pairs).await?;
+            tracing::info!(
+                "Loaded {} lemmas, {} index rows updated",
+                pairs.len(),
+                updated
+            );
+        }
+        Commands::ImportStrongs {
+            lexicon,
+            tagged
+        } => {
+            if lexicon.is_empty() && tagged.is_empty() {
+                return Err(AppError::validation(
+                    "Give at least one --lexicon or --tagged file"
+                ));
+            }
+
+            let loader = StrongsLoader::new(pool);
+            for file in &lexicon {
+                tracing::info!("Loading lexicon from {:?}", file);
+                let stats = loader.load_lexicon(file).await?;
+                tracing::info!("{}", stats);
+            }
+            for file in &tagged {
+                tracing::info!("Loading tagged text from {:?}", file);
+                let stats = loader.load_tagged_text(file).await?;
+                tracing::info!("{}", stats);
+            }
+        }
         Commands::Stats => {
             let books: i64 = sqlx::query_scalar!("SELECT COUNT(*) FROM bible_books")
                 .fetch_one(&pool)
@@ -75,10 +147,16 @@ async fn main() -> AppResult<()> {
                     .await?
                     .unwrap_or(0);
 
+            let lemmas: i64 =
+                sqlx::query_scalar("SELECT COUNT(DISTINCT lemma) FROM bible_word_index")
+                    .fetch_one(&pool)
+                    .await?;
+
             println!("Bible Statistics:");
             println!("  Books:  {books}");
             println!("  Verses: {verses}");
             println!("  Words:  {words}");
+            println!("  Lemmas: {lemmas}");
         }
     }
 
@@ -0,0 +1,330 @@
+//! Songbook data management CLI.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc
+};
+
+use clap::{Parser, Subcommand};
+use masterror::prelude::*;
+use revelation_server::{
+    DEFAULT_DUPLICATE_SCORE, SongbookLoader, SongbookService,
+    adapters::storage::LocalAttachmentStorage
+};
+use revelation_songbook::SongSummary;
+use sqlx::postgres::PgPoolOptions;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(name = "songbook-cli")]
+#[command(about = "Songbook data management CLI", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Import ChordPro/text files or a CSV manifest into a songbook
+    Import {
+        /// Songbook code
+        #[arg(short, long)]
+        songbook: String,
+        /// Directory with ChordPro or text files (`012 Title.cho`)
+        #[arg(
+            short,
+            long,
+            required_unless_present = "manifest",
+            conflicts_with = "manifest"
+        )]
+        dir:      Option<PathBuf>,
+        /// CSV manifest with `number,title,file,...` columns
+        #[arg(short, long)]
+        manifest: Option<PathBuf>,
+        /// Report changes without writing to the database
+        #[arg(long)]
+        dry_run:  bool
+    },
+    /// Load the song numbering of a songbook edition from CSV
+    EditionNumbers {
+        /// Songbook code
+        #[arg(short, long)]
+        songbook: String,
+        /// Edition ID or name
+        #[arg(short, long)]
+        edition:  String,
+        /// CSV with `edition_number,number` columns
+        #[arg(short, long)]
+        file:     PathBuf,
+        /// Report changes without writing to the database
+        #[arg(long)]
+        dry_run:  bool
+    },
+    /// Import a directory of OpenLyrics XML files
+    ImportOpenlyrics {
+        /// Directory with `.xml` files
+        #[arg(short, long)]
+        dir:      PathBuf,
+        /// Songbook code (defaults to the songbook named in each file)
+        #[arg(short, long)]
+        songbook: Option<String>
+    },
+    /// Export songs as OpenLyrics XML files into a directory
+    ExportOpenlyrics {
+        /// Output directory
+        #[arg(short, long)]
+        dir:      PathBuf,
+        /// Songbook code (defaults to all songs)
+        #[arg(short, long)]
+        songbook: Option<String>
+    },
+    /// List likely duplicate songs and propose merges
+    Duplicates {
+        /// Songbook code (defaults to all songs)
+        #[arg(short, long)]
+        songbook:  Option<String>,
+        /// Lowest similarity from 0 to 1
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_SCORE)]
+        min_score: f64,
+        /// Max pairs to list
+        #[arg(long, default_value_t = 100)]
+        limit:     i64,
+        /// Carry out the proposed merges of songs from the same songbook
+        #[arg(long)]
+        apply:     bool
+    },
+    /// List songs whose stated key disagrees with their chords
+    Keys {
+        /// Songbook code (defaults to all songs)
+        #[arg(short, long)]
+        songbook: Option<String>,
+        /// Also set missing keys to the ones detected from the chords
+        #[arg(long)]
+        fill:     bool
+    },
+    /// Merge a duplicate song into the song to keep
+    Merge {
+        /// Song to keep
+        #[arg(short, long)]
+        keep:      Uuid,
+        /// Song to merge and delete
+        #[arg(short, long)]
+        duplicate: Uuid
+    }
+}
+
+#[tokio::main]
+async fn main() -> AppResult<()> {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into())
+        ))
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let cli = Cli::parse();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&database_url)
+        .await?;
+
+    let songs = SongbookService::new(pool.clone(), Arc::new(LocalAttachmentStorage::from_env()?));
+
+    match cli.command {
+        Commands::Import {
+            songbook,
+            dir,
+            manifest,
+            dry_run
+        } => {
+            let loader = SongbookLoader::new(pool).dry_run(dry_run);
+
+            let stats = match (dir, manifest) {
+                (_, Some(manifest)) => loader.load_manifest(&songbook, &manifest).await?,
+                (Some(dir), None) => loader.load_dir(&songbook, &dir).await?,
+                (None, None) => unreachable!("clap requires --dir or --manifest")
+            };
+
+            if dry_run {
+                tracing::info!("Dry run: {}", stats);
+            } else {
+                tracing::info!("{}", stats);
+            }
+        }
+        Commands::EditionNumbers {
+            songbook,
+            edition,
+            file,
+            dry_run
+        } => {
+            let mapped = SongbookLoader::new(pool)
+                .dry_run(dry_run)
+                .load_edition_numbers(&songbook, &edition, &file)
+                .await?;
+
+            tracing::info!("Mapped {} songs to edition {}", mapped, edition);
+        }
+        Commands::ImportOpenlyrics {
+            dir,
+            songbook
+        } => {
+            let songbook_id = songbook_id(&songs, songbook.as_deref()).await?;
+            let (mut imported, mut failed) = (0, 0);
+
+            for path in files_with_extension(&dir, "xml")? {
+                let xml = std::fs::read_to_string(&path)?;
+
+                match songs.load_openlyrics(&xml, songbook_id).await {
+                    Ok(song) => {
+                        imported += 1;
+                        tracing::info!("Imported {:?} as {}", path, song.title);
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        tracing::warn!("Failed to import {:?}: {}", path, e);
+                    }
+                }
+            }
+
+            tracing::info!("Imported {} songs ({} failed)", imported, failed);
+        }
+        Commands::ExportOpenlyrics {
+            dir,
+            songbook
+        } => {
+            let songbook_id = songbook_id(&songs, songbook.as_deref()).await?;
+            std::fs::create_dir_all(&dir)?;
+
+            let ids = songs.list_song_ids(songbook_id).await?;
+            for id in &ids {
+                let xml = songs.export_openlyrics(*id).await?;
+                std::fs::write(dir.join(format!("{id}.xml")), xml)?;
+            }
+
+            tracing::info!("Exported {} songs to {:?}", ids.len(), dir);
+        }
+        Commands::Duplicates {
+            songbook,
+            min_score,
+            limit,
+            apply
+        } => {
+            let songbook_id = songbook_id(&songs, songbook.as_deref()).await?;
+            let duplicates = songs.find_duplicates(songbook_id, min_score, limit).await?;
+            let mut merged = Vec::new();
+
+            for pair in &duplicates {
+                let action = if pair.same_songbook {
+                    "merge"
+                } else {
+                    "relate"
+                };
+                println!(
+                    "{action} {:.2}  keep {} {}  <-  {} {}",
+                    pair.score,
+                    pair.keep.id,
+                    song_label(&pair.keep),
+                    pair.merge.id,
+                    song_label(&pair.merge)
+                );
+
+                // A song merged away earlier in this run cannot be merged again
+                let stale = merged.contains(&pair.keep.id) || merged.contains(&pair.merge.id);
+                if apply && pair.same_songbook && !stale {
+                    let result = songs.merge_songs(pair.keep.id, pair.merge.id).await?;
+                    merged.push(result.merged_id);
+                }
+            }
+
+            if apply {
+                tracing::info!(
+                    "Merged {} of {} duplicate pairs",
+                    merged.len(),
+                    duplicates.len()
+                );
+            } else {
+                tracing::info!(
+                    "Found {} duplicate pairs; `merge` pairs can be merged with --apply, \
+                     `relate` pairs from different songbooks are better related as versions",
+                    duplicates.len()
+                );
+            }
+        }
+        Commands::Keys {
+            songbook,
+            fill
+        } => {
+            let songbook_id = songbook_id(&songs, songbook.as_deref()).await?;
+            let mismatches = songs.find_key_mismatches(songbook_id).await?;
+
+            for mismatch in &mismatches {
+                let detected = mismatch.detected_key.as_deref().unwrap_or("?");
+                let note = if mismatch.invalid { "  (invalid)" } else { "" };
+                println!(
+                    "{} {}  stated {}  chords {}{note}",
+                    mismatch.song.id,
+                    song_label(&mismatch.song),
+                    mismatch.stated_key,
+                    detected
+                );
+            }
+            tracing::info!("Found {} songs with key mismatches", mismatches.len());
+
+            if fill {
+                let filled = songs.fill_missing_keys(songbook_id).await?;
+                tracing::info!("Set {} missing keys from chords", filled);
+            }
+        }
+        Commands::Merge {
+            keep,
+            duplicate
+        } => {
+            let result = songs.merge_songs(keep, duplicate).await?;
+            tracing::info!(
+                "Merged {} into {}: {} favorites, {} history entries, {} playlist items moved",
+                result.merged_id,
+                result.keep_id,
+                result.favorites_moved,
+                result.history_moved,
+                result.playlist_items_moved
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn songbook_id(songs: &SongbookService, code: Option<&str>) -> AppResult<Option<Uuid>> {
+    match code {
+        Some(code) => Ok(Some(songs.get_songbook_by_code(code).await?.id)),
+        None => Ok(None)
+    }
+}
+
+/// `[code #number] Title` of a song
+fn song_label(song: &SongSummary) -> String {
+    match (&song.songbook_code, song.number) {
+        (Some(code), Some(number)) => format!("[{code} #{number}] {}", song.title),
+        (Some(code), None) => format!("[{code}] {}", song.title),
+        _ => song.title.clone()
+    }
+}
+
+/// Files in `dir` with the given extension, sorted by name
+fn files_with_extension(dir: &Path, extension: &str) -> AppResult<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files)
+}
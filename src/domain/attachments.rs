@@ -0,0 +1,361 @@
+//! Media attached to songs: reference recordings, sheet music, rehearsal
+//! tracks and links to external pages.
+
+use chrono::{DateTime, Utc};
+use masterror::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Largest recording or rehearsal track accepted, 50 MiB
+pub const MAX_AUDIO_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Largest sheet music file accepted, 20 MiB
+pub const MAX_SHEET_MUSIC_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Bytes of a file needed to recognize its format
+pub const SIGNATURE_LENGTH: usize = 12;
+
+const AUDIO_TYPES: &[&str] = &[
+    "audio/mpeg",
+    "audio/mp4",
+    "audio/x-m4a",
+    "audio/aac",
+    "audio/ogg",
+    "audio/opus",
+    "audio/wav",
+    "audio/x-wav",
+    "audio/wave",
+    "audio/flac",
+    "audio/webm"
+];
+
+const SHEET_MUSIC_TYPES: &[&str] = &[
+    "application/pdf",
+    "image/png",
+    "image/jpeg",
+    "application/vnd.recordare.musicxml+xml",
+    "application/vnd.recordare.musicxml"
+];
+
+/// What a song attachment holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "song_attachment_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    /// Reference recording of the song
+    Recording,
+    /// PDF, image or MusicXML score
+    SheetMusic,
+    /// Backing track or single part for rehearsals
+    RehearsalTrack,
+    /// Page elsewhere, e.g. a video of the song
+    Link
+}
+
+impl AttachmentKind {
+    /// Media types accepted for uploads of this kind
+    pub fn content_types(self) -> &'static [&'static str] {
+        match self {
+            Self::Recording | Self::RehearsalTrack => AUDIO_TYPES,
+            Self::SheetMusic => SHEET_MUSIC_TYPES,
+            Self::Link => &[]
+        }
+    }
+
+    /// Largest upload of this kind in bytes
+    pub fn max_size(self) -> u64 {
+        match self {
+            Self::Recording | Self::RehearsalTrack => MAX_AUDIO_SIZE,
+            Self::SheetMusic => MAX_SHEET_MUSIC_SIZE,
+            Self::Link => 0
+        }
+    }
+}
+
+/// Media attached to a song
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongAttachment {
+    pub id:           Uuid,
+    pub song_id:      Uuid,
+    pub kind:         AttachmentKind,
+    pub title:        String,
+    pub file_name:    Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes:   Option<i64>,
+    /// Download address of files, or the linked page
+    pub url:          String,
+    pub created_at:   DateTime<Utc>
+}
+
+/// File upload to a song; the file itself is the request body
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UploadAttachment {
+    pub kind:      AttachmentKind,
+    pub title:     String,
+    pub file_name: Option<String>
+}
+
+impl UploadAttachment {
+    pub fn validate(&self, content_type: &str) -> AppResult<()> {
+        validate_title(&self.title)?;
+        if self.kind == AttachmentKind::Link {
+            return Err(AppError::validation("Links are added without a file"));
+        }
+        if let Some(file_name) = &self.file_name
+            && (file_name.trim().is_empty() || file_name.chars().count() > 255)
+        {
+            return Err(AppError::validation("File name must be 1-255 characters"));
+        }
+        if !self.kind.content_types().contains(&content_type) {
+            return Err(AppError::validation(format!(
+                "{content_type} is not accepted here; use one of {}",
+                self.kind.content_types().join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// File name without any directories the client sent along
+    pub fn base_file_name(&self) -> Option<&str> {
+        self.file_name
+            .as_deref()
+            .and_then(|name| name.rsplit(['/', '\\']).next())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// Link to a page about a song
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AddAttachmentLink {
+    pub title: String,
+    pub url:   String
+}
+
+impl AddAttachmentLink {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_title(&self.title)?;
+        let url = self.url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) || url.len() > 500 {
+            return Err(AppError::validation(
+                "Link must be an http(s) address of at most 500 characters"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Media type of a `Content-Type` header without parameters, lowercased
+pub fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Whether the first bytes of a file look like the declared media type.
+///
+/// Catches uploads that were given a wrong type by mistake, e.g. a Word
+/// document sent as a PDF.
+pub fn signature_matches(content_type: &str, head: &[u8]) -> bool {
+    match content_type {
+        "audio/mpeg" => {
+            head.starts_with(b"ID3")
+                || (head.len() > 1 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0)
+        }
+        "audio/aac" => head.len() > 1 && head[0] == 0xFF && head[1] & 0xF6 == 0xF0,
+        "audio/mp4" | "audio/x-m4a" => head.get(4..8) == Some(b"ftyp"),
+        "audio/ogg" | "audio/opus" => head.starts_with(b"OggS"),
+        "audio/wav" | "audio/x-wav" | "audio/wave" => {
+            head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE")
+        }
+        "audio/flac" => head.starts_with(b"fLaC"),
+        "audio/webm" => head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]),
+        "application/pdf" => head.starts_with(b"%PDF-"),
+        "image/png" => head.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "image/jpeg" => head.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "application/vnd.recordare.musicxml+xml" => head
+            .strip_prefix(b"\xEF\xBB\xBF")
+            .unwrap_or(head)
+            .trim_ascii_start()
+            .starts_with(b"<"),
+        "application/vnd.recordare.musicxml" => head.starts_with(b"PK\x03\x04"),
+        _ => false
+    }
+}
+
+/// Inclusive byte range of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end:   u64
+}
+
+impl ByteRange {
+    /// Number of bytes in the range
+    pub fn size(self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Part of a file asked for by a `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    Full,
+    Partial(ByteRange),
+    Unsatisfiable
+}
+
+impl RangeRequest {
+    /// Resolve a `Range` header against a file of `size` bytes.
+    ///
+    /// Only single `bytes` ranges are served partially; headers that cannot
+    /// be parsed, or ask for several ranges, get the whole file as RFC 9110
+    /// allows.
+    pub fn parse(header: Option<&str>, size: u64) -> Self {
+        let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+            return Self::Full;
+        };
+        if spec.contains(',') {
+            return Self::Full;
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+
+        let range = match (start.trim(), end.trim()) {
+            ("", "") => return Self::Full,
+            ("", suffix) => match suffix.parse::<u64>() {
+                Ok(0) => return Self::Unsatisfiable,
+                Ok(suffix) => ByteRange {
+                    start: size.saturating_sub(suffix),
+                    end:   size.saturating_sub(1)
+                },
+                Err(_) => return Self::Full
+            },
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Self::Full;
+                };
+                let end = match end {
+                    "" => u64::MAX,
+                    end => match end.parse::<u64>() {
+                        Ok(end) if end >= start => end,
+                        _ => return Self::Full
+                    }
+                };
+                ByteRange {
+                    start,
+                    end: end.min(size.saturating_sub(1))
+                }
+            }
+        };
+
+        if size == 0 || range.start >= size {
+            Self::Unsatisfiable
+        } else {
+            Self::Partial(range)
+        }
+    }
+}
+
+fn validate_title(title: &str) -> AppResult<()> {
+    let length = title.trim().chars().count();
+    if length == 0 || length > 200 {
+        return Err(AppError::validation("Title must be 1-200 characters"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(start: u64, end: u64) -> RangeRequest {
+        RangeRequest::Partial(ByteRange {
+            start,
+            end
+        })
+    }
+
+    #[test]
+    fn serves_whole_file_without_usable_header() {
+        for header in [
+            None,
+            Some("items=0-10"),
+            Some("bytes=0-1, 5-6"),
+            Some("bytes=-"),
+            Some("bytes=abc-"),
+            Some("bytes=10-5"),
+            Some("bytes=5")
+        ] {
+            assert_eq!(
+                RangeRequest::parse(header, 100),
+                RangeRequest::Full,
+                "{header:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_bounded_and_open_ranges() {
+        assert_eq!(RangeRequest::parse(Some("bytes=0-9"), 100), partial(0, 9));
+        assert_eq!(
+            RangeRequest::parse(Some(" bytes= 10 - 19 "), 100),
+            partial(10, 19)
+        );
+        assert_eq!(RangeRequest::parse(Some("bytes=90-"), 100), partial(90, 99));
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=90-500"), 100),
+            partial(90, 99)
+        );
+    }
+
+    #[test]
+    fn resolves_suffix_ranges() {
+        assert_eq!(RangeRequest::parse(Some("bytes=-10"), 100), partial(90, 99));
+        assert_eq!(RangeRequest::parse(Some("bytes=-500"), 100), partial(0, 99));
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=-0"), 100),
+            RangeRequest::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn refuses_ranges_outside_the_file() {
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=100-"), 100),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=0-9"), 0),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(
+            RangeRequest::parse(Some("bytes=-5"), 0),
+            RangeRequest::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn range_size_is_inclusive() {
+        assert_eq!(
+            ByteRange {
+                start: 10,
+                end:   19
+            }
+            .size(),
+            10
+        );
+        assert_eq!(
+            ByteRange {
+                start: 0, end: 0
+            }
+            .size(),
+            1
+        );
+    }
+}
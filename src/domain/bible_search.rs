@@ -0,0 +1,354 @@
+//! Bible full-text search parameters, result pages and match highlights.
+
+use masterror::prelude::*;
+use revelation_bible::{SearchResult, Testament};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Most verses returned on one page
+pub const MAX_BIBLE_SEARCH_LIMIT: i64 = 200;
+
+/// Marks the start of a matched term in `ts_headline` output
+pub const HIGHLIGHT_START: char = '\u{2}';
+
+/// Marks the end of a matched term in `ts_headline` output
+pub const HIGHLIGHT_STOP: char = '\u{3}';
+
+/// Longest phrase answered from the word index
+pub const MAX_PHRASE_WORDS: usize = 8;
+
+/// Largest distance of a `NEAR/n` query, in words
+pub const MAX_NEAR_DISTANCE: i16 = 50;
+
+/// Distance of a `NEAR` query without `/n`
+pub const DEFAULT_NEAR_DISTANCE: i16 = 5;
+
+/// How a Bible search query is matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BibleSearchMode {
+    /// Russian full-text search with stemming, best ranked first
+    #[default]
+    Fulltext,
+    /// Exact phrases, `NEAR/n` proximity and `prefix*` wildcards answered
+    /// from the word index, in canonical order
+    Positional
+}
+
+/// Bible search with optional filters.
+///
+/// `chapter_from` and `chapter_to` narrow a search within `book_id`.
+#[derive(Debug, Clone, Default)]
+pub struct BibleSearchParams {
+    pub query:        String,
+    pub mode:         BibleSearchMode,
+    pub testament:    Option<Testament>,
+    pub book_id:      Option<i16>,
+    pub chapter_from: Option<i16>,
+    pub chapter_to:   Option<i16>,
+    pub limit:        i64,
+    pub offset:       i64
+}
+
+impl BibleSearchParams {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.query.trim().is_empty() {
+            return Err(AppError::validation("Search query must not be empty"));
+        }
+        if self.book_id.is_none() && (self.chapter_from.is_some() || self.chapter_to.is_some()) {
+            return Err(AppError::validation(
+                "A chapter range needs the book_id it belongs to"
+            ));
+        }
+        if let (Some(from), Some(to)) = (self.chapter_from, self.chapter_to)
+            && from > to
+        {
+            return Err(AppError::validation(
+                "chapter_from must not be after chapter_to"
+            ));
+        }
+        if !(1..=MAX_BIBLE_SEARCH_LIMIT).contains(&self.limit) || self.offset < 0 {
+            return Err(AppError::validation(format!(
+                "limit must be 1-{MAX_BIBLE_SEARCH_LIMIT} and offset not negative"
+            )));
+        }
+        if self.mode == BibleSearchMode::Positional {
+            PositionalQuery::parse(&self.query)?;
+        }
+        Ok(())
+    }
+}
+
+/// Word of a positional query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexTerm {
+    /// Exactly this word
+    Word(String),
+    /// Any word starting with this prefix, written `prefix*`
+    Prefix(String)
+}
+
+impl IndexTerm {
+    /// Term of a query word, normalized like the word index; `None` for
+    /// tokens without letters or digits
+    fn parse(token: &str) -> Option<Self> {
+        let token = token.trim_matches(|c| c == '"' || c == '«' || c == '»');
+        let word = index_word(token);
+        if word.is_empty() {
+            return None;
+        }
+        if token.ends_with('*') {
+            Some(Self::Prefix(word))
+        } else {
+            Some(Self::Word(word))
+        }
+    }
+}
+
+/// Query answered from the positions of the word index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionalQuery {
+    /// Words next to each other in this order
+    Phrase(Vec<IndexTerm>),
+    /// Two words at most `distance` words apart, in either order
+    Near {
+        first:    IndexTerm,
+        second:   IndexTerm,
+        distance: i16
+    }
+}
+
+impl PositionalQuery {
+    /// Parse `word`, `prefix*`, `"a phrase"` or `word NEAR/n word`
+    pub fn parse(query: &str) -> AppResult<Self> {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let near = tokens
+            .iter()
+            .position(|t| *t == "NEAR" || t.starts_with("NEAR/"));
+
+        let Some(near) = near else {
+            let terms: Vec<IndexTerm> =
+                tokens.iter().filter_map(|t| IndexTerm::parse(t)).collect();
+            if terms.is_empty() {
+                return Err(AppError::validation(
+                    "Query needs at least one word of letters or digits"
+                ));
+            }
+            if terms.len() > MAX_PHRASE_WORDS {
+                return Err(AppError::validation(format!(
+                    "Phrases can have at most {MAX_PHRASE_WORDS} words"
+                )));
+            }
+            return Ok(Self::Phrase(terms));
+        };
+
+        let distance = match tokens[near].strip_prefix("NEAR/") {
+            Some(n) => n
+                .parse::<i16>()
+                .ok()
+                .filter(|n| (1..=MAX_NEAR_DISTANCE).contains(n))
+                .ok_or_else(|| {
+                    AppError::validation(format!(
+                        "NEAR distance must be 1-{MAX_NEAR_DISTANCE} words"
+                    ))
+                })?,
+            None => DEFAULT_NEAR_DISTANCE
+        };
+        let (left, right) = (&tokens[..near], &tokens[near + 1..]);
+        match (left, right) {
+            ([first], [second]) => match (IndexTerm::parse(first), IndexTerm::parse(second)) {
+                (Some(first), Some(second)) => Ok(Self::Near {
+                    first,
+                    second,
+                    distance
+                }),
+                _ => Err(AppError::validation("NEAR words need letters or digits"))
+            },
+            _ => Err(AppError::validation(
+                "NEAR joins exactly one word on each side, e.g. любовь NEAR/3 вера"
+            ))
+        }
+    }
+
+    /// Terms in query order
+    pub fn terms(&self) -> Vec<&IndexTerm> {
+        match self {
+            Self::Phrase(terms) => terms.iter().collect(),
+            Self::Near {
+                first,
+                second,
+                ..
+            } => vec![first, second]
+        }
+    }
+}
+
+/// Number of matching verses in a book, for a facet sidebar
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BookHits {
+    pub book_id:   i16,
+    pub book_name: String,
+    pub hits:      i64
+}
+
+/// Page of Bible search results, best matches first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BibleSearchPage {
+    /// Matching verses across all pages
+    pub total:   i64,
+    pub limit:   i64,
+    pub offset:  i64,
+    pub results: Vec<SearchResult>,
+    /// Hits per book within the testament filter, ignoring the book and
+    /// chapter filters so that other books stay selectable
+    pub books:   Vec<BookHits>
+}
+
+/// Character offsets `(start, end)` of the terms marked in a `ts_headline`
+/// produced with [`HIGHLIGHT_START`] and [`HIGHLIGHT_STOP`], relative to the
+/// text without the markers
+pub fn highlight_offsets(headline: &str) -> Vec<(usize, usize)> {
+    let mut offsets = Vec::new();
+    let mut position = 0;
+    let mut start = None;
+
+    for c in headline.chars() {
+        match c {
+            HIGHLIGHT_START => start = Some(position),
+            HIGHLIGHT_STOP => {
+                if let Some(start) = start.take() {
+                    offsets.push((start, position));
+                }
+            }
+            _ => position += 1
+        }
+    }
+    offsets
+}
+
+/// Character offsets `(start, end)` of the words of `text` at the given word
+/// index `positions`. Words are counted like the index counts them: split on
+/// whitespace, without punctuation, skipping tokens of punctuation only,
+/// starting from 1.
+pub fn word_offsets(text: &str, positions: &[i16]) -> Vec<(usize, usize)> {
+    let mut offsets = Vec::new();
+    let mut position = 0i16;
+    let mut offset = 0;
+
+    for token in text.split_inclusive(char::is_whitespace) {
+        let chars: Vec<char> = token.chars().collect();
+        let letters = chars.iter().filter(|c| is_index_char(**c)).count();
+        if letters > 0 {
+            position += 1;
+            if positions.contains(&position) {
+                let start = chars.iter().position(|c| is_index_char(*c));
+                let end = chars.iter().rposition(|c| is_index_char(*c));
+                if let (Some(start), Some(end)) = (start, end) {
+                    offsets.push((offset + start, offset + end + 1));
+                }
+            }
+        }
+        offset += chars.len();
+    }
+    offsets
+}
+
+/// Word as the word index stores it: lowercase, letters and digits only
+pub fn index_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| is_index_char(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Characters kept in words of the word index
+pub(crate) fn is_index_char(c: char) -> bool {
+    matches!(c, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё' | 'a'..='z' | 'A'..='Z' | '0'..='9')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: &str) -> IndexTerm {
+        IndexTerm::Word(value.to_string())
+    }
+
+    #[test]
+    fn parses_words_phrases_and_prefixes() {
+        assert_eq!(
+            PositionalQuery::parse("Любовь").unwrap(),
+            PositionalQuery::Phrase(vec![word("любовь")])
+        );
+        assert_eq!(
+            PositionalQuery::parse("«в начале» было").unwrap(),
+            PositionalQuery::Phrase(vec![word("в"), word("начале"), word("было")])
+        );
+        assert_eq!(
+            PositionalQuery::parse("благодат* — и").unwrap(),
+            PositionalQuery::Phrase(vec![IndexTerm::Prefix("благодат".to_string()), word("и")])
+        );
+    }
+
+    #[test]
+    fn parses_near_queries() {
+        assert_eq!(
+            PositionalQuery::parse("любовь NEAR/3 вера").unwrap(),
+            PositionalQuery::Near {
+                first:    word("любовь"),
+                second:   word("вера"),
+                distance: 3
+            }
+        );
+        assert_eq!(
+            PositionalQuery::parse("свет NEAR тьма*").unwrap(),
+            PositionalQuery::Near {
+                first:    word("свет"),
+                second:   IndexTerm::Prefix("тьма".to_string()),
+                distance: DEFAULT_NEAR_DISTANCE
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in [
+            "",
+            "— …",
+            "один два три четыре пять шесть семь восемь девять",
+            "любовь NEAR/0 вера",
+            "любовь NEAR/51 вера",
+            "любовь NEAR/x вера",
+            "любовь NEAR",
+            "вера и NEAR/2 любовь",
+            "любовь NEAR/2 —"
+        ] {
+            assert!(PositionalQuery::parse(query).is_err(), "{query:?}");
+        }
+    }
+
+    #[test]
+    fn counts_words_like_the_index() {
+        let text = "В начале было Слово, и Слово было у Бога.";
+        assert_eq!(word_offsets(text, &[1]), vec![(0, 1)]);
+        assert_eq!(word_offsets(text, &[4, 9]), vec![(14, 19), (36, 40)]);
+        assert_eq!(word_offsets(text, &[10]), Vec::new());
+
+        // Dashes are not words, and runs of whitespace count once
+        let text = "Свет — и  тьма";
+        assert_eq!(word_offsets(text, &[2, 3]), vec![(7, 8), (10, 14)]);
+    }
+
+    #[test]
+    fn finds_highlights_without_markers() {
+        let headline = format!(
+            "{HIGHLIGHT_START}Бог{HIGHLIGHT_STOP} есть {HIGHLIGHT_START}любовь{HIGHLIGHT_STOP}"
+        );
+        assert_eq!(highlight_offsets(&headline), vec![(0, 3), (9, 15)]);
+        assert_eq!(highlight_offsets("Бог есть любовь"), Vec::new());
+        assert_eq!(
+            highlight_offsets(&format!("{HIGHLIGHT_STOP}a{HIGHLIGHT_START}b")),
+            Vec::new()
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Near-identical songs left behind by repeated imports, and merging them.
+
+use revelation_songbook::SongSummary;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Default score from which two songs are reported as duplicates
+pub const DEFAULT_DUPLICATE_SCORE: f64 = 0.8;
+
+/// Pair of likely duplicate songs with the proposed survivor
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DuplicateSongs {
+    /// Song to keep: the numbered one, then the one with chords, the most
+    /// favorited and viewed, and finally the oldest
+    pub keep:               SongSummary,
+    /// Song to merge into `keep`
+    pub merge:              SongSummary,
+    /// Weighted similarity from 0 to 1
+    pub score:              f64,
+    /// Trigram similarity of the normalized lyrics
+    pub content_similarity: f64,
+    /// Trigram similarity of the normalized titles
+    pub title_similarity:   f64,
+    pub same_first_line:    bool,
+    /// Both songs are in the same songbook, or in none. Pairs from different
+    /// songbooks are usually versions of a hymn to relate rather than merge.
+    pub same_songbook:      bool
+}
+
+/// Duplicate song to merge into the song of the request path
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct MergeSong {
+    pub duplicate_id: Uuid
+}
+
+/// Outcome of merging a duplicate into the surviving song
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongMerge {
+    pub keep_id:              Uuid,
+    pub merged_id:            Uuid,
+    /// Favorites moved; users who had favorited both keep one
+    pub favorites_moved:      u64,
+    pub history_moved:        u64,
+    pub playlist_items_moved: u64
+}
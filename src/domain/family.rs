@@ -0,0 +1,71 @@
+//! Song families: an original song with its translations and the versions
+//! of the same hymn in different songbooks.
+
+use masterror::prelude::*;
+use revelation_songbook::{Song, SongSummary};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::SongAttachment;
+
+/// Song with the related songs of its family
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongDetails {
+    #[serde(flatten)]
+    pub song:         Song,
+    /// Family members in the same language, e.g. the hymn in other songbooks
+    pub versions:     Vec<FamilySong>,
+    /// Family members in other languages
+    pub translations: Vec<FamilySong>,
+    /// Recordings, sheet music, rehearsal tracks and links
+    pub attachments:  Vec<SongAttachment>
+}
+
+/// Member of a song family
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FamilySong {
+    pub song:        SongSummary,
+    /// Language code such as `ru` or `en`
+    pub language:    String,
+    pub is_original: bool
+}
+
+/// Song family with all visible members, the original first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongFamily {
+    pub id:      Uuid,
+    pub members: Vec<FamilySong>
+}
+
+/// Add a song to the family of a related song.
+///
+/// If neither song has a family yet, a new one is started; if both have
+/// one, the two families are merged.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct LinkSongFamily {
+    pub related_to:  Uuid,
+    /// Language of the song; defaults to the language of its songbook
+    pub language:    Option<String>,
+    /// Mark the song as the original the others were translated from
+    #[serde(default)]
+    pub is_original: bool
+}
+
+impl LinkSongFamily {
+    pub fn validate(&self, song_id: Uuid) -> AppResult<()> {
+        if self.related_to == song_id {
+            return Err(AppError::validation("Cannot relate a song to itself"));
+        }
+        if let Some(language) = &self.language {
+            let valid = (2..=10).contains(&language.len())
+                && language.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+            if !valid {
+                return Err(AppError::validation(
+                    "Language must be a code such as `ru` or `en`"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+//! Live worship presentation: leader commands and follower slides.
+
+use chrono::{DateTime, Utc};
+use masterror::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Leader command; omitted fields keep the current session state.
+///
+/// Switching to another song starts at its first section with the
+/// transposition set for that playlist item.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct LiveCommand {
+    /// Playlist item to show
+    pub item_id:   Option<Uuid>,
+    /// Song to show; must be part of the playlist
+    pub song_id:   Option<Uuid>,
+    /// Section index within the song
+    pub section:   Option<i32>,
+    pub transpose: Option<i16>,
+    /// Hide the lyrics (black screen) without leaving the song
+    pub blank:     Option<bool>
+}
+
+impl LiveCommand {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.section.is_some_and(|s| s < 0) {
+            return Err(AppError::validation("Section must not be negative"));
+        }
+        if self.transpose.is_some_and(|t| !(-12..=12).contains(&t)) {
+            return Err(AppError::validation(
+                "Transposition must be between -12 and 12 semitones"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Session state after the command; `switch_to` is the song and
+    /// transposition of the item or song the command switches to
+    pub fn apply_to(&self, state: LiveState, switch_to: Option<(Uuid, i16)>) -> LiveState {
+        let mut next = state;
+        if let Some((song_id, transpose)) = switch_to {
+            next.song_id = Some(song_id);
+            next.section = 0;
+            next.transpose = transpose;
+        }
+        if let Some(section) = self.section {
+            next.section = section;
+        }
+        if let Some(transpose) = self.transpose {
+            next.transpose = transpose;
+        }
+        if let Some(blank) = self.blank {
+            next.blank = blank;
+        }
+        next
+    }
+}
+
+/// What the leader has set: song, section, transposition and blanking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LiveState {
+    pub song_id:   Option<Uuid>,
+    pub section:   i32,
+    pub transpose: i16,
+    pub blank:     bool
+}
+
+/// Stored session state with the song being shown
+#[derive(Debug, Clone)]
+pub struct LiveSession {
+    pub playlist_id:  Uuid,
+    pub leader_id:    Uuid,
+    pub song_id:      Option<Uuid>,
+    pub section:      i32,
+    pub transpose:    i16,
+    pub blank:        bool,
+    pub updated_at:   DateTime<Utc>,
+    pub title:        Option<String>,
+    pub content:      Option<String>,
+    pub original_key: Option<String>
+}
+
+/// What followers display right now
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LiveSlide {
+    pub playlist_id:  Uuid,
+    pub leader_id:    Uuid,
+    pub song_id:      Option<Uuid>,
+    pub title:        Option<String>,
+    /// Section index within the song
+    pub section:      i32,
+    /// Verse order name of the section (`v1`, `c1`, ...)
+    pub section_name: Option<String>,
+    /// Verse order names of all sections, for navigation
+    pub sections:     Vec<String>,
+    /// Lyrics without chords, for projection
+    pub text:         String,
+    /// Transposed ChordPro lines of the section, for musicians
+    pub chords:       String,
+    /// Transposed key
+    pub key:          Option<String>,
+    pub transpose:    i16,
+    pub blank:        bool,
+    pub updated_at:   DateTime<Utc>
+}
+
+/// Message pushed to followers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    Slide(LiveSlide),
+    /// The leader ended the session
+    Ended {
+        playlist_id: Uuid
+    },
+    /// Rejected command, sent only to the connection that issued it
+    Error {
+        message: String
+    }
+}
@@ -1,5 +1,41 @@
 //! Domain types for the Revelation server.
 
+pub mod attachments;
 pub mod bible;
+pub mod bible_search;
+pub mod duplicates;
+pub mod family;
+pub mod live;
+pub mod music;
+pub mod notification;
+pub mod playlist;
+pub mod popularity;
+pub mod recommendations;
+pub mod reports;
+pub mod scripture;
+pub mod song_search;
+pub mod songbook;
+pub mod strongs;
+pub mod submissions;
+pub mod symphony;
+pub mod tags;
 
+pub use attachments::*;
 pub use bible::*;
+pub use bible_search::*;
+pub use duplicates::*;
+pub use family::*;
+pub use live::*;
+pub use music::*;
+pub use notification::*;
+pub use playlist::*;
+pub use popularity::*;
+pub use recommendations::*;
+pub use reports::*;
+pub use scripture::*;
+pub use song_search::*;
+pub use songbook::*;
+pub use strongs::*;
+pub use submissions::*;
+pub use symphony::*;
+pub use tags::*;
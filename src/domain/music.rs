@@ -0,0 +1,481 @@
+//! Musical keys and time signatures of songs, and key detection from the
+//! chords of their content.
+
+use std::{fmt, str::FromStr};
+
+use masterror::prelude::*;
+use revelation_songbook::{CreateSong, SongSummary, UpdateSong};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::formats::ChordProDocument;
+
+/// Slowest and fastest tempo accepted, in beats per minute
+pub const TEMPO_RANGE: std::ops::RangeInclusive<i32> = 1..=299;
+
+const SHARP_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"
+];
+const FLAT_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B"
+];
+
+/// Conventional spelling of major keys by tonic pitch class
+const MAJOR_TONICS: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"
+];
+/// Conventional spelling of minor keys by tonic pitch class
+const MINOR_TONICS: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "G#", "A", "Bb", "B"
+];
+
+/// Chords belonging to a major key, as (semitones above the tonic, quality)
+const MAJOR_CHORDS: [(u8, ChordQuality); 7] = [
+    (0, ChordQuality::Major),
+    (2, ChordQuality::Minor),
+    (4, ChordQuality::Minor),
+    (5, ChordQuality::Major),
+    (7, ChordQuality::Major),
+    (9, ChordQuality::Minor),
+    (11, ChordQuality::Diminished)
+];
+/// Chords belonging to a minor key, including the major dominant of
+/// harmonic minor
+const MINOR_CHORDS: [(u8, ChordQuality); 8] = [
+    (0, ChordQuality::Minor),
+    (2, ChordQuality::Diminished),
+    (3, ChordQuality::Major),
+    (5, ChordQuality::Minor),
+    (7, ChordQuality::Minor),
+    (7, ChordQuality::Major),
+    (8, ChordQuality::Major),
+    (10, ChordQuality::Major)
+];
+
+/// Major or minor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyMode {
+    Major,
+    Minor
+}
+
+/// Key of a song, written like `C`, `F#`, `Bb`, `Am` or `F#m`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MusicalKey {
+    tonic: String,
+    mode:  KeyMode
+}
+
+impl MusicalKey {
+    pub fn mode(&self) -> KeyMode {
+        self.mode
+    }
+
+    /// Pitch class of the tonic, 0 for C up to 11 for B
+    pub fn pitch_class(&self) -> u8 {
+        note_pitch_class(&self.tonic).unwrap_or_default()
+    }
+
+    /// Whether both keys sound the same, e.g. `C#m` and `Dbm`
+    pub fn is_enharmonic(&self, other: &Self) -> bool {
+        self.mode == other.mode && self.pitch_class() == other.pitch_class()
+    }
+}
+
+impl FromStr for MusicalKey {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> AppResult<Self> {
+        let value = value.trim();
+        let invalid = || {
+            AppError::validation(format!(
+                "Invalid key {value:?}; use a note A-G with an optional # or b, \
+                 followed by m for minor keys, e.g. C, F#, Bb, Am"
+            ))
+        };
+
+        let (tonic, rest) = split_note(value).ok_or_else(invalid)?;
+        let mode = match rest {
+            "" => KeyMode::Major,
+            "m" => KeyMode::Minor,
+            _ => return Err(invalid())
+        };
+        Ok(Self {
+            tonic,
+            mode
+        })
+    }
+}
+
+impl fmt::Display for MusicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            KeyMode::Major => write!(f, "{}", self.tonic),
+            KeyMode::Minor => write!(f, "{}m", self.tonic)
+        }
+    }
+}
+
+/// Time signature of a song, written like `4/4`, `3/4` or `6/8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub beats: u8,
+    pub unit:  u8
+}
+
+impl FromStr for TimeSignature {
+    type Err = AppError;
+
+    /// Also reads `C` as common time (4/4) and `C|` as cut time (2/2)
+    fn from_str(value: &str) -> AppResult<Self> {
+        let value = value.trim();
+        match value {
+            "C" => {
+                return Ok(Self {
+                    beats: 4, unit: 4
+                });
+            }
+            "C|" => {
+                return Ok(Self {
+                    beats: 2, unit: 2
+                });
+            }
+            _ => {}
+        }
+
+        let parsed = value
+            .split_once('/')
+            .and_then(|(beats, unit)| {
+                Some((
+                    beats.trim().parse::<u8>().ok()?,
+                    unit.trim().parse::<u8>().ok()?
+                ))
+            })
+            .filter(|(beats, unit)| {
+                (1..=32).contains(beats) && matches!(unit, 1 | 2 | 4 | 8 | 16 | 32)
+            });
+
+        match parsed {
+            Some((beats, unit)) => Ok(Self {
+                beats,
+                unit
+            }),
+            None => Err(AppError::validation(format!(
+                "Invalid time signature {value:?}; use beats/unit with a unit of \
+                 1, 2, 4, 8, 16 or 32, e.g. 4/4, 3/4, 6/8"
+            )))
+        }
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.beats, self.unit)
+    }
+}
+
+/// Canonical form of a key given by a client; blank keys are dropped
+pub fn normalize_key(key: Option<&str>) -> AppResult<Option<String>> {
+    key.map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| key.parse::<MusicalKey>().map(|key| key.to_string()))
+        .transpose()
+}
+
+/// Canonical form of a time signature given by a client; blank values are
+/// dropped
+pub fn normalize_time_signature(time_signature: Option<&str>) -> AppResult<Option<String>> {
+    time_signature
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<TimeSignature>().map(|ts| ts.to_string()))
+        .transpose()
+}
+
+/// Validate the key, tempo and time signature of a song before it is
+/// written, bringing them to canonical form
+pub fn normalize_song_music(song: &mut CreateSong) -> AppResult<()> {
+    song.original_key = normalize_key(song.original_key.as_deref())?;
+    song.time_signature = normalize_time_signature(song.time_signature.as_deref())?;
+    if song
+        .tempo
+        .is_some_and(|tempo| !TEMPO_RANGE.contains(&tempo))
+    {
+        return Err(AppError::validation(format!(
+            "Tempo must be {}-{} beats per minute",
+            TEMPO_RANGE.start(),
+            TEMPO_RANGE.end()
+        )));
+    }
+    Ok(())
+}
+
+/// Validate the key and time signature a song update sets, bringing them
+/// to canonical form
+pub fn normalize_update_music(song: &mut UpdateSong) -> AppResult<()> {
+    song.original_key = normalize_key(song.original_key.as_deref())?;
+    song.time_signature = normalize_time_signature(song.time_signature.as_deref())?;
+    Ok(())
+}
+
+/// Set a missing key of a song to the one its chords point to
+pub fn fill_missing_key(song: &mut CreateSong) {
+    if song.original_key.is_none() {
+        song.original_key = detect_key(&song.content).map(|key| key.to_string());
+    }
+}
+
+/// Key the chords of ChordPro content point to.
+///
+/// Every key is scored by how many chords belong to it, with extra weight
+/// for its tonic chord and for songs opening and especially closing on it.
+/// Returns `None` for content without chords or when no key explains most
+/// of them.
+pub fn detect_key(content: &str) -> Option<MusicalKey> {
+    let doc = ChordProDocument::parse(content);
+    let chords: Vec<Chord> = doc
+        .sections
+        .iter()
+        .flat_map(|section| &section.lines)
+        .flat_map(|line| line.chords())
+        .filter_map(Chord::parse)
+        .collect();
+    let (first, last) = (chords.first()?, chords.last()?);
+
+    let mut best: Option<(u32, u8, KeyMode)> = None;
+    for mode in [KeyMode::Major, KeyMode::Minor] {
+        let (scale, tonic_quality): (&[(u8, ChordQuality)], _) = match mode {
+            KeyMode::Major => (&MAJOR_CHORDS, ChordQuality::Major),
+            KeyMode::Minor => (&MINOR_CHORDS, ChordQuality::Minor)
+        };
+        for tonic in 0..12u8 {
+            let is_tonic = |chord: &Chord| chord.root == tonic && chord.quality == tonic_quality;
+            let fitting = chords
+                .iter()
+                .filter(|chord| scale.contains(&((chord.root + 12 - tonic) % 12, chord.quality)))
+                .count() as u32;
+            let score = fitting * 2
+                + chords.iter().filter(|chord| is_tonic(chord)).count() as u32
+                + if is_tonic(first) { 2 } else { 0 }
+                + if is_tonic(last) { 4 } else { 0 };
+
+            if fitting * 2 > chords.len() as u32
+                && best.is_none_or(|(best_score, ..)| score > best_score)
+            {
+                best = Some((score, tonic, mode));
+            }
+        }
+    }
+
+    let (_, tonic, mode) = best?;
+    // Spell the tonic the way the song does when it uses that note
+    let tonic = chords
+        .iter()
+        .find(|chord| chord.root == tonic)
+        .map(|chord| chord.spelling.clone())
+        .unwrap_or_else(|| {
+            match mode {
+                KeyMode::Major => MAJOR_TONICS[tonic as usize],
+                KeyMode::Minor => MINOR_TONICS[tonic as usize]
+            }
+            .to_string()
+        });
+    Some(MusicalKey {
+        tonic,
+        mode
+    })
+}
+
+/// Song whose stated key disagrees with its chords
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct KeyMismatch {
+    pub song:         SongSummary,
+    /// Key stored for the song
+    pub stated_key:   String,
+    /// Key the chords point to, if any
+    pub detected_key: Option<String>,
+    /// `stated_key` is not a valid key at all, e.g. `H` or `Cmaj` left by
+    /// older imports
+    pub invalid:      bool
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChordQuality {
+    Major,
+    Minor,
+    Diminished
+}
+
+/// Root and quality of a chord symbol; extensions and bass notes are
+/// ignored
+struct Chord {
+    root:     u8,
+    spelling: String,
+    quality:  ChordQuality
+}
+
+impl Chord {
+    fn parse(symbol: &str) -> Option<Self> {
+        let (spelling, rest) = split_note(symbol.trim())?;
+        let quality = if rest.starts_with("dim") || rest.starts_with('°') {
+            ChordQuality::Diminished
+        } else if rest.starts_with('m') && !rest.starts_with("maj") {
+            ChordQuality::Minor
+        } else {
+            ChordQuality::Major
+        };
+        Some(Self {
+            root: note_pitch_class(&spelling)?,
+            spelling,
+            quality
+        })
+    }
+}
+
+/// Split a leading note name such as `F#` or `Bb` off a symbol, writing
+/// `♯` and `♭` as `#` and `b`
+fn split_note(symbol: &str) -> Option<(String, &str)> {
+    let mut chars = symbol.char_indices();
+    let (_, letter) = chars.next().filter(|(_, c)| matches!(*c, 'A'..='G'))?;
+    let mut note = letter.to_string();
+    let rest = match chars.next() {
+        Some((i, c @ ('#' | '♯'))) => {
+            note.push('#');
+            &symbol[i + c.len_utf8()..]
+        }
+        Some((i, c @ ('b' | '♭'))) => {
+            note.push('b');
+            &symbol[i + c.len_utf8()..]
+        }
+        Some((i, _)) => &symbol[i..],
+        None => ""
+    };
+    Some((note, rest))
+}
+
+fn note_pitch_class(note: &str) -> Option<u8> {
+    SHARP_NAMES
+        .iter()
+        .chain(&FLAT_NAMES)
+        .position(|name| *name == note)
+        .map(|i| (i % 12) as u8)
+        .or(match note {
+            "E#" => Some(5),
+            "B#" => Some(0),
+            "Fb" => Some(4),
+            "Cb" => Some(11),
+            _ => None
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(value: &str) -> MusicalKey {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_keys() {
+        for value in ["C", "F#", "Bb", "Am", "F#m", "Ebm"] {
+            assert_eq!(key(value).to_string(), value);
+        }
+        assert_eq!(key(" C♯m ").to_string(), "C#m");
+        assert_eq!(key("B♭").to_string(), "Bb");
+        assert_eq!(key("Am").mode(), KeyMode::Minor);
+        assert_eq!(key("A").mode(), KeyMode::Major);
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        for value in ["", "H", "c", "Cmaj", "Amin", "C##", "Bbb"] {
+            assert!(value.parse::<MusicalKey>().is_err(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn compares_keys_enharmonically() {
+        assert!(key("C#m").is_enharmonic(&key("Dbm")));
+        assert!(key("Gb").is_enharmonic(&key("F#")));
+        assert!(key("Cb").is_enharmonic(&key("B")));
+        assert!(!key("C#").is_enharmonic(&key("C#m")));
+        assert!(!key("C").is_enharmonic(&key("D")));
+        assert_eq!(key("A").pitch_class(), 9);
+    }
+
+    #[test]
+    fn parses_and_prints_time_signatures() {
+        for (value, expected) in [
+            ("4/4", "4/4"),
+            (" 3 / 4 ", "3/4"),
+            ("6/8", "6/8"),
+            ("C", "4/4"),
+            ("C|", "2/2")
+        ] {
+            assert_eq!(
+                value.parse::<TimeSignature>().unwrap().to_string(),
+                expected
+            );
+        }
+        for value in ["", "4", "4/3", "0/4", "33/4", "x/4", "4/4/4"] {
+            assert!(value.parse::<TimeSignature>().is_err(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn normalizes_blank_values_away() {
+        assert_eq!(normalize_key(Some("  ")).unwrap(), None);
+        assert!(normalize_key(Some(" am ")).is_err());
+        assert_eq!(normalize_key(Some(" Am ")).unwrap().as_deref(), Some("Am"));
+        assert_eq!(normalize_time_signature(None).unwrap(), None);
+        assert_eq!(
+            normalize_time_signature(Some("C")).unwrap().as_deref(),
+            Some("4/4")
+        );
+    }
+
+    #[test]
+    fn normalizes_updated_key_and_time_signature() {
+        let mut song: UpdateSong = serde_json::from_value(serde_json::json!({
+            "original_key": " C♯m ",
+            "time_signature": "C|"
+        }))
+        .unwrap();
+        normalize_update_music(&mut song).unwrap();
+        assert_eq!(song.original_key.as_deref(), Some("C#m"));
+        assert_eq!(song.time_signature.as_deref(), Some("2/2"));
+
+        let mut song: UpdateSong =
+            serde_json::from_value(serde_json::json!({ "original_key": "H" })).unwrap();
+        assert!(normalize_update_music(&mut song).is_err());
+    }
+
+    #[test]
+    fn detects_major_and_minor_keys() {
+        let major = "[G]Amazing [C]grace, how [G]sweet the [D]sound\n\
+                     That [G]saved a [Em]wretch like [D]me [G]";
+        assert_eq!(detect_key(major), Some(key("G")));
+
+        let minor = "[Am]Hear my [Dm]cry, O [E]Lord, at[Am]tend\n\
+                     [F]Unto my [G]prayer [E]give [Am]ear";
+        assert_eq!(detect_key(minor), Some(key("Am")));
+    }
+
+    #[test]
+    fn spells_detected_tonic_like_the_song() {
+        let sharps = "[C#m]Line [F#m]one [G#]and [C#m]two";
+        assert_eq!(detect_key(sharps).unwrap().to_string(), "C#m");
+
+        let flats = "[Db]Line [Gb]one [Ab]and [Db]two";
+        assert_eq!(detect_key(flats).unwrap().to_string(), "Db");
+    }
+
+    #[test]
+    fn detects_nothing_without_fitting_chords() {
+        assert_eq!(detect_key("Just words, no chords"), None);
+        // Every major chord once: no key holds more than four of them
+        let chromatic = "[C]a [C#]b [D]c [D#]d [E]e [F]f [F#]g [G]h [G#]i [A]j [A#]k [B]l";
+        assert_eq!(detect_key(chromatic), None);
+    }
+}
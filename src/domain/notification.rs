@@ -0,0 +1,49 @@
+//! In-app notifications, listed to users by the notifications API.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    SongSubmissionApproved,
+    SongSubmissionRejected,
+    SongSubmissionPublished
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SongSubmissionApproved => "song_submission_approved",
+            Self::SongSubmissionRejected => "song_submission_rejected",
+            Self::SongSubmissionPublished => "song_submission_published"
+        }
+    }
+}
+
+/// Notification to send to a user
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub kind:    NotificationKind,
+    pub title:   String,
+    pub body:    Option<String>,
+    /// App path the notification leads to, e.g. `/songs/{id}`
+    pub link:    Option<String>
+}
+
+/// Notification received by a user
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UserNotification {
+    pub id:         Uuid,
+    /// [`NotificationKind`] in snake case
+    pub kind:       String,
+    pub title:      String,
+    pub body:       Option<String>,
+    pub link:       Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub read_at:    Option<DateTime<Utc>>
+}
@@ -0,0 +1,160 @@
+//! Playlist editing payloads and church setlists.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use masterror::prelude::*;
+use revelation_bible::Verse;
+use revelation_songbook::{PlaylistItem, SongPlaylist};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::scripture::validate_passage;
+use crate::formats::Slide;
+
+/// Playlist changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdatePlaylist {
+    pub name:        Option<String>,
+    pub description: Option<String>,
+    pub is_public:   Option<bool>,
+    pub event_date:  Option<NaiveDate>
+}
+
+impl UpdatePlaylist {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Playlist item changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdatePlaylistItem {
+    /// New 1-based position; other items shift to make room
+    pub position:            Option<i16>,
+    pub transpose_semitones: Option<i16>,
+    pub notes:               Option<String>
+}
+
+impl UpdatePlaylistItem {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.position.is_some_and(|p| p < 1) {
+            return Err(AppError::validation("Position must be positive"));
+        }
+        if self
+            .transpose_semitones
+            .is_some_and(|t| !(-12..=12).contains(&t))
+        {
+            return Err(AppError::validation(
+                "Transposition must be between -12 and 12 semitones"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// New order of a playlist: every item id exactly once
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReorderPlaylist {
+    pub item_ids: Vec<Uuid>
+}
+
+/// Copy of an existing playlist used as a template
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct DuplicatePlaylist {
+    /// Defaults to the template name
+    pub name:       Option<String>,
+    pub event_date: Option<NaiveDate>,
+    /// Defaults to the template church
+    pub church_id:  Option<Uuid>
+}
+
+impl DuplicatePlaylist {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Public read-only link to a playlist
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PlaylistShareLink {
+    /// Token for `GET /api/songs/shared/{token}`
+    pub token: String
+}
+
+/// Playlist opened through a share link
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SharedPlaylist {
+    pub playlist: SongPlaylist,
+    pub items:    Vec<PlaylistItem>
+}
+
+/// Projection slides for one playlist item
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PlaylistSlides {
+    pub item_id:  Uuid,
+    pub song_id:  Uuid,
+    pub position: i16,
+    pub title:    String,
+    pub notes:    Option<String>,
+    pub slides:   Vec<Slide>
+}
+
+/// Bible passage to read during a service
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AddPlaylistPassage {
+    /// Playlist item the passage follows; omitted to open the service
+    pub after_item_id: Option<Uuid>,
+    pub book_id:       i16,
+    pub chapter:       i16,
+    pub verse_start:   i16,
+    /// Last verse; defaults to `verse_start`
+    pub verse_end:     Option<i16>,
+    pub notes:         Option<String>
+}
+
+impl AddPlaylistPassage {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_passage(self.chapter, self.verse_start, self.verse_end)
+    }
+}
+
+/// Bible passage read during a service
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PlaylistPassage {
+    pub id:            Uuid,
+    /// Playlist item the passage follows; none when it opens the service
+    pub after_item_id: Option<Uuid>,
+    pub book_id:       i16,
+    pub book_name:     String,
+    pub chapter:       i16,
+    pub verse_start:   i16,
+    pub verse_end:     i16,
+    /// Reference such as `Ин 3:16-18`
+    pub reference:     String,
+    pub notes:         Option<String>,
+    pub verses:        Vec<Verse>
+}
+
+/// Church member who plans setlists
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WorshipTeamMember {
+    pub user_id:  Uuid,
+    pub name:     Option<String>,
+    pub added_at: DateTime<Utc>
+}
+
+fn validate_name(name: &str) -> AppResult<()> {
+    let length = name.trim().chars().count();
+    if length == 0 || length > 200 {
+        return Err(AppError::validation(
+            "Playlist name must be 1-200 characters"
+        ));
+    }
+    Ok(())
+}
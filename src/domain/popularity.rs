@@ -0,0 +1,65 @@
+//! Time-windowed song popularity and catalog orderings built on it.
+
+use chrono::{DateTime, Utc};
+use revelation_songbook::{SongSortBy, SongSummary};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Ordering by rolling popularity aggregates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum PopularityOrder {
+    /// Deduplicated views over the last 7 days
+    #[serde(rename = "popular_7d")]
+    Popular7d,
+    /// Deduplicated views over the last 30 days
+    #[serde(rename = "popular_30d")]
+    Popular30d,
+    /// Views and services of the last week against the weeks before
+    #[serde(rename = "trending")]
+    Trending,
+    /// Church services this calendar month
+    #[serde(rename = "most_sung_month")]
+    MostSungMonth
+}
+
+/// Song list ordering: the catalog orderings of [`SongSortBy`] plus the
+/// popularity ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum SongOrder {
+    Catalog(SongSortBy),
+    Popularity(PopularityOrder)
+}
+
+impl Default for SongOrder {
+    fn default() -> Self {
+        Self::Catalog(SongSortBy::default())
+    }
+}
+
+impl From<SongSortBy> for SongOrder {
+    fn from(sort: SongSortBy) -> Self {
+        Self::Catalog(sort)
+    }
+}
+
+/// Rolling popularity of a song as of the last aggregate refresh
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SongPopularity {
+    pub views_7d:       i32,
+    pub views_30d:      i32,
+    /// Setlists with a past event date that included the song
+    pub sung_7d:        i32,
+    pub sung_30d:       i32,
+    pub sung_month:     i32,
+    pub trending_score: f64
+}
+
+/// Song in a trending or most-sung list
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PopularSong {
+    pub song:         SongSummary,
+    pub popularity:   SongPopularity,
+    /// When the aggregates were last recomputed
+    pub refreshed_at: DateTime<Utc>
+}
@@ -0,0 +1,25 @@
+//! Similar-song recommendations.
+
+use revelation_songbook::SongSummary;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Song recommended for its likeness to one or more seed songs.
+///
+/// Counts are summed over all seed songs: the song itself for
+/// `/songs/{id}/similar`, the user's favorites for `/songs/recommended`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SimilarSong {
+    pub song:              SongSummary,
+    pub score:             f64,
+    pub shared_categories: i32,
+    pub shared_tags:       i32,
+    /// Same original key as a seed song
+    pub same_key:          bool,
+    /// Tempo within 10 BPM of a seed song
+    pub similar_tempo:     bool,
+    /// Playlists holding both songs
+    pub sung_together:     i32,
+    /// Users who viewed both songs
+    pub viewed_together:   i32
+}
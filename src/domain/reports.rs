@@ -0,0 +1,34 @@
+//! Song usage reporting for copyright licensing.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// How often a song was performed in church setlists
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongUsage {
+    pub song_id:       Uuid,
+    pub title:         String,
+    pub songbook_code: Option<String>,
+    pub number:        Option<i32>,
+    pub author_lyrics: Option<String>,
+    pub author_music:  Option<String>,
+    pub translator:    Option<String>,
+    pub copyright:     Option<String>,
+    /// Setlist entries, counting repeats within a service
+    pub times_used:    i64,
+    /// Distinct services the song was part of
+    pub services:      i64,
+    pub first_used:    NaiveDate,
+    pub last_used:     NaiveDate
+}
+
+/// Church song usage over a date range, most used first
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongUsageReport {
+    pub church_id: Uuid,
+    pub from:      NaiveDate,
+    pub to:        NaiveDate,
+    pub songs:     Vec<SongUsage>
+}
@@ -0,0 +1,83 @@
+//! Links between songs and the Scripture passages they are based on.
+
+use masterror::prelude::*;
+use revelation_bible::Verse;
+use revelation_songbook::SongSummary;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Passage to link to a song
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AddSongScripture {
+    pub book_id:     i16,
+    pub chapter:     i16,
+    pub verse_start: i16,
+    /// Last verse; defaults to `verse_start`
+    pub verse_end:   Option<i16>,
+    pub note:        Option<String>
+}
+
+impl AddSongScripture {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_passage(self.chapter, self.verse_start, self.verse_end)
+    }
+}
+
+/// Passage a song is based on
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongScripture {
+    pub id:          Uuid,
+    pub song_id:     Uuid,
+    pub book_id:     i16,
+    pub book_name:   String,
+    pub chapter:     i16,
+    pub verse_start: i16,
+    pub verse_end:   i16,
+    /// Reference such as `Пс 22:1-4`
+    pub reference:   String,
+    pub note:        Option<String>,
+    pub verses:      Vec<Verse>
+}
+
+/// Song linked to a passage of a chapter
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScriptureSong {
+    pub song:         SongSummary,
+    pub scripture_id: Uuid,
+    pub verse_start:  i16,
+    pub verse_end:    i16,
+    pub reference:    String,
+    pub note:         Option<String>
+}
+
+/// Verse whose wording the song lyrics share
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScriptureSuggestion {
+    pub book_id:      i16,
+    pub book_name:    String,
+    pub chapter:      i16,
+    pub verse:        i16,
+    pub reference:    String,
+    pub text:         String,
+    /// Distinct word stems found in both the verse and the lyrics
+    pub shared_words: i32,
+    pub score:        f64
+}
+
+/// Check a `chapter:verse_start-verse_end` range
+pub(crate) fn validate_passage(
+    chapter: i16,
+    verse_start: i16,
+    verse_end: Option<i16>
+) -> AppResult<()> {
+    if chapter < 1 || verse_start < 1 {
+        return Err(AppError::validation("Chapter and verse must be positive"));
+    }
+    if verse_end.is_some_and(|end| end < verse_start) {
+        return Err(AppError::validation(
+            "Passage must not end before it starts"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Song search parameters and autocomplete suggestions.
+
+use revelation_songbook::SongCategory;
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Full-text song search with optional filters.
+///
+/// The query matches title, alternative title, first line and lyrics.
+/// Titles also match with typos and in Latin transliteration
+/// (`blagodat` finds «Благодать»).
+#[derive(Debug, Clone, Default)]
+pub struct SongSearchParams {
+    pub query:        String,
+    pub songbook_id:  Option<Uuid>,
+    pub category:     Option<SongCategory>,
+    /// Songs must carry all of these tags
+    pub tag_ids:      Vec<Uuid>,
+    pub key:          Option<String>,
+    pub has_chords:   Option<bool>,
+    pub limit:        i64,
+    /// Return every song of a family instead of its best match only
+    pub all_versions: bool
+}
+
+/// Autocomplete suggestion for the song search box
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongSuggestion {
+    pub id:            Uuid,
+    pub title:         String,
+    pub first_line:    String,
+    pub songbook_code: Option<String>,
+    pub number:        Option<i32>
+}
@@ -0,0 +1,174 @@
+//! Songbook and edition management payloads.
+
+use masterror::prelude::*;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// New songbook.
+///
+/// Songbooks with `church_id` belong to that church; without it they are
+/// part of the shared catalog.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSongbook {
+    /// Unique code (`[a-z0-9_]`, e.g. `pesn_vozrozhdeniya`)
+    pub code:                 String,
+    pub name:                 String,
+    pub name_ru:              String,
+    pub description:          Option<String>,
+    pub cover_url:            Option<String>,
+    /// Private songbooks are visible only to the owning church
+    #[serde(default = "default_public")]
+    pub is_public:            bool,
+    /// Owning church
+    pub church_id:            Option<Uuid>,
+    pub year_first_published: Option<i16>,
+    pub year_latest_edition:  Option<i16>,
+    pub edition_name:         Option<String>,
+    pub total_songs_in_print: Option<i32>,
+    pub publisher:            Option<String>,
+    pub editor:               Option<String>,
+    pub isbn:                 Option<String>,
+    pub language:             Option<String>,
+    pub country:              Option<String>,
+    pub denomination:         Option<String>,
+    pub website_url:          Option<String>,
+    pub purchase_url:         Option<String>,
+    pub history:              Option<String>,
+    pub notes:                Option<String>
+}
+
+fn default_public() -> bool {
+    true
+}
+
+impl CreateSongbook {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_code(&self.code)?;
+        validate_name("name", &self.name)?;
+        validate_name("name_ru", &self.name_ru)
+    }
+}
+
+/// Songbook changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateSongbook {
+    pub code:                 Option<String>,
+    pub name:                 Option<String>,
+    pub name_ru:              Option<String>,
+    pub description:          Option<String>,
+    pub cover_url:            Option<String>,
+    pub is_public:            Option<bool>,
+    pub year_first_published: Option<i16>,
+    pub year_latest_edition:  Option<i16>,
+    pub edition_name:         Option<String>,
+    pub total_songs_in_print: Option<i32>,
+    pub publisher:            Option<String>,
+    pub editor:               Option<String>,
+    pub isbn:                 Option<String>,
+    pub language:             Option<String>,
+    pub country:              Option<String>,
+    pub denomination:         Option<String>,
+    pub website_url:          Option<String>,
+    pub purchase_url:         Option<String>,
+    pub history:              Option<String>,
+    pub notes:                Option<String>
+}
+
+impl UpdateSongbook {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(code) = &self.code {
+            validate_code(code)?;
+        }
+        if let Some(name) = &self.name {
+            validate_name("name", name)?;
+        }
+        if let Some(name_ru) = &self.name_ru {
+            validate_name("name_ru", name_ru)?;
+        }
+        Ok(())
+    }
+}
+
+/// New songbook edition
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSongbookEdition {
+    pub edition_name:   String,
+    pub year_published: i16,
+    pub songs_count:    i32,
+    pub publisher:      Option<String>,
+    pub isbn:           Option<String>,
+    pub notes:          Option<String>
+}
+
+impl CreateSongbookEdition {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_edition(&self.edition_name, self.songs_count)
+    }
+}
+
+/// Songbook edition changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateSongbookEdition {
+    pub edition_name:   Option<String>,
+    pub year_published: Option<i16>,
+    pub songs_count:    Option<i32>,
+    pub publisher:      Option<String>,
+    pub isbn:           Option<String>,
+    pub notes:          Option<String>
+}
+
+impl UpdateSongbookEdition {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_edition(
+            self.edition_name.as_deref().unwrap_or("-"),
+            self.songs_count.unwrap_or(0)
+        )
+    }
+}
+
+/// Song number in a specific songbook edition
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct EditionNumber {
+    /// Number of the song in the edition
+    pub number:  i32,
+    pub song_id: Uuid
+}
+
+fn validate_code(code: &str) -> AppResult<()> {
+    let valid = !code.is_empty()
+        && code.len() <= 50
+        && code
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if !valid {
+        return Err(AppError::validation(
+            "Songbook code must be 1-50 characters of a-z, 0-9 and _"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_name(field: &str, name: &str) -> AppResult<()> {
+    let length = name.trim().chars().count();
+    if length == 0 || length > 200 {
+        return Err(AppError::validation(format!(
+            "Songbook {field} must be 1-200 characters"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_edition(edition_name: &str, songs_count: i32) -> AppResult<()> {
+    let length = edition_name.trim().chars().count();
+    if length == 0 || length > 100 {
+        return Err(AppError::validation(
+            "Edition name must be 1-100 characters"
+        ));
+    }
+    if songs_count < 0 {
+        return Err(AppError::validation("Songs count must not be negative"));
+    }
+    Ok(())
+}
@@ -0,0 +1,351 @@
+//! Strong's numbers: lexicon entries of the Hebrew and Greek words behind
+//! the translation and the tags linking translated words to them.
+
+use std::{fmt, str::FromStr};
+
+use masterror::prelude::*;
+use revelation_bible::{SearchResult, Verse};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::domain::{WordForm, bible_search::is_index_char, index_word};
+
+/// Last book of the Old Testament
+const LAST_OLD_TESTAMENT_BOOK: i16 = 39;
+
+/// Language of a Strong's number; Hebrew numbers cover Biblical Aramaic too
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OriginalLanguage {
+    Hebrew,
+    Greek
+}
+
+impl OriginalLanguage {
+    /// Language a book was written in, by book id
+    pub fn of_book(book_id: i16) -> Self {
+        if book_id <= LAST_OLD_TESTAMENT_BOOK {
+            Self::Hebrew
+        } else {
+            Self::Greek
+        }
+    }
+
+    fn prefix(self) -> char {
+        match self {
+            Self::Hebrew => 'H',
+            Self::Greek => 'G'
+        }
+    }
+}
+
+/// Strong's number written like `H1254`, `G26` or `H1254a`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StrongsNumber {
+    language: OriginalLanguage,
+    number:   u32,
+    suffix:   Option<char>
+}
+
+impl StrongsNumber {
+    /// Parse a number that may lack its `H`/`G` prefix, as tagged texts
+    /// often write it, taking the language of the book it occurs in
+    pub fn parse_in(value: &str, language: OriginalLanguage) -> AppResult<Self> {
+        let value = value.trim();
+        if value.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("{}{value}", language.prefix()).parse()
+        } else {
+            value.parse()
+        }
+    }
+
+    pub fn language(&self) -> OriginalLanguage {
+        self.language
+    }
+}
+
+impl FromStr for StrongsNumber {
+    type Err = AppError;
+
+    /// Reads the prefix in either case and drops leading zeros, so `h0430`
+    /// is `H430`
+    fn from_str(value: &str) -> AppResult<Self> {
+        let value = value.trim();
+        let invalid = || {
+            AppError::validation(format!(
+                "Invalid Strong's number {value:?}; use H or G followed by up to five \
+                 digits, e.g. H430, G26"
+            ))
+        };
+
+        let mut chars = value.chars();
+        let language = match chars.next() {
+            Some('H' | 'h') => OriginalLanguage::Hebrew,
+            Some('G' | 'g') => OriginalLanguage::Greek,
+            _ => return Err(invalid())
+        };
+        let rest = chars.as_str();
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (digits, suffix) = rest.split_at(digits_end);
+        let suffix = match suffix {
+            "" => None,
+            s if s.len() == 1 && s.starts_with(|c: char| c.is_ascii_lowercase()) => {
+                s.chars().next()
+            }
+            _ => return Err(invalid())
+        };
+        let number = digits
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..100_000).contains(n))
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            language,
+            number,
+            suffix
+        })
+    }
+}
+
+impl fmt::Display for StrongsNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.language.prefix(), self.number)?;
+        if let Some(suffix) = self.suffix {
+            write!(f, "{suffix}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lexicon entry of a Strong's number
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StrongsEntry {
+    pub number:          String,
+    pub language:        OriginalLanguage,
+    /// Word in the original script, e.g. «אָהַב»
+    pub lemma:           String,
+    pub transliteration: Option<String>,
+    pub pronunciation:   Option<String>,
+    pub definition:      String,
+    /// How the word is rendered in translation
+    pub usage:           Option<String>
+}
+
+/// Lexicon entry with every verse where a word is tagged with it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StrongsDetails {
+    pub entry:       StrongsEntry,
+    /// Tagged words across the Bible
+    pub total:       i64,
+    /// Verses with at least one tagged word
+    pub verse_count: i64,
+    /// Translated words tagged with the number, most frequent first
+    pub renderings:  Vec<WordForm>,
+    pub limit:       i64,
+    pub offset:      i64,
+    /// Verses in canonical order with the tagged words highlighted
+    pub occurrences: Vec<SearchResult>
+}
+
+/// Translated word of a verse with the Strong's number behind it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaggedWord {
+    /// Word position in the verse, counted like the word index
+    pub position:        i16,
+    pub word:            String,
+    /// Character offsets of the word in the verse text, absent when the
+    /// text no longer has a word at this position
+    pub start:           Option<usize>,
+    pub end:             Option<usize>,
+    pub strongs:         String,
+    /// Original word and its transliteration, when the lexicon has the number
+    pub lemma:           Option<String>,
+    pub transliteration: Option<String>,
+    pub morphology:      Option<String>
+}
+
+/// Verse of a chapter, with its tagged words when they were asked for
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChapterVerse {
+    #[serde(flatten)]
+    pub verse: Verse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<TaggedWord>>
+}
+
+/// Strong's tag of a word read from a tagged text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordTag {
+    pub position:   i16,
+    pub word:       String,
+    pub strongs:    StrongsNumber,
+    pub morphology: Option<String>
+}
+
+/// Strong's tags of a verse written with inline tags after each word, as in
+/// `В начале<H7225> сотворил<WH1254><WTH8804> Бог<H430>`.
+///
+/// A tag holds a number with or without its `H`/`G` prefix, optionally
+/// written `WH…`/`WG…` as in GBF texts; `WT…` tags give the morphology of
+/// the number before them. Other tags are ignored. Words are counted like
+/// the word index counts the verse without the tags.
+pub fn parse_tagged_verse(text: &str, language: OriginalLanguage) -> AppResult<Vec<WordTag>> {
+    let mut tags: Vec<WordTag> = Vec::new();
+    let mut position = 0i16;
+    let mut word = String::new();
+
+    for token in text.split_whitespace() {
+        let (bare, token_tags) = split_tags(token);
+        if !index_word(&bare).is_empty() {
+            position += 1;
+            word = bare.trim_matches(|c| !is_index_char(c)).to_string();
+        }
+        if position == 0 {
+            continue;
+        }
+
+        for tag in token_tags {
+            if let Some(morphology) = tag.strip_prefix("WT") {
+                if let Some(last) = tags.last_mut()
+                    && last.position == position
+                {
+                    last.morphology = Some(morphology.to_string());
+                }
+                continue;
+            }
+            let number = tag.strip_prefix('W').unwrap_or(tag);
+            if !number.starts_with(|c: char| c.is_ascii_digit() || matches!(c, 'H' | 'G')) {
+                continue;
+            }
+            tags.push(WordTag {
+                position,
+                word: word.clone(),
+                strongs: StrongsNumber::parse_in(number, language)?,
+                morphology: None
+            });
+        }
+    }
+    Ok(tags)
+}
+
+/// Token without its `<…>` tags, and the tags
+fn split_tags(token: &str) -> (String, Vec<&str>) {
+    let mut bare = String::new();
+    let mut tags = Vec::new();
+    let mut rest = token;
+
+    while let Some(open) = rest.find('<') {
+        bare.push_str(&rest[..open]);
+        match rest[open..].find('>') {
+            Some(close) => {
+                tags.push(&rest[open + 1..open + close]);
+                rest = &rest[open + close + 1..];
+            }
+            None => {
+                rest = &rest[open..];
+                break;
+            }
+        }
+    }
+    bare.push_str(rest);
+    (bare, tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::word_offsets;
+
+    fn strongs(value: &str) -> StrongsNumber {
+        value.parse().unwrap()
+    }
+
+    fn tag(position: i16, word: &str, number: &str, morphology: Option<&str>) -> WordTag {
+        WordTag {
+            position,
+            word: word.to_string(),
+            strongs: strongs(number),
+            morphology: morphology.map(str::to_string)
+        }
+    }
+
+    #[test]
+    fn parses_and_prints_strongs_numbers() {
+        for (value, expected) in [
+            ("H430", "H430"),
+            ("h0430", "H430"),
+            (" G26 ", "G26"),
+            ("H1254a", "H1254a")
+        ] {
+            assert_eq!(strongs(value).to_string(), expected);
+        }
+        assert_eq!(strongs("g26").language(), OriginalLanguage::Greek);
+        assert_eq!(
+            StrongsNumber::parse_in("0430", OriginalLanguage::Hebrew).unwrap(),
+            strongs("H430")
+        );
+        assert_eq!(
+            StrongsNumber::parse_in("G26", OriginalLanguage::Hebrew).unwrap(),
+            strongs("G26")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_strongs_numbers() {
+        for value in ["", "X1", "H", "H0", "G26ab", "G26A", "H100000", "H-1", "26"] {
+            assert!(value.parse::<StrongsNumber>().is_err(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn tags_words_counted_like_the_index() {
+        let tags = parse_tagged_verse(
+            "В начале<H7225> — сотворил<WH1254><WTH8804> Бог<H430> небо<H8064>.",
+            OriginalLanguage::Hebrew
+        )
+        .unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                tag(2, "начале", "H7225", None),
+                tag(3, "сотворил", "H1254", Some("H8804")),
+                tag(4, "Бог", "H430", None),
+                tag(5, "небо", "H8064", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_morphology_without_a_number() {
+        let tags = parse_tagged_verse(
+            "<WTG5719>Любовь<G26> долготерпит<WTG5719>, милосердствует<5541>",
+            OriginalLanguage::Greek
+        )
+        .unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                tag(1, "Любовь", "G26", None),
+                tag(3, "милосердствует", "G5541", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_match_word_offsets() {
+        let tagged = "И сказал<H559> Бог<H430>: да будет<H1961> свет<H216>. — И стал свет<H216>.";
+        let text = "И сказал Бог: да будет свет. — И стал свет.";
+        let tags = parse_tagged_verse(tagged, OriginalLanguage::Hebrew).unwrap();
+        let positions: Vec<i16> = tags.iter().map(|tag| tag.position).collect();
+
+        let words: Vec<String> = word_offsets(text, &positions)
+            .into_iter()
+            .map(|(start, end)| text.chars().skip(start).take(end - start).collect())
+            .collect();
+        let tagged_words: Vec<&str> = tags.iter().map(|tag| tag.word.as_str()).collect();
+        assert_eq!(words, tagged_words);
+    }
+}
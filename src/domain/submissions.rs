@@ -0,0 +1,160 @@
+//! Songs suggested by members and the editor review that publishes them.
+
+use chrono::{DateTime, Utc};
+use masterror::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::normalize_key;
+
+/// Stage of a song submission.
+///
+/// `draft` → `pending` → `approved` → `published`, or `pending` →
+/// `rejected`; editing a rejected submission returns it to `draft`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "song_submission_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Draft,
+    Pending,
+    Approved,
+    Rejected,
+    Published
+}
+
+impl SubmissionStatus {
+    /// Whether the submitter may still change or delete the submission
+    pub fn is_editable(self) -> bool {
+        matches!(self, Self::Draft | Self::Rejected)
+    }
+}
+
+/// Song suggested by a member
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SongSubmission {
+    pub id:             Uuid,
+    pub submitter_id:   Uuid,
+    pub submitter_name: Option<String>,
+    pub status:         SubmissionStatus,
+    pub title:          String,
+    pub author_lyrics:  Option<String>,
+    pub author_music:   Option<String>,
+    pub translator:     Option<String>,
+    pub original_key:   Option<String>,
+    /// Lyrics, optionally with ChordPro chords
+    pub content:        String,
+    pub source_url:     Option<String>,
+    /// Message from the submitter to the reviewers
+    pub note:           Option<String>,
+    pub review_comment: Option<String>,
+    pub reviewed_by:    Option<Uuid>,
+    pub reviewed_at:    Option<DateTime<Utc>>,
+    /// Catalog song created on publishing
+    pub song_id:        Option<Uuid>,
+    pub submitted_at:   Option<DateTime<Utc>>,
+    pub created_at:     DateTime<Utc>,
+    pub updated_at:     DateTime<Utc>
+}
+
+/// New song submission, saved as a draft
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSongSubmission {
+    pub title:         String,
+    pub author_lyrics: Option<String>,
+    pub author_music:  Option<String>,
+    pub translator:    Option<String>,
+    pub original_key:  Option<String>,
+    pub content:       String,
+    pub source_url:    Option<String>,
+    pub note:          Option<String>
+}
+
+impl CreateSongSubmission {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_title(&self.title)?;
+        validate_content(&self.content)?;
+        normalize_key(self.original_key.as_deref())?;
+        Ok(())
+    }
+}
+
+/// Submission changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateSongSubmission {
+    pub title:         Option<String>,
+    pub author_lyrics: Option<String>,
+    pub author_music:  Option<String>,
+    pub translator:    Option<String>,
+    pub original_key:  Option<String>,
+    pub content:       Option<String>,
+    pub source_url:    Option<String>,
+    pub note:          Option<String>
+}
+
+impl UpdateSongSubmission {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(title) = &self.title {
+            validate_title(title)?;
+        }
+        if let Some(content) = &self.content {
+            validate_content(content)?;
+        }
+        normalize_key(self.original_key.as_deref())?;
+        Ok(())
+    }
+}
+
+/// Editor decision on a pending submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approve,
+    Reject
+}
+
+/// Review of a pending submission
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReviewSongSubmission {
+    pub decision: ReviewDecision,
+    /// Shown to the submitter; required when rejecting
+    pub comment:  Option<String>
+}
+
+impl ReviewSongSubmission {
+    pub fn validate(&self) -> AppResult<()> {
+        let has_comment = self
+            .comment
+            .as_deref()
+            .is_some_and(|c| !c.trim().is_empty());
+        if self.decision == ReviewDecision::Reject && !has_comment {
+            return Err(AppError::validation(
+                "A comment is required when rejecting a submission"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Songbook to publish an approved submission into
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PublishSongSubmission {
+    pub songbook_id: Uuid,
+    /// Number in the songbook; unnumbered when omitted
+    pub number:      Option<i32>
+}
+
+fn validate_title(title: &str) -> AppResult<()> {
+    let length = title.trim().chars().count();
+    if length == 0 || length > 300 {
+        return Err(AppError::validation("Title must be 1-300 characters"));
+    }
+    Ok(())
+}
+
+fn validate_content(content: &str) -> AppResult<()> {
+    if content.trim().is_empty() {
+        return Err(AppError::validation("Song lyrics must not be empty"));
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! Symphony (concordance) of a word: every form of it across the Bible with
+//! word statistics.
+
+use revelation_bible::SearchResult;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Place of a verse in the Bible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct VerseRef {
+    pub book_id: i16,
+    pub chapter: i16,
+    pub verse:   i16
+}
+
+/// Form of a word as it is written in the text, e.g. «любви» for «любовь»
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WordForm {
+    pub form:  String,
+    pub count: i64
+}
+
+/// Occurrences of a word in one book
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BookFrequency {
+    pub book_id:   i16,
+    pub book_name: String,
+    /// Occurrences of any form
+    pub count:     i64,
+    /// Verses with at least one occurrence
+    pub verses:    i64,
+    pub first:     VerseRef,
+    pub last:      VerseRef
+}
+
+/// All forms of a word across the Bible, grouped by lemma
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Symphony {
+    /// Word as it was asked for
+    pub word:        String,
+    /// Lemma shared by all forms found
+    pub lemma:       String,
+    /// Occurrences of any form
+    pub total_count: i64,
+    /// Verses with at least one occurrence
+    pub verse_count: i64,
+    /// Forms found, most frequent first
+    pub forms:       Vec<WordForm>,
+    /// Frequency per book, in canonical order
+    pub books:       Vec<BookFrequency>,
+    pub first:       Option<VerseRef>,
+    pub last:        Option<VerseRef>,
+    /// Verses in canonical order with every form highlighted
+    pub verses:      Vec<SearchResult>
+}
@@ -0,0 +1,71 @@
+//! Song tag management payloads.
+
+use masterror::prelude::*;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// New song tag
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSongTag {
+    /// Unique name (`[a-z0-9_]`, e.g. `russian_original`)
+    pub name:    String,
+    pub name_ru: String
+}
+
+impl CreateSongTag {
+    pub fn validate(&self) -> AppResult<()> {
+        validate_tag_name(&self.name)?;
+        validate_tag_name_ru(&self.name_ru)
+    }
+}
+
+/// Tag changes; omitted fields are left unchanged
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct UpdateSongTag {
+    pub name:    Option<String>,
+    pub name_ru: Option<String>
+}
+
+impl UpdateSongTag {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(name) = &self.name {
+            validate_tag_name(name)?;
+        }
+        if let Some(name_ru) = &self.name_ru {
+            validate_tag_name_ru(name_ru)?;
+        }
+        Ok(())
+    }
+}
+
+/// Merge a tag into another one: its songs get the target tag and the
+/// merged tag is deleted
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct MergeSongTags {
+    /// Tag that remains
+    pub into: Uuid
+}
+
+fn validate_tag_name(name: &str) -> AppResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 100
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if !valid {
+        return Err(AppError::validation(
+            "Tag name must be 1-100 characters of a-z, 0-9 and _"
+        ));
+    }
+    Ok(())
+}
+
+fn validate_tag_name_ru(name_ru: &str) -> AppResult<()> {
+    let length = name_ru.trim().chars().count();
+    if length == 0 || length > 100 {
+        return Err(AppError::validation("Tag name_ru must be 1-100 characters"));
+    }
+    Ok(())
+}
@@ -0,0 +1,530 @@
+//! ChordPro parsing and rendering.
+//!
+//! Song content is stored in ChordPro format. This module splits it into
+//! sections and chord/text segments so that interchange formats and
+//! projection can work on structured lyrics instead of raw text.
+
+/// Kind of a lyrics section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+    Verse,
+    Chorus,
+    PreChorus,
+    Bridge,
+    Intro,
+    Ending,
+    Other
+}
+
+impl SectionKind {
+    /// Short section prefix used in verse orders (v, c, p, b, i, e, o)
+    pub fn prefix(self) -> char {
+        match self {
+            Self::Verse => 'v',
+            Self::Chorus => 'c',
+            Self::PreChorus => 'p',
+            Self::Bridge => 'b',
+            Self::Intro => 'i',
+            Self::Ending => 'e',
+            Self::Other => 'o'
+        }
+    }
+
+    /// Resolve a section kind from its verse order prefix
+    pub fn from_prefix(prefix: char) -> Option<Self> {
+        match prefix.to_ascii_lowercase() {
+            'v' => Some(Self::Verse),
+            'c' => Some(Self::Chorus),
+            'p' => Some(Self::PreChorus),
+            'b' => Some(Self::Bridge),
+            'i' => Some(Self::Intro),
+            'e' => Some(Self::Ending),
+            'o' => Some(Self::Other),
+            _ => None
+        }
+    }
+
+    /// Human readable section name for printed sheets
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Verse => "Verse",
+            Self::Chorus => "Chorus",
+            Self::PreChorus => "Pre-Chorus",
+            Self::Bridge => "Bridge",
+            Self::Intro => "Intro",
+            Self::Ending => "Ending",
+            Self::Other => "Part"
+        }
+    }
+
+    /// Environment name used in `{start_of_...}` directives
+    fn environment(self) -> &'static str {
+        match self {
+            Self::Verse => "verse",
+            Self::Chorus => "chorus",
+            Self::PreChorus => "prechorus",
+            Self::Bridge => "bridge",
+            Self::Intro => "intro",
+            Self::Ending => "ending",
+            Self::Other => "part"
+        }
+    }
+
+    fn from_environment(name: &str) -> Self {
+        match name {
+            "verse" | "v" => Self::Verse,
+            "chorus" | "c" => Self::Chorus,
+            "prechorus" | "pre_chorus" => Self::PreChorus,
+            "bridge" | "b" => Self::Bridge,
+            "intro" => Self::Intro,
+            "ending" | "outro" => Self::Ending,
+            _ => Self::Other
+        }
+    }
+}
+
+/// Lyrics fragment optionally preceded by a chord
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub chord: Option<String>,
+    pub text:  String
+}
+
+/// Single lyrics line split into chord segments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Line {
+    pub segments: Vec<Segment>
+}
+
+impl Line {
+    /// Parse a ChordPro line with inline `[chord]` markers
+    pub fn parse(raw: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut chord = None;
+        let mut text = String::new();
+        let mut rest = raw;
+
+        while let Some(start) = rest.find('[') {
+            let Some(len) = rest[start..].find(']') else {
+                break;
+            };
+
+            text.push_str(&rest[..start]);
+            if chord.is_some() || !text.is_empty() {
+                segments.push(Segment {
+                    chord: chord.take(),
+                    text:  std::mem::take(&mut text)
+                });
+            }
+
+            chord = Some(rest[start + 1..start + len].trim().to_string());
+            rest = &rest[start + len + 1..];
+        }
+
+        text.push_str(rest);
+        if chord.is_some() || !text.is_empty() {
+            segments.push(Segment {
+                chord,
+                text
+            });
+        }
+
+        Self {
+            segments
+        }
+    }
+
+    /// Lyrics without chords
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    /// Whether the line carries at least one chord
+    pub fn has_chords(&self) -> bool {
+        self.segments.iter().any(|s| s.chord.is_some())
+    }
+
+    /// Chords of the line in order of appearance
+    pub fn chords(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|s| s.chord.as_deref())
+    }
+
+    /// Chords-over-lyrics rendering: a chord row (empty when the line has
+    /// no chords) and a lyrics row aligned to it by character columns
+    pub fn chord_rows(&self) -> (String, String) {
+        let mut chords = String::new();
+        let mut lyrics = String::new();
+        let last = self.segments.len().saturating_sub(1);
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            let chord = segment.chord.as_deref().unwrap_or_default();
+            let text_width = segment.text.chars().count();
+            let chord_width = chord.chars().count();
+            // Keep a space between adjacent chords
+            let width = if i == last || chord.is_empty() {
+                text_width
+            } else {
+                text_width.max(chord_width + 1)
+            };
+
+            chords.push_str(chord);
+            chords.extend(std::iter::repeat_n(' ', width.saturating_sub(chord_width)));
+            lyrics.push_str(&segment.text);
+            lyrics.extend(std::iter::repeat_n(' ', width - text_width));
+        }
+
+        (chords.trim_end().to_string(), lyrics.trim_end().to_string())
+    }
+
+    /// Render the line back to ChordPro
+    pub fn to_chordpro(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            if let Some(chord) = &segment.chord {
+                out.push('[');
+                out.push_str(chord);
+                out.push(']');
+            }
+            out.push_str(&segment.text);
+        }
+        out
+    }
+}
+
+/// Lyrics section (verse, chorus, bridge, ...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub kind:  SectionKind,
+    pub label: Option<String>,
+    pub lines: Vec<Line>
+}
+
+impl Section {
+    pub fn new(kind: SectionKind) -> Self {
+        Self {
+            kind,
+            label: None,
+            lines: Vec::new()
+        }
+    }
+
+    /// Section lyrics without chords, one line per row
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(Line::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Parsed ChordPro song
+#[derive(Debug, Clone, Default)]
+pub struct ChordProDocument {
+    pub title:     Option<String>,
+    pub subtitle:  Option<String>,
+    pub artist:    Option<String>,
+    pub lyricist:  Option<String>,
+    pub composer:  Option<String>,
+    pub copyright: Option<String>,
+    pub year:      Option<String>,
+    pub key:       Option<String>,
+    pub tempo:     Option<String>,
+    pub time:      Option<String>,
+    pub sections:  Vec<Section>
+}
+
+impl ChordProDocument {
+    /// Parse ChordPro content.
+    ///
+    /// Lines outside of explicit `{start_of_...}` environments are grouped
+    /// into verses separated by blank lines. `{chorus}` repeats the last
+    /// chorus.
+    pub fn parse(content: &str) -> Self {
+        let mut doc = Self::default();
+        let mut current: Option<Section> = None;
+        let mut explicit = false;
+
+        for raw in content.lines() {
+            let line = raw.trim_end();
+            let trimmed = line.trim();
+
+            if let Some(directive) = trimmed.strip_prefix('{').and_then(|d| d.strip_suffix('}')) {
+                let (name, value) = match directive.split_once(':') {
+                    Some((name, value)) => (name.trim().to_lowercase(), Some(value.trim())),
+                    None => (directive.trim().to_lowercase(), None)
+                };
+                let value = value.filter(|v| !v.is_empty()).map(str::to_string);
+
+                if let Some(env) = start_environment(&name) {
+                    doc.push(current.take());
+                    let mut section = Section::new(SectionKind::from_environment(env));
+                    section.label = value;
+                    current = Some(section);
+                    explicit = true;
+                    continue;
+                }
+
+                if end_environment(&name) {
+                    doc.push(current.take());
+                    explicit = false;
+                    continue;
+                }
+
+                match name.as_str() {
+                    "chorus" => {
+                        doc.push(current.take());
+                        explicit = false;
+                        let last = doc
+                            .sections
+                            .iter()
+                            .rev()
+                            .find(|s| s.kind == SectionKind::Chorus)
+                            .cloned();
+                        doc.push(last);
+                    }
+                    "title" | "t" => doc.title = value,
+                    "subtitle" | "st" => doc.subtitle = value,
+                    "artist" => doc.artist = value,
+                    "lyricist" => doc.lyricist = value,
+                    "composer" => doc.composer = value,
+                    "copyright" => doc.copyright = value,
+                    "year" => doc.year = value,
+                    "key" => doc.key = value,
+                    "tempo" => doc.tempo = value,
+                    "time" => doc.time = value,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                if !explicit {
+                    doc.push(current.take());
+                }
+                continue;
+            }
+
+            current
+                .get_or_insert_with(|| Section::new(SectionKind::Verse))
+                .lines
+                .push(Line::parse(line));
+        }
+
+        doc.push(current);
+        doc
+    }
+
+    fn push(&mut self, section: Option<Section>) {
+        if let Some(section) = section.filter(|s| !s.lines.is_empty()) {
+            self.sections.push(section);
+        }
+    }
+
+    /// Verse order names for every section (`v1`, `c1`, `v2`, `c1`, ...).
+    ///
+    /// Identical sections of the same kind share a name, so a repeated chorus
+    /// is referenced instead of numbered again.
+    pub fn section_names(&self) -> Vec<String> {
+        let mut distinct: Vec<(&Section, String)> = Vec::new();
+        let mut names = Vec::with_capacity(self.sections.len());
+
+        for section in &self.sections {
+            let existing = distinct
+                .iter()
+                .find(|(s, _)| s.kind == section.kind && s.lines == section.lines);
+
+            let name = match existing {
+                Some((_, name)) => name.clone(),
+                None => {
+                    let ordinal = distinct
+                        .iter()
+                        .filter(|(s, _)| s.kind == section.kind)
+                        .count()
+                        + 1;
+                    let name = format!("{}{}", section.kind.prefix(), ordinal);
+                    distinct.push((section, name.clone()));
+                    name
+                }
+            };
+
+            names.push(name);
+        }
+
+        names
+    }
+
+    /// Render the document back to ChordPro
+    pub fn to_chordpro(&self) -> String {
+        let mut out = String::new();
+
+        let meta = [
+            ("title", &self.title),
+            ("subtitle", &self.subtitle),
+            ("artist", &self.artist),
+            ("lyricist", &self.lyricist),
+            ("composer", &self.composer),
+            ("copyright", &self.copyright),
+            ("year", &self.year),
+            ("key", &self.key),
+            ("tempo", &self.tempo),
+            ("time", &self.time)
+        ];
+
+        for (name, value) in meta {
+            if let Some(value) = value {
+                out.push_str(&format!("{{{name}: {value}}}\n"));
+            }
+        }
+
+        for section in &self.sections {
+            let env = section.kind.environment();
+
+            if !out.is_empty() {
+                out.push('\n');
+            }
+
+            match &section.label {
+                Some(label) => out.push_str(&format!("{{start_of_{env}: {label}}}\n")),
+                None => out.push_str(&format!("{{start_of_{env}}}\n"))
+            }
+
+            for line in &section.lines {
+                out.push_str(&line.to_chordpro());
+                out.push('\n');
+            }
+
+            out.push_str(&format!("{{end_of_{env}}}\n"));
+        }
+
+        out
+    }
+}
+
+/// Environment name of a `{start_of_...}` / `{so...}` directive
+fn start_environment(name: &str) -> Option<&str> {
+    match name {
+        "soc" => Some("chorus"),
+        "sov" => Some("verse"),
+        "sob" => Some("bridge"),
+        _ => name.strip_prefix("start_of_")
+    }
+}
+
+fn end_environment(name: &str) -> bool {
+    matches!(name, "eoc" | "eov" | "eob") || name.starts_with("end_of_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_inline_chords() {
+        let line = Line::parse("[Am]Свят, [F]свят, свят[G]");
+
+        assert_eq!(line.text(), "Свят, свят, свят");
+        assert_eq!(line.chords().collect::<Vec<_>>(), ["Am", "F", "G"]);
+        assert_eq!(line.to_chordpro(), "[Am]Свят, [F]свят, свят[G]");
+    }
+
+    #[test]
+    fn keeps_unclosed_bracket_as_text() {
+        let line = Line::parse("[C]Слава [G");
+
+        assert_eq!(line.text(), "Слава [G");
+        assert!(line.has_chords());
+    }
+
+    #[test]
+    fn aligns_chords_over_lyrics() {
+        let (chords, lyrics) = Line::parse("[C]Ave [Am]Maria").chord_rows();
+
+        assert_eq!(chords, "C   Am");
+        assert_eq!(lyrics, "Ave Maria");
+    }
+
+    #[test]
+    fn separates_adjacent_chords() {
+        let (chords, lyrics) = Line::parse("[C][G/B]Ave").chord_rows();
+
+        assert_eq!(chords, "C G/B");
+        assert_eq!(lyrics, "  Ave");
+    }
+
+    #[test]
+    fn groups_plain_lines_into_verses() {
+        let doc = ChordProDocument::parse(
+            "{title: Песнь}\n{key: D}\n# comment\nПервая строка\nВторая\n\nТретья\n"
+        );
+
+        assert_eq!(doc.title.as_deref(), Some("Песнь"));
+        assert_eq!(doc.key.as_deref(), Some("D"));
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].text(), "Первая строка\nВторая");
+        assert_eq!(doc.sections[1].kind, SectionKind::Verse);
+    }
+
+    #[test]
+    fn repeats_last_chorus() {
+        let doc = ChordProDocument::parse(
+            "Куплет\n\n{soc}\nПрипев\n{eoc}\n\nВторой куплет\n\n{chorus}\n"
+        );
+
+        let kinds: Vec<SectionKind> = doc.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Verse,
+                SectionKind::Chorus,
+                SectionKind::Verse,
+                SectionKind::Chorus
+            ]
+        );
+        assert_eq!(doc.section_names(), ["v1", "c1", "v2", "c1"]);
+    }
+
+    #[test]
+    fn keeps_blank_lines_inside_environments() {
+        let doc =
+            ChordProDocument::parse("{start_of_bridge: Мост}\nРаз\n\nДва\n{end_of_bridge}\n");
+
+        assert_eq!(doc.sections.len(), 1);
+        assert_eq!(doc.sections[0].kind, SectionKind::Bridge);
+        assert_eq!(doc.sections[0].label.as_deref(), Some("Мост"));
+        assert_eq!(doc.sections[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn round_trips_rendered_document() {
+        let doc = ChordProDocument::parse(
+            "{title: Песнь}\n{tempo: 80}\n[D]Раз [A]два\n\n{start_of_chorus}\n[G]Три\n{end_of_chorus}\n"
+        );
+        let rendered = doc.to_chordpro();
+        let reparsed = ChordProDocument::parse(&rendered);
+
+        assert_eq!(reparsed.title, doc.title);
+        assert_eq!(reparsed.tempo, doc.tempo);
+        assert_eq!(reparsed.sections, doc.sections);
+    }
+
+    #[test]
+    fn maps_section_prefixes() {
+        for kind in [
+            SectionKind::Verse,
+            SectionKind::Chorus,
+            SectionKind::PreChorus,
+            SectionKind::Bridge,
+            SectionKind::Intro,
+            SectionKind::Ending,
+            SectionKind::Other
+        ] {
+            assert_eq!(SectionKind::from_prefix(kind.prefix()), Some(kind));
+        }
+        assert_eq!(SectionKind::from_prefix('x'), None);
+    }
+}
@@ -0,0 +1,18 @@
+//! Song content formats: ChordPro parsing, projection slides, printable
+//! setlists and interchange with other worship software.
+
+pub mod chordpro;
+pub mod openlp;
+pub mod openlyrics;
+pub mod pdf;
+pub mod setlist;
+pub mod slides;
+pub mod usage;
+pub mod zip;
+
+pub use chordpro::{ChordProDocument, Line, Section, SectionKind, Segment};
+pub use openlp::setlist_to_osz;
+pub use openlyrics::{OpenLyricsSong, from_openlyrics, to_openlyrics};
+pub use setlist::{Setlist, SetlistSong, setlist_to_chordpro, setlist_to_pdf, setlist_to_text};
+pub use slides::{CreditsPosition, DEFAULT_SLIDE_LINES, Slide, SlideOptions, build_slides};
+pub use usage::usage_to_csv;
@@ -0,0 +1,206 @@
+//! OpenLP service files.
+//!
+//! An `.osz` file is a ZIP archive with one `service_data.osj` JSON document
+//! listing the service items. Songs carry their OpenLyrics XML, so OpenLP adds
+//! unknown songs to its library when the service is opened; Bible passages are
+//! plain text items with one slide per verse.
+
+use masterror::prelude::*;
+use serde_json::{Value, json};
+
+use super::{
+    chordpro::ChordProDocument,
+    openlyrics::to_openlyrics,
+    setlist::{Setlist, SetlistSong},
+    zip::ZipWriter
+};
+use crate::domain::PlaylistPassage;
+
+/// Service file format understood by OpenLP 2.4 and later
+const SERVICE_FILE_VERSION: u8 = 3;
+
+/// `ServiceItemType.Text`
+const TEXT_ITEM: u8 = 1;
+
+/// OpenLP item capabilities: preview, edit, loop, update on load, add if new,
+/// soft breaks
+const SONG_CAPABILITIES: [u8; 6] = [1, 2, 5, 8, 9, 13];
+
+/// OpenLP item capabilities: preview, loop, word split
+const BIBLE_CAPABILITIES: [u8; 3] = [1, 5, 14];
+
+/// Render a setlist as an OpenLP service file
+pub fn setlist_to_osz(setlist: &Setlist) -> AppResult<Vec<u8>> {
+    let mut items = vec![json!({
+        "openlp_core": {
+            "lite-service": false,
+            "service-theme": "",
+            "openlp-servicefile-version": SERVICE_FILE_VERSION
+        }
+    })];
+
+    let passages_after = |item_id: Option<uuid::Uuid>| {
+        setlist
+            .passages
+            .iter()
+            .filter(move |p| p.after_item_id == item_id)
+            .map(passage_item)
+    };
+
+    items.extend(passages_after(None));
+    for song in &setlist.songs {
+        items.push(song_item(song));
+        items.extend(passages_after(Some(song.item_id)));
+    }
+
+    // Passages whose anchor item left the playlist close the service
+    items.extend(
+        setlist
+            .passages
+            .iter()
+            .filter(|p| {
+                p.after_item_id
+                    .is_some_and(|id| !setlist.songs.iter().any(|s| s.item_id == id))
+            })
+            .map(passage_item)
+    );
+
+    let json = serde_json::to_vec(&items)
+        .map_err(|e| AppError::internal(format!("Service file encoding failed: {e}")))?;
+
+    let mut zip = ZipWriter::new();
+    zip.add("service_data.osj", &json)?;
+    zip.finish()
+}
+
+fn song_item(item: &SetlistSong) -> Value {
+    let song = item.transposed();
+    let document = ChordProDocument::parse(&song.content);
+    let names = document.section_names();
+
+    let authors: Vec<&str> = [&song.author_lyrics, &song.author_music, &song.translator]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    let copyright = song.copyright.clone().unwrap_or_default();
+
+    let mut footer = vec![song.title.clone()];
+    if !authors.is_empty() {
+        footer.push(format!("Written by: {}", authors.join(", ")));
+    }
+    if !copyright.is_empty() {
+        footer.push(format!("© {}", copyright.trim_start_matches('©').trim()));
+    }
+
+    let slides: Vec<Value> = document
+        .sections
+        .iter()
+        .zip(&names)
+        .map(|(section, name)| {
+            let text = section.text();
+            json!({
+                "title": text.lines().next().unwrap_or_default(),
+                "raw_slide": text,
+                "verseTag": name.to_uppercase()
+            })
+        })
+        .collect();
+
+    let alternate_title = song.title_alt.clone().unwrap_or_default();
+
+    json!({
+        "serviceitem": {
+            "header": {
+                "name": "songs",
+                "plugin": "songs",
+                "theme": null,
+                "title": song.title,
+                "footer": footer,
+                "type": TEXT_ITEM,
+                "audit": [song.title, authors, copyright, ""],
+                "notes": item.notes.clone().unwrap_or_default(),
+                "from_plugin": false,
+                "capabilities": SONG_CAPABILITIES,
+                "search": "",
+                "data": {
+                    "title": format!(
+                        "{}@{}",
+                        song.title.to_lowercase(),
+                        alternate_title.to_lowercase()
+                    ),
+                    "alternate_title": alternate_title,
+                    "authors": authors.join(", ")
+                },
+                "xml_version": to_openlyrics(&song, item.songbook_name.as_deref()),
+                "auto_play_slides_once": false,
+                "auto_play_slides_loop": false,
+                "timed_slide_interval": 0,
+                "start_time": 0,
+                "end_time": 0,
+                "media_length": 0,
+                "background_audio": [],
+                "theme_overwritten": false,
+                "will_auto_start": false,
+                "processor": null
+            },
+            "data": slides
+        }
+    })
+}
+
+fn passage_item(passage: &PlaylistPassage) -> Value {
+    let title = if passage.verse_end > passage.verse_start {
+        format!(
+            "{} {}:{}-{}",
+            passage.book_name, passage.chapter, passage.verse_start, passage.verse_end
+        )
+    } else {
+        format!(
+            "{} {}:{}",
+            passage.book_name, passage.chapter, passage.verse_start
+        )
+    };
+
+    let slides: Vec<Value> = passage
+        .verses
+        .iter()
+        .map(|verse| {
+            json!({
+                "title": format!("{} {}:{}", passage.book_name, verse.chapter, verse.verse),
+                "raw_slide": format!("{{su}}{}:{}{{/su}} {}", verse.chapter, verse.verse, verse.text),
+                "verseTag": null
+            })
+        })
+        .collect();
+
+    json!({
+        "serviceitem": {
+            "header": {
+                "name": "bibles",
+                "plugin": "bibles",
+                "theme": null,
+                "title": title,
+                "footer": [title],
+                "type": TEXT_ITEM,
+                "audit": "",
+                "notes": passage.notes.clone().unwrap_or_default(),
+                "from_plugin": false,
+                "capabilities": BIBLE_CAPABILITIES,
+                "search": "",
+                "data": {},
+                "auto_play_slides_once": false,
+                "auto_play_slides_loop": false,
+                "timed_slide_interval": 0,
+                "start_time": 0,
+                "end_time": 0,
+                "media_length": 0,
+                "background_audio": [],
+                "theme_overwritten": false,
+                "will_auto_start": false,
+                "processor": null
+            },
+            "data": slides
+        }
+    })
+}
@@ -0,0 +1,619 @@
+//! OpenLyrics XML conversion.
+//!
+//! OpenLyrics (<https://openlyrics.org>) is the song exchange format used by
+//! OpenLP and other worship software. Songs are converted through the
+//! structured ChordPro representation, so verse order and chords survive the
+//! round trip.
+
+use masterror::prelude::*;
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    reader::Reader
+};
+use revelation_songbook::{CreateSong, Song, SongCategory};
+
+use super::chordpro::{ChordProDocument, Line, Section, SectionKind, Segment};
+
+const NAMESPACE: &str = "http://openlyrics.info/namespace/2009/song";
+
+/// Song parsed from an OpenLyrics document
+#[derive(Debug, Clone)]
+pub struct OpenLyricsSong {
+    /// Song data; `songbook_id` is left empty for the caller to resolve
+    pub song:          CreateSong,
+    /// Songbook name from `<songbooks>`, if present
+    pub songbook_name: Option<String>
+}
+
+/// Render a song as an OpenLyrics document
+pub fn to_openlyrics(song: &Song, songbook_name: Option<&str>) -> String {
+    let doc = ChordProDocument::parse(&song.content);
+    let names = doc.section_names();
+    let generator = concat!("revelation-server ", env!("CARGO_PKG_VERSION"));
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<song xmlns=\"{NAMESPACE}\" version=\"0.9\" createdIn=\"{generator}\" \
+         modifiedIn=\"{generator}\" modifiedDate=\"{}\">\n",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S")
+    ));
+
+    xml.push_str("  <properties>\n    <titles>\n");
+    element(&mut xml, 6, "title", &song.title);
+    if let Some(title_alt) = &song.title_alt {
+        element(&mut xml, 6, "title", title_alt);
+    }
+    xml.push_str("    </titles>\n");
+
+    let authors = [
+        ("words", &song.author_lyrics),
+        ("music", &song.author_music),
+        ("translation", &song.translator)
+    ];
+    if authors.iter().any(|(_, a)| a.is_some()) {
+        xml.push_str("    <authors>\n");
+        for (kind, author) in authors {
+            if let Some(author) = author {
+                xml.push_str(&format!(
+                    "      <author type=\"{kind}\">{}</author>\n",
+                    escape(author)
+                ));
+            }
+        }
+        xml.push_str("    </authors>\n");
+    }
+
+    if let Some(copyright) = &song.copyright {
+        element(&mut xml, 4, "copyright", copyright);
+    }
+    if let Some(year) = song.year_written {
+        element(&mut xml, 4, "released", &year.to_string());
+    }
+    if let Some(key) = &song.original_key {
+        element(&mut xml, 4, "key", key);
+    }
+    if let Some(tempo) = song.tempo {
+        xml.push_str(&format!("    <tempo type=\"bpm\">{tempo}</tempo>\n"));
+    }
+    if let Some(time_signature) = &song.time_signature {
+        element(&mut xml, 4, "timeSignature", time_signature);
+    }
+    if !names.is_empty() {
+        element(&mut xml, 4, "verseOrder", &names.join(" "));
+    }
+
+    if let Some(name) = songbook_name {
+        match song.number {
+            Some(number) => xml.push_str(&format!(
+                "    <songbooks>\n      <songbook name=\"{}\" entry=\"{number}\"/>\n    </songbooks>\n",
+                escape(name)
+            )),
+            None => xml.push_str(&format!(
+                "    <songbooks>\n      <songbook name=\"{}\"/>\n    </songbooks>\n",
+                escape(name)
+            ))
+        }
+    }
+
+    if !song.categories.is_empty() {
+        xml.push_str("    <themes>\n");
+        for category in &song.categories {
+            element(&mut xml, 6, "theme", category.name_ru());
+        }
+        xml.push_str("    </themes>\n");
+    }
+
+    xml.push_str("  </properties>\n  <lyrics>\n");
+
+    let mut written: Vec<&str> = Vec::new();
+    for (section, name) in doc.sections.iter().zip(&names) {
+        if written.contains(&name.as_str()) {
+            continue;
+        }
+        written.push(name);
+
+        let lines = section
+            .lines
+            .iter()
+            .map(line_to_xml)
+            .collect::<Vec<_>>()
+            .join("<br/>");
+
+        xml.push_str(&format!(
+            "    <verse name=\"{name}\">\n      <lines>{lines}</lines>\n    </verse>\n"
+        ));
+    }
+
+    xml.push_str("  </lyrics>\n</song>\n");
+    xml
+}
+
+/// Parse an OpenLyrics document into song data
+pub fn from_openlyrics(xml: &str) -> AppResult<OpenLyricsSong> {
+    let mut reader = Reader::from_str(xml);
+
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut titles: Vec<String> = Vec::new();
+    let mut authors: Vec<(String, String)> = Vec::new();
+    let mut author_type = String::new();
+    let mut themes: Vec<String> = Vec::new();
+    let mut songbook: Option<(String, Option<String>)> = None;
+    let mut properties = Properties::default();
+    let mut verses: Vec<(String, Section)> = Vec::new();
+    let mut verse: Option<(String, Section)> = None;
+    let mut line = Line::default();
+
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => {
+                let name = local_name(&e);
+                match name.as_str() {
+                    "author" => {
+                        author_type = attribute(&e, "type")?.unwrap_or_default();
+                    }
+                    "verse" => {
+                        let verse_name = attribute(&e, "name")?.unwrap_or_default();
+                        let kind = verse_name
+                            .chars()
+                            .next()
+                            .and_then(SectionKind::from_prefix)
+                            .unwrap_or(SectionKind::Other);
+                        verse = Some((verse_name.to_lowercase(), Section::new(kind)));
+                    }
+                    "chord" => start_chord(&mut line, chord_name(&e)?),
+                    // Some exporters write `<songbook ...></songbook>`
+                    "songbook" => read_songbook(&e, &mut songbook)?,
+                    _ => {}
+                }
+                text.clear();
+                path.push(name);
+            }
+            Event::Empty(e) => match local_name(&e).as_str() {
+                "br" => finish_line(&mut verse, &mut line),
+                "chord" => start_chord(&mut line, chord_name(&e)?),
+                "songbook" => read_songbook(&e, &mut songbook)?,
+                _ => {}
+            },
+            Event::Text(t) => {
+                let value = t.unescape().map_err(invalid)?;
+                if path.iter().any(|p| p == "lines") {
+                    append_text(&mut line, &value);
+                } else {
+                    text.push_str(&value);
+                }
+            }
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let value = text.trim().to_string();
+                match name.as_str() {
+                    "title" if !value.is_empty() => titles.push(value),
+                    "author" if !value.is_empty() => {
+                        authors.push((std::mem::take(&mut author_type), value));
+                    }
+                    "theme" if !value.is_empty() => themes.push(value),
+                    "copyright" => properties.copyright = non_empty(value),
+                    "released" => properties.released = non_empty(value),
+                    "key" => properties.key = non_empty(value),
+                    "tempo" => properties.tempo = non_empty(value),
+                    "timeSignature" => properties.time_signature = non_empty(value),
+                    "verseOrder" => properties.verse_order = non_empty(value),
+                    "lines" => finish_line(&mut verse, &mut line),
+                    "verse" => {
+                        if let Some(v) = verse.take() {
+                            verses.push(v);
+                        }
+                    }
+                    _ => {}
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut titles = titles.into_iter();
+    let title = titles
+        .next()
+        .ok_or_else(|| AppError::validation("OpenLyrics song has no title"))?;
+
+    let mut sections: Vec<Section> = properties
+        .verse_order
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|name| {
+            let name = name.to_lowercase();
+            verses
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, s)| s.clone())
+        })
+        .collect();
+
+    if sections.is_empty() {
+        sections = verses.into_iter().map(|(_, s)| s).collect();
+    }
+
+    let content = ChordProDocument {
+        title: Some(title.clone()),
+        key: properties.key.clone(),
+        sections,
+        ..Default::default()
+    }
+    .to_chordpro();
+
+    let authors_of = |kinds: &[&str]| {
+        let names: Vec<&str> = authors
+            .iter()
+            .filter(|(kind, _)| kinds.contains(&kind.as_str()))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    };
+
+    let (songbook_name, number) = match songbook {
+        Some((name, entry)) => (Some(name), entry.and_then(|e| e.trim().parse().ok())),
+        None => (None, None)
+    };
+
+    let song = CreateSong {
+        songbook_id: None,
+        number,
+        title,
+        title_alt: titles.next(),
+        author_lyrics: authors_of(&["words", ""]),
+        author_music: authors_of(&["music"]),
+        translator: authors_of(&["translation"]),
+        year_written: properties
+            .released
+            .as_deref()
+            .and_then(|r| r.get(..4))
+            .and_then(|y| y.parse().ok()),
+        copyright: properties.copyright,
+        original_key: properties.key,
+        tempo: properties.tempo.and_then(|t| t.parse().ok()),
+        time_signature: properties.time_signature,
+        content,
+        source_url: None,
+        categories: themes
+            .iter()
+            .filter_map(|t| category_from_theme(t))
+            .collect(),
+        tag_ids: Vec::new()
+    };
+
+    Ok(OpenLyricsSong {
+        song,
+        songbook_name
+    })
+}
+
+#[derive(Default)]
+struct Properties {
+    copyright:      Option<String>,
+    released:       Option<String>,
+    key:            Option<String>,
+    tempo:          Option<String>,
+    time_signature: Option<String>,
+    verse_order:    Option<String>
+}
+
+fn element(xml: &mut String, indent: usize, name: &str, value: &str) {
+    xml.push_str(&format!(
+        "{:indent$}<{name}>{}</{name}>\n",
+        "",
+        escape(value)
+    ));
+}
+
+fn line_to_xml(line: &Line) -> String {
+    let mut out = String::new();
+    for segment in &line.segments {
+        if let Some(chord) = &segment.chord {
+            out.push_str(&format!("<chord name=\"{}\"/>", escape(chord)));
+        }
+        out.push_str(&escape(&segment.text));
+    }
+    out
+}
+
+fn invalid(e: impl std::fmt::Display) -> AppError {
+    AppError::validation(format!("Invalid OpenLyrics XML: {e}"))
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+fn attribute(e: &BytesStart, name: &str) -> AppResult<Option<String>> {
+    match e.try_get_attribute(name).map_err(invalid)? {
+        Some(attr) => Ok(Some(attr.unescape_value().map_err(invalid)?.into_owned())),
+        None => Ok(None)
+    }
+}
+
+/// Take the first `<songbook name=".." entry="..">` of the document
+fn read_songbook(
+    e: &BytesStart,
+    songbook: &mut Option<(String, Option<String>)>
+) -> AppResult<()> {
+    if songbook.is_none()
+        && let Some(name) = attribute(e, "name")?
+    {
+        *songbook = Some((name, attribute(e, "entry")?));
+    }
+    Ok(())
+}
+
+/// Chord name from either the `name` attribute or the OpenLyrics 0.9
+/// `root`/`structure`/`bass` form
+fn chord_name(e: &BytesStart) -> AppResult<String> {
+    if let Some(name) = attribute(e, "name")? {
+        return Ok(name);
+    }
+
+    let mut chord = attribute(e, "root")?.unwrap_or_default();
+    chord.push_str(match attribute(e, "structure")?.as_deref() {
+        Some("min") | Some("m") => "m",
+        Some("dom7") | Some("7") => "7",
+        Some("maj7") => "maj7",
+        Some("min7") | Some("m7") => "m7",
+        Some("sus2") => "sus2",
+        Some("sus4") => "sus4",
+        Some("dim") => "dim",
+        Some("aug") => "aug",
+        _ => ""
+    });
+    if let Some(bass) = attribute(e, "bass")? {
+        chord.push('/');
+        chord.push_str(&bass);
+    }
+
+    Ok(chord)
+}
+
+fn start_chord(line: &mut Line, chord: String) {
+    line.segments.push(Segment {
+        chord: Some(chord),
+        text:  String::new()
+    });
+}
+
+/// Append text to the current line, collapsing XML formatting whitespace
+fn append_text(line: &mut Line, value: &str) {
+    let mut collapsed = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find(char::is_whitespace) {
+        collapsed.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(rest.len(), |len| start + len);
+        let run = &rest[start..end];
+        collapsed.push_str(if run.contains('\n') { " " } else { run });
+        rest = &rest[end..];
+    }
+    collapsed.push_str(rest);
+
+    if collapsed.is_empty() {
+        return;
+    }
+
+    match line.segments.last_mut() {
+        Some(segment) => segment.text.push_str(&collapsed),
+        None => line.segments.push(Segment {
+            chord: None,
+            text:  collapsed
+        })
+    }
+}
+
+fn finish_line(verse: &mut Option<(String, Section)>, line: &mut Line) {
+    let mut line = std::mem::take(line);
+    if let Some(first) = line.segments.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = line.segments.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    line.segments
+        .retain(|s| s.chord.is_some() || !s.text.is_empty());
+
+    if let Some((_, section)) = verse
+        && !line.segments.is_empty()
+    {
+        section.lines.push(line);
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+fn category_from_theme(theme: &str) -> Option<SongCategory> {
+    let theme = theme.to_lowercase();
+    SongCategory::all()
+        .iter()
+        .copied()
+        .find(|c| c.name_ru().to_lowercase() == theme)
+        .or_else(|| serde_json::from_value(serde_json::Value::String(theme)).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    const SONG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<song xmlns="http://openlyrics.info/namespace/2009/song" version="0.9">
+  <properties>
+    <titles>
+      <title>Великий Бог</title>
+      <title>How Great Thou Art</title>
+    </titles>
+    <authors>
+      <author type="words">Карл Боберг</author>
+      <author type="translation">И. С. Проханов</author>
+    </authors>
+    <released>1885-03-01</released>
+    <key>A</key>
+    <tempo type="bpm">72</tempo>
+    <verseOrder>v1 c1 v2 c1</verseOrder>
+    <songbooks>
+      <songbook name="Песнь Возрождения" entry="245"/>
+    </songbooks>
+  </properties>
+  <lyrics>
+    <verse name="v1">
+      <lines><chord name="A"/>Великий Бог, когда на <chord name="D"/>мир смотрю я<br/>В восторге я</lines>
+    </verse>
+    <verse name="c1">
+      <lines>Тогда поёт душа моя</lines>
+    </verse>
+    <verse name="v2">
+      <lines>Когда гуляю я в лесу</lines>
+    </verse>
+  </lyrics>
+</song>
+"#;
+
+    fn song_from(content: &str) -> Song {
+        Song {
+            id:              Uuid::nil(),
+            songbook_id:     None,
+            songbook_code:   None,
+            number:          Some(12),
+            title:           "Тебе пою".to_string(),
+            title_alt:       None,
+            author_lyrics:   Some("Автор & сын".to_string()),
+            author_music:    None,
+            translator:      None,
+            year_written:    Some(1990),
+            copyright:       None,
+            original_key:    Some("G".to_string()),
+            tempo:           Some(90),
+            time_signature:  Some("3/4".to_string()),
+            content:         content.to_string(),
+            first_line:      String::new(),
+            categories:      Vec::new(),
+            tags:            Vec::new(),
+            is_favorite:     false,
+            user_transpose:  0,
+            views_count:     0,
+            favorites_count: 0
+        }
+    }
+
+    #[test]
+    fn reads_properties() {
+        let parsed = from_openlyrics(SONG).unwrap();
+        let song = parsed.song;
+
+        assert_eq!(song.title, "Великий Бог");
+        assert_eq!(song.title_alt.as_deref(), Some("How Great Thou Art"));
+        assert_eq!(song.author_lyrics.as_deref(), Some("Карл Боберг"));
+        assert_eq!(song.translator.as_deref(), Some("И. С. Проханов"));
+        assert_eq!(song.year_written, Some(1885));
+        assert_eq!(song.original_key.as_deref(), Some("A"));
+        assert_eq!(song.tempo, Some(72));
+        assert_eq!(song.number, Some(245));
+        assert_eq!(song.songbook_id, None);
+        assert_eq!(parsed.songbook_name.as_deref(), Some("Песнь Возрождения"));
+    }
+
+    #[test]
+    fn follows_verse_order_and_keeps_chords() {
+        let song = from_openlyrics(SONG).unwrap().song;
+        let doc = ChordProDocument::parse(&song.content);
+
+        let kinds: Vec<SectionKind> = doc.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Verse,
+                SectionKind::Chorus,
+                SectionKind::Verse,
+                SectionKind::Chorus
+            ]
+        );
+        assert_eq!(
+            doc.sections[0].lines[0].to_chordpro(),
+            "[A]Великий Бог, когда на [D]мир смотрю я"
+        );
+        assert_eq!(doc.sections[0].lines[1].text(), "В восторге я");
+    }
+
+    #[test]
+    fn reads_songbook_written_with_end_tag() {
+        let xml = SONG.replace(
+            r#"<songbook name="Песнь Возрождения" entry="245"/>"#,
+            r#"<songbook name="Гусли" entry="17"></songbook>"#
+        );
+        let parsed = from_openlyrics(&xml).unwrap();
+
+        assert_eq!(parsed.songbook_name.as_deref(), Some("Гусли"));
+        assert_eq!(parsed.song.number, Some(17));
+    }
+
+    #[test]
+    fn takes_first_songbook() {
+        let xml = SONG.replace(
+            "    </songbooks>",
+            "      <songbook name=\"Гусли\" entry=\"17\"/>\n    </songbooks>"
+        );
+        let parsed = from_openlyrics(&xml).unwrap();
+
+        assert_eq!(parsed.songbook_name.as_deref(), Some("Песнь Возрождения"));
+        assert_eq!(parsed.song.number, Some(245));
+    }
+
+    #[test]
+    fn builds_chords_from_root_and_structure() {
+        let xml = SONG.replace(
+            r#"<chord name="D"/>"#,
+            r#"<chord root="F#" structure="min" bass="E"/>"#
+        );
+        let song = from_openlyrics(&xml).unwrap().song;
+
+        assert!(song.content.contains("[F#m/E]мир"));
+    }
+
+    #[test]
+    fn rejects_song_without_title() {
+        let xml = SONG
+            .replace("<title>Великий Бог</title>", "")
+            .replace("<title>How Great Thou Art</title>", "");
+
+        assert!(from_openlyrics(&xml).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_export() {
+        let content = "{start_of_verse}\n[G]Тебе пою, [C]Господь\n{end_of_verse}\n\n\
+                       {start_of_chorus}\nАллилуйя\n{end_of_chorus}\n\n\
+                       {start_of_verse}\nВторой куплет\n{end_of_verse}\n\n\
+                       {start_of_chorus}\nАллилуйя\n{end_of_chorus}\n";
+        let song = song_from(content);
+
+        let xml = to_openlyrics(&song, Some("Сборник"));
+        assert!(xml.contains("<verseOrder>v1 c1 v2 c1</verseOrder>"));
+        assert!(xml.contains("Автор &amp; сын"));
+
+        let parsed = from_openlyrics(&xml).unwrap();
+        assert_eq!(parsed.songbook_name.as_deref(), Some("Сборник"));
+        assert_eq!(parsed.song.number, Some(12));
+        assert_eq!(parsed.song.title, song.title);
+        assert_eq!(parsed.song.author_lyrics, song.author_lyrics);
+        assert_eq!(parsed.song.year_written, Some(1990));
+        assert_eq!(parsed.song.tempo, Some(90));
+        assert_eq!(parsed.song.time_signature.as_deref(), Some("3/4"));
+
+        let original = ChordProDocument::parse(content);
+        let imported = ChordProDocument::parse(&parsed.song.content);
+        assert_eq!(original.sections, imported.sections);
+    }
+}
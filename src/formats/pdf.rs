@@ -0,0 +1,618 @@
+//! Minimal PDF writer.
+//!
+//! Pages hold positioned text in one embedded TrueType font. Text is encoded
+//! as glyph IDs (`Identity-H`), so every script the font covers prints
+//! correctly, and a `ToUnicode` map keeps it searchable. This is enough for
+//! chord sheets and needs no external tools.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    io::Write as _
+};
+
+use flate2::{Compression, write::ZlibEncoder};
+use masterror::prelude::*;
+
+/// A4 page width in points
+pub const PAGE_WIDTH: f32 = 595.0;
+
+/// A4 page height in points
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+/// DejaVu Sans Mono, built into the binary so PDFs need no system fonts
+pub const EMBEDDED_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// TrueType font metrics needed for embedding
+pub struct TrueTypeFont<'a> {
+    data:         &'a [u8],
+    name:         String,
+    units_per_em: u16,
+    bbox:         [i16; 4],
+    ascent:       i16,
+    descent:      i16,
+    advances:     Vec<u16>,
+    glyphs:       HashMap<u32, u16>
+}
+
+impl<'a> TrueTypeFont<'a> {
+    /// Parse the tables of a TrueType (`.ttf`) font
+    pub fn parse(data: &'a [u8]) -> AppResult<Self> {
+        Self::read(data).ok_or_else(|| AppError::internal("PDF font is not a valid TrueType font"))
+    }
+
+    fn read(data: &'a [u8]) -> Option<Self> {
+        let mut tables = HashMap::new();
+        for i in 0..read_u16(data, 4)? as usize {
+            let record = 12 + i * 16;
+            let tag = data.get(record..record + 4)?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            tables.insert(tag, data.get(offset..offset + length)?);
+        }
+
+        let head = *tables.get(b"head".as_slice())?;
+        let hhea = *tables.get(b"hhea".as_slice())?;
+        let hmtx = *tables.get(b"hmtx".as_slice())?;
+        let cmap = *tables.get(b"cmap".as_slice())?;
+
+        let metrics = read_u16(hhea, 34)? as usize;
+        let advances = (0..metrics)
+            .map(|i| read_u16(hmtx, i * 4))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            data,
+            name: tables
+                .get(b"name".as_slice())
+                .and_then(|table| read_postscript_name(table))
+                .unwrap_or_else(|| "Embedded".to_string()),
+            units_per_em: read_u16(head, 18)?,
+            bbox: [
+                read_u16(head, 36)? as i16,
+                read_u16(head, 38)? as i16,
+                read_u16(head, 40)? as i16,
+                read_u16(head, 42)? as i16
+            ],
+            ascent: read_u16(hhea, 4)? as i16,
+            descent: read_u16(hhea, 6)? as i16,
+            advances,
+            glyphs: read_cmap(cmap)?
+        })
+    }
+
+    fn glyph(&self, c: char) -> u16 {
+        self.glyphs.get(&(c as u32)).copied().unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> u16 {
+        self.advances
+            .get(glyph as usize)
+            .or(self.advances.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Scale font units to thousandths of the font size
+    fn scale(&self, units: i32) -> i32 {
+        units * 1000 / i32::from(self.units_per_em.max(1))
+    }
+
+    /// Width of a text run in points
+    pub fn width(&self, text: &str, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .map(|c| u32::from(self.advance(self.glyph(c))))
+            .sum();
+        units as f32 * size / f32::from(self.units_per_em.max(1))
+    }
+}
+
+struct PdfPage {
+    content: String,
+    links:   Vec<([f32; 4], usize)>
+}
+
+/// Document under construction
+pub struct PdfDocument<'a> {
+    font:    TrueTypeFont<'a>,
+    pages:   Vec<PdfPage>,
+    outline: Vec<(String, usize)>,
+    /// Glyphs drawn so far with the character they show
+    used:    BTreeMap<u16, char>
+}
+
+impl<'a> PdfDocument<'a> {
+    pub fn new(font: TrueTypeFont<'a>) -> Self {
+        Self {
+            font,
+            pages: Vec::new(),
+            outline: Vec::new(),
+            used: BTreeMap::new()
+        }
+    }
+
+    pub fn font(&self) -> &TrueTypeFont<'a> {
+        &self.font
+    }
+
+    /// Append an empty page and return its index
+    pub fn add_page(&mut self) -> usize {
+        self.pages.push(PdfPage {
+            content: String::new(),
+            links:   Vec::new()
+        });
+        self.pages.len() - 1
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Draw text with its baseline starting at `(x, y)`
+    pub fn text(&mut self, page: usize, x: f32, y: f32, size: f32, text: &str) {
+        let mut glyphs = String::with_capacity(text.len() * 4);
+        for c in text.chars() {
+            let glyph = self.font.glyph(c);
+            self.used.entry(glyph).or_insert(c);
+            let _ = write!(glyphs, "{glyph:04X}");
+        }
+
+        let _ = writeln!(
+            self.pages[page].content,
+            "BT /F1 {size:.2} Tf {x:.2} {y:.2} Td <{glyphs}> Tj ET"
+        );
+    }
+
+    /// Make a rectangle `[x1, y1, x2, y2]` on `page` jump to `target`
+    pub fn link(&mut self, page: usize, rect: [f32; 4], target: usize) {
+        self.pages[page].links.push((rect, target));
+    }
+
+    /// Add a top-level bookmark pointing at `page`
+    pub fn bookmark(&mut self, title: &str, page: usize) {
+        self.outline.push((title.to_string(), page));
+    }
+
+    /// Serialize the document
+    pub fn finish(self) -> AppResult<Vec<u8>> {
+        // 1 catalog, 2 page tree, 3-7 font, 8 outline root, then a page and
+        // its content stream per page, then bookmarks
+        const OUTLINES: usize = 8;
+        let page_id = |index: usize| 9 + index * 2;
+        let bookmark_id = |index: usize| 9 + self.pages.len() * 2 + index;
+
+        let mut writer = ObjectWriter::new();
+
+        writer.object(
+            1,
+            "<< /Type /Catalog /Pages 2 0 R /Outlines 8 0 R /PageMode /UseOutlines >>"
+        );
+
+        let kids = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", page_id(i)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writer.object(
+            2,
+            &format!(
+                "<< /Type /Pages /Kids [{kids}] /Count {} >>",
+                self.pages.len()
+            )
+        );
+
+        self.write_font(&mut writer)?;
+
+        if self.outline.is_empty() {
+            writer.object(OUTLINES, "<< /Type /Outlines /Count 0 >>");
+        } else {
+            writer.object(
+                OUTLINES,
+                &format!(
+                    "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
+                    bookmark_id(0),
+                    bookmark_id(self.outline.len() - 1),
+                    self.outline.len()
+                )
+            );
+        }
+
+        for (i, page) in self.pages.iter().enumerate() {
+            let annots = page
+                .links
+                .iter()
+                .map(|(rect, target)| {
+                    format!(
+                        "<< /Type /Annot /Subtype /Link /Rect [{:.2} {:.2} {:.2} {:.2}] \
+                         /Border [0 0 0] /Dest [{} 0 R /Fit] >>",
+                        rect[0],
+                        rect[1],
+                        rect[2],
+                        rect[3],
+                        page_id(*target)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            writer.object(
+                page_id(i),
+                &format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                     /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R /Annots [{annots}] >>",
+                    page_id(i) + 1
+                )
+            );
+            writer.stream(page_id(i) + 1, "", page.content.as_bytes())?;
+        }
+
+        for (i, (title, page)) in self.outline.iter().enumerate() {
+            let mut item = format!(
+                "<< /Title {} /Parent {OUTLINES} 0 R /Dest [{} 0 R /Fit]",
+                text_string(title),
+                page_id(*page)
+            );
+            if i > 0 {
+                let _ = write!(item, " /Prev {} 0 R", bookmark_id(i - 1));
+            }
+            if i + 1 < self.outline.len() {
+                let _ = write!(item, " /Next {} 0 R", bookmark_id(i + 1));
+            }
+            item.push_str(" >>");
+            writer.object(bookmark_id(i), &item);
+        }
+
+        Ok(writer.finish(1))
+    }
+
+    /// Objects 3-7: Type0 font, CID font, descriptor, font file and
+    /// `ToUnicode` map
+    fn write_font(&self, writer: &mut ObjectWriter) -> AppResult<()> {
+        let font = &self.font;
+        let name: String = font
+            .name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+
+        writer.object(
+            3,
+            &format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H \
+                 /DescendantFonts [4 0 R] /ToUnicode 7 0 R >>"
+            )
+        );
+
+        let widths = self
+            .used
+            .keys()
+            .map(|&glyph| format!("{glyph} [{}]", font.scale(i32::from(font.advance(glyph)))))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writer.object(
+            4,
+            &format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{name} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor 5 0 R /CIDToGIDMap /Identity /W [{widths}] >>"
+            )
+        );
+
+        let [x_min, y_min, x_max, y_max] = font.bbox.map(|v| font.scale(i32::from(v)));
+        let ascent = font.scale(i32::from(font.ascent));
+        writer.object(
+            5,
+            &format!(
+                "<< /Type /FontDescriptor /FontName /{name} /Flags 32 \
+                 /FontBBox [{x_min} {y_min} {x_max} {y_max}] /ItalicAngle 0 \
+                 /Ascent {ascent} /Descent {} /CapHeight {ascent} /StemV 80 \
+                 /FontFile2 6 0 R >>",
+                font.scale(i32::from(font.descent))
+            )
+        );
+
+        writer.stream(6, &format!("/Length1 {}", font.data.len()), font.data)?;
+
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n"
+        );
+        let used: Vec<_> = self.used.iter().collect();
+        for chunk in used.chunks(100) {
+            let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+            for (glyph, c) in chunk {
+                let unicode: String = c
+                    .encode_utf16(&mut [0; 2])
+                    .iter()
+                    .map(|unit| format!("{unit:04X}"))
+                    .collect();
+                let _ = writeln!(cmap, "<{glyph:04X}> <{unicode}>");
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        writer.stream(7, "", cmap.as_bytes())
+    }
+}
+
+/// Accumulates numbered objects and their byte offsets
+struct ObjectWriter {
+    out:     Vec<u8>,
+    offsets: BTreeMap<usize, usize>
+}
+
+impl ObjectWriter {
+    fn new() -> Self {
+        Self {
+            out:     b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+            offsets: BTreeMap::new()
+        }
+    }
+
+    fn object(&mut self, id: usize, body: &str) {
+        self.offsets.insert(id, self.out.len());
+        self.out
+            .extend_from_slice(format!("{id} 0 obj\n{body}\nendobj\n").as_bytes());
+    }
+
+    /// Write a Flate-compressed stream object
+    fn stream(&mut self, id: usize, dict: &str, data: &[u8]) -> AppResult<()> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(data)
+            .map_err(|e| AppError::internal(format!("PDF compression failed: {e}")))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| AppError::internal(format!("PDF compression failed: {e}")))?;
+
+        self.offsets.insert(id, self.out.len());
+        self.out.extend_from_slice(
+            format!(
+                "{id} 0 obj\n<< /Length {} /Filter /FlateDecode {dict} >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes()
+        );
+        self.out.extend_from_slice(&compressed);
+        self.out.extend_from_slice(b"\nendstream\nendobj\n");
+        Ok(())
+    }
+
+    fn finish(mut self, root: usize) -> Vec<u8> {
+        let size = self.offsets.keys().max().copied().unwrap_or(0) + 1;
+        let xref = self.out.len();
+
+        let mut table = format!("xref\n0 {size}\n0000000000 65535 f \n");
+        for id in 1..size {
+            match self.offsets.get(&id) {
+                Some(offset) => {
+                    let _ = writeln!(table, "{offset:010} 00000 n ");
+                }
+                None => table.push_str("0000000000 65535 f \n")
+            }
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {size} /Root {root} 0 R >>\nstartxref\n{xref}\n%%EOF\n"
+        );
+
+        self.out.extend_from_slice(table.as_bytes());
+        self.out
+    }
+}
+
+/// PDF text string in UTF-16BE, for bookmark titles
+fn text_string(text: &str) -> String {
+    let mut out = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(out, "{unit:04X}");
+    }
+    out.push('>');
+    out
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Character to glyph map from a Unicode BMP (format 4) `cmap` subtable
+fn read_cmap(cmap: &[u8]) -> Option<HashMap<u32, u16>> {
+    let subtable = (0..read_u16(cmap, 2)? as usize).find_map(|i| {
+        let record = 4 + i * 8;
+        let platform = read_u16(cmap, record)?;
+        let encoding = read_u16(cmap, record + 2)?;
+        let offset = read_u32(cmap, record + 4)? as usize;
+        let unicode = platform == 0 || (platform == 3 && encoding == 1);
+        (unicode && read_u16(cmap, offset)? == 4).then(|| cmap.get(offset..))?
+    })?;
+
+    let segments_x2 = read_u16(subtable, 6)? as usize;
+    let ends = 14;
+    let starts = ends + segments_x2 + 2;
+    let deltas = starts + segments_x2;
+    let range_offsets = deltas + segments_x2;
+
+    let mut glyphs = HashMap::new();
+    for i in 0..segments_x2 / 2 {
+        let end = read_u16(subtable, ends + i * 2)?;
+        let start = read_u16(subtable, starts + i * 2)?;
+        let delta = read_u16(subtable, deltas + i * 2)?;
+        let range_offset = read_u16(subtable, range_offsets + i * 2)? as usize;
+
+        if start == 0xFFFF {
+            break;
+        }
+
+        for code in start..=end {
+            let glyph = if range_offset == 0 {
+                code.wrapping_add(delta)
+            } else {
+                let index = range_offsets + i * 2 + range_offset + (code - start) as usize * 2;
+                match read_u16(subtable, index)? {
+                    0 => 0,
+                    glyph => glyph.wrapping_add(delta)
+                }
+            };
+            if glyph != 0 {
+                glyphs.insert(u32::from(code), glyph);
+            }
+        }
+    }
+
+    Some(glyphs)
+}
+
+/// PostScript name (name ID 6) of the font
+fn read_postscript_name(table: &[u8]) -> Option<String> {
+    let storage = read_u16(table, 4)? as usize;
+
+    (0..read_u16(table, 2)? as usize).find_map(|i| {
+        let record = 6 + i * 12;
+        if read_u16(table, record + 6)? != 6 {
+            return None;
+        }
+
+        let platform = read_u16(table, record)?;
+        let length = read_u16(table, record + 8)? as usize;
+        let offset = storage + read_u16(table, record + 10)? as usize;
+        let bytes = table.get(offset..offset + length)?;
+
+        match platform {
+            3 | 0 => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                    .collect();
+                String::from_utf16(&units).ok()
+            }
+            _ => Some(bytes.iter().map(|&b| b as char).collect())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    /// Byte offset of `needle` at or after `from`
+    fn find(pdf: &[u8], needle: &str, from: usize) -> Option<usize> {
+        pdf[from..]
+            .windows(needle.len())
+            .position(|window| window == needle.as_bytes())
+            .map(|position| from + position)
+    }
+
+    /// The document with binary stream bytes replaced, for text checks
+    fn as_text(pdf: &[u8]) -> String {
+        String::from_utf8_lossy(pdf).into_owned()
+    }
+
+    /// Inflated body of stream object `id`
+    fn stream(pdf: &[u8], id: usize) -> Vec<u8> {
+        let object = find(pdf, &format!("\n{id} 0 obj\n"), 0).unwrap() + 1;
+        let start = find(pdf, "stream\n", object).unwrap() + "stream\n".len();
+        let end = find(pdf, "\nendstream", start).unwrap();
+
+        let mut data = Vec::new();
+        ZlibDecoder::new(&pdf[start..end])
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn reads_embedded_font() {
+        let font = TrueTypeFont::parse(EMBEDDED_FONT).unwrap();
+
+        assert_eq!(font.name, "DejaVuSansMono");
+        assert_ne!(font.glyph('A'), 0);
+        assert_ne!(font.glyph('Ж'), 0);
+        assert_eq!(font.glyph('\u{E000}'), 0);
+        assert_eq!(font.width("i", 10.0), font.width("M", 10.0));
+        assert_eq!(font.width("MM", 10.0), 2.0 * font.width("M", 10.0));
+    }
+
+    #[test]
+    fn rejects_invalid_font() {
+        assert!(TrueTypeFont::parse(b"not a font").is_err());
+        assert!(TrueTypeFont::parse(&EMBEDDED_FONT[..64]).is_err());
+    }
+
+    #[test]
+    fn cross_reference_points_at_objects() {
+        let mut pdf = PdfDocument::new(TrueTypeFont::parse(EMBEDDED_FONT).unwrap());
+        let page = pdf.add_page();
+        pdf.text(page, 50.0, 700.0, 12.0, "Hello");
+        let bytes = pdf.finish().unwrap();
+
+        assert!(bytes.starts_with(b"%PDF-1.7\n"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+
+        let xref = find(&bytes, "\nxref\n", 0).unwrap() + 1;
+        let table = as_text(&bytes[xref..]);
+        let startxref: usize = table.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert_eq!(startxref, xref);
+
+        let entries = table
+            .lines()
+            .skip(3)
+            .take_while(|line| !line.starts_with("trailer"));
+        for (id, entry) in (1..).zip(entries) {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(
+                bytes[offset..].starts_with(format!("{id} 0 obj\n").as_bytes()),
+                "object {id} is not at {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn encodes_text_as_glyphs_with_unicode_map() {
+        let mut pdf = PdfDocument::new(TrueTypeFont::parse(EMBEDDED_FONT).unwrap());
+        let page = pdf.add_page();
+        pdf.text(page, 50.0, 700.0, 12.0, "Ж");
+        let glyph = pdf.font().glyph('Ж');
+        let bytes = pdf.finish().unwrap();
+
+        let content = String::from_utf8(stream(&bytes, 10)).unwrap();
+        assert!(content.contains(&format!("<{glyph:04X}> Tj")));
+
+        let cmap = String::from_utf8(stream(&bytes, 7)).unwrap();
+        assert!(cmap.contains(&format!("<{glyph:04X}> <0416>")));
+    }
+
+    #[test]
+    fn writes_bookmarks_and_links() {
+        let mut pdf = PdfDocument::new(TrueTypeFont::parse(EMBEDDED_FONT).unwrap());
+        let contents = pdf.add_page();
+        let song = pdf.add_page();
+        pdf.link(contents, [50.0, 700.0, 545.0, 712.0], song);
+        pdf.bookmark("Первая", contents);
+        pdf.bookmark("Вторая", song);
+        let text = as_text(&pdf.finish().unwrap());
+
+        // Pages are objects 9 and 11, bookmarks 13 and 14
+        assert!(text.contains("/Type /Pages /Kids [9 0 R 11 0 R] /Count 2"));
+        assert!(text.contains("/Type /Outlines /First 13 0 R /Last 14 0 R /Count 2"));
+        assert!(text.contains("/Dest [11 0 R /Fit] >>] >>"));
+        assert!(text.contains(&format!("/Title {} /Parent 8 0 R", text_string("Вторая"))));
+        assert!(text.contains("/Prev 13 0 R"));
+    }
+
+    #[test]
+    fn encodes_bookmark_titles_as_utf16() {
+        assert_eq!(text_string("Aж"), "<FEFF00410436>");
+    }
+}
@@ -0,0 +1,363 @@
+//! Setlist export: printable chord sheets and ChordPro bundles.
+//!
+//! Every song is rendered with its playlist item's transposition and notes.
+//! Text and PDF output start with a table of contents and put each song on
+//! a new page.
+
+use masterror::prelude::*;
+use revelation_songbook::{Song, SongPlaylist, transpose_content, transpose_key};
+use uuid::Uuid;
+
+use super::{
+    chordpro::{ChordProDocument, SectionKind},
+    pdf::{PAGE_HEIGHT, PAGE_WIDTH, PdfDocument, TrueTypeFont}
+};
+use crate::domain::PlaylistPassage;
+
+/// Playlist item prepared for export
+#[derive(Debug, Clone)]
+pub struct SetlistSong {
+    pub item_id:             Uuid,
+    pub position:            i16,
+    pub song:                Song,
+    pub songbook_name:       Option<String>,
+    pub transpose_semitones: i16,
+    pub notes:               Option<String>
+}
+
+impl SetlistSong {
+    /// Song content in the key of this item
+    pub fn content(&self) -> String {
+        transpose_content(&self.song.content, self.transpose_semitones.into())
+    }
+
+    /// Song with its content in the key of this item
+    pub fn transposed(&self) -> Song {
+        Song {
+            content: self.content(),
+            original_key: self.key(),
+            ..self.song.clone()
+        }
+    }
+
+    /// Key of this item
+    pub fn key(&self) -> Option<String> {
+        self.song
+            .original_key
+            .as_deref()
+            .map(|key| transpose_key(key, self.transpose_semitones.into(), false))
+    }
+}
+
+/// Playlist with its songs in order
+#[derive(Debug, Clone)]
+pub struct Setlist {
+    pub playlist: SongPlaylist,
+    pub songs:    Vec<SetlistSong>,
+    /// Bible readings placed between the songs
+    pub passages: Vec<PlaylistPassage>
+}
+
+impl Setlist {
+    fn heading(&self) -> String {
+        match self.playlist.event_date {
+            Some(date) => format!("{} — {date}", self.playlist.name),
+            None => self.playlist.name.clone()
+        }
+    }
+}
+
+/// Printable row of a chord sheet
+enum SheetRow {
+    Title(String),
+    Meta(String),
+    Heading(String),
+    /// Chord row (may be empty) with the lyrics row under it
+    Line(String, String),
+    Gap
+}
+
+fn song_title(song: &SetlistSong) -> String {
+    format!("{}. {}", song.position, song.song.title)
+}
+
+fn sheet(song: &SetlistSong) -> Vec<SheetRow> {
+    let mut rows = vec![SheetRow::Title(song_title(song))];
+
+    if let Some(key) = song.key() {
+        let meta = match song.transpose_semitones {
+            0 => format!("Key: {key}"),
+            semitones => format!("Key: {key} ({semitones:+})")
+        };
+        rows.push(SheetRow::Meta(meta));
+    }
+
+    let authors = [
+        ("Words", &song.song.author_lyrics),
+        ("Music", &song.song.author_music),
+        ("Translation", &song.song.translator)
+    ]
+    .into_iter()
+    .filter_map(|(label, name)| name.as_ref().map(|name| format!("{label}: {name}")))
+    .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        rows.push(SheetRow::Meta(authors.join(" · ")));
+    }
+    if let Some(copyright) = &song.song.copyright {
+        rows.push(SheetRow::Meta(copyright.clone()));
+    }
+    if let Some(notes) = song.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        rows.push(SheetRow::Meta(format!("Notes: {}", notes.trim())));
+    }
+
+    let document = ChordProDocument::parse(&song.content());
+    let names = document.section_names();
+
+    for (section, name) in document.sections.iter().zip(&names) {
+        rows.push(SheetRow::Gap);
+
+        let heading = match &section.label {
+            Some(label) => label.clone(),
+            None => {
+                let number = name.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+                if number == "1" && section.kind != SectionKind::Verse {
+                    section.kind.name().to_string()
+                } else {
+                    format!("{} {number}", section.kind.name())
+                }
+            }
+        };
+        rows.push(SheetRow::Heading(heading));
+
+        for line in &section.lines {
+            let (chords, lyrics) = line.chord_rows();
+            rows.push(SheetRow::Line(chords, lyrics));
+        }
+    }
+
+    rows
+}
+
+/// Plain-text chord sheets separated by form feeds
+pub fn setlist_to_text(setlist: &Setlist) -> String {
+    let mut out = format!("{}\n\nContents\n", setlist.heading());
+    for song in &setlist.songs {
+        out.push_str(&format!("  {}\n", song_title(song)));
+    }
+
+    for song in &setlist.songs {
+        out.push('\u{c}');
+        for row in sheet(song) {
+            match row {
+                SheetRow::Title(text) | SheetRow::Meta(text) => out.push_str(&text),
+                SheetRow::Heading(text) => out.push_str(&format!("[{text}]")),
+                SheetRow::Line(chords, lyrics) => {
+                    if !chords.is_empty() {
+                        out.push_str(&chords);
+                        out.push('\n');
+                    }
+                    out.push_str(&lyrics);
+                }
+                SheetRow::Gap => {}
+            }
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// All songs in one ChordPro file, separated by `{new_song}`
+pub fn setlist_to_chordpro(setlist: &Setlist) -> String {
+    let mut out = format!("# {}\n", setlist.heading());
+    for song in &setlist.songs {
+        out.push_str(&format!("# {}\n", song_title(song)));
+    }
+
+    for (i, song) in setlist.songs.iter().enumerate() {
+        out.push('\n');
+        if i > 0 {
+            out.push_str("{new_song}\n");
+        }
+
+        // Stored content may carry its own metadata; the song record and the
+        // item's key take precedence
+        let mut document = ChordProDocument::parse(&song.content());
+        document.title = Some(song.song.title.clone());
+        document.key = song.key().or(document.key);
+        document.lyricist = song.song.author_lyrics.clone().or(document.lyricist);
+        document.composer = song.song.author_music.clone().or(document.composer);
+        document.copyright = song.song.copyright.clone().or(document.copyright);
+
+        let sections = std::mem::take(&mut document.sections);
+        out.push_str(&document.to_chordpro());
+        if let Some(notes) = song.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            out.push_str(&format!("{{comment: {}}}\n", notes.trim()));
+        }
+        out.push('\n');
+        out.push_str(
+            &ChordProDocument {
+                sections,
+                ..Default::default()
+            }
+            .to_chordpro()
+        );
+    }
+
+    out
+}
+
+const MARGIN: f32 = 50.0;
+const LINE_SPACING: f32 = 1.3;
+const HEADING_SIZE: f32 = 18.0;
+const TITLE_SIZE: f32 = 15.0;
+const TEXT_SIZE: f32 = 11.0;
+const META_SIZE: f32 = 9.0;
+
+/// Chord sheets as a PDF with a linked table of contents and bookmarks.
+///
+/// `font` is a TrueType font covering the song languages; a monospaced one
+/// keeps chords aligned over the lyrics.
+pub fn setlist_to_pdf(setlist: &Setlist, font: &[u8]) -> AppResult<Vec<u8>> {
+    let mut pdf = PdfDocument::new(TrueTypeFont::parse(font)?);
+
+    // Contents pages come first, so reserve them before laying out songs
+    let row = TEXT_SIZE * LINE_SPACING;
+    let header = (HEADING_SIZE + TEXT_SIZE) * LINE_SPACING + row;
+    let first_rows = ((PAGE_HEIGHT - 2.0 * MARGIN - header) / row) as usize;
+    let other_rows = ((PAGE_HEIGHT - 2.0 * MARGIN) / row) as usize;
+    let contents_pages = 1 + setlist
+        .songs
+        .len()
+        .saturating_sub(first_rows)
+        .div_ceil(other_rows);
+
+    let mut layout = Layout {
+        page: 0, y: 0.0
+    };
+    for _ in 0..contents_pages {
+        pdf.add_page();
+    }
+
+    let mut starts = Vec::with_capacity(setlist.songs.len());
+    for song in &setlist.songs {
+        layout.new_page(&mut pdf);
+        starts.push(layout.page);
+        pdf.bookmark(&song_title(song), layout.page);
+        layout.song(&mut pdf, song);
+    }
+
+    // Contents with page numbers and links
+    layout.page = 0;
+    layout.y = PAGE_HEIGHT - MARGIN;
+    layout.row(&mut pdf, HEADING_SIZE, &setlist.heading());
+    layout.y -= row;
+    layout.row(&mut pdf, TEXT_SIZE, "Contents");
+
+    for (song, &start) in setlist.songs.iter().zip(&starts) {
+        if layout.y - row < MARGIN {
+            layout.page += 1;
+            layout.y = PAGE_HEIGHT - MARGIN;
+        }
+
+        let number = (start + 1).to_string();
+        let number_x = PAGE_WIDTH - MARGIN - pdf.font().width(&number, TEXT_SIZE);
+        let baseline = layout.y - TEXT_SIZE;
+        pdf.text(layout.page, number_x, baseline, TEXT_SIZE, &number);
+        pdf.link(
+            layout.page,
+            [
+                MARGIN,
+                baseline - 3.0,
+                PAGE_WIDTH - MARGIN,
+                baseline + TEXT_SIZE
+            ],
+            start
+        );
+        layout.row(&mut pdf, TEXT_SIZE, &song_title(song));
+    }
+
+    let total = pdf.page_count();
+    for page in 0..total {
+        let footer = format!("{} / {total}", page + 1);
+        let x = (PAGE_WIDTH - pdf.font().width(&footer, META_SIZE)) / 2.0;
+        pdf.text(page, x, MARGIN / 2.0, META_SIZE, &footer);
+    }
+
+    pdf.finish()
+}
+
+/// Write position on the current page
+struct Layout {
+    page: usize,
+    y:    f32
+}
+
+impl Layout {
+    fn new_page(&mut self, pdf: &mut PdfDocument) {
+        self.page = pdf.add_page();
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Start a new page unless `height` more points fit on this one
+    fn reserve(&mut self, pdf: &mut PdfDocument, height: f32) {
+        if self.y - height < MARGIN {
+            self.new_page(pdf);
+        }
+    }
+
+    /// Write one row at the left margin
+    fn row(&mut self, pdf: &mut PdfDocument, size: f32, text: &str) {
+        self.reserve(pdf, size * LINE_SPACING);
+        if !text.is_empty() {
+            pdf.text(self.page, MARGIN, self.y - size, size, text);
+        }
+        self.y -= size * LINE_SPACING;
+    }
+
+    fn song(&mut self, pdf: &mut PdfDocument, song: &SetlistSong) {
+        let columns = ((PAGE_WIDTH - 2.0 * MARGIN) / pdf.font().width("M", TEXT_SIZE)) as usize;
+
+        for row in sheet(song) {
+            match row {
+                SheetRow::Title(text) => self.row(pdf, TITLE_SIZE, &text),
+                SheetRow::Meta(text) => {
+                    for part in wrap(&text, columns) {
+                        self.row(pdf, META_SIZE, &part);
+                    }
+                }
+                SheetRow::Heading(text) => {
+                    // Keep a heading together with the first line under it
+                    self.reserve(pdf, 3.0 * TEXT_SIZE * LINE_SPACING);
+                    self.row(pdf, META_SIZE, &text);
+                }
+                SheetRow::Line(chords, lyrics) => {
+                    let chords = wrap(&chords, columns);
+                    let lyrics = wrap(&lyrics, columns);
+                    for i in 0..chords.len().max(lyrics.len()) {
+                        let chord_row = chords.get(i).filter(|c| !c.trim().is_empty());
+                        let rows = if chord_row.is_some() { 2.0 } else { 1.0 };
+                        self.reserve(pdf, rows * TEXT_SIZE * LINE_SPACING);
+                        if let Some(chord_row) = chord_row {
+                            self.row(pdf, TEXT_SIZE, chord_row);
+                        }
+                        self.row(pdf, TEXT_SIZE, lyrics.get(i).map_or("", String::as_str));
+                    }
+                }
+                SheetRow::Gap => self.y -= TEXT_SIZE * 0.5
+            }
+        }
+    }
+}
+
+/// Split a row into chunks of at most `columns` characters
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars
+        .chunks(columns.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
@@ -0,0 +1,271 @@
+//! Projection slides built from song content.
+//!
+//! Sections are shown in song order or in a custom verse order
+//! (`V1 C V2 C B C`), chords are dropped, and long sections are split into
+//! evenly sized slides of at most `max_lines` lines.
+
+use masterror::prelude::*;
+use revelation_songbook::Song;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::chordpro::{ChordProDocument, Line, Section};
+
+/// Default number of lines per slide
+pub const DEFAULT_SLIDE_LINES: usize = 4;
+
+/// Slide that carries the song title and copyright
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditsPosition {
+    #[default]
+    First,
+    Last,
+    None
+}
+
+/// Slide building options
+#[derive(Debug, Clone)]
+pub struct SlideOptions {
+    /// Maximum lyric lines per slide
+    pub max_lines:   usize,
+    /// Custom verse order, e.g. `V1 C V2 C B C`; song order when empty
+    pub verse_order: Option<String>,
+    pub credits:     CreditsPosition
+}
+
+impl Default for SlideOptions {
+    fn default() -> Self {
+        Self {
+            max_lines:   DEFAULT_SLIDE_LINES,
+            verse_order: None,
+            credits:     CreditsPosition::default()
+        }
+    }
+}
+
+/// One projected slide
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Slide {
+    /// Verse order name of the source section (`v1`, `c1`, ...)
+    pub section:   String,
+    /// Lyrics without chords
+    pub lines:     Vec<String>,
+    /// Song title, on the credits slide only
+    pub title:     Option<String>,
+    /// Song copyright, on the credits slide only
+    pub copyright: Option<String>
+}
+
+/// Build projection slides for a song
+pub fn build_slides(song: &Song, options: &SlideOptions) -> AppResult<Vec<Slide>> {
+    let document = ChordProDocument::parse(&song.content);
+    let names = document.section_names();
+    let order = section_order(&document.sections, &names, options.verse_order.as_deref())?;
+    let max_lines = options.max_lines.max(1);
+
+    let mut slides = Vec::new();
+    for (section, name) in order {
+        let lines: Vec<String> = section
+            .lines
+            .iter()
+            .map(Line::text)
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        // Even split: 6 lines at 4 per slide become 3 + 3, not 4 + 2
+        let parts = lines.len().div_ceil(max_lines);
+        let size = lines.len().div_ceil(parts);
+
+        for chunk in lines.chunks(size) {
+            slides.push(Slide {
+                section:   name.to_string(),
+                lines:     chunk.to_vec(),
+                title:     None,
+                copyright: None
+            });
+        }
+    }
+
+    let credits = match options.credits {
+        CreditsPosition::First => slides.first_mut(),
+        CreditsPosition::Last => slides.last_mut(),
+        CreditsPosition::None => None
+    };
+    if let Some(slide) = credits {
+        slide.title = Some(song.title.clone());
+        slide.copyright = song.copyright.clone();
+    }
+
+    Ok(slides)
+}
+
+/// Sections in the requested verse order.
+///
+/// Order entries are section names as in OpenLyrics (`v1`, `c1`, `b1`);
+/// a bare kind (`C`) means the first section of that kind.
+fn section_order<'a>(
+    sections: &'a [Section],
+    names: &'a [String],
+    verse_order: Option<&str>
+) -> AppResult<Vec<(&'a Section, &'a str)>> {
+    let entries: Vec<&str> = verse_order
+        .unwrap_or_default()
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(sections
+            .iter()
+            .zip(names.iter().map(String::as_str))
+            .collect());
+    }
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let mut name = entry.to_lowercase();
+            if !name.ends_with(|c: char| c.is_ascii_digit()) {
+                name.push('1');
+            }
+
+            sections
+                .iter()
+                .zip(names)
+                .find(|(_, n)| **n == name)
+                .map(|(section, n)| (section, n.as_str()))
+                .ok_or_else(|| {
+                    AppError::validation(format!(
+                        "Unknown section '{entry}' in verse order; the song has {}",
+                        distinct(names).join(", ")
+                    ))
+                })
+        })
+        .collect()
+}
+
+fn distinct(names: &[String]) -> Vec<&str> {
+    let mut seen: Vec<&str> = Vec::new();
+    for name in names {
+        if !seen.contains(&name.as_str()) {
+            seen.push(name);
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    const CONTENT: &str = "{start_of_verse}\n[G]Line one\nLine two\nLine three\nLine four\nLine five\nLine six\n{end_of_verse}\n{start_of_chorus}\nChorus [D]line\n{end_of_chorus}";
+
+    fn song_from(content: &str) -> Song {
+        Song {
+            id:              Uuid::nil(),
+            songbook_id:     None,
+            songbook_code:   None,
+            number:          None,
+            title:           "Тебе пою".to_string(),
+            title_alt:       None,
+            author_lyrics:   None,
+            author_music:    None,
+            translator:      None,
+            year_written:    None,
+            copyright:       Some("Public domain".to_string()),
+            original_key:    None,
+            tempo:           None,
+            time_signature:  None,
+            content:         content.to_string(),
+            first_line:      String::new(),
+            categories:      Vec::new(),
+            tags:            Vec::new(),
+            is_favorite:     false,
+            user_transpose:  0,
+            views_count:     0,
+            favorites_count: 0
+        }
+    }
+
+    fn options(max_lines: usize, verse_order: Option<&str>) -> SlideOptions {
+        SlideOptions {
+            max_lines,
+            verse_order: verse_order.map(str::to_string),
+            credits: CreditsPosition::First
+        }
+    }
+
+    #[test]
+    fn splits_sections_evenly_without_chords() {
+        let slides = build_slides(&song_from(CONTENT), &options(4, None)).unwrap();
+
+        let sizes: Vec<usize> = slides.iter().map(|slide| slide.lines.len()).collect();
+        assert_eq!(sizes, [3, 3, 1]);
+        assert_eq!(slides[0].lines[0], "Line one");
+        assert_eq!(slides[2].lines, ["Chorus line"]);
+        assert_eq!(slides[0].section, "v1");
+        assert_eq!(slides[2].section, "c1");
+    }
+
+    #[test]
+    fn follows_verse_order() {
+        let slides = build_slides(&song_from(CONTENT), &options(6, Some("C V1, c"))).unwrap();
+
+        let sections: Vec<&str> = slides.iter().map(|slide| slide.section.as_str()).collect();
+        assert_eq!(sections, ["c1", "v1", "c1"]);
+    }
+
+    #[test]
+    fn rejects_unknown_section() {
+        assert!(build_slides(&song_from(CONTENT), &options(4, Some("V1 B"))).is_err());
+    }
+
+    #[test]
+    fn places_credits() {
+        let song = song_from(CONTENT);
+
+        let first = build_slides(&song, &options(6, None)).unwrap();
+        assert_eq!(first[0].title.as_deref(), Some("Тебе пою"));
+        assert_eq!(first[0].copyright.as_deref(), Some("Public domain"));
+        assert!(first[1].title.is_none());
+
+        let last = build_slides(
+            &song,
+            &SlideOptions {
+                credits: CreditsPosition::Last,
+                ..options(6, None)
+            }
+        )
+        .unwrap();
+        assert!(last[0].title.is_none());
+        assert_eq!(last[1].title.as_deref(), Some("Тебе пою"));
+
+        let none = build_slides(
+            &song,
+            &SlideOptions {
+                credits: CreditsPosition::None,
+                ..options(6, None)
+            }
+        )
+        .unwrap();
+        assert!(none.iter().all(|slide| slide.title.is_none()));
+    }
+
+    #[test]
+    fn skips_empty_sections() {
+        let content =
+            "{start_of_verse}\n\n{end_of_verse}\n{start_of_chorus}\nOnly line\n{end_of_chorus}";
+        let slides = build_slides(&song_from(content), &options(4, None)).unwrap();
+
+        assert_eq!(slides.len(), 1);
+        assert_eq!(slides[0].lines, ["Only line"]);
+    }
+}
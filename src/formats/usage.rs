@@ -0,0 +1,135 @@
+//! CSV export of church song usage reports.
+//!
+//! Text cells that a spreadsheet would read as a formula are prefixed with
+//! an apostrophe, so a song title cannot run code when the report is opened.
+
+use masterror::prelude::*;
+
+use crate::domain::SongUsageReport;
+
+const HEADER: [&str; 11] = [
+    "Title",
+    "Songbook",
+    "Number",
+    "Words",
+    "Music",
+    "Translation",
+    "Copyright",
+    "Times Used",
+    "Services",
+    "First Used",
+    "Last Used"
+];
+
+/// One row per song, most used first
+pub fn usage_to_csv(report: &SongUsageReport) -> AppResult<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_error = |e: csv::Error| AppError::internal(format!("CSV encoding failed: {e}"));
+
+    writer.write_record(HEADER).map_err(csv_error)?;
+    for song in &report.songs {
+        writer
+            .write_record([
+                text(&song.title),
+                text(song.songbook_code.as_deref().unwrap_or_default()),
+                song.number.map(|n| n.to_string()).unwrap_or_default(),
+                text(song.author_lyrics.as_deref().unwrap_or_default()),
+                text(song.author_music.as_deref().unwrap_or_default()),
+                text(song.translator.as_deref().unwrap_or_default()),
+                text(song.copyright.as_deref().unwrap_or_default()),
+                song.times_used.to_string(),
+                song.services.to_string(),
+                song.first_used.to_string(),
+                song.last_used.to_string()
+            ])
+            .map_err(csv_error)?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| AppError::internal(format!("CSV encoding failed: {e}")))?;
+    String::from_utf8(bytes).map_err(|e| AppError::internal(format!("CSV encoding failed: {e}")))
+}
+
+/// Text cell, neutralized when it starts like a formula
+fn text(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::domain::SongUsage;
+
+    fn usage(title: &str, copyright: Option<&str>) -> SongUsage {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        SongUsage {
+            song_id:       Uuid::nil(),
+            title:         title.to_string(),
+            songbook_code: Some("pv".to_string()),
+            number:        Some(245),
+            author_lyrics: Some("Карл Боберг".to_string()),
+            author_music:  None,
+            translator:    Some("И. С. Проханов".to_string()),
+            copyright:     copyright.map(str::to_string),
+            times_used:    3,
+            services:      2,
+            first_used:    day,
+            last_used:     day
+        }
+    }
+
+    fn report(songs: Vec<SongUsage>) -> SongUsageReport {
+        SongUsageReport {
+            church_id: Uuid::nil(),
+            from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+            songs
+        }
+    }
+
+    #[test]
+    fn writes_header_and_rows() {
+        let csv = usage_to_csv(&report(vec![usage("Великий Бог, Ты", None)])).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "\"Великий Бог, Ты\",pv,245,Карл Боберг,,И. С. Проханов,,3,2,2024-03-10,2024-03-10"
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn neutralizes_formulas() {
+        let songs = vec![
+            usage("=HYPERLINK(\"http://example.com\")", Some("@SUM(A1)")),
+            usage("+1", Some("-2")),
+            usage("Тебе пою", Some("Public domain")),
+        ];
+        let csv = usage_to_csv(&report(songs)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert!(lines[1].starts_with("\"'=HYPERLINK(\"\"http://example.com\"\")\","));
+        assert!(lines[1].contains(",'@SUM(A1),"));
+        assert!(lines[2].starts_with("'+1,"));
+        assert!(lines[2].contains(",'-2,"));
+        assert!(lines[3].starts_with("Тебе пою,"));
+        assert!(lines[3].contains(",Public domain,"));
+    }
+
+    #[test]
+    fn prefixes_only_formula_starts() {
+        assert_eq!(text("Аминь - аминь"), "Аминь - аминь");
+        assert_eq!(text(""), "");
+        assert_eq!(text("\t=1"), "'\t=1");
+    }
+}
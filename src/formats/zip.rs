@@ -0,0 +1,245 @@
+//! Minimal ZIP archive writer for service file exports.
+//!
+//! Entries are deflate-compressed with UTF-8 names; no ZIP64, encryption or
+//! extra fields.
+
+use std::io::Write as _;
+
+use chrono::{Datelike, Timelike, Utc};
+use flate2::{Compression, Crc, write::DeflateEncoder};
+use masterror::prelude::*;
+
+/// Local header flag: file name is UTF-8
+const UTF8_NAMES: u16 = 0x0800;
+
+/// Deflate compression method
+const DEFLATE: u16 = 8;
+
+struct Entry {
+    name:       String,
+    crc:        u32,
+    compressed: u32,
+    size:       u32,
+    offset:     u32
+}
+
+/// ZIP archive built in memory
+pub struct ZipWriter {
+    out:     Vec<u8>,
+    entries: Vec<Entry>,
+    time:    u16,
+    date:    u16
+}
+
+impl Default for ZipWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        let now = Utc::now();
+        Self {
+            out:     Vec::new(),
+            entries: Vec::new(),
+            time:    ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+            date:    (((now.year() - 1980).max(0) as u32) << 9 | now.month() << 5 | now.day())
+                as u16
+        }
+    }
+
+    /// Add a file to the archive
+    pub fn add(&mut self, name: &str, data: &[u8]) -> AppResult<()> {
+        let mut crc = Crc::new();
+        crc.update(data);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(data)
+            .map_err(|e| AppError::internal(format!("ZIP compression failed: {e}")))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| AppError::internal(format!("ZIP compression failed: {e}")))?;
+
+        let entry = Entry {
+            name:       name.to_string(),
+            crc:        crc.sum(),
+            compressed: size(compressed.len())?,
+            size:       size(data.len())?,
+            offset:     size(self.out.len())?
+        };
+
+        self.out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&20u16.to_le_bytes());
+        self.header(&entry);
+        self.out.extend_from_slice(&0u16.to_le_bytes());
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(&compressed);
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory and return the archive bytes
+    pub fn finish(mut self) -> AppResult<Vec<u8>> {
+        let directory = size(self.out.len())?;
+        let entries = std::mem::take(&mut self.entries);
+
+        for entry in &entries {
+            self.out.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            self.out.extend_from_slice(&20u16.to_le_bytes());
+            self.out.extend_from_slice(&20u16.to_le_bytes());
+            self.header(entry);
+            // Extra field, comment, disk number, internal and external
+            // attributes
+            self.out.extend_from_slice(&[0; 12]);
+            self.out.extend_from_slice(&entry.offset.to_le_bytes());
+            self.out.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_size = size(self.out.len())? - directory;
+        let count = u16::try_from(entries.len())
+            .map_err(|_| AppError::internal("Too many ZIP entries"))?;
+
+        self.out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.out.extend_from_slice(&[0; 4]);
+        self.out.extend_from_slice(&count.to_le_bytes());
+        self.out.extend_from_slice(&count.to_le_bytes());
+        self.out.extend_from_slice(&directory_size.to_le_bytes());
+        self.out.extend_from_slice(&directory.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes());
+
+        Ok(self.out)
+    }
+
+    /// Fields shared by local and central headers, from flags to name length
+    fn header(&mut self, entry: &Entry) {
+        self.out.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+        self.out.extend_from_slice(&DEFLATE.to_le_bytes());
+        self.out.extend_from_slice(&self.time.to_le_bytes());
+        self.out.extend_from_slice(&self.date.to_le_bytes());
+        self.out.extend_from_slice(&entry.crc.to_le_bytes());
+        self.out.extend_from_slice(&entry.compressed.to_le_bytes());
+        self.out.extend_from_slice(&entry.size.to_le_bytes());
+        self.out
+            .extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+    }
+}
+
+fn size(len: usize) -> AppResult<u32> {
+    u32::try_from(len).map_err(|_| AppError::internal("ZIP archive exceeds 4 GiB"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read as _;
+
+    use flate2::read::DeflateDecoder;
+
+    use super::*;
+
+    fn u16_at(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3]
+        ])
+    }
+
+    /// Read every entry back through the central directory, checking sizes
+    /// and checksums against the local headers
+    fn read_archive(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(archive, end), 0x0605_4b50);
+        assert_eq!(u16_at(archive, end + 20), 0, "no archive comment");
+
+        let count = u16_at(archive, end + 10) as usize;
+        assert_eq!(u16_at(archive, end + 8) as usize, count);
+        let directory = u32_at(archive, end + 16) as usize;
+        assert_eq!(directory + u32_at(archive, end + 12) as usize, end);
+
+        let mut entries = Vec::with_capacity(count);
+        let mut record = directory;
+        for _ in 0..count {
+            assert_eq!(u32_at(archive, record), 0x0201_4b50);
+            assert_eq!(u16_at(archive, record + 8), UTF8_NAMES);
+            assert_eq!(u16_at(archive, record + 10), DEFLATE);
+            let crc = u32_at(archive, record + 16);
+            let compressed = u32_at(archive, record + 20) as usize;
+            let size = u32_at(archive, record + 24) as usize;
+            let name_len = u16_at(archive, record + 28) as usize;
+            let local = u32_at(archive, record + 42) as usize;
+            let name = &archive[record + 46..record + 46 + name_len];
+
+            // The local header repeats the central one
+            assert_eq!(u32_at(archive, local), 0x0403_4b50);
+            assert_eq!(
+                archive[local + 6..local + 26],
+                archive[record + 8..record + 28]
+            );
+            assert_eq!(&archive[local + 30..local + 30 + name_len], name);
+
+            let start = local + 30 + name_len;
+            let mut data = Vec::new();
+            DeflateDecoder::new(&archive[start..start + compressed])
+                .read_to_end(&mut data)
+                .unwrap();
+            assert_eq!(data.len(), size);
+
+            let mut check = Crc::new();
+            check.update(&data);
+            assert_eq!(check.sum(), crc);
+
+            entries.push((String::from_utf8(name.to_vec()).unwrap(), data));
+            record += 46 + name_len;
+        }
+        assert_eq!(record, end);
+        entries
+    }
+
+    #[test]
+    fn round_trips_entries() {
+        let song = "Великий Бог, когда на мир смотрю я\n".repeat(50);
+        let mut zip = ZipWriter::new();
+        zip.add("service.osj", b"{\"items\": []}").unwrap();
+        zip.add("Песни/Тебе пою.txt", song.as_bytes()).unwrap();
+        zip.add("empty", b"").unwrap();
+
+        let entries = read_archive(&zip.finish().unwrap());
+
+        assert_eq!(
+            entries,
+            [
+                ("service.osj".to_string(), b"{\"items\": []}".to_vec()),
+                ("Песни/Тебе пою.txt".to_string(), song.into_bytes()),
+                ("empty".to_string(), Vec::new())
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_empty_archive() {
+        let archive = ZipWriter::new().finish().unwrap();
+
+        assert_eq!(archive.len(), 22);
+        assert!(read_archive(&archive).is_empty());
+    }
+
+    #[test]
+    fn stamps_entries_with_dos_time() {
+        let zip = ZipWriter::new();
+        let month = (zip.date >> 5) & 0x0f;
+        let day = zip.date & 0x1f;
+
+        assert!(zip.date >> 9 >= 2024 - 1980);
+        assert!((1..=12).contains(&month));
+        assert!((1..=31).contains(&day));
+        assert!(zip.time >> 11 < 24);
+    }
+}
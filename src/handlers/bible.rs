@@ -9,20 +9,27 @@ use masterror::prelude::*;
 use revelation_bible::{
     Book, ChapterInfo, DailyReading, Pericope, SearchResult, Testament, Verse
 };
+use revelation_server::{
+    BibleSearchMode, BibleSearchPage, BibleSearchParams, ChapterVerse, ScriptureSong,
+    StrongsDetails, Symphony
+};
 use serde::Deserialize;
 use utoipa::{OpenApi, ToSchema};
 
-use crate::state::AppState;
+use crate::{middleware::OptionalClaims, state::AppState};
 
 #[derive(OpenApi)]
 #[openapi(paths(
     get_books,
     get_chapter,
+    get_chapter_songs,
     get_pericopes,
     get_chapters_info,
     get_verse,
     search,
+    search_page,
     symphony,
+    get_strongs,
     get_today_reading,
     get_day_reading
 ))]
@@ -32,6 +39,10 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/books", get(get_books))
         .route("/books/{book_id}/chapters/{chapter}", get(get_chapter))
+        .route(
+            "/books/{book_id}/chapters/{chapter}/songs",
+            get(get_chapter_songs)
+        )
         .route("/books/{book_id}/pericopes", get(get_pericopes))
         .route("/books/{book_id}/chapters-info", get(get_chapters_info))
         .route(
@@ -39,7 +50,9 @@ pub fn routes() -> Router<AppState> {
             get(get_verse)
         )
         .route("/search", get(search))
+        .route("/search/page", get(search_page))
         .route("/symphony/{word}", get(symphony))
+        .route("/strongs/{number}", get(get_strongs))
         .route("/today", get(get_today_reading))
         .route("/day/{day}", get(get_day_reading))
 }
@@ -71,25 +84,70 @@ struct BooksQueryInternal {
     testament: Option<Testament>
 }
 
+#[derive(Debug, Deserialize)]
+struct ChapterQuery {
+    #[serde(default)]
+    tagged: bool
+}
+
 #[utoipa::path(
     get,
     tag = "Bible",
     path = "/api/bible/books/{book_id}/chapters/{chapter}",
     params(
         ("book_id" = i16, Path, description = "Book ID"),
-        ("chapter" = i16, Path, description = "Chapter number")
+        ("chapter" = i16, Path, description = "Chapter number"),
+        ("tagged" = Option<bool>, Query, description = "Include the Strong's tags of each verse's words")
     ),
     responses(
-        (status = 200, description = "Chapter verses", body = Vec<Verse>),
+        (status = 200, description = "Chapter verses", body = Vec<ChapterVerse>),
         (status = 404, description = "Book or chapter not found")
     )
 )]
 async fn get_chapter(
     State(state): State<AppState>,
-    Path((book_id, chapter)): Path<(i16, i16)>
-) -> AppResult<Json<Vec<Verse>>> {
+    Path((book_id, chapter)): Path<(i16, i16)>,
+    Query(query): Query<ChapterQuery>
+) -> AppResult<Json<Vec<ChapterVerse>>> {
+    if query.tagged {
+        let verses = state.bible.get_chapter_tagged(book_id, chapter).await?;
+        return Ok(Json(verses));
+    }
+
     let verses = state.bible.get_chapter(book_id, chapter).await?;
-    Ok(Json(verses))
+    Ok(Json(
+        verses
+            .into_iter()
+            .map(|verse| ChapterVerse {
+                verse,
+                words: None
+            })
+            .collect()
+    ))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Bible",
+    path = "/api/bible/books/{book_id}/chapters/{chapter}/songs",
+    params(
+        ("book_id" = i16, Path, description = "Book ID"),
+        ("chapter" = i16, Path, description = "Chapter number")
+    ),
+    responses(
+        (status = 200, description = "Songs based on passages of the chapter, in verse order", body = Vec<ScriptureSong>)
+    )
+)]
+async fn get_chapter_songs(
+    State(state): State<AppState>,
+    Path((book_id, chapter)): Path<(i16, i16)>,
+    claims: OptionalClaims
+) -> AppResult<Json<Vec<ScriptureSong>>> {
+    let songs = state
+        .songs
+        .list_chapter_songs(book_id, chapter, claims.user_id())
+        .await?;
+    Ok(Json(songs))
 }
 
 #[utoipa::path(
@@ -154,10 +212,27 @@ async fn get_verse(
 #[derive(Deserialize, ToSchema)]
 pub struct SearchQuery {
     q:     String,
+    #[serde(default)]
+    mode:  BibleSearchMode,
     #[serde(default = "default_limit")]
     limit: i64
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct SearchPageQuery {
+    q:            String,
+    #[serde(default)]
+    mode:         BibleSearchMode,
+    testament:    Option<Testament>,
+    book_id:      Option<i16>,
+    chapter_from: Option<i16>,
+    chapter_to:   Option<i16>,
+    #[serde(default = "default_limit")]
+    limit:        i64,
+    #[serde(default)]
+    offset:       i64
+}
+
 fn default_limit() -> i64 {
     50
 }
@@ -167,67 +242,137 @@ fn default_limit() -> i64 {
     tag = "Bible",
     path = "/api/bible/search",
     params(
-        ("q" = String, Query, description = "Search query"),
-        ("limit" = Option<i64>, Query, description = "Max results (default 50)")
+        ("q" = String, Query, description = "Search query; in positional mode a word, a prefix*, a \"phrase\" or word NEAR/n word"),
+        ("mode" = Option<BibleSearchMode>, Query, description = "fulltext (default) or positional"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50, at most 200 in positional mode)")
     ),
     responses(
-        (status = 200, description = "Search results", body = Vec<SearchResult>)
+        (status = 200, description = "Search results", body = Vec<SearchResult>),
+        (status = 400, description = "Invalid positional query")
     )
 )]
 async fn search(
     State(state): State<AppState>,
     Query(query): Query<SearchQuery>
 ) -> AppResult<Json<Vec<SearchResult>>> {
-    let results = state.bible.search(&query.q, query.limit).await?;
+    let results = match query.mode {
+        BibleSearchMode::Fulltext => state.bible.search(&query.q, query.limit).await?,
+        BibleSearchMode::Positional => {
+            let params = BibleSearchParams {
+                query: query.q,
+                mode: query.mode,
+                limit: query.limit,
+                ..Default::default()
+            };
+            state.bible.search_page(&params).await?.results
+        }
+    };
     Ok(Json(results))
 }
 
+#[utoipa::path(
+    get,
+    tag = "Bible",
+    path = "/api/bible/search/page",
+    params(
+        ("q" = String, Query, description = "Search query; in positional mode a word, a prefix*, a \"phrase\" or word NEAR/n word"),
+        ("mode" = Option<BibleSearchMode>, Query, description = "fulltext (default) or positional"),
+        ("testament" = Option<String>, Query, description = "Only this testament: old or new"),
+        ("book_id" = Option<i16>, Query, description = "Only this book"),
+        ("chapter_from" = Option<i16>, Query, description = "First chapter of book_id to search"),
+        ("chapter_to" = Option<i16>, Query, description = "Last chapter of book_id to search"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50, at most 200)"),
+        ("offset" = Option<i64>, Query, description = "Results to skip")
+    ),
+    responses(
+        (status = 200, description = "Matching verses with highlight offsets, total hits and hits per book", body = BibleSearchPage),
+        (status = 400, description = "Empty query or invalid filters")
+    )
+)]
+async fn search_page(
+    State(state): State<AppState>,
+    Query(query): Query<SearchPageQuery>
+) -> AppResult<Json<BibleSearchPage>> {
+    let params = BibleSearchParams {
+        query:        query.q,
+        mode:         query.mode,
+        testament:    query.testament,
+        book_id:      query.book_id,
+        chapter_from: query.chapter_from,
+        chapter_to:   query.chapter_to,
+        limit:        query.limit,
+        offset:       query.offset
+    };
+
+    let page = state.bible.search_page(&params).await?;
+    Ok(Json(page))
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct LimitQuery {
     limit: Option<i64>
 }
 
-#[derive(serde::Serialize, ToSchema)]
-pub struct SymphonyResponse {
-    word:        String,
-    total_count: i64
-}
-
 #[utoipa::path(
     get,
     tag = "Bible",
     path = "/api/bible/symphony/{word}",
     params(
-        ("word" = String, Path, description = "Word to search"),
-        ("limit" = Option<i64>, Query, description = "Max results (default 100)")
+        ("word" = String, Path, description = "Word in any form"),
+        ("limit" = Option<i64>, Query, description = "Max verses (default 100, max 200)")
     ),
     responses(
-        (status = 200, description = "Word occurrences", body = SymphonyResponse)
+        (status = 200, description = "All forms of the word with frequencies per book", body = Symphony),
+        (status = 400, description = "Word has no letters or digits")
     )
 )]
 async fn symphony(
     State(state): State<AppState>,
     Path(word): Path<String>,
     Query(query): Query<LimitQuery>
-) -> AppResult<Json<SymphonyResponseFull>> {
-    let count = state.bible.word_count(&word).await?;
-    let verses = state
+) -> AppResult<Json<Symphony>> {
+    let symphony = state
         .bible
         .symphony(&word, query.limit.unwrap_or(100))
         .await?;
+    Ok(Json(symphony))
+}
 
-    Ok(Json(SymphonyResponseFull {
-        word,
-        total_count: count,
-        verses
-    }))
+#[derive(Deserialize)]
+struct StrongsQuery {
+    limit:  Option<i64>,
+    offset: Option<i64>
 }
 
-#[derive(serde::Serialize)]
-struct SymphonyResponseFull {
-    word:        String,
-    total_count: i64,
-    verses:      Vec<SearchResult>
+#[utoipa::path(
+    get,
+    tag = "Bible",
+    path = "/api/bible/strongs/{number}",
+    params(
+        ("number" = String, Path, description = "Strong's number, e.g. H430 or G26"),
+        ("limit" = Option<i64>, Query, description = "Max verses (default 50, max 200)"),
+        ("offset" = Option<i64>, Query, description = "Verses to skip (default 0)")
+    ),
+    responses(
+        (status = 200, description = "Lexicon entry with the verses it occurs in", body = StrongsDetails),
+        (status = 400, description = "Invalid number or paging"),
+        (status = 404, description = "Number not in the lexicon")
+    )
+)]
+async fn get_strongs(
+    State(state): State<AppState>,
+    Path(number): Path<String>,
+    Query(query): Query<StrongsQuery>
+) -> AppResult<Json<StrongsDetails>> {
+    let details = state
+        .bible
+        .get_strongs(
+            &number,
+            query.limit.unwrap_or(50),
+            query.offset.unwrap_or(0)
+        )
+        .await?;
+    Ok(Json(details))
 }
 
 #[utoipa::path(
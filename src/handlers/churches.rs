@@ -1,15 +1,22 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     routing::{get, post, put}
 };
+use chrono::NaiveDate;
 use masterror::prelude::*;
 use revelation_church::{
     Church, CreateChurch, JoinChurch, Membership, UpdateChurch, UpdateMemberRole
 };
+use revelation_server::{WorshipTeamMember, formats::usage_to_csv};
+use revelation_songbook::SongPlaylist;
+use revelation_user::Claims;
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::state::AppState;
+use crate::{middleware::OptionalClaims, state::AppState};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
@@ -22,6 +29,13 @@ pub fn routes() -> Router<AppState> {
             "/{church_id}/members/{user_id}/role",
             put(update_member_role)
         )
+        .route("/{church_id}/setlists", get(list_setlists))
+        .route("/{church_id}/song-usage", get(song_usage))
+        .route("/{church_id}/worship-team", get(list_worship_team))
+        .route(
+            "/{church_id}/worship-team/{user_id}",
+            put(add_worship_team_member).delete(remove_worship_team_member)
+        )
 }
 
 async fn create_church(
@@ -208,3 +222,107 @@ async fn update_member_role(
 
     Ok(Json(membership))
 }
+
+#[derive(Debug, Deserialize)]
+struct SetlistQuery {
+    from: Option<NaiveDate>,
+    to:   Option<NaiveDate>
+}
+
+/// Upcoming services: church setlists by event date
+async fn list_setlists(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(church_id): Path<Uuid>,
+    Query(query): Query<SetlistQuery>
+) -> AppResult<Json<Vec<SongPlaylist>>> {
+    let setlists = state
+        .songs
+        .list_church_setlists(church_id, query.from, query.to, claims.user_id())
+        .await?;
+    Ok(Json(setlists))
+}
+
+/// Song usage report format
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UsageFormat {
+    #[default]
+    Json,
+    Csv
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageQuery {
+    from:   Option<NaiveDate>,
+    to:     Option<NaiveDate>,
+    #[serde(default)]
+    format: UsageFormat
+}
+
+/// Copyright licensing report: songs performed in church setlists
+async fn song_usage(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(church_id): Path<Uuid>,
+    Query(query): Query<UsageQuery>
+) -> AppResult<Response> {
+    let report = state
+        .songs
+        .church_song_usage(claims.user_id(), church_id, query.from, query.to)
+        .await?;
+
+    match query.format {
+        UsageFormat::Json => Ok(Json(report).into_response()),
+        UsageFormat::Csv => {
+            let csv = usage_to_csv(&report)?;
+            let headers = [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"song-usage-{}-{}.csv\"",
+                        report.from, report.to
+                    )
+                )
+            ];
+            Ok((headers, csv).into_response())
+        }
+    }
+}
+
+async fn list_worship_team(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(church_id): Path<Uuid>
+) -> AppResult<Json<Vec<WorshipTeamMember>>> {
+    let members = state
+        .songs
+        .list_worship_team(claims.user_id(), church_id)
+        .await?;
+    Ok(Json(members))
+}
+
+async fn add_worship_team_member(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((church_id, user_id)): Path<(Uuid, Uuid)>
+) -> AppResult<()> {
+    state
+        .songs
+        .add_worship_team_member(claims.user_id(), church_id, user_id)
+        .await?;
+    Ok(())
+}
+
+async fn remove_worship_team_member(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((church_id, user_id)): Path<(Uuid, Uuid)>
+) -> AppResult<()> {
+    state
+        .songs
+        .remove_worship_team_member(claims.user_id(), church_id, user_id)
+        .await?;
+    Ok(())
+}
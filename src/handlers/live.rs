@@ -0,0 +1,249 @@
+//! Live worship presentation handlers.
+//!
+//! The leader (anyone who may edit the playlist) drives the session over
+//! the WebSocket or `PUT .../live`; followers receive slides over the
+//! WebSocket or Server-Sent Events.
+
+use std::convert::Infallible;
+
+use axum::{
+    Json, Router,
+    extract::{
+        Path, State,
+        ws::{Message, WebSocket, WebSocketUpgrade}
+    },
+    response::{
+        Response,
+        sse::{Event, KeepAlive, Sse}
+    },
+    routing::get
+};
+use masterror::prelude::*;
+use revelation_server::{LiveCommand, LiveEvent, LiveHub, LiveSlide};
+use revelation_user::Claims;
+use tokio_stream::{Stream, StreamExt};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{middleware::OptionalClaims, state::AppState};
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_live_slide,
+    send_live_command,
+    end_live_session,
+    live_events,
+    live_socket
+))]
+pub struct LiveApiDoc;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/playlists/{id}/live",
+            get(get_live_slide)
+                .put(send_live_command)
+                .delete(end_live_session)
+        )
+        .route("/playlists/{id}/live/events", get(live_events))
+        .route("/playlists/{id}/live/ws", get(live_socket))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/live",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Slide currently shown", body = LiveSlide),
+        (status = 404, description = "Playlist is not being presented")
+    )
+)]
+async fn get_live_slide(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>
+) -> AppResult<Json<LiveSlide>> {
+    state
+        .songs
+        .get_visible_playlist(id, claims.user_id())
+        .await?;
+
+    let slide = state
+        .live
+        .current_slide(id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Playlist is not being presented"))?;
+    Ok(Json(slide))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/live",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    request_body = LiveCommand,
+    responses(
+        (status = 200, description = "Slide broadcast to followers", body = LiveSlide),
+        (status = 403, description = "Not allowed to lead this playlist")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn send_live_command(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(command): Json<LiveCommand>
+) -> AppResult<Json<LiveSlide>> {
+    state
+        .songs
+        .ensure_can_edit_playlist(claims.user_id(), id)
+        .await?;
+
+    let slide = state.live.apply(id, claims.user_id(), &command).await?;
+    Ok(Json(slide))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/live",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Session ended"),
+        (status = 403, description = "Not allowed to lead this playlist"),
+        (status = 404, description = "Playlist is not being presented")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn end_live_session(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state
+        .songs
+        .ensure_can_edit_playlist(claims.user_id(), id)
+        .await?;
+
+    state.live.end(id).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/live/events",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Server-Sent Events stream of `LiveEvent` JSON, starting with the current slide", content_type = "text/event-stream")
+    )
+)]
+async fn live_events(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    state
+        .songs
+        .get_visible_playlist(id, claims.user_id())
+        .await?;
+
+    let updates = state.live.subscribe(id);
+    let current = state.live.current_slide(id).await?.map(LiveEvent::Slide);
+
+    let events = tokio_stream::iter(current)
+        .chain(updates.filter_map(Result::ok))
+        .filter_map(|event| Event::default().json_data(event).ok())
+        .map(Ok);
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/live/ws",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 101, description = "WebSocket of `LiveEvent` JSON messages; the leader may send `LiveCommand` JSON")
+    )
+)]
+async fn live_socket(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>,
+    upgrade: WebSocketUpgrade
+) -> AppResult<Response> {
+    state
+        .songs
+        .get_visible_playlist(id, claims.user_id())
+        .await?;
+
+    // Only users who may edit the playlist lead; others just follow
+    let leader = match claims.user_id() {
+        Some(user_id) if state.songs.can_edit_playlist(user_id, id).await? => Some(user_id),
+        _ => None
+    };
+
+    Ok(upgrade.on_upgrade(move |socket| run_live_socket(socket, state.live, id, leader)))
+}
+
+async fn run_live_socket(
+    mut socket: WebSocket,
+    live: LiveHub,
+    playlist_id: Uuid,
+    leader: Option<Uuid>
+) {
+    let mut updates = live.subscribe(playlist_id);
+
+    if let Ok(Some(slide)) = live.current_slide(playlist_id).await
+        && send_event(&mut socket, &LiveEvent::Slide(slide))
+            .await
+            .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.next() => match update {
+                Some(Ok(event)) => {
+                    if send_event(&mut socket, &event).await.is_err() {
+                        break;
+                    }
+                }
+                // Lagged followers skip the slides they missed
+                Some(Err(_)) => continue,
+                None => break
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let result = match leader {
+                        Some(leader_id) => match serde_json::from_str::<LiveCommand>(text.as_str()) {
+                            Ok(command) => live.apply(playlist_id, leader_id, &command).await.map(drop),
+                            Err(error) => Err(AppError::bad_request(format!("Invalid command: {error}")))
+                        },
+                        None => Err(AppError::forbidden("Not allowed to lead this playlist"))
+                    };
+
+                    if let Err(error) = result {
+                        let rejected = LiveEvent::Error {
+                            message: error.to_string()
+                        };
+                        if send_event(&mut socket, &rejected).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &LiveEvent) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(event).map_err(axum::Error::new)?;
+    socket.send(Message::Text(json.into())).await
+}
@@ -13,10 +13,12 @@ mod bible;
 mod churches;
 mod feed;
 mod health;
+mod live;
 mod songs;
 mod users;
 
 pub use bible::BibleApiDoc;
+pub use live::LiveApiDoc;
 pub use songs::SongsApiDoc;
 pub use users::UsersApiDoc;
 
@@ -67,7 +69,7 @@ pub fn api_routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health::health_check))
         .nest("/bible", bible::routes())
-        .nest("/songs", songs::routes())
+        .nest("/songs", songs::routes().merge(live::routes()))
         .nest("/users", users::routes())
         .nest("/churches", churches::routes())
         .nest("/feed", feed::routes())
@@ -77,6 +79,7 @@ pub fn merged_openapi() -> UtoipaOpenApi {
     let mut openapi = ApiDoc::openapi();
     openapi.merge(BibleApiDoc::openapi());
     openapi.merge(SongsApiDoc::openapi());
+    openapi.merge(LiveApiDoc::openapi());
     openapi.merge(UsersApiDoc::openapi());
     openapi
 }
@@ -1,56 +1,219 @@
 //! Song API handlers
 
+use std::io;
+
 use axum::{
     Json, Router,
+    body::Body,
     extract::{Path, Query, State},
-    routing::{delete, get, post}
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+    routing::{delete, get, patch, post, put}
 };
 use masterror::prelude::*;
+use revelation_server::{
+    AddAttachmentLink, AddPlaylistPassage, AddSongScripture, AttachmentKind, CreateSongSubmission,
+    CreateSongTag, CreateSongbook, CreateSongbookEdition, DEFAULT_DUPLICATE_SCORE,
+    DuplicatePlaylist, DuplicateSongs, EditionNumber, KeyMismatch, LinkSongFamily, MergeSong,
+    MergeSongTags, PlaylistPassage, PlaylistShareLink, PlaylistSlides, PopularSong,
+    PopularityOrder, PublishSongSubmission, RangeRequest, ReorderPlaylist, ReviewSongSubmission,
+    ScriptureSuggestion, SharedPlaylist, SimilarSong, SongAttachment, SongDetails, SongFamily,
+    SongMerge, SongOrder, SongScripture, SongSearchParams, SongSubmission, SongSuggestion,
+    SubmissionStatus, UpdatePlaylist, UpdatePlaylistItem, UpdateSongSubmission, UpdateSongTag,
+    UpdateSongbook, UpdateSongbookEdition, UploadAttachment, essence,
+    formats::{
+        CreditsPosition, DEFAULT_SLIDE_LINES, Slide, SlideOptions, setlist_to_chordpro,
+        setlist_to_text
+    }
+};
 use revelation_songbook::{
     AddToPlaylist, CreatePlaylist, CreateSong, PlaylistItem, Song, SongCategory, SongFilters,
     SongHistoryEntry, SongPlaylist, SongSearchResult, SongSortBy, SongSummary, SongTag, Songbook,
     SongbookEdition, UpdateSong, transpose_content, transpose_key
 };
+use revelation_user::Claims;
 use serde::Deserialize;
+use tokio_stream::StreamExt;
 use utoipa::{OpenApi, ToSchema};
 use uuid::Uuid;
 
-use crate::state::AppState;
+use crate::{
+    middleware::{OptionalClaims, ViewerKey},
+    state::AppState
+};
 
 #[derive(OpenApi)]
 #[openapi(paths(
     list_songbooks,
     get_songbook,
+    create_songbook,
+    update_songbook,
+    delete_songbook,
     get_songbook_editions,
+    create_edition,
+    update_edition,
+    delete_edition,
     list_songbook_songs,
+    list_songs_by_number,
+    get_song_by_number,
+    set_edition_numbers,
     list_songs,
+    list_trending,
+    list_most_sung,
+    list_recommended,
     search_songs,
+    suggest_songs,
     list_categories,
     list_by_category,
     list_tags,
+    create_tag,
+    update_tag,
+    delete_tag,
+    merge_tags,
+    list_tag_songs,
+    add_song_tag,
+    remove_song_tag,
+    get_song_family,
+    link_song_family,
+    unlink_song_family,
+    list_song_scripture,
+    add_song_scripture,
+    remove_song_scripture,
+    suggest_song_scripture,
     get_song,
     get_song_transposed,
+    list_similar,
+    export_song,
+    import_song,
     create_song,
     update_song,
-    delete_song
+    delete_song,
+    update_playlist,
+    update_playlist_item,
+    reorder_playlist,
+    duplicate_playlist,
+    share_playlist,
+    unshare_playlist,
+    get_shared_playlist,
+    export_playlist,
+    list_playlist_passages,
+    add_playlist_passage,
+    remove_playlist_passage,
+    list_missing_copyright,
+    list_key_mismatches,
+    list_duplicates,
+    merge_song,
+    list_attachments,
+    upload_attachment,
+    add_attachment_link,
+    download_attachment,
+    delete_attachment,
+    list_my_submissions,
+    create_submission,
+    list_submission_queue,
+    get_submission,
+    update_submission,
+    delete_submission,
+    submit_submission,
+    review_submission,
+    publish_submission,
+    get_song_slides,
+    get_playlist_slides
 ))]
 pub struct SongsApiDoc;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         // Songbooks
-        .route("/songbooks", get(list_songbooks))
-        .route("/songbooks/{id}", get(get_songbook))
-        .route("/songbooks/{id}/editions", get(get_songbook_editions))
+        .route("/songbooks", get(list_songbooks).post(create_songbook))
+        .route(
+            "/songbooks/{id}",
+            get(get_songbook)
+                .put(update_songbook)
+                .delete(delete_songbook)
+        )
+        .route(
+            "/songbooks/{id}/editions",
+            get(get_songbook_editions).post(create_edition)
+        )
+        .route(
+            "/songbooks/{id}/editions/{edition_id}",
+            put(update_edition).delete(delete_edition)
+        )
+        .route(
+            "/songbooks/{id}/editions/{edition_id}/numbers",
+            put(set_edition_numbers)
+        )
         .route("/songbooks/{id}/songs", get(list_songbook_songs))
+        .route("/songbooks/{id}/numbers", get(list_songs_by_number))
+        .route("/songbooks/{id}/numbers/{number}", get(get_song_by_number))
         // Songs
         .route("/", get(list_songs).post(create_song))
         .route("/search", get(search_songs))
+        .route("/trending", get(list_trending))
+        .route("/most-sung", get(list_most_sung))
+        .route("/recommended", get(list_recommended))
+        .route("/suggest", get(suggest_songs))
+        .route("/import", post(import_song))
         .route("/categories", get(list_categories))
         .route("/categories/{category}", get(list_by_category))
-        .route("/tags", get(list_tags))
+        .route("/tags", get(list_tags).post(create_tag))
+        .route("/tags/{id}", put(update_tag).delete(delete_tag))
+        .route("/tags/{id}/merge", post(merge_tags))
+        .route("/tags/{id}/songs", get(list_tag_songs))
+        .route("/admin/missing-copyright", get(list_missing_copyright))
+        .route("/admin/key-mismatches", get(list_key_mismatches))
+        .route("/admin/duplicates", get(list_duplicates))
+        // Attachments
+        .route(
+            "/attachments/{id}",
+            get(download_attachment).delete(delete_attachment)
+        )
+        // Submissions
+        .route(
+            "/submissions",
+            get(list_my_submissions).post(create_submission)
+        )
+        .route("/submissions/queue", get(list_submission_queue))
+        .route(
+            "/submissions/{id}",
+            get(get_submission)
+                .put(update_submission)
+                .delete(delete_submission)
+        )
+        .route("/submissions/{id}/submit", post(submit_submission))
+        .route("/submissions/{id}/review", post(review_submission))
+        .route("/submissions/{id}/publish", post(publish_submission))
         .route("/{id}", get(get_song).put(update_song).delete(delete_song))
         .route("/{id}/transpose/{semitones}", get(get_song_transposed))
+        .route("/{id}/similar", get(list_similar))
+        .route("/{id}/merge", post(merge_song))
+        .route(
+            "/{id}/tags/{tag_id}",
+            put(add_song_tag).delete(remove_song_tag)
+        )
+        .route(
+            "/{id}/family",
+            get(get_song_family)
+                .put(link_song_family)
+                .delete(unlink_song_family)
+        )
+        .route(
+            "/{id}/scripture",
+            get(list_song_scripture).post(add_song_scripture)
+        )
+        .route("/{id}/scripture/suggestions", get(suggest_song_scripture))
+        .route(
+            "/{id}/scripture/{scripture_id}",
+            delete(remove_song_scripture)
+        )
+        .route(
+            "/{id}/attachments",
+            get(list_attachments).post(upload_attachment)
+        )
+        .route("/{id}/attachments/links", post(add_attachment_link))
+        .route("/{id}/export", get(export_song))
+        .route("/{id}/slides", get(get_song_slides))
         // Favorites
         .route("/favorites", get(list_favorites))
         .route(
@@ -61,14 +224,36 @@ pub fn routes() -> Router<AppState> {
         .route("/history", get(list_history))
         // Playlists
         .route("/playlists", get(list_playlists).post(create_playlist))
-        .route("/playlists/{id}", get(get_playlist).delete(delete_playlist))
+        .route(
+            "/playlists/{id}",
+            get(get_playlist)
+                .patch(update_playlist)
+                .delete(delete_playlist)
+        )
+        .route("/playlists/{id}/duplicate", post(duplicate_playlist))
+        .route("/playlists/{id}/slides", get(get_playlist_slides))
+        .route("/playlists/{id}/export", get(export_playlist))
+        .route(
+            "/playlists/{id}/passages",
+            get(list_playlist_passages).post(add_playlist_passage)
+        )
+        .route(
+            "/playlists/{id}/passages/{passage_id}",
+            delete(remove_playlist_passage)
+        )
+        .route(
+            "/playlists/{id}/share",
+            post(share_playlist).delete(unshare_playlist)
+        )
+        .route("/shared/{token}", get(get_shared_playlist))
         .route(
             "/playlists/{id}/songs",
             get(get_playlist_songs).post(add_to_playlist)
         )
+        .route("/playlists/{id}/songs/order", put(reorder_playlist))
         .route(
             "/playlists/{id}/songs/{item_id}",
-            delete(remove_from_playlist)
+            patch(update_playlist_item).delete(remove_from_playlist)
         )
 }
 
@@ -81,11 +266,14 @@ pub fn routes() -> Router<AppState> {
     tag = "Songs",
     path = "/api/songs/songbooks",
     responses(
-        (status = 200, description = "List of songbooks", body = Vec<Songbook>)
+        (status = 200, description = "Public songbooks and private songbooks of the user's churches", body = Vec<Songbook>)
     )
 )]
-async fn list_songbooks(State(state): State<AppState>) -> AppResult<Json<Vec<Songbook>>> {
-    let songbooks = state.songs.list_songbooks().await?;
+async fn list_songbooks(
+    State(state): State<AppState>,
+    claims: OptionalClaims
+) -> AppResult<Json<Vec<Songbook>>> {
+    let songbooks = state.songs.list_songbooks(claims.user_id()).await?;
     Ok(Json(songbooks))
 }
 
@@ -94,7 +282,7 @@ async fn list_songbooks(State(state): State<AppState>) -> AppResult<Json<Vec<Son
     tag = "Songs",
     path = "/api/songs/songbooks/{id}",
     params(
-        ("id" = Uuid, Path, description = "Songbook ID")
+        ("id" = String, Path, description = "Songbook ID or code")
     ),
     responses(
         (status = 200, description = "Songbook details", body = Songbook),
@@ -103,44 +291,216 @@ async fn list_songbooks(State(state): State<AppState>) -> AppResult<Json<Vec<Son
 )]
 async fn get_songbook(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>
+    Path(id_or_code): Path<String>,
+    claims: OptionalClaims
 ) -> AppResult<Json<Songbook>> {
-    let songbook = state.songs.get_songbook(id).await?;
+    let songbook = state
+        .songs
+        .get_visible_songbook(&id_or_code, claims.user_id())
+        .await?;
     Ok(Json(songbook))
 }
 
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/songbooks",
+    request_body = CreateSongbook,
+    responses(
+        (status = 200, description = "Created songbook", body = Songbook),
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "Not a song editor or church pastor/admin"),
+        (status = 409, description = "Songbook code is already taken")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn create_songbook(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(songbook): Json<CreateSongbook>
+) -> AppResult<Json<Songbook>> {
+    let created = state
+        .songs
+        .create_songbook(claims.user_id(), songbook)
+        .await?;
+    Ok(Json(created))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID")
+    ),
+    request_body = UpdateSongbook,
+    responses(
+        (status = 200, description = "Updated songbook", body = Songbook),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 404, description = "Songbook not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_songbook(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(songbook): Json<UpdateSongbook>
+) -> AppResult<Json<Songbook>> {
+    let updated = state
+        .songs
+        .update_songbook(claims.user_id(), id, songbook)
+        .await?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID")
+    ),
+    responses(
+        (status = 200, description = "Songbook deleted"),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 404, description = "Songbook not found"),
+        (status = 409, description = "Songbook still contains songs")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn delete_songbook(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_songbook(claims.user_id(), id).await?;
+    Ok(())
+}
+
 #[utoipa::path(
     get,
     tag = "Songs",
     path = "/api/songs/songbooks/{id}/editions",
     params(
-        ("id" = Uuid, Path, description = "Songbook ID")
+        ("id" = String, Path, description = "Songbook ID or code")
     ),
     responses(
-        (status = 200, description = "Songbook editions", body = Vec<SongbookEdition>)
+        (status = 200, description = "Songbook editions", body = Vec<SongbookEdition>),
+        (status = 404, description = "Songbook not found")
     )
 )]
 async fn get_songbook_editions(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>
+    Path(id_or_code): Path<String>,
+    claims: OptionalClaims
 ) -> AppResult<Json<Vec<SongbookEdition>>> {
-    let editions = state.songs.get_songbook_editions(id).await?;
+    let songbook = state
+        .songs
+        .get_visible_songbook(&id_or_code, claims.user_id())
+        .await?;
+    let editions = state.songs.get_songbook_editions(songbook.id).await?;
     Ok(Json(editions))
 }
 
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/editions",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID")
+    ),
+    request_body = CreateSongbookEdition,
+    responses(
+        (status = 200, description = "Created edition", body = SongbookEdition),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 409, description = "Edition with this name already exists")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn create_edition(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(edition): Json<CreateSongbookEdition>
+) -> AppResult<Json<SongbookEdition>> {
+    let created = state
+        .songs
+        .create_edition(claims.user_id(), id, edition)
+        .await?;
+    Ok(Json(created))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/editions/{edition_id}",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID"),
+        ("edition_id" = Uuid, Path, description = "Edition ID")
+    ),
+    request_body = UpdateSongbookEdition,
+    responses(
+        (status = 200, description = "Updated edition", body = SongbookEdition),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 404, description = "Edition not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_edition(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, edition_id)): Path<(Uuid, Uuid)>,
+    Json(edition): Json<UpdateSongbookEdition>
+) -> AppResult<Json<SongbookEdition>> {
+    let updated = state
+        .songs
+        .update_edition(claims.user_id(), id, edition_id, edition)
+        .await?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/editions/{edition_id}",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID"),
+        ("edition_id" = Uuid, Path, description = "Edition ID")
+    ),
+    responses(
+        (status = 200, description = "Edition deleted"),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 404, description = "Edition not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn delete_edition(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, edition_id)): Path<(Uuid, Uuid)>
+) -> AppResult<()> {
+    state
+        .songs
+        .delete_edition(claims.user_id(), id, edition_id)
+        .await?;
+    Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 struct SongListQuery {
     songbook_id: Option<Uuid>,
     category:    Option<SongCategory>,
     tag_id:      Option<Uuid>,
+    /// Comma-separated tag names or IDs; songs must carry all of them
+    tags:        Option<String>,
     key:         Option<String>,
     search:      Option<String>,
     #[serde(default)]
     limit:       Option<i64>,
     #[serde(default)]
     offset:      Option<i64>,
-    sort_by:     Option<SongSortBy>,
-    user_id:     Option<Uuid>
+    sort_by:     Option<SongOrder>
 }
 
 #[utoipa::path(
@@ -148,10 +508,9 @@ struct SongListQuery {
     tag = "Songs",
     path = "/api/songs/songbooks/{id}/songs",
     params(
-        ("id" = Uuid, Path, description = "Songbook ID"),
+        ("id" = String, Path, description = "Songbook ID or code"),
         ("limit" = Option<i64>, Query, description = "Limit results"),
-        ("offset" = Option<i64>, Query, description = "Offset for pagination"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID for favorites")
+        ("offset" = Option<i64>, Query, description = "Offset for pagination")
     ),
     responses(
         (status = 200, description = "Songs in songbook", body = Vec<SongSummary>)
@@ -159,21 +518,147 @@ struct SongListQuery {
 )]
 async fn list_songbook_songs(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
+    Path(id_or_code): Path<String>,
+    claims: OptionalClaims,
     Query(query): Query<SongListQuery>
 ) -> AppResult<Json<Vec<SongSummary>>> {
+    let user_id = claims.user_id();
+    let songbook = state
+        .songs
+        .get_visible_songbook(&id_or_code, user_id)
+        .await?;
+
     let filters = SongFilters {
-        songbook_id: Some(id),
+        songbook_id: Some(songbook.id),
         limit: query.limit,
         offset: query.offset,
-        sort_by: Some(SongSortBy::Number),
         ..Default::default()
     };
 
-    let songs = state.songs.list_songs(&filters, query.user_id).await?;
+    let songs = state
+        .songs
+        .list_songs(&filters, &[], SongSortBy::Number.into(), user_id)
+        .await?;
+    Ok(Json(songs))
+}
+
+#[derive(Debug, Deserialize)]
+struct NumberQuery {
+    edition: Option<String>
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/numbers/{number}",
+    params(
+        ("id" = String, Path, description = "Songbook ID or code"),
+        ("number" = i32, Path, description = "Song number"),
+        ("edition" = Option<String>, Query, description = "Edition ID or name (defaults to the current numbering)")
+    ),
+    responses(
+        (status = 200, description = "Song with this number", body = SongDetails),
+        (status = 404, description = "Songbook, edition or song not found")
+    )
+)]
+async fn get_song_by_number(
+    State(state): State<AppState>,
+    Path((id_or_code, number)): Path<(String, i32)>,
+    claims: OptionalClaims,
+    Query(query): Query<NumberQuery>
+) -> AppResult<Json<SongDetails>> {
+    let user_id = claims.user_id();
+    let songbook = state
+        .songs
+        .get_visible_songbook(&id_or_code, user_id)
+        .await?;
+
+    let song = state
+        .songs
+        .get_song_by_number(songbook.id, number, query.edition.as_deref(), user_id)
+        .await?;
+    Ok(Json(song))
+}
+
+#[derive(Debug, Deserialize)]
+struct NumberRangeQuery {
+    from:    i32,
+    to:      i32,
+    edition: Option<String>
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/numbers",
+    params(
+        ("id" = String, Path, description = "Songbook ID or code"),
+        ("from" = i32, Query, description = "First number"),
+        ("to" = i32, Query, description = "Last number (at most 200 songs per range)"),
+        ("edition" = Option<String>, Query, description = "Edition ID or name (defaults to the current numbering)")
+    ),
+    responses(
+        (status = 200, description = "Songs in the number range, numbered as in the edition", body = Vec<SongSummary>),
+        (status = 400, description = "Invalid range"),
+        (status = 404, description = "Songbook or edition not found")
+    )
+)]
+async fn list_songs_by_number(
+    State(state): State<AppState>,
+    Path(id_or_code): Path<String>,
+    claims: OptionalClaims,
+    Query(query): Query<NumberRangeQuery>
+) -> AppResult<Json<Vec<SongSummary>>> {
+    let user_id = claims.user_id();
+    let songbook = state
+        .songs
+        .get_visible_songbook(&id_or_code, user_id)
+        .await?;
+
+    let songs = state
+        .songs
+        .list_songs_by_number(
+            songbook.id,
+            query.from,
+            query.to,
+            query.edition.as_deref(),
+            user_id
+        )
+        .await?;
     Ok(Json(songs))
 }
 
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/songbooks/{id}/editions/{edition_id}/numbers",
+    params(
+        ("id" = Uuid, Path, description = "Songbook ID"),
+        ("edition_id" = Uuid, Path, description = "Edition ID")
+    ),
+    request_body = Vec<EditionNumber>,
+    responses(
+        (status = 200, description = "Edition numbering replaced"),
+        (status = 400, description = "Songs outside of the songbook"),
+        (status = 403, description = "Not allowed to manage this songbook"),
+        (status = 404, description = "Edition not found"),
+        (status = 409, description = "Duplicate number or song")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn set_edition_numbers(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, edition_id)): Path<(Uuid, Uuid)>,
+    Json(numbers): Json<Vec<EditionNumber>>
+) -> AppResult<()> {
+    state
+        .songs
+        .set_edition_numbers(claims.user_id(), id, edition_id, numbers)
+        .await?;
+    Ok(())
+}
+
 // ============================================================================
 // Songs
 // ============================================================================
@@ -186,12 +671,12 @@ async fn list_songbook_songs(
         ("songbook_id" = Option<Uuid>, Query, description = "Filter by songbook"),
         ("category" = Option<String>, Query, description = "Filter by category"),
         ("tag_id" = Option<Uuid>, Query, description = "Filter by tag"),
+        ("tags" = Option<String>, Query, description = "Comma-separated tag names or IDs; songs must carry all of them"),
         ("key" = Option<String>, Query, description = "Filter by key"),
         ("search" = Option<String>, Query, description = "Search text"),
         ("limit" = Option<i64>, Query, description = "Limit results"),
         ("offset" = Option<i64>, Query, description = "Offset"),
-        ("sort_by" = Option<String>, Query, description = "Sort by: title, number, created_at"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID for favorites")
+        ("sort_by" = Option<String>, Query, description = "Sort by: title, number, views_desc, favorites_desc, recently_added, has_chords_first, no_chords_first, popular_7d, popular_30d, trending, most_sung_month")
     ),
     responses(
         (status = 200, description = "List of songs", body = Vec<SongSummary>)
@@ -199,8 +684,14 @@ async fn list_songbook_songs(
 )]
 async fn list_songs(
     State(state): State<AppState>,
+    claims: OptionalClaims,
     Query(query): Query<SongListQuery>
 ) -> AppResult<Json<Vec<SongSummary>>> {
+    let user_id = claims.user_id();
+    let tag_ids = state
+        .songs
+        .resolve_tags(&tag_names(query.tags.as_deref()))
+        .await?;
     let filters = SongFilters {
         songbook_id: query.songbook_id,
         category:    query.category,
@@ -209,84 +700,253 @@ async fn list_songs(
         search:      query.search,
         limit:       query.limit,
         offset:      query.offset,
-        sort_by:     query.sort_by
+        sort_by:     None
     };
 
-    let songs = state.songs.list_songs(&filters, query.user_id).await?;
+    let songs = state
+        .songs
+        .list_songs(
+            &filters,
+            &tag_ids,
+            query.sort_by.unwrap_or_default(),
+            user_id
+        )
+        .await?;
     Ok(Json(songs))
 }
 
 #[derive(Debug, Deserialize)]
-struct SearchQuery {
-    q:       String,
-    #[serde(default = "default_limit")]
-    limit:   i64,
-    user_id: Option<Uuid>
+struct PopularQuery {
+    songbook_id: Option<Uuid>,
+    #[serde(default = "default_popular_limit")]
+    limit:       i64
 }
 
-fn default_limit() -> i64 {
-    50
+fn default_popular_limit() -> i64 {
+    20
 }
 
 #[utoipa::path(
     get,
     tag = "Songs",
-    path = "/api/songs/search",
+    path = "/api/songs/trending",
     params(
-        ("q" = String, Query, description = "Search query"),
-        ("limit" = Option<i64>, Query, description = "Max results (default 50)"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID for favorites")
+        ("songbook_id" = Option<Uuid>, Query, description = "Filter by songbook"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 20, at most 100)")
     ),
     responses(
-        (status = 200, description = "Search results", body = Vec<SongSearchResult>)
+        (status = 200, description = "Songs gaining views and services this week", body = Vec<PopularSong>)
     )
 )]
-async fn search_songs(
+async fn list_trending(
     State(state): State<AppState>,
-    Query(query): Query<SearchQuery>
-) -> AppResult<Json<Vec<SongSearchResult>>> {
-    let results = state
+    claims: OptionalClaims,
+    Query(query): Query<PopularQuery>
+) -> AppResult<Json<Vec<PopularSong>>> {
+    let songs = state
         .songs
-        .search_songs(&query.q, query.limit, query.user_id)
+        .list_popular(
+            PopularityOrder::Trending,
+            query.songbook_id,
+            query.limit,
+            claims.user_id()
+        )
         .await?;
-    Ok(Json(results))
-}
-
-#[derive(Debug, Deserialize)]
-struct UserQuery {
-    user_id: Option<Uuid>
+    Ok(Json(songs))
 }
 
 #[utoipa::path(
     get,
     tag = "Songs",
-    path = "/api/songs/{id}",
+    path = "/api/songs/most-sung",
     params(
-        ("id" = Uuid, Path, description = "Song ID"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID for favorites")
+        ("songbook_id" = Option<Uuid>, Query, description = "Filter by songbook"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 20, at most 100)")
     ),
     responses(
-        (status = 200, description = "Song details", body = Song),
-        (status = 404, description = "Song not found")
+        (status = 200, description = "Songs in the most setlists held this month", body = Vec<PopularSong>)
     )
 )]
-async fn get_song(
+async fn list_most_sung(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Query(query): Query<UserQuery>
-) -> AppResult<Json<Song>> {
-    let song = state.songs.get_song(id, query.user_id).await?;
-    Ok(Json(song))
-}
-
-#[utoipa::path(
+    claims: OptionalClaims,
+    Query(query): Query<PopularQuery>
+) -> AppResult<Json<Vec<PopularSong>>> {
+    let songs = state
+        .songs
+        .list_popular(
+            PopularityOrder::MostSungMonth,
+            query.songbook_id,
+            query.limit,
+            claims.user_id()
+        )
+        .await?;
+    Ok(Json(songs))
+}
+
+#[derive(Debug, Deserialize)]
+struct RecommendedQuery {
+    #[serde(default = "default_popular_limit")]
+    limit: i64
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/recommended",
+    params(
+        ("limit" = Option<i64>, Query, description = "Max results (default 20, at most 50)")
+    ),
+    responses(
+        (status = 200, description = "Songs like the user's favorites", body = Vec<SimilarSong>)
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_recommended(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<RecommendedQuery>
+) -> AppResult<Json<Vec<SimilarSong>>> {
+    let songs = state
+        .songs
+        .recommended_songs(claims.user_id(), query.limit)
+        .await?;
+    Ok(Json(songs))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q:            String,
+    #[serde(default = "default_limit")]
+    limit:        i64,
+    songbook_id:  Option<Uuid>,
+    category:     Option<SongCategory>,
+    tag_id:       Option<Uuid>,
+    tags:         Option<String>,
+    key:          Option<String>,
+    has_chords:   Option<bool>,
+    #[serde(default)]
+    all_versions: bool
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/search",
+    params(
+        ("q" = String, Query, description = "Search query; titles match with typos and in Latin transliteration"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50)"),
+        ("songbook_id" = Option<Uuid>, Query, description = "Filter by songbook"),
+        ("category" = Option<String>, Query, description = "Filter by category"),
+        ("tag_id" = Option<Uuid>, Query, description = "Filter by tag"),
+        ("tags" = Option<String>, Query, description = "Comma-separated tag names or IDs; songs must carry all of them"),
+        ("key" = Option<String>, Query, description = "Filter by key"),
+        ("has_chords" = Option<bool>, Query, description = "Only songs with (or without) chords"),
+        ("all_versions" = Option<bool>, Query, description = "List every version and translation instead of the best match of each song family")
+    ),
+    responses(
+        (status = 200, description = "Search results", body = Vec<SongSearchResult>)
+    )
+)]
+async fn search_songs(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Query(query): Query<SearchQuery>
+) -> AppResult<Json<Vec<SongSearchResult>>> {
+    let user_id = claims.user_id();
+    let mut tag_ids = state
+        .songs
+        .resolve_tags(&tag_names(query.tags.as_deref()))
+        .await?;
+    tag_ids.extend(query.tag_id);
+
+    let params = SongSearchParams {
+        query: query.q,
+        songbook_id: query.songbook_id,
+        category: query.category,
+        tag_ids,
+        key: query.key,
+        has_chords: query.has_chords,
+        limit: query.limit,
+        all_versions: query.all_versions
+    };
+
+    let results = state.songs.search_songs(&params, user_id).await?;
+    Ok(Json(results))
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestQuery {
+    q:           String,
+    #[serde(default = "default_suggest_limit")]
+    limit:       i64,
+    songbook_id: Option<Uuid>
+}
+
+fn default_suggest_limit() -> i64 {
+    10
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/suggest",
+    params(
+        ("q" = String, Query, description = "Typed prefix of a title or first line"),
+        ("limit" = Option<i64>, Query, description = "Max suggestions (default 10, max 20)"),
+        ("songbook_id" = Option<Uuid>, Query, description = "Filter by songbook")
+    ),
+    responses(
+        (status = 200, description = "Title suggestions", body = Vec<SongSuggestion>)
+    )
+)]
+async fn suggest_songs(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Query(query): Query<SuggestQuery>
+) -> AppResult<Json<Vec<SongSuggestion>>> {
+    let user_id = claims.user_id();
+    let suggestions = state
+        .songs
+        .suggest_songs(&query.q, query.songbook_id, query.limit, user_id)
+        .await?;
+    Ok(Json(suggestions))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Song ID")
+    ),
+    responses(
+        (status = 200, description = "Song details with its versions and translations", body = SongDetails),
+        (status = 404, description = "Song not found")
+    )
+)]
+async fn get_song(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims,
+    viewer: ViewerKey
+) -> AppResult<Json<SongDetails>> {
+    let user_id = claims.user_id();
+    let song = state.songs.get_song(id, user_id, viewer.as_deref()).await?;
+    Ok(Json(song))
+}
+
+#[utoipa::path(
     get,
     tag = "Songs",
     path = "/api/songs/{id}/transpose/{semitones}",
     params(
         ("id" = Uuid, Path, description = "Song ID"),
-        ("semitones" = i32, Path, description = "Semitones to transpose (-12 to 12)"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID")
+        ("semitones" = i32, Path, description = "Semitones to transpose (-12 to 12)")
     ),
     responses(
         (status = 200, description = "Transposed song", body = Song)
@@ -295,9 +955,11 @@ async fn get_song(
 async fn get_song_transposed(
     State(state): State<AppState>,
     Path((id, semitones)): Path<(Uuid, i32)>,
-    Query(query): Query<UserQuery>
+    claims: OptionalClaims
 ) -> AppResult<Json<Song>> {
-    let mut song = state.songs.get_song(id, query.user_id).await?;
+    // The song was already viewed in its own key; transposing is not a view
+    let user_id = claims.user_id();
+    let mut song = state.songs.load_song(id, user_id).await?;
 
     song.content = transpose_content(&song.content, semitones);
 
@@ -308,6 +970,131 @@ async fn get_song_transposed(
     Ok(Json(song))
 }
 
+#[derive(Debug, Deserialize)]
+struct SimilarQuery {
+    #[serde(default = "default_popular_limit")]
+    limit: i64
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/similar",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 20, at most 50)")
+    ),
+    responses(
+        (status = 200, description = "Songs like this one, best match first", body = Vec<SimilarSong>),
+        (status = 404, description = "Song not found")
+    )
+)]
+async fn list_similar(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims,
+    Query(query): Query<SimilarQuery>
+) -> AppResult<Json<Vec<SimilarSong>>> {
+    let user_id = claims.user_id();
+    let songs = state.songs.similar_songs(id, query.limit, user_id).await?;
+    Ok(Json(songs))
+}
+
+/// Song interchange format
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SongFormat {
+    #[default]
+    OpenLyrics
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    #[serde(default)]
+    format: SongFormat
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/export",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("format" = Option<String>, Query, description = "Export format: openlyrics (default)")
+    ),
+    responses(
+        (status = 200, description = "Song document", body = String, content_type = "application/xml"),
+        (status = 404, description = "Song not found")
+    )
+)]
+async fn export_song(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims,
+    Query(query): Query<ExportQuery>
+) -> AppResult<Response> {
+    state
+        .songs
+        .ensure_song_visible(id, claims.user_id())
+        .await?;
+    match query.format {
+        SongFormat::OpenLyrics => {
+            let xml = state.songs.export_openlyrics(id).await?;
+            let headers = [
+                (
+                    header::CONTENT_TYPE,
+                    "application/xml; charset=utf-8".to_string()
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{id}.xml\"")
+                )
+            ];
+            Ok((headers, xml).into_response())
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportQuery {
+    #[serde(default)]
+    format:      SongFormat,
+    songbook_id: Option<Uuid>
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/import",
+    params(
+        ("format" = Option<String>, Query, description = "Import format: openlyrics (default)"),
+        ("songbook_id" = Option<Uuid>, Query, description = "Target songbook (defaults to the songbook named in the document)")
+    ),
+    request_body(content = String, content_type = "application/xml", description = "OpenLyrics document"),
+    responses(
+        (status = 200, description = "Imported song", body = Song),
+        (status = 400, description = "Invalid document"),
+        (status = 403, description = "Not allowed to add songs to this songbook")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn import_song(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ImportQuery>,
+    body: String
+) -> AppResult<Json<Song>> {
+    let song = match query.format {
+        SongFormat::OpenLyrics => {
+            state
+                .songs
+                .import_openlyrics(claims.user_id(), &body, query.songbook_id)
+                .await?
+        }
+    };
+    Ok(Json(song))
+}
+
 #[utoipa::path(
     post,
     tag = "Songs",
@@ -315,15 +1102,17 @@ async fn get_song_transposed(
     request_body = CreateSong,
     responses(
         (status = 200, description = "Created song", body = Song),
-        (status = 400, description = "Validation error")
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "Not allowed to add songs to this songbook")
     ),
     security(("cookieAuth" = []))
 )]
 async fn create_song(
     State(state): State<AppState>,
+    claims: Claims,
     Json(song): Json<CreateSong>
 ) -> AppResult<Json<Song>> {
-    let created = state.songs.create_song(song).await?;
+    let created = state.songs.create_song(claims.user_id(), song).await?;
     Ok(Json(created))
 }
 
@@ -337,16 +1126,18 @@ async fn create_song(
     request_body = UpdateSong,
     responses(
         (status = 200, description = "Updated song", body = Song),
+        (status = 403, description = "Not allowed to edit songs of this songbook"),
         (status = 404, description = "Song not found")
     ),
     security(("cookieAuth" = []))
 )]
 async fn update_song(
     State(state): State<AppState>,
+    claims: Claims,
     Path(id): Path<Uuid>,
     Json(song): Json<UpdateSong>
 ) -> AppResult<Json<Song>> {
-    let updated = state.songs.update_song(id, song).await?;
+    let updated = state.songs.update_song(claims.user_id(), id, song).await?;
     Ok(Json(updated))
 }
 
@@ -359,12 +1150,17 @@ async fn update_song(
     ),
     responses(
         (status = 200, description = "Song deleted"),
+        (status = 403, description = "Not allowed to delete songs of this songbook"),
         (status = 404, description = "Song not found")
     ),
     security(("cookieAuth" = []))
 )]
-async fn delete_song(State(state): State<AppState>, Path(id): Path<Uuid>) -> AppResult<()> {
-    state.songs.delete_song(id).await?;
+async fn delete_song(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_song(claims.user_id(), id).await?;
     Ok(())
 }
 
@@ -407,8 +1203,7 @@ struct CategoryInfoFull {
 #[derive(Debug, Deserialize)]
 struct CategoryQuery {
     #[serde(default = "default_limit")]
-    limit:   i64,
-    user_id: Option<Uuid>
+    limit: i64
 }
 
 #[utoipa::path(
@@ -417,8 +1212,7 @@ struct CategoryQuery {
     path = "/api/songs/categories/{category}",
     params(
         ("category" = String, Path, description = "Category name"),
-        ("limit" = Option<i64>, Query, description = "Max results"),
-        ("user_id" = Option<Uuid>, Query, description = "User ID")
+        ("limit" = Option<i64>, Query, description = "Max results")
     ),
     responses(
         (status = 200, description = "Songs in category", body = Vec<SongSummary>)
@@ -427,84 +1221,1076 @@ struct CategoryQuery {
 async fn list_by_category(
     State(state): State<AppState>,
     Path(category): Path<SongCategory>,
+    claims: OptionalClaims,
     Query(query): Query<CategoryQuery>
 ) -> AppResult<Json<Vec<SongSummary>>> {
+    let user_id = claims.user_id();
     let songs = state
         .songs
-        .list_by_category(category, query.limit, query.user_id)
+        .list_by_category(category, query.limit, user_id)
         .await?;
     Ok(Json(songs))
 }
 
+#[derive(Debug, Deserialize)]
+struct MissingCopyrightQuery {
+    songbook_id: Option<Uuid>,
+    #[serde(default = "default_limit")]
+    limit:       i64,
+    #[serde(default)]
+    offset:      i64
+}
+
 #[utoipa::path(
     get,
     tag = "Songs",
-    path = "/api/songs/tags",
+    path = "/api/songs/admin/missing-copyright",
+    params(
+        ("songbook_id" = Option<Uuid>, Query, description = "Only songs of this songbook"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50)"),
+        ("offset" = Option<i64>, Query, description = "Results to skip")
+    ),
     responses(
-        (status = 200, description = "List of tags", body = Vec<SongTag>)
-    )
+        (status = 200, description = "Songs without copyright information", body = Vec<SongSummary>),
+        (status = 403, description = "Not a song editor")
+    ),
+    security(("cookieAuth" = []))
 )]
-async fn list_tags(State(state): State<AppState>) -> AppResult<Json<Vec<SongTag>>> {
-    let tags = state.songs.list_tags().await?;
-    Ok(Json(tags))
-}
-
-// ============================================================================
-// Favorites (require auth)
-// ============================================================================
-
-#[derive(Debug, Deserialize)]
-struct FavoritesQuery {
-    user_id: Uuid
-}
-
-async fn list_favorites(
+async fn list_missing_copyright(
     State(state): State<AppState>,
-    Query(query): Query<FavoritesQuery>
+    claims: Claims,
+    Query(query): Query<MissingCopyrightQuery>
 ) -> AppResult<Json<Vec<SongSummary>>> {
-    let songs = state.songs.list_favorites(query.user_id).await?;
+    let songs = state
+        .songs
+        .list_missing_copyright(
+            claims.user_id(),
+            query.songbook_id,
+            query.limit.clamp(1, 500),
+            query.offset.max(0)
+        )
+        .await?;
     Ok(Json(songs))
 }
 
 #[derive(Debug, Deserialize)]
-struct AddFavoriteQuery {
-    user_id: Uuid
-}
-
-async fn add_favorite(
-    State(state): State<AppState>,
-    Path(song_id): Path<Uuid>,
-    Query(query): Query<AddFavoriteQuery>
-) -> AppResult<()> {
-    state.songs.add_favorite(query.user_id, song_id).await?;
-    Ok(())
+struct KeyMismatchesQuery {
+    songbook_id: Option<Uuid>,
+    #[serde(default = "default_limit")]
+    limit:       i64,
+    #[serde(default)]
+    offset:      i64
 }
 
-async fn remove_favorite(
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/admin/key-mismatches",
+    params(
+        ("songbook_id" = Option<Uuid>, Query, description = "Only songs of this songbook"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50)"),
+        ("offset" = Option<i64>, Query, description = "Results to skip")
+    ),
+    responses(
+        (status = 200, description = "Songs whose stated key is invalid or disagrees with their chords", body = Vec<KeyMismatch>),
+        (status = 403, description = "Not a song editor")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_key_mismatches(
     State(state): State<AppState>,
-    Path(song_id): Path<Uuid>,
-    Query(query): Query<AddFavoriteQuery>
-) -> AppResult<()> {
-    state.songs.remove_favorite(query.user_id, song_id).await?;
-    Ok(())
+    claims: Claims,
+    Query(query): Query<KeyMismatchesQuery>
+) -> AppResult<Json<Vec<KeyMismatch>>> {
+    let mismatches = state
+        .songs
+        .list_key_mismatches(
+            claims.user_id(),
+            query.songbook_id,
+            query.limit.clamp(1, 500),
+            query.offset.max(0)
+        )
+        .await?;
+    Ok(Json(mismatches))
 }
 
-// ============================================================================
-// History
-// ============================================================================
-
 #[derive(Debug, Deserialize)]
-struct HistoryQuery {
-    user_id: Uuid,
-    #[serde(default = "default_history_limit")]
-    limit:   i64
+struct DuplicatesQuery {
+    songbook_id: Option<Uuid>,
+    #[serde(default = "default_duplicate_score")]
+    min_score:   f64,
+    #[serde(default = "default_limit")]
+    limit:       i64
 }
 
-fn default_history_limit() -> i64 {
-    20
+fn default_duplicate_score() -> f64 {
+    DEFAULT_DUPLICATE_SCORE
 }
 
-async fn list_history(
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/admin/duplicates",
+    params(
+        ("songbook_id" = Option<Uuid>, Query, description = "Only pairs with a song of this songbook"),
+        ("min_score" = Option<f64>, Query, description = "Lowest similarity from 0 to 1 (default 0.8)"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50)")
+    ),
+    responses(
+        (status = 200, description = "Likely duplicate songs, most similar first", body = Vec<DuplicateSongs>),
+        (status = 400, description = "Invalid min_score"),
+        (status = 403, description = "Not a song editor")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_duplicates(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<DuplicatesQuery>
+) -> AppResult<Json<Vec<DuplicateSongs>>> {
+    let duplicates = state
+        .songs
+        .list_duplicates(
+            claims.user_id(),
+            query.songbook_id,
+            query.min_score,
+            query.limit
+        )
+        .await?;
+    Ok(Json(duplicates))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/{id}/merge",
+    params(("id" = Uuid, Path, description = "Song to keep")),
+    request_body = MergeSong,
+    responses(
+        (status = 200, description = "Duplicate merged into the song and deleted", body = SongMerge),
+        (status = 400, description = "Song merged into itself or into a song of another songbook"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn merge_song(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(merge): Json<MergeSong>
+) -> AppResult<Json<SongMerge>> {
+    let merged = state
+        .songs
+        .merge_duplicate(claims.user_id(), id, merge)
+        .await?;
+    Ok(Json(merged))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/tags",
+    responses(
+        (status = 200, description = "List of tags", body = Vec<SongTag>)
+    )
+)]
+async fn list_tags(State(state): State<AppState>) -> AppResult<Json<Vec<SongTag>>> {
+    let tags = state.songs.list_tags().await?;
+    Ok(Json(tags))
+}
+
+/// Tag names or IDs from a comma-separated `tags` filter
+fn tag_names(tags: Option<&str>) -> Vec<String> {
+    tags.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/tags",
+    request_body = CreateSongTag,
+    responses(
+        (status = 200, description = "Created tag", body = SongTag),
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "Not a song editor"),
+        (status = 409, description = "Tag name is already taken")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn create_tag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(tag): Json<CreateSongTag>
+) -> AppResult<Json<SongTag>> {
+    let created = state.songs.create_tag(claims.user_id(), tag).await?;
+    Ok(Json(created))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/tags/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Tag ID")
+    ),
+    request_body = UpdateSongTag,
+    responses(
+        (status = 200, description = "Renamed tag", body = SongTag),
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Tag not found"),
+        (status = 409, description = "Tag name is already taken")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_tag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(tag): Json<UpdateSongTag>
+) -> AppResult<Json<SongTag>> {
+    let updated = state.songs.update_tag(claims.user_id(), id, tag).await?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/tags/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Tag ID")
+    ),
+    responses(
+        (status = 200, description = "Tag deleted and removed from its songs"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Tag not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn delete_tag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_tag(claims.user_id(), id).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/tags/{id}/merge",
+    params(
+        ("id" = Uuid, Path, description = "Tag to merge and delete")
+    ),
+    request_body = MergeSongTags,
+    responses(
+        (status = 200, description = "Remaining tag", body = SongTag),
+        (status = 400, description = "Tag merged into itself"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Tag not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn merge_tags(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(merge): Json<MergeSongTags>
+) -> AppResult<Json<SongTag>> {
+    let tag = state.songs.merge_tags(claims.user_id(), id, merge).await?;
+    Ok(Json(tag))
+}
+
+#[derive(Debug, Deserialize)]
+struct TagSongsQuery {
+    limit:   Option<i64>,
+    offset:  Option<i64>,
+    sort_by: Option<SongOrder>
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/tags/{id}/songs",
+    params(
+        ("id" = Uuid, Path, description = "Tag ID"),
+        ("limit" = Option<i64>, Query, description = "Limit results"),
+        ("offset" = Option<i64>, Query, description = "Offset"),
+        ("sort_by" = Option<String>, Query, description = "Sort order, as for /api/songs")
+    ),
+    responses(
+        (status = 200, description = "Songs with this tag", body = Vec<SongSummary>),
+        (status = 404, description = "Tag not found")
+    )
+)]
+async fn list_tag_songs(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims,
+    Query(query): Query<TagSongsQuery>
+) -> AppResult<Json<Vec<SongSummary>>> {
+    let user_id = claims.user_id();
+    let filters = SongFilters {
+        limit: query.limit,
+        offset: query.offset,
+        ..Default::default()
+    };
+
+    let songs = state
+        .songs
+        .list_tag_songs(id, &filters, query.sort_by.unwrap_or_default(), user_id)
+        .await?;
+    Ok(Json(songs))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/{id}/tags/{tag_id}",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("tag_id" = Uuid, Path, description = "Tag ID")
+    ),
+    responses(
+        (status = 200, description = "Tags of the song", body = Vec<SongTag>),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song or tag not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn add_song_tag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, tag_id)): Path<(Uuid, Uuid)>
+) -> AppResult<Json<Vec<SongTag>>> {
+    let tags = state
+        .songs
+        .add_song_tag(claims.user_id(), id, tag_id)
+        .await?;
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/{id}/tags/{tag_id}",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("tag_id" = Uuid, Path, description = "Tag ID")
+    ),
+    responses(
+        (status = 200, description = "Remaining tags of the song", body = Vec<SongTag>),
+        (status = 403, description = "Not a song editor")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn remove_song_tag(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, tag_id)): Path<(Uuid, Uuid)>
+) -> AppResult<Json<Vec<SongTag>>> {
+    let tags = state
+        .songs
+        .remove_song_tag(claims.user_id(), id, tag_id)
+        .await?;
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/family",
+    params(
+        ("id" = Uuid, Path, description = "Song ID")
+    ),
+    responses(
+        (status = 200, description = "Versions and translations of the song, the original first", body = SongFamily),
+        (status = 404, description = "Song is not in a family")
+    )
+)]
+async fn get_song_family(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims
+) -> AppResult<Json<SongFamily>> {
+    let user_id = claims.user_id();
+    let family = state.songs.get_song_family(id, user_id).await?;
+    Ok(Json(family))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/{id}/family",
+    params(("id" = Uuid, Path, description = "Song ID")),
+    request_body = LinkSongFamily,
+    responses(
+        (status = 200, description = "Family the song now belongs to", body = SongFamily),
+        (status = 400, description = "Invalid language or a song related to itself"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn link_song_family(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(link): Json<LinkSongFamily>
+) -> AppResult<Json<SongFamily>> {
+    let family = state
+        .songs
+        .link_song_family(claims.user_id(), id, link)
+        .await?;
+    Ok(Json(family))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/{id}/family",
+    params(("id" = Uuid, Path, description = "Song ID")),
+    responses(
+        (status = 200, description = "Song taken out of its family"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song is not in a family")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn unlink_song_family(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.unlink_song_family(claims.user_id(), id).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/scripture",
+    params(
+        ("id" = Uuid, Path, description = "Song ID")
+    ),
+    responses(
+        (status = 200, description = "Passages the song is based on, with verses", body = Vec<SongScripture>),
+        (status = 404, description = "Song not found")
+    )
+)]
+async fn list_song_scripture(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims
+) -> AppResult<Json<Vec<SongScripture>>> {
+    let user_id = claims.user_id();
+    let passages = state.songs.list_song_scripture(id, user_id).await?;
+    Ok(Json(passages))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/{id}/scripture",
+    params(("id" = Uuid, Path, description = "Song ID")),
+    request_body = AddSongScripture,
+    responses(
+        (status = 200, description = "Passages of the song", body = Vec<SongScripture>),
+        (status = 400, description = "Passage is not in the Bible"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found"),
+        (status = 409, description = "Passage is already linked")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn add_song_scripture(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(passage): Json<AddSongScripture>
+) -> AppResult<Json<Vec<SongScripture>>> {
+    let passages = state
+        .songs
+        .add_song_scripture(claims.user_id(), id, passage)
+        .await?;
+    Ok(Json(passages))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/{id}/scripture/{scripture_id}",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("scripture_id" = Uuid, Path, description = "Passage link ID")
+    ),
+    responses(
+        (status = 200, description = "Passage unlinked"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Passage not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn remove_song_scripture(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, scripture_id)): Path<(Uuid, Uuid)>
+) -> AppResult<()> {
+    state
+        .songs
+        .remove_song_scripture(claims.user_id(), id, scripture_id)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptureSuggestionQuery {
+    #[serde(default = "default_popular_limit")]
+    limit: i64
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/scripture/suggestions",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 20, at most 50)")
+    ),
+    responses(
+        (status = 200, description = "Verses whose wording the lyrics share, best match first", body = Vec<ScriptureSuggestion>),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn suggest_song_scripture(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ScriptureSuggestionQuery>
+) -> AppResult<Json<Vec<ScriptureSuggestion>>> {
+    let suggestions = state
+        .songs
+        .suggest_song_scripture(claims.user_id(), id, query.limit)
+        .await?;
+    Ok(Json(suggestions))
+}
+
+// ============================================================================
+// Attachments
+// ============================================================================
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/attachments",
+    params(("id" = Uuid, Path, description = "Song ID")),
+    responses(
+        (status = 200, description = "Recordings, sheet music, rehearsal tracks and links", body = Vec<SongAttachment>)
+    )
+)]
+async fn list_attachments(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims
+) -> AppResult<Json<Vec<SongAttachment>>> {
+    let attachments = state.songs.list_attachments(id, claims.user_id()).await?;
+    Ok(Json(attachments))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/{id}/attachments",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("kind" = AttachmentKind, Query, description = "recording, sheet_music or rehearsal_track"),
+        ("title" = String, Query, description = "Title shown to musicians"),
+        ("file_name" = Option<String>, Query, description = "Original file name")
+    ),
+    request_body(
+        content = Vec<u8>,
+        content_type = "application/octet-stream",
+        description = "The file, sent with its own Content-Type"
+    ),
+    responses(
+        (status = 200, description = "Stored attachment", body = SongAttachment),
+        (status = 400, description = "Media type not accepted, content not matching it, or file too large"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn upload_attachment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Query(upload): Query<UploadAttachment>,
+    headers: HeaderMap,
+    body: Body
+) -> AppResult<Json<SongAttachment>> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(essence)
+        .ok_or_else(|| AppError::validation("Content-Type of the file is required"))?;
+
+    let data = Box::pin(
+        body.into_data_stream()
+            .map(|chunk| chunk.map_err(io::Error::other))
+    );
+    let attachment = state
+        .songs
+        .upload_attachment(claims.user_id(), id, upload, &content_type, data)
+        .await?;
+    Ok(Json(attachment))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/{id}/attachments/links",
+    params(("id" = Uuid, Path, description = "Song ID")),
+    request_body = AddAttachmentLink,
+    responses(
+        (status = 200, description = "Link attachment", body = SongAttachment),
+        (status = 400, description = "Validation error"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Song not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn add_attachment_link(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(link): Json<AddAttachmentLink>
+) -> AppResult<Json<SongAttachment>> {
+    let attachment = state
+        .songs
+        .add_attachment_link(claims.user_id(), id, link)
+        .await?;
+    Ok(Json(attachment))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/attachments/{id}",
+    params(("id" = Uuid, Path, description = "Attachment ID")),
+    responses(
+        (status = 200, description = "Whole file", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "Byte range asked for with a Range header", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 307, description = "Redirect to the page of a link attachment"),
+        (status = 404, description = "Attachment not found"),
+        (status = 416, description = "Range outside the file")
+    )
+)]
+async fn download_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    claims: OptionalClaims,
+    headers: HeaderMap
+) -> AppResult<Response> {
+    let attachment = state.songs.get_attachment(id, claims.user_id()).await?;
+    let (Some(content_type), Some(size)) = (attachment.content_type, attachment.size_bytes) else {
+        return Ok(Redirect::temporary(&attachment.url).into_response());
+    };
+
+    let size = size as u64;
+    let range = RangeRequest::parse(
+        headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok()),
+        size
+    );
+    let file_name = attachment.file_name.unwrap_or(attachment.title);
+    let file_headers = [
+        (
+            header::CONTENT_DISPOSITION,
+            content_disposition(&content_type, &file_name)
+        ),
+        (header::CONTENT_TYPE, content_type),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        (header::ACCEPT_RANGES, "bytes".to_string())
+    ];
+
+    match range {
+        RangeRequest::Full => {
+            let data = state.songs.read_attachment(id, None).await?;
+            Ok((
+                file_headers,
+                [(header::CONTENT_LENGTH, size.to_string())],
+                Body::from_stream(data)
+            )
+                .into_response())
+        }
+        RangeRequest::Partial(range) => {
+            let data = state.songs.read_attachment(id, Some(range)).await?;
+            Ok((
+                StatusCode::PARTIAL_CONTENT,
+                file_headers,
+                [
+                    (header::CONTENT_LENGTH, range.size().to_string()),
+                    (
+                        header::CONTENT_RANGE,
+                        format!("bytes {}-{}/{size}", range.start, range.end)
+                    )
+                ],
+                Body::from_stream(data)
+            )
+                .into_response())
+        }
+        RangeRequest::Unsatisfiable => Ok((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{size}"))]
+        )
+            .into_response())
+    }
+}
+
+/// `Content-Disposition` of a stored file, with an ASCII fallback for
+/// clients without RFC 6266 `filename*` support.
+///
+/// Audio, images and PDFs are shown in the browser; anything else, such as
+/// MusicXML, is downloaded so the browser never renders it as a page.
+fn content_disposition(content_type: &str, file_name: &str) -> String {
+    let disposition = match content_type {
+        "application/pdf" | "image/png" | "image/jpeg" => "inline",
+        _ if content_type.starts_with("audio/") => "inline",
+        _ => "attachment"
+    };
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_'
+        })
+        .collect();
+    let encoded: String = file_name
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}")
+        })
+        .collect();
+    format!("{disposition}; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/attachments/{id}",
+    params(("id" = Uuid, Path, description = "Attachment ID")),
+    responses(
+        (status = 200, description = "Attachment and its file deleted"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Attachment not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn delete_attachment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_attachment(claims.user_id(), id).await?;
+    Ok(())
+}
+
+// ============================================================================
+// Submissions (require auth)
+// ============================================================================
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/submissions",
+    responses(
+        (status = 200, description = "Songs the user suggested, newest first", body = Vec<SongSubmission>)
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_my_submissions(
+    State(state): State<AppState>,
+    claims: Claims
+) -> AppResult<Json<Vec<SongSubmission>>> {
+    let submissions = state.songs.list_my_submissions(claims.user_id()).await?;
+    Ok(Json(submissions))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/submissions",
+    request_body = CreateSongSubmission,
+    responses(
+        (status = 200, description = "Draft submission", body = SongSubmission),
+        (status = 400, description = "Validation error")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn create_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(submission): Json<CreateSongSubmission>
+) -> AppResult<Json<SongSubmission>> {
+    let created = state
+        .songs
+        .create_submission(claims.user_id(), submission)
+        .await?;
+    Ok(Json(created))
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmissionQueueQuery {
+    status: Option<SubmissionStatus>,
+    #[serde(default = "default_limit")]
+    limit:  i64,
+    #[serde(default)]
+    offset: i64
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/submissions/queue",
+    params(
+        ("status" = Option<SubmissionStatus>, Query, description = "Submission status (default pending)"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50)"),
+        ("offset" = Option<i64>, Query, description = "Results to skip")
+    ),
+    responses(
+        (status = 200, description = "Submissions awaiting editors, the longest waiting first", body = Vec<SongSubmission>),
+        (status = 403, description = "Not a song editor")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_submission_queue(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<SubmissionQueueQuery>
+) -> AppResult<Json<Vec<SongSubmission>>> {
+    let submissions = state
+        .songs
+        .list_submission_queue(claims.user_id(), query.status, query.limit, query.offset)
+        .await?;
+    Ok(Json(submissions))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    responses(
+        (status = 200, description = "Submission", body = SongSubmission),
+        (status = 403, description = "Neither the submitter nor a song editor"),
+        (status = 404, description = "Submission not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn get_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<Json<SongSubmission>> {
+    let submission = state.songs.get_submission(claims.user_id(), id).await?;
+    Ok(Json(submission))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    request_body = UpdateSongSubmission,
+    responses(
+        (status = 200, description = "Updated draft", body = SongSubmission),
+        (status = 403, description = "Not the submitter"),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is under review or published")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(changes): Json<UpdateSongSubmission>
+) -> AppResult<Json<SongSubmission>> {
+    let submission = state
+        .songs
+        .update_submission(claims.user_id(), id, changes)
+        .await?;
+    Ok(Json(submission))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    responses(
+        (status = 200, description = "Submission deleted"),
+        (status = 403, description = "Not the submitter"),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is under review or published")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn delete_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_submission(claims.user_id(), id).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}/submit",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    responses(
+        (status = 200, description = "Submission awaiting review", body = SongSubmission),
+        (status = 403, description = "Not the submitter"),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is not a draft")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn submit_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<Json<SongSubmission>> {
+    let submission = state.songs.submit_submission(claims.user_id(), id).await?;
+    Ok(Json(submission))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}/review",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    request_body = ReviewSongSubmission,
+    responses(
+        (status = 200, description = "Approved or rejected submission; the submitter is notified", body = SongSubmission),
+        (status = 400, description = "Rejection without a comment"),
+        (status = 403, description = "Not a song editor"),
+        (status = 404, description = "Submission not found"),
+        (status = 409, description = "Submission is not pending")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn review_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(review): Json<ReviewSongSubmission>
+) -> AppResult<Json<SongSubmission>> {
+    let submission = state
+        .songs
+        .review_submission(claims.user_id(), id, review)
+        .await?;
+    Ok(Json(submission))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/submissions/{id}/publish",
+    params(("id" = Uuid, Path, description = "Submission ID")),
+    request_body = PublishSongSubmission,
+    responses(
+        (status = 200, description = "Published submission with its catalog song; the submitter is notified", body = SongSubmission),
+        (status = 403, description = "Not allowed to review submissions or manage the songbook"),
+        (status = 404, description = "Submission or songbook not found"),
+        (status = 409, description = "Submission is not approved, or the number is taken")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn publish_submission(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(publish): Json<PublishSongSubmission>
+) -> AppResult<Json<SongSubmission>> {
+    let submission = state
+        .songs
+        .publish_submission(claims.user_id(), id, publish)
+        .await?;
+    Ok(Json(submission))
+}
+
+// ============================================================================
+// Favorites (require auth)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct FavoritesQuery {
+    user_id: Uuid
+}
+
+async fn list_favorites(
+    State(state): State<AppState>,
+    Query(query): Query<FavoritesQuery>
+) -> AppResult<Json<Vec<SongSummary>>> {
+    let songs = state.songs.list_favorites(query.user_id).await?;
+    Ok(Json(songs))
+}
+
+#[derive(Debug, Deserialize)]
+struct AddFavoriteQuery {
+    user_id: Uuid
+}
+
+async fn add_favorite(
+    State(state): State<AppState>,
+    Path(song_id): Path<Uuid>,
+    Query(query): Query<AddFavoriteQuery>
+) -> AppResult<()> {
+    state.songs.add_favorite(query.user_id, song_id).await?;
+    Ok(())
+}
+
+async fn remove_favorite(
+    State(state): State<AppState>,
+    Path(song_id): Path<Uuid>,
+    Query(query): Query<AddFavoriteQuery>
+) -> AppResult<()> {
+    state.songs.remove_favorite(query.user_id, song_id).await?;
+    Ok(())
+}
+
+// ============================================================================
+// History
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    user_id: Uuid,
+    #[serde(default = "default_history_limit")]
+    limit:   i64
+}
+
+fn default_history_limit() -> i64 {
+    20
+}
+
+async fn list_history(
     State(state): State<AppState>,
     Query(query): Query<HistoryQuery>
 ) -> AppResult<Json<Vec<SongHistoryEntry>>> {
@@ -516,71 +2302,482 @@ async fn list_history(
 // Playlists
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
-struct PlaylistQuery {
-    user_id: Uuid
-}
-
 async fn list_playlists(
     State(state): State<AppState>,
-    Query(query): Query<PlaylistQuery>
+    claims: Claims
 ) -> AppResult<Json<Vec<SongPlaylist>>> {
-    let playlists = state.songs.list_playlists(query.user_id).await?;
+    let playlists = state.songs.list_playlists(claims.user_id()).await?;
     Ok(Json(playlists))
 }
 
 async fn create_playlist(
     State(state): State<AppState>,
-    Query(query): Query<PlaylistQuery>,
+    claims: Claims,
     Json(playlist): Json<CreatePlaylist>
 ) -> AppResult<Json<SongPlaylist>> {
-    let created = state.songs.create_playlist(query.user_id, playlist).await?;
+    let created = state
+        .songs
+        .create_playlist(claims.user_id(), playlist)
+        .await?;
     Ok(Json(created))
 }
 
 async fn get_playlist(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Query(query): Query<PlaylistQuery>
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>
 ) -> AppResult<Json<SongPlaylist>> {
-    let playlist = state.songs.get_playlist(id, query.user_id).await?;
+    let playlist = state.songs.get_playlist(id, claims.user_id()).await?;
     Ok(Json(playlist))
 }
 
 async fn get_playlist_songs(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Query(query): Query<PlaylistQuery>
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>
 ) -> AppResult<Json<Vec<PlaylistItem>>> {
-    let items = state.songs.get_playlist_items(id, query.user_id).await?;
+    let items = state.songs.get_playlist_items(id, claims.user_id()).await?;
     Ok(Json(items))
 }
 
 async fn add_to_playlist(
     State(state): State<AppState>,
+    claims: Claims,
     Path(id): Path<Uuid>,
     Json(item): Json<AddToPlaylist>
 ) -> AppResult<()> {
-    state.songs.add_to_playlist(id, item).await?;
+    state
+        .songs
+        .add_to_playlist(claims.user_id(), id, item)
+        .await?;
     Ok(())
 }
 
 async fn remove_from_playlist(
     State(state): State<AppState>,
+    claims: Claims,
     Path((playlist_id, item_id)): Path<(Uuid, Uuid)>
 ) -> AppResult<()> {
     state
         .songs
-        .remove_from_playlist(playlist_id, item_id)
+        .remove_from_playlist(claims.user_id(), playlist_id, item_id)
         .await?;
     Ok(())
 }
 
 async fn delete_playlist(
     State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.delete_playlist(id, claims.user_id()).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    patch,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    request_body = UpdatePlaylist,
+    responses(
+        (status = 200, description = "Updated playlist", body = SongPlaylist),
+        (status = 403, description = "Not the playlist owner"),
+        (status = 404, description = "Playlist not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_playlist(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(playlist): Json<UpdatePlaylist>
+) -> AppResult<Json<SongPlaylist>> {
+    let updated = state
+        .songs
+        .update_playlist(claims.user_id(), id, playlist)
+        .await?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    patch,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/songs/{item_id}",
+    params(
+        ("id" = Uuid, Path, description = "Playlist ID"),
+        ("item_id" = Uuid, Path, description = "Playlist item ID")
+    ),
+    request_body = UpdatePlaylistItem,
+    responses(
+        (status = 200, description = "Playlist items in their new order", body = Vec<PlaylistItem>),
+        (status = 403, description = "Not the playlist owner"),
+        (status = 404, description = "Playlist or item not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn update_playlist_item(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, item_id)): Path<(Uuid, Uuid)>,
+    Json(item): Json<UpdatePlaylistItem>
+) -> AppResult<Json<Vec<PlaylistItem>>> {
+    let items = state
+        .songs
+        .update_playlist_item(claims.user_id(), id, item_id, item)
+        .await?;
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/songs/order",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    request_body = ReorderPlaylist,
+    responses(
+        (status = 200, description = "Playlist items in their new order", body = Vec<PlaylistItem>),
+        (status = 400, description = "Item ids do not match the playlist"),
+        (status = 403, description = "Not the playlist owner")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn reorder_playlist(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(order): Json<ReorderPlaylist>
+) -> AppResult<Json<Vec<PlaylistItem>>> {
+    let items = state
+        .songs
+        .reorder_playlist(claims.user_id(), id, &order.item_ids)
+        .await?;
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/duplicate",
+    params(("id" = Uuid, Path, description = "Template playlist ID")),
+    request_body = DuplicatePlaylist,
+    responses(
+        (status = 200, description = "New playlist with the template's songs", body = SongPlaylist),
+        (status = 404, description = "Template not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn duplicate_playlist(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(copy): Json<DuplicatePlaylist>
+) -> AppResult<Json<SongPlaylist>> {
+    let playlist = state
+        .songs
+        .duplicate_playlist(claims.user_id(), id, copy)
+        .await?;
+    Ok(Json(playlist))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/share",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Read-only share link", body = PlaylistShareLink),
+        (status = 403, description = "Not allowed to edit the playlist")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn share_playlist(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<Json<PlaylistShareLink>> {
+    let link = state.songs.share_playlist(claims.user_id(), id).await?;
+    Ok(Json(link))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/share",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Share link revoked"),
+        (status = 403, description = "Not allowed to edit the playlist")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn unshare_playlist(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.songs.unshare_playlist(claims.user_id(), id).await?;
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/shared/{token}",
+    params(("token" = String, Path, description = "Share token")),
+    responses(
+        (status = 200, description = "Shared playlist with its songs", body = SharedPlaylist),
+        (status = 404, description = "Link not found or revoked")
+    )
+)]
+async fn get_shared_playlist(
+    State(state): State<AppState>,
+    Path(token): Path<String>
+) -> AppResult<Json<SharedPlaylist>> {
+    let shared = state.songs.get_shared_playlist(&token).await?;
+    Ok(Json(shared))
+}
+
+/// Setlist export format
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SetlistFormat {
+    #[default]
+    Pdf,
+    ChordPro,
+    Txt,
+    /// OpenLP service file
+    Osz
+}
+
+#[derive(Debug, Deserialize)]
+struct SetlistExportQuery {
+    #[serde(default)]
+    format: SetlistFormat
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/export",
+    params(
+        ("id" = Uuid, Path, description = "Playlist ID"),
+        ("format" = Option<String>, Query, description = "Export format: pdf (default), chordpro, txt or osz (OpenLP service)")
+    ),
+    responses(
+        (status = 200, description = "Chord sheets with per-item transposition and notes", body = Vec<u8>, content_type = "application/pdf"),
+        (status = 404, description = "Playlist not found or not visible")
+    )
+)]
+async fn export_playlist(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SetlistExportQuery>
+) -> AppResult<Response> {
+    let viewer = claims.user_id();
+
+    let (body, content_type, extension) = match query.format {
+        SetlistFormat::Pdf => (
+            state.songs.export_setlist_pdf(id, viewer).await?,
+            "application/pdf",
+            "pdf"
+        ),
+        SetlistFormat::ChordPro => {
+            let setlist = state.songs.get_setlist(id, viewer).await?;
+            (
+                setlist_to_chordpro(&setlist).into_bytes(),
+                "application/vnd.chordpro; charset=utf-8",
+                "chordpro"
+            )
+        }
+        SetlistFormat::Txt => {
+            let setlist = state.songs.get_setlist(id, viewer).await?;
+            (
+                setlist_to_text(&setlist).into_bytes(),
+                "text/plain; charset=utf-8",
+                "txt"
+            )
+        }
+        SetlistFormat::Osz => (
+            state.songs.export_setlist_osz(id, viewer).await?,
+            "application/zip",
+            "osz"
+        )
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"setlist-{id}.{extension}\"")
+        )
+    ];
+    Ok((headers, body).into_response())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/passages",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    responses(
+        (status = 200, description = "Bible passages read during the service", body = Vec<PlaylistPassage>),
+        (status = 404, description = "Playlist not found or not visible")
+    )
+)]
+async fn list_playlist_passages(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>
+) -> AppResult<Json<Vec<PlaylistPassage>>> {
+    let passages = state
+        .songs
+        .list_playlist_passages(id, claims.user_id())
+        .await?;
+    Ok(Json(passages))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/passages",
+    params(("id" = Uuid, Path, description = "Playlist ID")),
+    request_body = AddPlaylistPassage,
+    responses(
+        (status = 200, description = "Passages of the playlist", body = Vec<PlaylistPassage>),
+        (status = 400, description = "Unknown verses or item not in the playlist"),
+        (status = 403, description = "Not allowed to edit the playlist")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn add_playlist_passage(
+    State(state): State<AppState>,
+    claims: Claims,
     Path(id): Path<Uuid>,
-    Query(query): Query<PlaylistQuery>
+    Json(passage): Json<AddPlaylistPassage>
+) -> AppResult<Json<Vec<PlaylistPassage>>> {
+    let passages = state
+        .songs
+        .add_playlist_passage(claims.user_id(), id, passage)
+        .await?;
+    Ok(Json(passages))
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/passages/{passage_id}",
+    params(
+        ("id" = Uuid, Path, description = "Playlist ID"),
+        ("passage_id" = Uuid, Path, description = "Passage ID")
+    ),
+    responses(
+        (status = 200, description = "Passage removed"),
+        (status = 403, description = "Not allowed to edit the playlist"),
+        (status = 404, description = "Passage not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn remove_playlist_passage(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, passage_id)): Path<(Uuid, Uuid)>
 ) -> AppResult<()> {
-    state.songs.delete_playlist(id, query.user_id).await?;
+    state
+        .songs
+        .remove_playlist_passage(claims.user_id(), id, passage_id)
+        .await?;
     Ok(())
 }
+
+// ============================================================================
+// Slides
+// ============================================================================
+
+/// Most lines a single slide may hold
+const MAX_SLIDE_LINES: usize = 20;
+
+#[derive(Debug, Deserialize)]
+struct SlideQuery {
+    max_lines: Option<usize>,
+    order:     Option<String>,
+    #[serde(default)]
+    credits:   CreditsPosition
+}
+
+impl SlideQuery {
+    fn options(&self) -> AppResult<SlideOptions> {
+        let max_lines = self.max_lines.unwrap_or(DEFAULT_SLIDE_LINES);
+        if !(1..=MAX_SLIDE_LINES).contains(&max_lines) {
+            return Err(AppError::validation(format!(
+                "max_lines must be between 1 and {MAX_SLIDE_LINES}"
+            )));
+        }
+
+        Ok(SlideOptions {
+            max_lines,
+            verse_order: self.order.clone(),
+            credits: self.credits
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/{id}/slides",
+    params(
+        ("id" = Uuid, Path, description = "Song ID"),
+        ("max_lines" = Option<usize>, Query, description = "Lines per slide (1-20, default 4)"),
+        ("order" = Option<String>, Query, description = "Verse order, e.g. `V1 C V2 C B C`"),
+        ("credits" = Option<CreditsPosition>, Query, description = "Slide with title and copyright: first (default), last or none")
+    ),
+    responses(
+        (status = 200, description = "Projection slides without chords", body = Vec<Slide>),
+        (status = 400, description = "Invalid line count or unknown section in verse order"),
+        (status = 404, description = "Song not found")
+    )
+)]
+async fn get_song_slides(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SlideQuery>
+) -> AppResult<Json<Vec<Slide>>> {
+    let options = query.options()?;
+    let slides = state
+        .songs
+        .song_slides(id, &options, claims.user_id())
+        .await?;
+    Ok(Json(slides))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Songs",
+    path = "/api/songs/playlists/{id}/slides",
+    params(
+        ("id" = Uuid, Path, description = "Playlist ID"),
+        ("max_lines" = Option<usize>, Query, description = "Lines per slide (1-20, default 4)"),
+        ("credits" = Option<CreditsPosition>, Query, description = "Slide with title and copyright: first (default), last or none")
+    ),
+    responses(
+        (status = 200, description = "Slides of every song in playlist order", body = Vec<PlaylistSlides>),
+        (status = 400, description = "Invalid line count"),
+        (status = 404, description = "Playlist not found or not visible")
+    )
+)]
+async fn get_playlist_slides(
+    State(state): State<AppState>,
+    claims: OptionalClaims,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SlideQuery>
+) -> AppResult<Json<Vec<PlaylistSlides>>> {
+    let options = query.options()?;
+    let slides = state
+        .songs
+        .playlist_slides(id, &options, claims.user_id())
+        .await?;
+    Ok(Json(slides))
+}
@@ -1,18 +1,29 @@
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{Path, Query, State},
     routing::{get, post, put}
 };
 use masterror::prelude::*;
+use revelation_server::UserNotification;
 use revelation_user::{
     BindEmail, BindPhone, BindTelegram, Claims, RUser, RUserPublic, UpdateProfileRequest
 };
+use serde::Deserialize;
 use utoipa::OpenApi;
+use uuid::Uuid;
 
 use crate::state::AppState;
 
 #[derive(OpenApi)]
-#[openapi(paths(get_me, update_profile, bind_telegram, bind_email, bind_phone))]
+#[openapi(paths(
+    get_me,
+    update_profile,
+    bind_telegram,
+    bind_email,
+    bind_phone,
+    list_notifications,
+    mark_notification_read
+))]
 pub struct UsersApiDoc;
 
 pub fn routes() -> Router<AppState> {
@@ -22,6 +33,8 @@ pub fn routes() -> Router<AppState> {
         .route("/me/bind/telegram", post(bind_telegram))
         .route("/me/bind/email", post(bind_email))
         .route("/me/bind/phone", post(bind_phone))
+        .route("/me/notifications", get(list_notifications))
+        .route("/me/notifications/{id}/read", post(mark_notification_read))
 }
 
 #[utoipa::path(
@@ -236,3 +249,62 @@ async fn bind_phone(
 
     Ok(Json(user.into()))
 }
+
+#[derive(Debug, Deserialize)]
+struct NotificationsQuery {
+    #[serde(default)]
+    unread_only: bool,
+    #[serde(default = "default_notifications_limit")]
+    limit:       i64
+}
+
+fn default_notifications_limit() -> i64 {
+    50
+}
+
+#[utoipa::path(
+    get,
+    tag = "Users",
+    path = "/api/users/me/notifications",
+    params(
+        ("unread_only" = Option<bool>, Query, description = "Only unread notifications"),
+        ("limit" = Option<i64>, Query, description = "Max results (default 50, max 100)")
+    ),
+    responses(
+        (status = 200, description = "Notifications, newest first", body = Vec<UserNotification>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn list_notifications(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<NotificationsQuery>
+) -> AppResult<Json<Vec<UserNotification>>> {
+    let notifications = state
+        .notifications
+        .list_notifications(claims.user_id(), query.unread_only, query.limit)
+        .await?;
+    Ok(Json(notifications))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Users",
+    path = "/api/users/me/notifications/{id}/read",
+    params(("id" = Uuid, Path, description = "Notification ID")),
+    responses(
+        (status = 200, description = "Notification marked as read"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Notification not found")
+    ),
+    security(("cookieAuth" = []))
+)]
+async fn mark_notification_read(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>
+) -> AppResult<()> {
+    state.notifications.mark_read(claims.user_id(), id).await?;
+    Ok(())
+}
@@ -4,9 +4,14 @@
 
 pub mod adapters;
 pub mod domain;
+pub mod formats;
 pub mod loader;
 pub mod services;
+pub mod songbook_loader;
+pub mod strongs_loader;
 
 pub use domain::*;
 pub use loader::{BibleLoader, LoadStats};
-pub use services::{BibleService, NotificationService, SongbookService};
+pub use services::{BibleService, LiveHub, NotificationService, SongbookService};
+pub use songbook_loader::{ImportStats, SongbookLoader};
+pub use strongs_loader::{StrongsLoader, StrongsStats};
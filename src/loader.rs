@@ -6,6 +6,8 @@ use masterror::prelude::*;
 use serde::Deserialize;
 use sqlx::PgPool;
 
+use crate::adapters::postgres::PgBibleIndex;
+
 /// Book abbreviation mapping to database IDs
 const BOOK_MAPPING: &[(&str, i16)] = &[
     // Old Testament
@@ -148,8 +150,8 @@ impl BibleLoader {
 
         // Build word index for search
         tracing::info!("Building word index...");
-        self.build_word_index().await?;
-        tracing::info!("Word index built");
+        let index = PgBibleIndex::new(self.pool.clone()).rebuild(false).await?;
+        tracing::info!("{}", index);
 
         // Update chapters count in bible_books
         self.update_chapters_count().await?;
@@ -198,26 +200,6 @@ impl BibleLoader {
         Ok(count)
     }
 
-    async fn build_word_index(&self) -> AppResult<()> {
-        sqlx::query!(
-            r#"
-            INSERT INTO bible_word_index (word, verse_id, position)
-            SELECT
-                LOWER(REGEXP_REPLACE(word, '[^а-яА-ЯёЁa-zA-Z0-9]', '', 'g')) as word,
-                v.id as verse_id,
-                ROW_NUMBER() OVER (PARTITION BY v.id ORDER BY ordinality)::smallint as position
-            FROM bible_verses v,
-                 LATERAL REGEXP_SPLIT_TO_TABLE(v.text, '\s+') WITH ORDINALITY AS t(word, ordinality)
-            WHERE LENGTH(REGEXP_REPLACE(word, '[^а-яА-ЯёЁa-zA-Z0-9]', '', 'g')) >= 3
-            ON CONFLICT DO NOTHING
-            "#
-        )
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
     async fn update_chapters_count(&self) -> AppResult<()> {
         sqlx::query!(
             r#"
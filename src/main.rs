@@ -1,7 +1,8 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 use axum::Router;
 use masterror::prelude::*;
+use revelation_server::adapters::storage::LocalAttachmentStorage;
 use sqlx::postgres::PgPoolOptions;
 use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -11,6 +12,7 @@ mod handlers;
 mod middleware;
 mod state;
 
+use middleware::ViewerKeys;
 use state::AppState;
 
 #[tokio::main]
@@ -31,7 +33,13 @@ async fn main() -> AppResult<()> {
         .connect(&database_url)
         .await?;
 
-    let state = AppState::new(pool);
+    let state = AppState::new(
+        pool,
+        ViewerKeys::from_env()?,
+        Arc::new(LocalAttachmentStorage::from_env()?)
+    );
+    tokio::spawn(state.live.clone().listen());
+    tokio::spawn(state.songs.clone().refresh_popularity_periodically());
 
     let app = Router::new()
         .route("/health", axum::routing::get(|| async { "ok" }))
@@ -49,7 +57,11 @@ async fn main() -> AppResult<()> {
     tracing::info!("Swagger UI: http://localhost:3000/swagger-ui");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>()
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,31 @@
+//! Authentication extractors.
+
+use std::convert::Infallible;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use revelation_user::Claims;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Claims of the request if it is authenticated.
+///
+/// Public endpoints use it to include data only the signed-in user may see.
+pub struct OptionalClaims(pub Option<Claims>);
+
+impl OptionalClaims {
+    pub fn user_id(&self) -> Option<Uuid> {
+        self.0.as_ref().map(Claims::user_id)
+    }
+}
+
+impl FromRequestParts<AppState> for OptionalClaims {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(Claims::from_request_parts(parts, state).await.ok()))
+    }
+}
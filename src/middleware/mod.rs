@@ -1,4 +1,9 @@
+mod auth;
+mod viewer;
+
+pub use auth::*;
+pub use viewer::*;
+
 // Middleware will be added here
-// - Auth middleware (extract user from UUID header/cookie)
 // - Rate limiting
 // - Request logging
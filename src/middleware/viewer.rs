@@ -0,0 +1,231 @@
+//! Viewer identity for counting views.
+
+use std::{
+    convert::Infallible,
+    fmt::Write as _,
+    net::{IpAddr, SocketAddr},
+    sync::Arc
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, header, request::Parts}
+};
+use hmac::{Hmac, Mac};
+use masterror::prelude::*;
+use revelation_user::Claims;
+use sha2::Sha256;
+
+use crate::state::AppState;
+
+/// Environment variable with the secret keying anonymous viewer hashes
+pub const VIEWER_SECRET_ENV: &str = "VIEWER_KEY_SECRET";
+
+/// Environment variable with the number of reverse proxies in front of the
+/// server; `X-Forwarded-For` is ignored when unset
+pub const TRUSTED_PROXIES_ENV: &str = "TRUSTED_PROXIES";
+
+/// Shortest accepted viewer secret, in bytes
+const MIN_SECRET_LEN: usize = 32;
+
+/// How anonymous viewers are told apart.
+///
+/// The address comes from the `X-Forwarded-For` entry appended by the
+/// outermost trusted proxy, or from the connection itself; entries a client
+/// sends are never trusted. Address and user agent are hashed with a server
+/// secret, so keys cannot be reversed or forged.
+#[derive(Clone)]
+pub struct ViewerKeys {
+    secret:          Arc<[u8]>,
+    trusted_proxies: usize
+}
+
+impl ViewerKeys {
+    pub fn new(secret: &[u8], trusted_proxies: usize) -> Self {
+        Self {
+            secret: secret.into(),
+            trusted_proxies
+        }
+    }
+
+    /// Read `VIEWER_KEY_SECRET` and `TRUSTED_PROXIES`
+    pub fn from_env() -> AppResult<Self> {
+        let secret = std::env::var(VIEWER_SECRET_ENV)
+            .map_err(|_| AppError::internal(format!("{VIEWER_SECRET_ENV} must be set")))?;
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(AppError::internal(format!(
+                "{VIEWER_SECRET_ENV} must be at least {MIN_SECRET_LEN} bytes"
+            )));
+        }
+
+        let trusted_proxies = match std::env::var(TRUSTED_PROXIES_ENV) {
+            Ok(value) => value.parse().map_err(|_| {
+                AppError::internal(format!("{TRUSTED_PROXIES_ENV} must be a number"))
+            })?,
+            Err(_) => 0
+        };
+
+        Ok(Self::new(secret.as_bytes(), trusted_proxies))
+    }
+
+    /// Client address: the `X-Forwarded-For` entry added by the outermost
+    /// trusted proxy, else the peer address
+    fn client_address(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        if self.trusted_proxies == 0 {
+            return peer;
+        }
+
+        // Each proxy appends the address it was connected from, so with
+        // `n` proxies the client is the n-th entry from the right
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+
+        forwarded
+            .len()
+            .checked_sub(self.trusted_proxies)
+            .and_then(|index| forwarded[index].parse().ok())
+            .or(peer)
+    }
+
+    /// Keyed hash of an anonymous client
+    fn anonymous_key(&self, address: IpAddr, agent: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(address.to_string().as_bytes());
+        mac.update(&[0]);
+        mac.update(agent.as_bytes());
+
+        let digest = mac.finalize().into_bytes();
+        let mut key = String::from("anon:");
+        for byte in &digest[..16] {
+            let _ = write!(key, "{byte:02x}");
+        }
+        key
+    }
+}
+
+/// Key under which a view is counted once per day.
+///
+/// Signed-in users are keyed by their ID; anonymous clients by a keyed hash
+/// of their address and user agent, so raw addresses are never stored.
+/// `None` when the client cannot be identified at all.
+pub struct ViewerKey(pub Option<String>);
+
+impl ViewerKey {
+    pub fn as_deref(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl FromRequestParts<AppState> for ViewerKey {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState
+    ) -> Result<Self, Self::Rejection> {
+        if let Ok(claims) = Claims::from_request_parts(parts, state).await {
+            return Ok(Self(Some(claims.user_id().to_string())));
+        }
+
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let Some(address) = state.viewer_keys.client_address(&parts.headers, peer) else {
+            return Ok(Self(None));
+        };
+
+        let agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        Ok(Self(Some(state.viewer_keys.anonymous_key(address, agent))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    fn forwarded(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn ignores_forwarded_for_without_proxies() {
+        let keys = ViewerKeys::new(SECRET, 0);
+        let headers = forwarded(&["203.0.113.7"]);
+
+        assert_eq!(
+            keys.client_address(&headers, Some(ip("10.0.0.1"))),
+            Some(ip("10.0.0.1"))
+        );
+    }
+
+    #[test]
+    fn takes_entry_added_by_trusted_proxy() {
+        let headers = forwarded(&["198.51.100.1, 203.0.113.7", "10.0.0.2"]);
+
+        assert_eq!(
+            ViewerKeys::new(SECRET, 1).client_address(&headers, None),
+            Some(ip("10.0.0.2"))
+        );
+        assert_eq!(
+            ViewerKeys::new(SECRET, 2).client_address(&headers, None),
+            Some(ip("203.0.113.7"))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_peer_on_short_or_invalid_header() {
+        let keys = ViewerKeys::new(SECRET, 2);
+        let peer = Some(ip("10.0.0.1"));
+
+        assert_eq!(
+            keys.client_address(&forwarded(&["203.0.113.7"]), peer),
+            peer
+        );
+        assert_eq!(
+            keys.client_address(&forwarded(&["junk, 10.0.0.2"]), peer),
+            peer
+        );
+        assert_eq!(keys.client_address(&HeaderMap::new(), peer), peer);
+    }
+
+    #[test]
+    fn keys_depend_on_secret_address_and_agent() {
+        let keys = ViewerKeys::new(SECRET, 0);
+        let key = keys.anonymous_key(ip("203.0.113.7"), "Firefox");
+
+        assert!(key.starts_with("anon:"));
+        assert_eq!(key.len(), "anon:".len() + 32);
+        assert_eq!(key, keys.anonymous_key(ip("203.0.113.7"), "Firefox"));
+        assert_ne!(key, keys.anonymous_key(ip("203.0.113.8"), "Firefox"));
+        assert_ne!(key, keys.anonymous_key(ip("203.0.113.7"), "Chrome"));
+        assert_ne!(
+            key,
+            ViewerKeys::new(b"another secret of enough length!", 0)
+                .anonymous_key(ip("203.0.113.7"), "Firefox")
+        );
+    }
+}
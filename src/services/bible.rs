@@ -1,10 +1,18 @@
-use masterror::AppResult;
+use masterror::prelude::*;
 use revelation_bible::{
     Book, ChapterInfo, DailyReading, Pericope, SearchResult, Testament, Verse
 };
 use sqlx::PgPool;
 
-use crate::adapters::postgres::{PgBibleRepository, PgBibleSearch, PgReadingPlan};
+use crate::{
+    adapters::postgres::{
+        IndexStats, PgBibleIndex, PgBibleRepository, PgBibleSearch, PgReadingPlan, PgStrongs
+    },
+    domain::{
+        BibleSearchPage, BibleSearchParams, ChapterVerse, MAX_BIBLE_SEARCH_LIMIT, StrongsDetails,
+        StrongsNumber, Symphony, TaggedWord, index_word, word_offsets
+    }
+};
 
 /// Bible service combining all bible-related adapters
 #[derive(Clone)]
@@ -38,6 +46,45 @@ impl BibleService {
             .await
     }
 
+    /// Verses of a chapter with the Strong's tags of their words
+    pub async fn get_chapter_tagged(
+        &self,
+        book_id: i16,
+        chapter: i16
+    ) -> AppResult<Vec<ChapterVerse>> {
+        let verses = self.get_chapter(book_id, chapter).await?;
+        let tags = PgStrongs::new(self.pool.clone())
+            .chapter_tags(book_id, chapter)
+            .await?;
+
+        Ok(verses
+            .into_iter()
+            .map(|verse| {
+                let words = tags
+                    .iter()
+                    .filter(|tag| tag.verse == verse.verse)
+                    .map(|tag| {
+                        let offsets = word_offsets(&verse.text, &[tag.position]);
+                        TaggedWord {
+                            position:        tag.position,
+                            word:            tag.word.clone(),
+                            start:           offsets.first().map(|(start, _)| *start),
+                            end:             offsets.first().map(|(_, end)| *end),
+                            strongs:         tag.strongs.clone(),
+                            lemma:           tag.lemma.clone(),
+                            transliteration: tag.transliteration.clone(),
+                            morphology:      tag.morphology.clone()
+                        }
+                    })
+                    .collect();
+                ChapterVerse {
+                    verse,
+                    words: Some(words)
+                }
+            })
+            .collect())
+    }
+
     pub async fn get_verse(
         &self,
         book_id: i16,
@@ -64,6 +111,7 @@ impl BibleService {
             .await
     }
 
+    /// Verses matching a full-text query, best ranked first
     pub async fn search(&self, query: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
         use revelation_bible::ports::BibleSearch;
         PgBibleSearch::new(self.pool.clone())
@@ -71,18 +119,79 @@ impl BibleService {
             .await
     }
 
-    pub async fn symphony(&self, word: &str, limit: i64) -> AppResult<Vec<SearchResult>> {
-        use revelation_bible::ports::BibleSearch;
+    /// Page of verses matching a full-text or positional query, with hit
+    /// counts
+    pub async fn search_page(&self, params: &BibleSearchParams) -> AppResult<BibleSearchPage> {
+        params.validate()?;
         PgBibleSearch::new(self.pool.clone())
-            .symphony(word, limit)
+            .search_page(params)
             .await
     }
 
+    /// Every form of a word across the Bible with its frequency per book
+    /// and the first `limit` verses it occurs in
+    pub async fn symphony(&self, word: &str, limit: i64) -> AppResult<Symphony> {
+        if index_word(word).is_empty() {
+            return Err(AppError::validation("Word must contain letters or digits"));
+        }
+        PgBibleSearch::new(self.pool.clone())
+            .word_symphony(word, limit.clamp(1, MAX_BIBLE_SEARCH_LIMIT))
+            .await
+    }
+
+    /// Occurrences of any form of a word
     pub async fn word_count(&self, word: &str) -> AppResult<i64> {
         use revelation_bible::ports::BibleSearch;
         PgBibleSearch::new(self.pool.clone()).word_count(word).await
     }
 
+    /// Index verses added or changed since the last rebuild; `full`
+    /// reindexes every verse
+    pub async fn rebuild_word_index(&self, full: bool) -> AppResult<IndexStats> {
+        PgBibleIndex::new(self.pool.clone()).rebuild(full).await
+    }
+
+    /// Load dictionary forms of words as `(word, lemma)` pairs and apply them
+    /// to the word index, returning the number of index rows changed
+    pub async fn load_lemmas(&self, pairs: &[(String, String)]) -> AppResult<u64> {
+        let index = PgBibleIndex::new(self.pool.clone());
+        index.load_lemmas(pairs).await?;
+        index.refresh_lemmas().await
+    }
+
+    /// Lexicon entry of a Strong's number with a page of the verses where
+    /// words are tagged with it
+    pub async fn get_strongs(
+        &self,
+        number: &str,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<StrongsDetails> {
+        let number: StrongsNumber = number.parse()?;
+        if !(1..=MAX_BIBLE_SEARCH_LIMIT).contains(&limit) || offset < 0 {
+            return Err(AppError::validation(format!(
+                "limit must be 1-{MAX_BIBLE_SEARCH_LIMIT} and offset not negative"
+            )));
+        }
+
+        let strongs = PgStrongs::new(self.pool.clone());
+        let entry = strongs
+            .get_entry(&number)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("Strong's number {number} not found")))?;
+        let (total, verse_count) = strongs.count_occurrences(&number).await?;
+
+        Ok(StrongsDetails {
+            entry,
+            total,
+            verse_count,
+            renderings: strongs.renderings(&number).await?,
+            limit,
+            offset,
+            occurrences: strongs.occurrences(&number, limit, offset).await?
+        })
+    }
+
     pub async fn get_today(&self) -> AppResult<Option<DailyReading>> {
         use revelation_bible::ports::ReadingPlan;
         PgReadingPlan::new(self.pool.clone()).get_today().await
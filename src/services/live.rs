@@ -0,0 +1,271 @@
+//! Live worship presentation hub.
+//!
+//! The leader's commands are stored in `live_sessions`; a trigger turns
+//! every change into `NOTIFY worship_live`. Each server replica listens on
+//! that channel and pushes the rebuilt slide to the followers connected to
+//! it, so leader and followers may be served by different replicas.
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll},
+    time::Duration
+};
+
+use masterror::prelude::*;
+use revelation_songbook::{transpose_content, transpose_key};
+use serde::Deserialize;
+use sqlx::{PgPool, postgres::PgListener};
+use tokio::sync::broadcast;
+use tokio_stream::{
+    Stream,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError}
+};
+use uuid::Uuid;
+
+use crate::{
+    adapters::postgres::PgLiveSessions,
+    domain::{LiveCommand, LiveEvent, LiveSession, LiveSlide},
+    formats::{ChordProDocument, Line}
+};
+
+/// Postgres channel carrying live session changes
+const LIVE_CHANNEL: &str = "worship_live";
+
+/// Events buffered per playlist for slow followers
+const EVENT_BUFFER: usize = 16;
+
+/// Pause before listening again after the listener connection failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Payload of a `worship_live` notification
+#[derive(Deserialize)]
+struct LiveNotification {
+    playlist_id: Uuid,
+    ended:       bool
+}
+
+/// In-process fan-out of live slides to followers
+#[derive(Clone)]
+pub struct LiveHub {
+    pool:     PgPool,
+    channels: Arc<Mutex<HashMap<Uuid, broadcast::Sender<LiveEvent>>>>
+}
+
+impl LiveHub {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            channels: Arc::default()
+        }
+    }
+
+    /// Follow the live session of a playlist
+    pub fn subscribe(&self, playlist_id: Uuid) -> LiveSubscription {
+        let receiver = self
+            .channels()
+            .entry(playlist_id)
+            .or_insert_with(|| broadcast::channel(EVENT_BUFFER).0)
+            .subscribe();
+
+        LiveSubscription {
+            hub: self.clone(),
+            playlist_id,
+            events: Some(BroadcastStream::new(receiver))
+        }
+    }
+
+    /// Slide currently shown, if the playlist is being presented
+    pub async fn current_slide(&self, playlist_id: Uuid) -> AppResult<Option<LiveSlide>> {
+        let session = PgLiveSessions::new(self.pool.clone())
+            .get_session(playlist_id)
+            .await?;
+        Ok(session.as_ref().map(build_slide))
+    }
+
+    /// Apply a leader command and return the resulting slide.
+    ///
+    /// Followers, including those on other replicas, receive the slide
+    /// through the `worship_live` notification.
+    pub async fn apply(
+        &self,
+        playlist_id: Uuid,
+        leader_id: Uuid,
+        command: &LiveCommand
+    ) -> AppResult<LiveSlide> {
+        command.validate()?;
+        let sessions = PgLiveSessions::new(self.pool.clone());
+
+        let switch_to = if let Some(item_id) = command.item_id {
+            let item = sessions
+                .find_item(playlist_id, item_id)
+                .await?
+                .ok_or_else(|| AppError::not_found("Playlist item not found"))?;
+            Some(item)
+        } else if let Some(song) = command.song_id {
+            let item_transpose = sessions
+                .find_song_item(playlist_id, song)
+                .await?
+                .ok_or_else(|| AppError::validation("Song is not part of the playlist"))?;
+            Some((song, item_transpose))
+        } else {
+            None
+        };
+
+        sessions
+            .update_session(playlist_id, leader_id, |state| {
+                command.apply_to(state, switch_to)
+            })
+            .await?;
+
+        let session = sessions
+            .get_session(playlist_id)
+            .await?
+            .ok_or_else(|| AppError::internal("Live session disappeared"))?;
+        Ok(build_slide(&session))
+    }
+
+    /// End the live session; followers receive `ended`
+    pub async fn end(&self, playlist_id: Uuid) -> AppResult<()> {
+        PgLiveSessions::new(self.pool.clone())
+            .end_session(playlist_id)
+            .await
+    }
+
+    /// Forward `worship_live` notifications to local followers for the
+    /// lifetime of the server
+    pub async fn listen(self) {
+        loop {
+            if let Err(error) = self.forward_notifications().await {
+                tracing::warn!("Live session listener failed: {error}");
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn forward_notifications(&self) -> AppResult<()> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(LIVE_CHANNEL).await?;
+
+        loop {
+            let notification = listener.recv().await?;
+            let Ok(change) = serde_json::from_str::<LiveNotification>(notification.payload())
+            else {
+                continue;
+            };
+
+            if !self.channels().contains_key(&change.playlist_id) {
+                continue;
+            }
+
+            let event = if change.ended {
+                LiveEvent::Ended {
+                    playlist_id: change.playlist_id
+                }
+            } else {
+                match self.current_slide(change.playlist_id).await? {
+                    Some(slide) => LiveEvent::Slide(slide),
+                    None => continue
+                }
+            };
+
+            self.publish(change.playlist_id, event);
+        }
+    }
+
+    fn publish(&self, playlist_id: Uuid, event: LiveEvent) {
+        let mut channels = self.channels();
+        let delivered = channels
+            .get(&playlist_id)
+            .is_some_and(|sender| sender.send(event).is_ok());
+
+        // Sending fails once the last follower is gone
+        if !delivered {
+            channels.remove(&playlist_id);
+        }
+    }
+
+    /// Drop the channel of a playlist nobody follows any more
+    fn prune(&self, playlist_id: Uuid) {
+        let mut channels = self.channels();
+        if channels
+            .get(&playlist_id)
+            .is_some_and(|sender| sender.receiver_count() == 0)
+        {
+            channels.remove(&playlist_id);
+        }
+    }
+
+    fn channels(&self) -> MutexGuard<'_, HashMap<Uuid, broadcast::Sender<LiveEvent>>> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Events of a live session for one follower. Lagging followers get an
+/// error item and continue with the newest events; the playlist channel
+/// is dropped with its last subscription.
+pub struct LiveSubscription {
+    hub:         LiveHub,
+    playlist_id: Uuid,
+    events:      Option<BroadcastStream<LiveEvent>>
+}
+
+impl Stream for LiveSubscription {
+    type Item = Result<LiveEvent, BroadcastStreamRecvError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.events.as_mut() {
+            Some(events) => Pin::new(events).poll_next(cx),
+            None => Poll::Ready(None)
+        }
+    }
+}
+
+impl Drop for LiveSubscription {
+    fn drop(&mut self) {
+        // Release the receiver first so the hub sees the follower gone
+        self.events.take();
+        self.hub.prune(self.playlist_id);
+    }
+}
+
+/// Slide for the current section of the session's song
+fn build_slide(session: &LiveSession) -> LiveSlide {
+    let document = ChordProDocument::parse(session.content.as_deref().unwrap_or_default());
+    let sections = document.section_names();
+    let index = usize::try_from(session.section).unwrap_or_default();
+    let semitones = i32::from(session.transpose);
+
+    let (text, chords) = match document.sections.get(index) {
+        Some(section) if !session.blank => {
+            let chordpro = section
+                .lines
+                .iter()
+                .map(Line::to_chordpro)
+                .collect::<Vec<_>>()
+                .join("\n");
+            (section.text(), transpose_content(&chordpro, semitones))
+        }
+        _ => (String::new(), String::new())
+    };
+
+    LiveSlide {
+        playlist_id: session.playlist_id,
+        leader_id: session.leader_id,
+        song_id: session.song_id,
+        title: session.title.clone(),
+        section: session.section,
+        section_name: sections.get(index).cloned(),
+        sections,
+        text,
+        chords,
+        key: session
+            .original_key
+            .as_deref()
+            .map(|key| transpose_key(key, semitones, false)),
+        transpose: session.transpose,
+        blank: session.blank,
+        updated_at: session.updated_at
+    }
+}
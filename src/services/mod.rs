@@ -1,7 +1,9 @@
 mod bible;
+mod live;
 mod notification;
 mod songbook;
 
 pub use bible::*;
+pub use live::*;
 pub use notification::*;
 pub use songbook::*;
@@ -1,8 +1,12 @@
 use masterror::AppResult;
 use revelation_user::TelegramRecipient;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::adapters::postgres::PgNotificationRepository;
+use crate::{
+    adapters::postgres::PgNotificationRepository,
+    domain::{NewNotification, UserNotification}
+};
 
 /// Notification service for managing notification delivery
 #[derive(Clone)]
@@ -24,4 +28,29 @@ impl NotificationService {
             .get_telegram_recipients()
             .await
     }
+
+    /// Notify a user in the app. The notification is only stored for the
+    /// in-app list; this server does not deliver it anywhere else
+    pub async fn notify(&self, notification: &NewNotification) -> AppResult<Uuid> {
+        PgNotificationRepository::new(self.pool.clone())
+            .create_notification(notification)
+            .await
+    }
+
+    pub async fn list_notifications(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        limit: i64
+    ) -> AppResult<Vec<UserNotification>> {
+        PgNotificationRepository::new(self.pool.clone())
+            .list_notifications(user_id, unread_only, limit.clamp(1, 100))
+            .await
+    }
+
+    pub async fn mark_read(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        PgNotificationRepository::new(self.pool.clone())
+            .mark_read(user_id, id)
+            .await
+    }
 }
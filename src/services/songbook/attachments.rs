@@ -0,0 +1,189 @@
+//! Sheet music, audio and other song attachments.
+
+use std::io;
+
+use bytes::{Bytes, BytesMut};
+use masterror::prelude::*;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::{
+        postgres::{PgSongAttachments, PgSongRead},
+        storage::{ByteStream, StoredFile}
+    },
+    domain::{
+        AddAttachmentLink, ByteRange, SIGNATURE_LENGTH, SongAttachment, UploadAttachment,
+        signature_matches
+    }
+};
+
+impl SongbookService {
+    /// Attachments of a song the viewer may see
+    pub async fn list_attachments(
+        &self,
+        song_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<SongAttachment>> {
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(song_id, viewer)
+            .await?;
+        PgSongAttachments::new(self.pool.clone())
+            .list_attachments(song_id)
+            .await
+    }
+
+    /// Attachment of a song the viewer may see
+    pub async fn get_attachment(
+        &self,
+        id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<SongAttachment> {
+        let attachment = PgSongAttachments::new(self.pool.clone())
+            .get_attachment(id)
+            .await?;
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(attachment.song_id, viewer)
+            .await?;
+        Ok(attachment)
+    }
+
+    /// Store an uploaded file and attach it to a song.
+    ///
+    /// The file is streamed to the storage and refused once it outgrows the
+    /// limit of its kind, or when its first bytes do not match
+    /// `content_type`.
+    pub async fn upload_attachment(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        upload: UploadAttachment,
+        content_type: &str,
+        mut data: ByteStream
+    ) -> AppResult<SongAttachment> {
+        upload.validate(content_type)?;
+        self.ensure_song_editor(user_id, "manage song attachments")
+            .await?;
+        PgSongRead::new(self.pool.clone())
+            .load_song(song_id, None)
+            .await?;
+
+        let mut head = BytesMut::new();
+        while head.len() < SIGNATURE_LENGTH {
+            match data.next().await {
+                Some(chunk) => head.extend_from_slice(&chunk?),
+                None => break
+            }
+        }
+        if !signature_matches(content_type, &head) {
+            return Err(AppError::validation(format!(
+                "File content does not look like {content_type}"
+            )));
+        }
+
+        let max_size = upload.kind.max_size();
+        let mut received = 0u64;
+        let limited: ByteStream = Box::pin(tokio_stream::once(Ok(head.freeze())).chain(data).map(
+            move |chunk: io::Result<Bytes>| {
+                let chunk = chunk?;
+                received += chunk.len() as u64;
+                if received > max_size {
+                    return Err(io::Error::from(io::ErrorKind::FileTooLarge));
+                }
+                Ok(chunk)
+            }
+        ));
+
+        let id = Uuid::now_v7();
+        let key = format!("songs/{song_id}/{id}");
+        let size = match self.storage.put(&key, limited).await {
+            Ok(size) => size,
+            Err(error) if error.kind() == io::ErrorKind::FileTooLarge => {
+                return Err(AppError::validation(format!(
+                    "File is larger than {} MiB",
+                    max_size / (1024 * 1024)
+                )));
+            }
+            Err(error) => {
+                return Err(AppError::internal(format!(
+                    "Cannot store attachment: {error}"
+                )));
+            }
+        };
+
+        let file = StoredFile {
+            key,
+            content_type: content_type.to_string(),
+            size
+        };
+        let attachments = PgSongAttachments::new(self.pool.clone());
+        if let Err(error) = attachments
+            .create_file(id, song_id, &upload, &file, user_id)
+            .await
+        {
+            self.delete_stored_file(&file.key).await;
+            return Err(error);
+        }
+        attachments.get_attachment(id).await
+    }
+
+    /// Attach a link to a page about the song, e.g. a video
+    pub async fn add_attachment_link(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        link: AddAttachmentLink
+    ) -> AppResult<SongAttachment> {
+        link.validate()?;
+        self.ensure_song_editor(user_id, "manage song attachments")
+            .await?;
+        PgSongRead::new(self.pool.clone())
+            .load_song(song_id, None)
+            .await?;
+
+        let attachments = PgSongAttachments::new(self.pool.clone());
+        let id = attachments.create_link(song_id, &link, user_id).await?;
+        attachments.get_attachment(id).await
+    }
+
+    /// Contents of a file attachment, or the inclusive `range` of it
+    pub async fn read_attachment(
+        &self,
+        id: Uuid,
+        range: Option<ByteRange>
+    ) -> AppResult<ByteStream> {
+        let key = PgSongAttachments::new(self.pool.clone())
+            .storage_key(id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Link attachments have no file"))?;
+
+        self.storage
+            .get(&key, range)
+            .await
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => AppError::not_found("Attachment file is missing"),
+                _ => AppError::internal(format!("Cannot read attachment: {error}"))
+            })
+    }
+
+    pub async fn delete_attachment(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        self.ensure_song_editor(user_id, "manage song attachments")
+            .await?;
+        let key = PgSongAttachments::new(self.pool.clone())
+            .delete_attachment(id)
+            .await?;
+        if let Some(key) = key {
+            self.delete_stored_file(&key).await;
+        }
+        Ok(())
+    }
+
+    /// The metadata is already gone, so a file that cannot be removed is
+    /// only logged
+    pub(super) async fn delete_stored_file(&self, key: &str) {
+        if let Err(error) = self.storage.delete(key).await {
+            tracing::warn!("Attachment file {key} was not deleted: {error}");
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Church setlist calendar, song usage and worship team.
+
+use chrono::{Duration, NaiveDate, Utc};
+use masterror::prelude::*;
+use revelation_songbook::SongPlaylist;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgPlaylistRepository, PgSongAccess, PgSongReports, PgWorshipTeam},
+    domain::{SongUsageReport, WorshipTeamMember}
+};
+
+/// Default span of the church setlist calendar
+const DEFAULT_SETLIST_DAYS: i64 = 30;
+
+/// Longest span of the church setlist calendar
+const MAX_SETLIST_DAYS: i64 = 366;
+
+/// Default span of church song usage reports, ending today
+const DEFAULT_USAGE_DAYS: i64 = 90;
+
+impl SongbookService {
+    /// Church setlists by event date; defaults to the next
+    /// `DEFAULT_SETLIST_DAYS` days
+    pub async fn list_church_setlists(
+        &self,
+        church_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<SongPlaylist>> {
+        let from = from.unwrap_or_else(|| Utc::now().date_naive());
+        let to = to.unwrap_or(from + Duration::days(DEFAULT_SETLIST_DAYS));
+
+        if to < from || (to - from).num_days() > MAX_SETLIST_DAYS {
+            return Err(AppError::validation(format!(
+                "Date range must be ascending and span at most {MAX_SETLIST_DAYS} days"
+            )));
+        }
+
+        PgPlaylistRepository::new(self.pool.clone())
+            .list_church_setlists(church_id, from, to, viewer)
+            .await
+    }
+
+    /// Song usage of a church over a date range for licensing reports;
+    /// defaults to the last `DEFAULT_USAGE_DAYS` days
+    pub async fn church_song_usage(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>
+    ) -> AppResult<SongUsageReport> {
+        let allowed = PgSongAccess::new(self.pool.clone())
+            .can_edit_church_setlists(user_id, church_id)
+            .await?;
+        if !allowed {
+            return Err(AppError::forbidden(
+                "Only the worship team can view song usage"
+            ));
+        }
+
+        let to = to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = match from {
+            Some(from) => from,
+            None => to
+                .checked_sub_signed(Duration::days(DEFAULT_USAGE_DAYS))
+                .ok_or_else(|| AppError::validation("Date range starts before the calendar"))?
+        };
+        if to < from || (to - from).num_days() > MAX_SETLIST_DAYS {
+            return Err(AppError::validation(format!(
+                "Date range must be ascending and span at most {MAX_SETLIST_DAYS} days"
+            )));
+        }
+
+        let songs = PgSongReports::new(self.pool.clone())
+            .song_usage(church_id, from, to)
+            .await?;
+
+        Ok(SongUsageReport {
+            church_id,
+            from,
+            to,
+            songs
+        })
+    }
+
+    /// Worship team of a church, listed to its members only
+    pub async fn list_worship_team(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid
+    ) -> AppResult<Vec<WorshipTeamMember>> {
+        let is_member = PgSongAccess::new(self.pool.clone())
+            .is_church_member(user_id, church_id)
+            .await?;
+        if !is_member {
+            return Err(AppError::forbidden(
+                "Only church members can see the worship team"
+            ));
+        }
+
+        PgWorshipTeam::new(self.pool.clone())
+            .list_members(church_id)
+            .await
+    }
+
+    pub async fn add_worship_team_member(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid,
+        member_id: Uuid
+    ) -> AppResult<()> {
+        self.ensure_can_manage_worship_team(user_id, church_id)
+            .await?;
+        PgWorshipTeam::new(self.pool.clone())
+            .add_member(church_id, member_id)
+            .await
+    }
+
+    pub async fn remove_worship_team_member(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid,
+        member_id: Uuid
+    ) -> AppResult<()> {
+        self.ensure_can_manage_worship_team(user_id, church_id)
+            .await?;
+        PgWorshipTeam::new(self.pool.clone())
+            .remove_member(church_id, member_id)
+            .await
+    }
+
+    async fn ensure_can_manage_worship_team(
+        &self,
+        user_id: Uuid,
+        church_id: Uuid
+    ) -> AppResult<()> {
+        let allowed = PgSongAccess::new(self.pool.clone())
+            .can_manage_worship_team(user_id, church_id)
+            .await?;
+
+        if !allowed {
+            return Err(AppError::forbidden(
+                "Only pastors and admins can manage the worship team"
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Duplicate song detection and merging.
+
+use masterror::prelude::*;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::PgSongDuplicates,
+    domain::{DuplicateSongs, MergeSong, SongMerge}
+};
+
+impl SongbookService {
+    /// Likely duplicate songs for editors to review
+    pub async fn list_duplicates(
+        &self,
+        user_id: Uuid,
+        songbook_id: Option<Uuid>,
+        min_score: f64,
+        limit: i64
+    ) -> AppResult<Vec<DuplicateSongs>> {
+        self.ensure_song_editor(user_id, "merge songs").await?;
+        self.find_duplicates(songbook_id, min_score, limit).await
+    }
+
+    /// Likely duplicate songs, scoring at least `min_score` out of 1
+    pub async fn find_duplicates(
+        &self,
+        songbook_id: Option<Uuid>,
+        min_score: f64,
+        limit: i64
+    ) -> AppResult<Vec<DuplicateSongs>> {
+        if !(0.0..=1.0).contains(&min_score) {
+            return Err(AppError::validation("min_score must be between 0 and 1"));
+        }
+        PgSongDuplicates::new(self.pool.clone())
+            .find_duplicates(songbook_id, min_score, limit.clamp(1, 500))
+            .await
+    }
+
+    /// Merge a duplicate into the song `keep`, on behalf of an editor
+    pub async fn merge_duplicate(
+        &self,
+        user_id: Uuid,
+        keep: Uuid,
+        merge: MergeSong
+    ) -> AppResult<SongMerge> {
+        self.ensure_song_editor(user_id, "merge songs").await?;
+        self.merge_songs(keep, merge.duplicate_id).await
+    }
+
+    /// Move the favorites, history and playlist items of `duplicate` onto
+    /// `keep` and delete `duplicate`.
+    ///
+    /// Only songs of one songbook are merged; the same song in another
+    /// songbook is a version of it and is related as a family instead.
+    pub async fn merge_songs(&self, keep: Uuid, duplicate: Uuid) -> AppResult<SongMerge> {
+        if keep == duplicate {
+            return Err(AppError::validation("Cannot merge a song into itself"));
+        }
+
+        let duplicates = PgSongDuplicates::new(self.pool.clone());
+        let (keep_songbook, duplicate_songbook) = duplicates.songbooks_of(keep, duplicate).await?;
+        if keep_songbook != duplicate_songbook {
+            return Err(AppError::validation(
+                "Songs from different songbooks are versions; relate them instead of merging"
+            ));
+        }
+
+        duplicates.merge(keep, duplicate).await
+    }
+}
@@ -0,0 +1,59 @@
+//! Song families: versions and translations of one song.
+
+use masterror::prelude::*;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgSongFamilies, PgSongRead},
+    domain::{LinkSongFamily, SongFamily}
+};
+
+impl SongbookService {
+    /// Family of a song with all its visible members
+    pub async fn get_song_family(
+        &self,
+        song_id: Uuid,
+        user_id: Option<Uuid>
+    ) -> AppResult<SongFamily> {
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(song_id, user_id)
+            .await?;
+
+        let families = PgSongFamilies::new(self.pool.clone());
+        let id = families
+            .family_of(song_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Song is not in a family"))?;
+
+        Ok(SongFamily {
+            id,
+            members: families.family_members(id, user_id).await?
+        })
+    }
+
+    /// Relate a song to another one as its version or translation
+    pub async fn link_song_family(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        link: LinkSongFamily
+    ) -> AppResult<SongFamily> {
+        link.validate(song_id)?;
+        self.ensure_song_editor(user_id, "relate versions and translations")
+            .await?;
+
+        let families = PgSongFamilies::new(self.pool.clone());
+        let id = families.link(song_id, &link).await?;
+        Ok(SongFamily {
+            id,
+            members: families.family_members(id, Some(user_id)).await?
+        })
+    }
+
+    pub async fn unlink_song_family(&self, user_id: Uuid, song_id: Uuid) -> AppResult<()> {
+        self.ensure_song_editor(user_id, "relate versions and translations")
+            .await?;
+        PgSongFamilies::new(self.pool.clone()).unlink(song_id).await
+    }
+}
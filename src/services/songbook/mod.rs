@@ -0,0 +1,95 @@
+//! Songbook service combining all song-related adapters, one module per
+//! feature.
+
+use std::sync::Arc;
+
+use masterror::prelude::*;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::adapters::{
+    postgres::{PgSongAccess, PgSongbookRead},
+    storage::AttachmentStorage
+};
+
+mod attachments;
+mod churches;
+mod duplicates;
+mod families;
+mod playlists;
+mod reports;
+mod scripture;
+mod setlists;
+mod songbooks;
+mod songs;
+mod submissions;
+mod tags;
+
+pub use setlists::PDF_FONT_ENV;
+
+/// Songbook service combining all song-related adapters
+#[derive(Clone)]
+pub struct SongbookService {
+    pool:    PgPool,
+    storage: Arc<dyn AttachmentStorage>
+}
+
+impl SongbookService {
+    /// Service keeping attachment files in `storage`
+    pub fn new(pool: PgPool, storage: Arc<dyn AttachmentStorage>) -> Self {
+        Self {
+            pool,
+            storage
+        }
+    }
+
+    /// Require the song editor role; `action` completes "Only song editors
+    /// can ..." in the error
+    async fn ensure_song_editor(&self, user_id: Uuid, action: &str) -> AppResult<()> {
+        let is_editor = PgSongAccess::new(self.pool.clone())
+            .is_song_editor(user_id)
+            .await?;
+        if !is_editor {
+            return Err(AppError::forbidden(format!(
+                "Only song editors can {action}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Require songbook management rights for a church (or the shared
+    /// catalog when `church_id` is `None`)
+    async fn ensure_can_manage(&self, user_id: Uuid, church_id: Option<Uuid>) -> AppResult<()> {
+        let allowed = PgSongAccess::new(self.pool.clone())
+            .can_manage_songbooks(user_id, church_id)
+            .await?;
+
+        if !allowed {
+            return Err(AppError::forbidden("Not allowed to manage this songbook"));
+        }
+        Ok(())
+    }
+
+    async fn ensure_can_manage_songbook(&self, user_id: Uuid, songbook_id: Uuid) -> AppResult<()> {
+        let church_id = PgSongbookRead::new(self.pool.clone())
+            .get_songbook_church(songbook_id)
+            .await?;
+        self.ensure_can_manage(user_id, church_id).await
+    }
+
+    /// Require management rights for `songbook_id`, or the song editor role
+    /// for songs outside any songbook
+    async fn ensure_can_manage_songs(
+        &self,
+        user_id: Uuid,
+        songbook_id: Option<Uuid>
+    ) -> AppResult<()> {
+        match songbook_id {
+            Some(songbook_id) => self.ensure_can_manage_songbook(user_id, songbook_id).await,
+            None => {
+                self.ensure_song_editor(user_id, "manage songs outside a songbook")
+                    .await
+            }
+        }
+    }
+}
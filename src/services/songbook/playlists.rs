@@ -0,0 +1,253 @@
+//! Playlists, their items and share links.
+
+use masterror::prelude::*;
+use revelation_songbook::{AddToPlaylist, CreatePlaylist, PlaylistItem, SongPlaylist};
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgPlaylistRepository, PgSongAccess},
+    domain::{
+        DuplicatePlaylist, PlaylistShareLink, SharedPlaylist, UpdatePlaylist, UpdatePlaylistItem
+    }
+};
+
+impl SongbookService {
+    pub async fn list_playlists(&self, user_id: Uuid) -> AppResult<Vec<SongPlaylist>> {
+        use revelation_songbook::ports::PlaylistRepository;
+        PgPlaylistRepository::new(self.pool.clone())
+            .list_playlists(user_id)
+            .await
+    }
+
+    pub async fn create_playlist(
+        &self,
+        user_id: Uuid,
+        playlist: CreatePlaylist
+    ) -> AppResult<SongPlaylist> {
+        use revelation_songbook::ports::PlaylistRepository;
+        if let Some(church_id) = playlist.church_id {
+            self.ensure_can_edit_setlists(user_id, church_id).await?;
+        }
+        PgPlaylistRepository::new(self.pool.clone())
+            .create_playlist(user_id, playlist)
+            .await
+    }
+
+    /// Playlist visible to the viewer, see [`Self::get_visible_playlist`]
+    pub async fn get_playlist(&self, id: Uuid, viewer: Option<Uuid>) -> AppResult<SongPlaylist> {
+        self.get_visible_playlist(id, viewer).await
+    }
+
+    /// Items of a playlist visible to the viewer
+    pub async fn get_playlist_items(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<PlaylistItem>> {
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        repository.get_visible_playlist(playlist_id, viewer).await?;
+        repository.list_items(playlist_id, viewer).await
+    }
+
+    pub async fn add_to_playlist(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        item: AddToPlaylist
+    ) -> AppResult<()> {
+        use revelation_songbook::ports::PlaylistRepository;
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+        PgPlaylistRepository::new(self.pool.clone())
+            .add_to_playlist(playlist_id, item)
+            .await
+    }
+
+    pub async fn remove_from_playlist(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        item_id: Uuid
+    ) -> AppResult<()> {
+        use revelation_songbook::ports::PlaylistRepository;
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+        PgPlaylistRepository::new(self.pool.clone())
+            .remove_from_playlist(playlist_id, item_id)
+            .await
+    }
+
+    pub async fn delete_playlist(&self, id: Uuid, user_id: Uuid) -> AppResult<()> {
+        use revelation_songbook::ports::PlaylistRepository;
+        PgPlaylistRepository::new(self.pool.clone())
+            .delete_playlist(id, user_id)
+            .await
+    }
+
+    /// Playlist visible to the viewer (owner, public, or church setlist of
+    /// the viewer's church)
+    pub async fn get_visible_playlist(
+        &self,
+        id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<SongPlaylist> {
+        PgPlaylistRepository::new(self.pool.clone())
+            .get_visible_playlist(id, viewer)
+            .await
+    }
+
+    /// Playlists are edited by their owner; church setlists also by the
+    /// church worship team
+    pub async fn can_edit_playlist(&self, user_id: Uuid, playlist_id: Uuid) -> AppResult<bool> {
+        let (owner, church_id) = PgPlaylistRepository::new(self.pool.clone())
+            .get_playlist_access(playlist_id)
+            .await?;
+
+        if owner == user_id {
+            return Ok(true);
+        }
+        match church_id {
+            Some(church_id) => {
+                PgSongAccess::new(self.pool.clone())
+                    .can_edit_church_setlists(user_id, church_id)
+                    .await
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub async fn ensure_can_edit_playlist(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid
+    ) -> AppResult<()> {
+        if !self.can_edit_playlist(user_id, playlist_id).await? {
+            return Err(AppError::forbidden("Not allowed to edit this playlist"));
+        }
+        Ok(())
+    }
+
+    async fn ensure_can_edit_setlists(&self, user_id: Uuid, church_id: Uuid) -> AppResult<()> {
+        let allowed = PgSongAccess::new(self.pool.clone())
+            .can_edit_church_setlists(user_id, church_id)
+            .await?;
+
+        if !allowed {
+            return Err(AppError::forbidden(
+                "Only the worship team can edit church setlists"
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn update_playlist(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        playlist: UpdatePlaylist
+    ) -> AppResult<SongPlaylist> {
+        use revelation_songbook::ports::PlaylistRepository;
+        playlist.validate()?;
+        self.ensure_can_edit_playlist(user_id, id).await?;
+
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        repository.update_playlist(id, &playlist).await?;
+        repository.get_playlist(id, user_id).await
+    }
+
+    pub async fn update_playlist_item(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        item_id: Uuid,
+        item: UpdatePlaylistItem
+    ) -> AppResult<Vec<PlaylistItem>> {
+        use revelation_songbook::ports::PlaylistRepository;
+        item.validate()?;
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        repository
+            .update_playlist_item(playlist_id, item_id, &item)
+            .await?;
+        repository.get_playlist_items(playlist_id, user_id).await
+    }
+
+    pub async fn reorder_playlist(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        item_ids: &[Uuid]
+    ) -> AppResult<Vec<PlaylistItem>> {
+        use revelation_songbook::ports::PlaylistRepository;
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        repository.reorder_playlist(playlist_id, item_ids).await?;
+        repository.get_playlist_items(playlist_id, user_id).await
+    }
+
+    /// Start a new playlist from an existing one (own or public)
+    pub async fn duplicate_playlist(
+        &self,
+        user_id: Uuid,
+        template_id: Uuid,
+        copy: DuplicatePlaylist
+    ) -> AppResult<SongPlaylist> {
+        use revelation_songbook::ports::PlaylistRepository;
+        copy.validate()?;
+
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        let (_, template_church) = repository.get_playlist_access(template_id).await?;
+        if let Some(church_id) = copy.church_id.or(template_church) {
+            self.ensure_can_edit_setlists(user_id, church_id).await?;
+        }
+
+        let id = repository
+            .duplicate_playlist(template_id, user_id, &copy)
+            .await?;
+        repository.get_playlist(id, user_id).await
+    }
+
+    /// Create (or return the existing) public read-only link
+    pub async fn share_playlist(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid
+    ) -> AppResult<PlaylistShareLink> {
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        let token = match repository.get_share_token(playlist_id).await? {
+            Some(token) => token,
+            None => {
+                let token = Uuid::new_v4().simple().to_string();
+                repository
+                    .set_share_token(playlist_id, Some(&token))
+                    .await?;
+                token
+            }
+        };
+
+        Ok(PlaylistShareLink {
+            token
+        })
+    }
+
+    pub async fn unshare_playlist(&self, user_id: Uuid, playlist_id: Uuid) -> AppResult<()> {
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+        PgPlaylistRepository::new(self.pool.clone())
+            .set_share_token(playlist_id, None)
+            .await
+    }
+
+    pub async fn get_shared_playlist(&self, token: &str) -> AppResult<SharedPlaylist> {
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        let playlist = repository.get_shared_playlist(token).await?;
+        let items = repository.list_items(playlist.id, None).await?;
+
+        Ok(SharedPlaylist {
+            playlist,
+            items
+        })
+    }
+}
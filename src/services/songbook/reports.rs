@@ -0,0 +1,130 @@
+//! Catalog quality reports for song editors.
+
+use std::pin::pin;
+
+use masterror::prelude::*;
+use revelation_songbook::SongSummary;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgSongReports, PgSongWrite},
+    domain::{KeyMismatch, MusicalKey, detect_key}
+};
+
+impl SongbookService {
+    /// Catalog songs without copyright information, for song editors
+    pub async fn list_missing_copyright(
+        &self,
+        user_id: Uuid,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<SongSummary>> {
+        self.ensure_song_editor(user_id, "review the catalog")
+            .await?;
+
+        PgSongReports::new(self.pool.clone())
+            .missing_copyright(songbook_id, limit, offset)
+            .await
+    }
+
+    /// Songs whose stated key disagrees with their chords, for song editors
+    pub async fn list_key_mismatches(
+        &self,
+        user_id: Uuid,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<KeyMismatch>> {
+        self.ensure_song_editor(user_id, "review the catalog")
+            .await?;
+
+        self.scan_key_mismatches(songbook_id, offset.max(0) as usize, limit.max(0) as usize)
+            .await
+    }
+
+    /// Songs with chords whose stated key is invalid or not the one the
+    /// chords point to. Enharmonic spellings such as `C#` and `Db` agree.
+    pub async fn find_key_mismatches(
+        &self,
+        songbook_id: Option<Uuid>
+    ) -> AppResult<Vec<KeyMismatch>> {
+        self.scan_key_mismatches(songbook_id, 0, usize::MAX).await
+    }
+
+    /// Key mismatches `offset..offset + limit` in catalog order; keys can
+    /// only be detected from the chords, so songs are read until the page
+    /// is full
+    async fn scan_key_mismatches(
+        &self,
+        songbook_id: Option<Uuid>,
+        offset: usize,
+        limit: usize
+    ) -> AppResult<Vec<KeyMismatch>> {
+        let reports = PgSongReports::new(self.pool.clone());
+        let mut songs = pin!(reports.songs_with_chords(songbook_id));
+
+        let mut skipped = 0;
+        let mut mismatches = Vec::new();
+        while mismatches.len() < limit
+            && let Some(song) = songs.next().await
+        {
+            let (song, content) = song?;
+            let Some(mismatch) = key_mismatch(song, &content) else {
+                continue;
+            };
+            if skipped < offset {
+                skipped += 1;
+            } else {
+                mismatches.push(mismatch);
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Set the missing keys of songs with chords to the detected ones and
+    /// return how many were set
+    pub async fn fill_missing_keys(&self, songbook_id: Option<Uuid>) -> AppResult<usize> {
+        let reports = PgSongReports::new(self.pool.clone());
+        let mut songs = pin!(reports.songs_with_chords(songbook_id));
+        let writer = PgSongWrite::new(self.pool.clone());
+
+        let mut filled = 0;
+        while let Some(song) = songs.next().await {
+            let (song, content) = song?;
+            if song.original_key.is_some() {
+                continue;
+            }
+            if let Some(key) = detect_key(&content)
+                && writer.fill_original_key(song.id, &key.to_string()).await?
+            {
+                filled += 1;
+            }
+        }
+        Ok(filled)
+    }
+}
+
+/// Mismatch of a song with a stated key, unless the key agrees with the
+/// chords or none can be detected
+fn key_mismatch(song: SongSummary, content: &str) -> Option<KeyMismatch> {
+    let stated_key = song.original_key.clone()?;
+    let detected = detect_key(content);
+    let invalid = match stated_key.parse::<MusicalKey>() {
+        Ok(stated) => {
+            if detected.as_ref().is_none_or(|d| d.is_enharmonic(&stated)) {
+                return None;
+            }
+            false
+        }
+        Err(_) => true
+    };
+    Some(KeyMismatch {
+        song,
+        stated_key,
+        detected_key: detected.map(|key| key.to_string()),
+        invalid
+    })
+}
@@ -0,0 +1,84 @@
+//! Scripture passages linked to songs.
+
+use masterror::prelude::*;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgSongRead, PgSongScripture},
+    domain::{AddSongScripture, ScriptureSong, ScriptureSuggestion, SongScripture}
+};
+
+impl SongbookService {
+    /// Scripture passages a song is based on
+    pub async fn list_song_scripture(
+        &self,
+        song_id: Uuid,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongScripture>> {
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(song_id, user_id)
+            .await?;
+        PgSongScripture::new(self.pool.clone())
+            .list_song_scripture(song_id)
+            .await
+    }
+
+    /// Link a passage to a song, returning all of its passages
+    pub async fn add_song_scripture(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        passage: AddSongScripture
+    ) -> AppResult<Vec<SongScripture>> {
+        passage.validate()?;
+        self.ensure_song_editor(user_id, "link Scripture passages")
+            .await?;
+
+        let scripture = PgSongScripture::new(self.pool.clone());
+        scripture.add_scripture(song_id, &passage, user_id).await?;
+        scripture.list_song_scripture(song_id).await
+    }
+
+    pub async fn remove_song_scripture(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        scripture_id: Uuid
+    ) -> AppResult<()> {
+        self.ensure_song_editor(user_id, "link Scripture passages")
+            .await?;
+        PgSongScripture::new(self.pool.clone())
+            .remove_scripture(song_id, scripture_id)
+            .await
+    }
+
+    /// Verses the lyrics of a song echo, for editors linking passages
+    pub async fn suggest_song_scripture(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        limit: i64
+    ) -> AppResult<Vec<ScriptureSuggestion>> {
+        self.ensure_song_editor(user_id, "link Scripture passages")
+            .await?;
+        PgSongRead::new(self.pool.clone())
+            .load_song(song_id, Some(user_id))
+            .await?;
+        PgSongScripture::new(self.pool.clone())
+            .suggest(song_id, limit.clamp(1, 50))
+            .await
+    }
+
+    /// Songs linked to passages of a Bible chapter
+    pub async fn list_chapter_songs(
+        &self,
+        book_id: i16,
+        chapter: i16,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<ScriptureSong>> {
+        PgSongScripture::new(self.pool.clone())
+            .chapter_songs(book_id, chapter, user_id)
+            .await
+    }
+}
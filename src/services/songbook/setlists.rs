@@ -0,0 +1,193 @@
+//! Projection slides, setlist exports and service passages.
+
+use std::collections::HashMap;
+
+use masterror::prelude::*;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgPlaylistPassages, PgPlaylistRepository, PgSongRead},
+    domain::{AddPlaylistPassage, PlaylistPassage, PlaylistSlides},
+    formats::{
+        Setlist, SetlistSong, Slide, SlideOptions, build_slides, pdf::EMBEDDED_FONT,
+        setlist_to_osz, setlist_to_pdf
+    }
+};
+
+/// Environment variable with a TrueType font replacing the embedded one in
+/// PDF setlists
+pub const PDF_FONT_ENV: &str = "SONG_PDF_FONT";
+
+impl SongbookService {
+    /// Projection slides for a song
+    pub async fn song_slides(
+        &self,
+        id: Uuid,
+        options: &SlideOptions,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<Slide>> {
+        let song = PgSongRead::new(self.pool.clone())
+            .load_visible_song(id, user_id)
+            .await?;
+        build_slides(&song, options)
+    }
+
+    /// Projection slides for every song of a playlist, in playlist order.
+    ///
+    /// A custom verse order names sections of a single song, so playlists
+    /// always use each song's own order. Songs the viewer may not see are
+    /// skipped.
+    pub async fn playlist_slides(
+        &self,
+        playlist_id: Uuid,
+        options: &SlideOptions,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<PlaylistSlides>> {
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        repository.get_visible_playlist(playlist_id, viewer).await?;
+        let items = repository.list_items(playlist_id, viewer).await?;
+
+        let options = SlideOptions {
+            verse_order: None,
+            ..options.clone()
+        };
+        let song_ids: Vec<Uuid> = items.iter().map(|item| item.song.id).collect();
+        let songs = PgSongRead::new(self.pool.clone())
+            .load_visible_songs(&song_ids, viewer)
+            .await?;
+
+        let mut result = Vec::with_capacity(items.len());
+        for item in items {
+            let Some(song) = songs.get(&item.song.id) else {
+                continue;
+            };
+            result.push(PlaylistSlides {
+                item_id:  item.id,
+                song_id:  song.id,
+                position: item.position,
+                title:    song.title.clone(),
+                notes:    item.notes,
+                slides:   build_slides(song, &options)?
+            });
+        }
+        Ok(result)
+    }
+
+    /// Playlist songs for export, each with its item's transposition and
+    /// notes
+    pub async fn get_setlist(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Setlist> {
+        let repository = PgPlaylistRepository::new(self.pool.clone());
+        let playlist = repository.get_visible_playlist(playlist_id, viewer).await?;
+        let items = repository.list_items(playlist_id, viewer).await?;
+
+        let reader = PgSongRead::new(self.pool.clone());
+        let mut songbooks: HashMap<Uuid, String> = HashMap::new();
+        let mut songs = Vec::with_capacity(items.len());
+        for item in items {
+            let song = reader.load_song(item.song.id, viewer).await?;
+
+            let songbook_name = match song.songbook_id {
+                Some(id) => match songbooks.get(&id) {
+                    Some(name) => Some(name.clone()),
+                    None => {
+                        let name = self.get_songbook(id).await?.name_ru;
+                        songbooks.insert(id, name.clone());
+                        Some(name)
+                    }
+                },
+                None => None
+            };
+
+            songs.push(SetlistSong {
+                item_id: item.id,
+                position: item.position,
+                song,
+                songbook_name,
+                transpose_semitones: item.transpose_semitones,
+                notes: item.notes
+            });
+        }
+
+        let passages = PgPlaylistPassages::new(self.pool.clone())
+            .list_passages(playlist_id)
+            .await?;
+
+        Ok(Setlist {
+            playlist,
+            songs,
+            passages
+        })
+    }
+
+    /// Printable PDF of a setlist, in the embedded font unless
+    /// `SONG_PDF_FONT` names another one
+    pub async fn export_setlist_pdf(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<u8>> {
+        let setlist = self.get_setlist(playlist_id, viewer).await?;
+
+        let Ok(path) = std::env::var(PDF_FONT_ENV) else {
+            return setlist_to_pdf(&setlist, EMBEDDED_FONT);
+        };
+        let font = tokio::fs::read(&path)
+            .await
+            .map_err(|e| AppError::internal(format!("Cannot read PDF font {path}: {e}")))?;
+
+        setlist_to_pdf(&setlist, &font)
+    }
+
+    /// OpenLP service file of a setlist
+    pub async fn export_setlist_osz(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<u8>> {
+        let setlist = self.get_setlist(playlist_id, viewer).await?;
+        setlist_to_osz(&setlist)
+    }
+
+    /// Bible passages of a visible playlist
+    pub async fn list_playlist_passages(
+        &self,
+        playlist_id: Uuid,
+        viewer: Option<Uuid>
+    ) -> AppResult<Vec<PlaylistPassage>> {
+        self.get_visible_playlist(playlist_id, viewer).await?;
+        PgPlaylistPassages::new(self.pool.clone())
+            .list_passages(playlist_id)
+            .await
+    }
+
+    pub async fn add_playlist_passage(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        passage: AddPlaylistPassage
+    ) -> AppResult<Vec<PlaylistPassage>> {
+        passage.validate()?;
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+
+        let passages = PgPlaylistPassages::new(self.pool.clone());
+        passages.add_passage(playlist_id, &passage).await?;
+        passages.list_passages(playlist_id).await
+    }
+
+    pub async fn remove_playlist_passage(
+        &self,
+        user_id: Uuid,
+        playlist_id: Uuid,
+        passage_id: Uuid
+    ) -> AppResult<()> {
+        self.ensure_can_edit_playlist(user_id, playlist_id).await?;
+        PgPlaylistPassages::new(self.pool.clone())
+            .remove_passage(playlist_id, passage_id)
+            .await
+    }
+}
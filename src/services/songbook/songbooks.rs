@@ -0,0 +1,155 @@
+//! Songbooks, their editions and edition numbering.
+
+use masterror::prelude::*;
+use revelation_songbook::{Songbook, SongbookEdition};
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{PgSongbookRead, PgSongbookWrite},
+    domain::{
+        CreateSongbook, CreateSongbookEdition, EditionNumber, UpdateSongbook,
+        UpdateSongbookEdition
+    }
+};
+
+impl SongbookService {
+    /// Songbooks visible to the viewer (public and own church songbooks)
+    pub async fn list_songbooks(&self, viewer: Option<Uuid>) -> AppResult<Vec<Songbook>> {
+        PgSongbookRead::new(self.pool.clone())
+            .list_visible_songbooks(viewer)
+            .await
+    }
+
+    /// Songbook by ID or code, hidden from viewers outside the owning church
+    pub async fn get_visible_songbook(
+        &self,
+        id_or_code: &str,
+        viewer: Option<Uuid>
+    ) -> AppResult<Songbook> {
+        PgSongbookRead::new(self.pool.clone())
+            .get_visible_songbook(id_or_code, viewer)
+            .await
+    }
+
+    pub async fn get_songbook(&self, id: Uuid) -> AppResult<Songbook> {
+        use revelation_songbook::ports::SongbookRead;
+        PgSongbookRead::new(self.pool.clone())
+            .get_songbook(id)
+            .await
+    }
+
+    pub async fn get_songbook_by_code(&self, code: &str) -> AppResult<Songbook> {
+        use revelation_songbook::ports::SongbookRead;
+        PgSongbookRead::new(self.pool.clone())
+            .get_songbook_by_code(code)
+            .await
+    }
+
+    pub async fn get_songbook_editions(
+        &self,
+        songbook_id: Uuid
+    ) -> AppResult<Vec<SongbookEdition>> {
+        use revelation_songbook::ports::SongbookRead;
+        PgSongbookRead::new(self.pool.clone())
+            .get_editions(songbook_id)
+            .await
+    }
+
+    pub async fn create_songbook(
+        &self,
+        user_id: Uuid,
+        songbook: CreateSongbook
+    ) -> AppResult<Songbook> {
+        songbook.validate()?;
+        self.ensure_can_manage(user_id, songbook.church_id).await?;
+
+        let id = PgSongbookWrite::new(self.pool.clone())
+            .create_songbook(&songbook)
+            .await?;
+        self.get_songbook(id).await
+    }
+
+    pub async fn update_songbook(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        songbook: UpdateSongbook
+    ) -> AppResult<Songbook> {
+        songbook.validate()?;
+        self.ensure_can_manage_songbook(user_id, id).await?;
+
+        PgSongbookWrite::new(self.pool.clone())
+            .update_songbook(id, &songbook)
+            .await?;
+        self.get_songbook(id).await
+    }
+
+    pub async fn delete_songbook(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        self.ensure_can_manage_songbook(user_id, id).await?;
+        PgSongbookWrite::new(self.pool.clone())
+            .delete_songbook(id)
+            .await
+    }
+
+    pub async fn create_edition(
+        &self,
+        user_id: Uuid,
+        songbook_id: Uuid,
+        edition: CreateSongbookEdition
+    ) -> AppResult<SongbookEdition> {
+        edition.validate()?;
+        self.ensure_can_manage_songbook(user_id, songbook_id)
+            .await?;
+
+        PgSongbookWrite::new(self.pool.clone())
+            .create_edition(songbook_id, &edition)
+            .await
+    }
+
+    pub async fn update_edition(
+        &self,
+        user_id: Uuid,
+        songbook_id: Uuid,
+        id: Uuid,
+        edition: UpdateSongbookEdition
+    ) -> AppResult<SongbookEdition> {
+        edition.validate()?;
+        self.ensure_can_manage_songbook(user_id, songbook_id)
+            .await?;
+
+        PgSongbookWrite::new(self.pool.clone())
+            .update_edition(songbook_id, id, &edition)
+            .await
+    }
+
+    pub async fn delete_edition(
+        &self,
+        user_id: Uuid,
+        songbook_id: Uuid,
+        id: Uuid
+    ) -> AppResult<()> {
+        self.ensure_can_manage_songbook(user_id, songbook_id)
+            .await?;
+
+        PgSongbookWrite::new(self.pool.clone())
+            .delete_edition(songbook_id, id)
+            .await
+    }
+
+    /// Replace the song numbering of an edition
+    pub async fn set_edition_numbers(
+        &self,
+        user_id: Uuid,
+        songbook_id: Uuid,
+        edition_id: Uuid,
+        numbers: Vec<EditionNumber>
+    ) -> AppResult<()> {
+        self.ensure_can_manage_songbook(user_id, songbook_id)
+            .await?;
+
+        PgSongbookWrite::new(self.pool.clone())
+            .set_edition_numbers(songbook_id, edition_id, &numbers)
+            .await
+    }
+}
@@ -0,0 +1,432 @@
+//! Song reading, search, popularity, writing and personal lists.
+
+use masterror::prelude::*;
+use revelation_songbook::{
+    CreateSong, Song, SongCategory, SongFilters, SongHistoryEntry, SongSearchResult, SongSummary,
+    UpdateSong
+};
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::{
+        PgSongAttachments, PgSongFamilies, PgSongFavorites, PgSongHistory, PgSongPopularity,
+        PgSongRead, PgSongRecommendations, PgSongSearch, PgSongWrite, PgSongbookRead
+    },
+    domain::{
+        PopularSong, PopularityOrder, SimilarSong, SongDetails, SongOrder, SongSearchParams,
+        SongSuggestion, detect_key, fill_missing_key, normalize_song_music,
+        normalize_update_music
+    },
+    formats::{OpenLyricsSong, from_openlyrics, to_openlyrics}
+};
+
+/// Largest number range returned at once
+const MAX_NUMBER_RANGE: i32 = 200;
+
+/// How often rolling song popularity is recomputed
+const POPULARITY_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+impl SongbookService {
+    /// Songs matching the filters and carrying all `tag_ids`; `order` takes
+    /// precedence over `filters.sort_by`
+    pub async fn list_songs(
+        &self,
+        filters: &SongFilters,
+        tag_ids: &[Uuid],
+        order: SongOrder,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        PgSongRead::new(self.pool.clone())
+            .list_songs_ordered(filters, tag_ids, order, user_id)
+            .await
+    }
+
+    /// Songs with the most activity by a popularity ordering, from the last
+    /// aggregate refresh
+    pub async fn list_popular(
+        &self,
+        order: PopularityOrder,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<PopularSong>> {
+        PgSongPopularity::new(self.pool.clone())
+            .list_popular(order, songbook_id, limit.clamp(1, 100), user_id)
+            .await
+    }
+
+    /// Songs like this one by categories, tags, key, tempo and how often
+    /// they are sung and viewed together
+    pub async fn similar_songs(
+        &self,
+        id: Uuid,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SimilarSong>> {
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(id, user_id)
+            .await?;
+
+        PgSongRecommendations::new(self.pool.clone())
+            .similar_to(&[id], limit.clamp(1, 50), user_id)
+            .await
+    }
+
+    /// Songs like the user's favorites; empty until they favorite a song
+    pub async fn recommended_songs(
+        &self,
+        user_id: Uuid,
+        limit: i64
+    ) -> AppResult<Vec<SimilarSong>> {
+        let recommendations = PgSongRecommendations::new(self.pool.clone());
+        let favorites = recommendations.favorite_ids(user_id).await?;
+        if favorites.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        recommendations
+            .similar_to(&favorites, limit.clamp(1, 50), Some(user_id))
+            .await
+    }
+
+    /// Recompute the rolling popularity aggregates
+    pub async fn refresh_popularity(&self) -> AppResult<()> {
+        PgSongPopularity::new(self.pool.clone()).refresh().await
+    }
+
+    /// Refresh the popularity aggregates every quarter of an hour
+    /// for the lifetime of the server
+    pub async fn refresh_popularity_periodically(self) {
+        let mut interval = tokio::time::interval(POPULARITY_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(error) = self.refresh_popularity().await {
+                tracing::warn!("Song popularity refresh failed: {error}");
+            }
+        }
+    }
+
+    /// Song for display with its versions and translations, counting a
+    /// view once per `viewer` and day
+    pub async fn get_song(
+        &self,
+        id: Uuid,
+        user_id: Option<Uuid>,
+        viewer: Option<&str>
+    ) -> AppResult<SongDetails> {
+        let song = PgSongRead::new(self.pool.clone())
+            .view_song(id, user_id, viewer)
+            .await?;
+        self.song_details(song, user_id).await
+    }
+
+    /// Split the other members of a song's family into versions in its
+    /// language and translations
+    async fn song_details(&self, song: Song, user_id: Option<Uuid>) -> AppResult<SongDetails> {
+        let families = PgSongFamilies::new(self.pool.clone());
+        let members = match families.family_of(song.id).await? {
+            Some(family_id) => families.family_members(family_id, user_id).await?,
+            None => Vec::new()
+        };
+
+        let language = members
+            .iter()
+            .find(|m| m.song.id == song.id)
+            .map(|m| m.language.clone());
+        let (versions, translations) = members
+            .into_iter()
+            .filter(|m| m.song.id != song.id)
+            .partition(|m| Some(&m.language) == language.as_ref());
+
+        let attachments = PgSongAttachments::new(self.pool.clone())
+            .list_attachments(song.id)
+            .await?;
+
+        Ok(SongDetails {
+            song,
+            versions,
+            translations,
+            attachments
+        })
+    }
+
+    /// Song without counting a view or writing history; songs in songbooks
+    /// hidden from the user are not found
+    pub async fn load_song(&self, id: Uuid, user_id: Option<Uuid>) -> AppResult<Song> {
+        PgSongRead::new(self.pool.clone())
+            .load_visible_song(id, user_id)
+            .await
+    }
+
+    /// Fail with not found when the song is in a songbook hidden from the user
+    pub async fn ensure_song_visible(&self, id: Uuid, user_id: Option<Uuid>) -> AppResult<()> {
+        PgSongRead::new(self.pool.clone())
+            .ensure_visible(id, user_id)
+            .await
+    }
+
+    /// Song by its number in a songbook, optionally in the numbering of an
+    /// edition given by ID or name. Looking a number up browses the songbook
+    /// and counts no view.
+    pub async fn get_song_by_number(
+        &self,
+        songbook_id: Uuid,
+        number: i32,
+        edition: Option<&str>,
+        user_id: Option<Uuid>
+    ) -> AppResult<SongDetails> {
+        let reader = PgSongRead::new(self.pool.clone());
+
+        let id = match edition {
+            Some(edition) => {
+                let edition_id = PgSongbookRead::new(self.pool.clone())
+                    .find_edition_id(songbook_id, edition)
+                    .await?;
+                reader
+                    .find_song_id_in_edition(songbook_id, edition_id, number)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::not_found(format!("No song number {number} in this edition"))
+                    })?
+            }
+            None => reader
+                .find_song_id(songbook_id, number)
+                .await?
+                .ok_or_else(|| {
+                    AppError::not_found(format!("No song number {number} in this songbook"))
+                })?
+        };
+
+        let mut song = reader.load_song(id, user_id).await?;
+        song.number = Some(number);
+        self.song_details(song, user_id).await
+    }
+
+    /// Songs numbered `from..=to` in a songbook, optionally in the numbering
+    /// of an edition given by ID or name
+    pub async fn list_songs_by_number(
+        &self,
+        songbook_id: Uuid,
+        from: i32,
+        to: i32,
+        edition: Option<&str>,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        if from > to || i64::from(to) - i64::from(from) >= i64::from(MAX_NUMBER_RANGE) {
+            return Err(AppError::validation(format!(
+                "Number range must be ascending and span at most {MAX_NUMBER_RANGE} songs"
+            )));
+        }
+
+        let edition_id = match edition {
+            Some(edition) => Some(
+                PgSongbookRead::new(self.pool.clone())
+                    .find_edition_id(songbook_id, edition)
+                    .await?
+            ),
+            None => None
+        };
+
+        PgSongRead::new(self.pool.clone())
+            .list_songs_by_number(songbook_id, from, to, edition_id, user_id)
+            .await
+    }
+
+    pub async fn search_songs(
+        &self,
+        params: &SongSearchParams,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSearchResult>> {
+        PgSongSearch::new(self.pool.clone())
+            .search(params, user_id)
+            .await
+    }
+
+    pub async fn suggest_songs(
+        &self,
+        prefix: &str,
+        songbook_id: Option<Uuid>,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSuggestion>> {
+        PgSongSearch::new(self.pool.clone())
+            .suggest(prefix, songbook_id, limit, user_id)
+            .await
+    }
+
+    pub async fn list_by_category(
+        &self,
+        category: SongCategory,
+        limit: i64,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        use revelation_songbook::ports::SongSearch;
+        PgSongSearch::new(self.pool.clone())
+            .list_by_category(category, limit, user_id)
+            .await
+    }
+
+    /// Create a song with a validated key, tempo and time signature; a
+    /// missing key is detected from the chords.
+    ///
+    /// Other users propose songs through submissions.
+    pub async fn create_song(&self, user_id: Uuid, song: CreateSong) -> AppResult<Song> {
+        self.ensure_can_manage_songs(user_id, song.songbook_id)
+            .await?;
+        self.insert_song(song).await
+    }
+
+    async fn insert_song(&self, mut song: CreateSong) -> AppResult<Song> {
+        use revelation_songbook::ports::SongWrite;
+        normalize_song_music(&mut song)?;
+        fill_missing_key(&mut song);
+        PgSongWrite::new(self.pool.clone()).create_song(song).await
+    }
+
+    /// Update a song, detecting its key from the chords when it has none
+    pub async fn update_song(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        mut song: UpdateSong
+    ) -> AppResult<Song> {
+        use revelation_songbook::ports::SongWrite;
+        self.ensure_can_manage_song(user_id, id).await?;
+        normalize_update_music(&mut song)?;
+        let writer = PgSongWrite::new(self.pool.clone());
+        let mut updated = writer.update_song(id, song).await?;
+
+        if updated.original_key.is_none()
+            && let Some(key) = detect_key(&updated.content)
+        {
+            let key = key.to_string();
+            writer.fill_original_key(id, &key).await?;
+            updated.original_key = Some(key);
+        }
+        Ok(updated)
+    }
+
+    /// Delete a song together with its attachment files
+    pub async fn delete_song(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        use revelation_songbook::ports::SongWrite;
+        self.ensure_can_manage_song(user_id, id).await?;
+        let keys = PgSongAttachments::new(self.pool.clone())
+            .song_storage_keys(id)
+            .await?;
+        PgSongWrite::new(self.pool.clone()).delete_song(id).await?;
+        for key in keys {
+            self.delete_stored_file(&key).await;
+        }
+        Ok(())
+    }
+
+    async fn ensure_can_manage_song(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        let songbook_id = PgSongRead::new(self.pool.clone())
+            .get_song_songbook(id)
+            .await?;
+        self.ensure_can_manage_songs(user_id, songbook_id).await
+    }
+
+    pub async fn list_song_ids(&self, songbook_id: Option<Uuid>) -> AppResult<Vec<Uuid>> {
+        PgSongRead::new(self.pool.clone())
+            .list_song_ids(songbook_id)
+            .await
+    }
+
+    /// Export a song as an OpenLyrics document
+    pub async fn export_openlyrics(&self, id: Uuid) -> AppResult<String> {
+        let song = PgSongRead::new(self.pool.clone())
+            .load_song(id, None)
+            .await?;
+
+        let songbook_name = match song.songbook_id {
+            Some(songbook_id) => Some(self.get_songbook(songbook_id).await?.name_ru),
+            None => None
+        };
+
+        Ok(to_openlyrics(&song, songbook_name.as_deref()))
+    }
+
+    /// Import an OpenLyrics document as a new song.
+    ///
+    /// Without an explicit `songbook_id` the songbook named in the document
+    /// is matched by code or name; the number is dropped if none matches.
+    /// Importing into a songbook takes the right to manage it, importing
+    /// outside of one takes the song editor role.
+    pub async fn import_openlyrics(
+        &self,
+        user_id: Uuid,
+        xml: &str,
+        songbook_id: Option<Uuid>
+    ) -> AppResult<Song> {
+        let song = self.read_openlyrics(xml, songbook_id).await?;
+        self.create_song(user_id, song).await
+    }
+
+    /// Import an OpenLyrics document without access checks, for the
+    /// command-line tools
+    pub async fn load_openlyrics(&self, xml: &str, songbook_id: Option<Uuid>) -> AppResult<Song> {
+        let song = self.read_openlyrics(xml, songbook_id).await?;
+        self.insert_song(song).await
+    }
+
+    async fn read_openlyrics(
+        &self,
+        xml: &str,
+        songbook_id: Option<Uuid>
+    ) -> AppResult<CreateSong> {
+        let OpenLyricsSong {
+            mut song,
+            songbook_name
+        } = from_openlyrics(xml)?;
+
+        song.songbook_id = match (songbook_id, songbook_name) {
+            (Some(id), _) => Some(id),
+            (None, Some(name)) => {
+                PgSongbookRead::new(self.pool.clone())
+                    .find_songbook_id(&name)
+                    .await?
+            }
+            (None, None) => None
+        };
+
+        if song.songbook_id.is_none() {
+            song.number = None;
+        }
+
+        Ok(song)
+    }
+
+    pub async fn list_favorites(&self, user_id: Uuid) -> AppResult<Vec<SongSummary>> {
+        use revelation_songbook::ports::SongFavorites;
+        PgSongFavorites::new(self.pool.clone())
+            .list_favorites(user_id)
+            .await
+    }
+
+    pub async fn add_favorite(&self, user_id: Uuid, song_id: Uuid) -> AppResult<()> {
+        use revelation_songbook::ports::SongFavorites;
+        PgSongFavorites::new(self.pool.clone())
+            .add_favorite(user_id, song_id)
+            .await
+    }
+
+    pub async fn remove_favorite(&self, user_id: Uuid, song_id: Uuid) -> AppResult<()> {
+        use revelation_songbook::ports::SongFavorites;
+        PgSongFavorites::new(self.pool.clone())
+            .remove_favorite(user_id, song_id)
+            .await
+    }
+
+    pub async fn list_recent(
+        &self,
+        user_id: Uuid,
+        limit: i64
+    ) -> AppResult<Vec<SongHistoryEntry>> {
+        use revelation_songbook::ports::SongHistory;
+        PgSongHistory::new(self.pool.clone())
+            .list_recent(user_id, limit)
+            .await
+    }
+}
@@ -0,0 +1,246 @@
+//! Song suggestions and their review.
+
+use masterror::prelude::*;
+use revelation_songbook::CreateSong;
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::PgSongSubmissions,
+    domain::{
+        CreateSongSubmission, NewNotification, NotificationKind, PublishSongSubmission,
+        ReviewDecision, ReviewSongSubmission, SongSubmission, SubmissionStatus,
+        UpdateSongSubmission, fill_missing_key, normalize_song_music
+    },
+    services::NotificationService
+};
+
+impl SongbookService {
+    /// Save a song suggestion as a draft
+    pub async fn create_submission(
+        &self,
+        user_id: Uuid,
+        submission: CreateSongSubmission
+    ) -> AppResult<SongSubmission> {
+        submission.validate()?;
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let id = submissions.create_submission(user_id, &submission).await?;
+        submissions.get_submission(id).await
+    }
+
+    pub async fn list_my_submissions(&self, user_id: Uuid) -> AppResult<Vec<SongSubmission>> {
+        PgSongSubmissions::new(self.pool.clone())
+            .list_by_submitter(user_id)
+            .await
+    }
+
+    /// Submission for its submitter or a song editor
+    pub async fn get_submission(&self, user_id: Uuid, id: Uuid) -> AppResult<SongSubmission> {
+        let submission = PgSongSubmissions::new(self.pool.clone())
+            .get_submission(id)
+            .await?;
+        if submission.submitter_id != user_id {
+            self.ensure_song_editor(user_id, "review submissions")
+                .await?;
+        }
+        Ok(submission)
+    }
+
+    pub async fn update_submission(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        changes: UpdateSongSubmission
+    ) -> AppResult<SongSubmission> {
+        changes.validate()?;
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let submission = submissions.get_submission(id).await?;
+        ensure_submitter(&submission, user_id)?;
+        if !submission.status.is_editable() {
+            return Err(AppError::conflict(
+                "Only draft or rejected submissions can be changed"
+            ));
+        }
+
+        submissions.update_submission(id, &changes).await?;
+        submissions.get_submission(id).await
+    }
+
+    /// Send a draft to the editors' review queue
+    pub async fn submit_submission(&self, user_id: Uuid, id: Uuid) -> AppResult<SongSubmission> {
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let submission = submissions.get_submission(id).await?;
+        ensure_submitter(&submission, user_id)?;
+        if submission.status != SubmissionStatus::Draft {
+            return Err(AppError::conflict(
+                "Only drafts can be submitted for review"
+            ));
+        }
+
+        submissions.submit(id).await?;
+        submissions.get_submission(id).await
+    }
+
+    pub async fn delete_submission(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let submission = submissions.get_submission(id).await?;
+        ensure_submitter(&submission, user_id)?;
+        if !submission.status.is_editable() {
+            return Err(AppError::conflict(
+                "Only draft or rejected submissions can be deleted"
+            ));
+        }
+
+        submissions.delete_submission(id).await
+    }
+
+    /// Submissions in a status for song editors, pending ones by default
+    pub async fn list_submission_queue(
+        &self,
+        user_id: Uuid,
+        status: Option<SubmissionStatus>,
+        limit: i64,
+        offset: i64
+    ) -> AppResult<Vec<SongSubmission>> {
+        self.ensure_song_editor(user_id, "review submissions")
+            .await?;
+        PgSongSubmissions::new(self.pool.clone())
+            .list_by_status(
+                status.unwrap_or(SubmissionStatus::Pending),
+                limit.clamp(1, 200),
+                offset.max(0)
+            )
+            .await
+    }
+
+    /// Approve or reject a pending submission and tell the submitter
+    pub async fn review_submission(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        review: ReviewSongSubmission
+    ) -> AppResult<SongSubmission> {
+        review.validate()?;
+        self.ensure_song_editor(user_id, "review submissions")
+            .await?;
+
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let submission = submissions.get_submission(id).await?;
+        if submission.status != SubmissionStatus::Pending {
+            return Err(AppError::conflict(
+                "Only pending submissions can be reviewed"
+            ));
+        }
+
+        let comment = review
+            .comment
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty());
+        let (status, kind, title) = match review.decision {
+            ReviewDecision::Approve => (
+                SubmissionStatus::Approved,
+                NotificationKind::SongSubmissionApproved,
+                format!("«{}» was approved", submission.title)
+            ),
+            ReviewDecision::Reject => (
+                SubmissionStatus::Rejected,
+                NotificationKind::SongSubmissionRejected,
+                format!("«{}» was not accepted", submission.title)
+            )
+        };
+
+        submissions.review(id, user_id, status, comment).await?;
+        self.notify_submitter(&submission, kind, title, comment.map(str::to_string), None)
+            .await;
+        submissions.get_submission(id).await
+    }
+
+    /// Create the catalog song of an approved submission in a songbook and
+    /// tell the submitter
+    pub async fn publish_submission(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        publish: PublishSongSubmission
+    ) -> AppResult<SongSubmission> {
+        self.ensure_song_editor(user_id, "review submissions")
+            .await?;
+        self.ensure_can_manage_songbook(user_id, publish.songbook_id)
+            .await?;
+
+        let submissions = PgSongSubmissions::new(self.pool.clone());
+        let submission = submissions.get_submission(id).await?;
+        if submission.status != SubmissionStatus::Approved {
+            return Err(AppError::conflict(
+                "Only approved submissions can be published"
+            ));
+        }
+
+        let mut song = CreateSong {
+            songbook_id:    Some(publish.songbook_id),
+            number:         publish.number,
+            title:          submission.title.clone(),
+            title_alt:      None,
+            author_lyrics:  submission.author_lyrics.clone(),
+            author_music:   submission.author_music.clone(),
+            translator:     submission.translator.clone(),
+            year_written:   None,
+            copyright:      None,
+            original_key:   submission.original_key.clone(),
+            tempo:          None,
+            time_signature: None,
+            content:        submission.content.clone(),
+            source_url:     submission.source_url.clone(),
+            categories:     Vec::new(),
+            tag_ids:        Vec::new()
+        };
+        normalize_song_music(&mut song)?;
+        fill_missing_key(&mut song);
+
+        let song_id = submissions.publish(id, &song).await?;
+        self.notify_submitter(
+            &submission,
+            NotificationKind::SongSubmissionPublished,
+            format!("«{}» is now in the songbook", submission.title),
+            None,
+            Some(format!("/songs/{song_id}"))
+        )
+        .await;
+        submissions.get_submission(id).await
+    }
+
+    /// The review outcome is already saved, so a failed notification is
+    /// logged rather than failing the request
+    async fn notify_submitter(
+        &self,
+        submission: &SongSubmission,
+        kind: NotificationKind,
+        title: String,
+        body: Option<String>,
+        link: Option<String>
+    ) {
+        let notification = NewNotification {
+            user_id: submission.submitter_id,
+            kind,
+            title,
+            body,
+            link: link.or_else(|| Some(format!("/songs/submissions/{}", submission.id)))
+        };
+        if let Err(error) = NotificationService::new(self.pool.clone())
+            .notify(&notification)
+            .await
+        {
+            tracing::warn!("Submission {} notification failed: {error}", submission.id);
+        }
+    }
+}
+
+fn ensure_submitter(submission: &SongSubmission, user_id: Uuid) -> AppResult<()> {
+    if submission.submitter_id != user_id {
+        return Err(AppError::forbidden(
+            "Only the submitter can change a submission"
+        ));
+    }
+    Ok(())
+}
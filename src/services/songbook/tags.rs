@@ -0,0 +1,106 @@
+//! Song tags.
+
+use masterror::prelude::*;
+use revelation_songbook::{SongFilters, SongSummary, SongTag};
+use uuid::Uuid;
+
+use super::SongbookService;
+use crate::{
+    adapters::postgres::PgSongTags,
+    domain::{CreateSongTag, MergeSongTags, SongOrder, UpdateSongTag}
+};
+
+impl SongbookService {
+    pub async fn list_tags(&self) -> AppResult<Vec<SongTag>> {
+        use revelation_songbook::ports::SongTags;
+        PgSongTags::new(self.pool.clone()).list_tags().await
+    }
+
+    /// Tag IDs for tag names or IDs given in a filter
+    pub async fn resolve_tags(&self, names: &[String]) -> AppResult<Vec<Uuid>> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        PgSongTags::new(self.pool.clone()).resolve_tags(names).await
+    }
+
+    /// Songs with a tag
+    pub async fn list_tag_songs(
+        &self,
+        tag_id: Uuid,
+        filters: &SongFilters,
+        order: SongOrder,
+        user_id: Option<Uuid>
+    ) -> AppResult<Vec<SongSummary>> {
+        PgSongTags::new(self.pool.clone()).get_tag(tag_id).await?;
+        self.list_songs(filters, &[tag_id], order, user_id).await
+    }
+
+    pub async fn create_tag(&self, user_id: Uuid, tag: CreateSongTag) -> AppResult<SongTag> {
+        tag.validate()?;
+        self.ensure_song_editor(user_id, "manage tags").await?;
+        PgSongTags::new(self.pool.clone()).create_tag(&tag).await
+    }
+
+    pub async fn update_tag(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        tag: UpdateSongTag
+    ) -> AppResult<SongTag> {
+        tag.validate()?;
+        self.ensure_song_editor(user_id, "manage tags").await?;
+        PgSongTags::new(self.pool.clone())
+            .update_tag(id, &tag)
+            .await
+    }
+
+    /// Merge tag `id` into `merge.into`, returning the remaining tag
+    pub async fn merge_tags(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        merge: MergeSongTags
+    ) -> AppResult<SongTag> {
+        if merge.into == id {
+            return Err(AppError::validation("Cannot merge a tag into itself"));
+        }
+        self.ensure_song_editor(user_id, "manage tags").await?;
+
+        let tags = PgSongTags::new(self.pool.clone());
+        tags.get_tag(id).await?;
+        tags.get_tag(merge.into).await?;
+        tags.merge_tags(id, merge.into).await
+    }
+
+    pub async fn delete_tag(&self, user_id: Uuid, id: Uuid) -> AppResult<()> {
+        self.ensure_song_editor(user_id, "manage tags").await?;
+        PgSongTags::new(self.pool.clone()).delete_tag(id).await
+    }
+
+    /// Tag a song, returning its tags
+    pub async fn add_song_tag(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        tag_id: Uuid
+    ) -> AppResult<Vec<SongTag>> {
+        self.ensure_song_editor(user_id, "manage tags").await?;
+        let tags = PgSongTags::new(self.pool.clone());
+        tags.add_song_tag(song_id, tag_id).await?;
+        tags.song_tags(song_id).await
+    }
+
+    /// Untag a song, returning its remaining tags
+    pub async fn remove_song_tag(
+        &self,
+        user_id: Uuid,
+        song_id: Uuid,
+        tag_id: Uuid
+    ) -> AppResult<Vec<SongTag>> {
+        self.ensure_song_editor(user_id, "manage tags").await?;
+        let tags = PgSongTags::new(self.pool.clone());
+        tags.remove_song_tag(song_id, tag_id).await?;
+        tags.song_tags(song_id).await
+    }
+}
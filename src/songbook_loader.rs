@@ -0,0 +1,436 @@
+//! Songbook data loader from ChordPro files, CSV manifests and edition
+//! numberings.
+
+use std::path::{Path, PathBuf};
+
+use masterror::prelude::*;
+use revelation_songbook::{CreateSong, Song, SongCategory, ports::SongWrite};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    adapters::postgres::{PgSongRead, PgSongWrite, PgSongbookRead, PgSongbookWrite},
+    domain::{EditionNumber, fill_missing_key, normalize_song_music},
+    formats::ChordProDocument
+};
+
+/// File extensions recognized as song files
+const SONG_EXTENSIONS: &[&str] = &["cho", "chordpro", "chopro", "crd", "pro", "txt"];
+
+/// Row of a CSV manifest.
+///
+/// `file` points to a ChordPro or text file relative to the manifest.
+/// Non-empty columns override metadata found in the file.
+#[derive(Debug, Deserialize)]
+struct ManifestRow {
+    number:         Option<i32>,
+    title:          Option<String>,
+    file:           PathBuf,
+    title_alt:      Option<String>,
+    author_lyrics:  Option<String>,
+    author_music:   Option<String>,
+    translator:     Option<String>,
+    year_written:   Option<i16>,
+    copyright:      Option<String>,
+    original_key:   Option<String>,
+    tempo:          Option<i32>,
+    time_signature: Option<String>,
+    /// Category codes separated by `;`
+    categories:     Option<String>
+}
+
+/// Row of an edition numbering CSV
+#[derive(Debug, Deserialize)]
+struct EditionNumberRow {
+    edition_number: i32,
+    number:         i32
+}
+
+/// Result of importing a single song
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Created,
+    Updated,
+    Skipped
+}
+
+/// Loads songs into a songbook, matching existing songs by number
+pub struct SongbookLoader {
+    pool:    PgPool,
+    dry_run: bool
+}
+
+impl SongbookLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            dry_run: false
+        }
+    }
+
+    /// Report what would change without writing to the database
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Load every song file of a directory into the songbook.
+    ///
+    /// The song number is taken from the leading digits of the file name
+    /// (`012 Title.cho`), the title from `{title}` or the rest of the name.
+    pub async fn load_dir(
+        &self,
+        songbook_code: &str,
+        dir: impl AsRef<Path>
+    ) -> AppResult<ImportStats> {
+        let songbook_id = self.songbook_id(songbook_code).await?;
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir.as_ref())
+            .map_err(|e| AppError::internal(format!("Failed to read directory: {e}")))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_song_file(path))
+            .collect();
+        files.sort();
+
+        let mut stats = ImportStats::default();
+
+        for path in files {
+            let content = read_song_file(&path)?;
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let (number, name) = split_number(&stem);
+
+            let mut song = song_from_chordpro(content, songbook_id);
+            song.number = number;
+            if song.title.is_empty() {
+                song.title = name.to_string();
+            }
+
+            self.load_song(song, &path, &mut stats).await?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Load songs listed in a CSV manifest into the songbook.
+    ///
+    /// Expected columns: `number`, `title`, `file`, and optionally
+    /// `title_alt`, `author_lyrics`, `author_music`, `translator`,
+    /// `year_written`, `copyright`, `original_key`, `tempo`,
+    /// `time_signature`, `categories`.
+    pub async fn load_manifest(
+        &self,
+        songbook_code: &str,
+        path: impl AsRef<Path>
+    ) -> AppResult<ImportStats> {
+        let songbook_id = self.songbook_id(songbook_code).await?;
+        let base = path.as_ref().parent().unwrap_or(Path::new("."));
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path.as_ref())
+            .map_err(|e| AppError::internal(format!("Failed to read manifest: {e}")))?;
+
+        let mut stats = ImportStats::default();
+
+        for row in reader.deserialize::<ManifestRow>() {
+            let row = row.map_err(|e| AppError::internal(format!("Invalid manifest row: {e}")))?;
+            let file = base.join(&row.file);
+            let content = read_song_file(&file)?;
+
+            let mut song = song_from_chordpro(content, songbook_id);
+            apply_manifest_row(&mut song, row)?;
+
+            self.load_song(song, &file, &mut stats).await?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Load the numbering of a songbook edition from a CSV file with
+    /// `edition_number,number` columns, where `number` is the current number.
+    ///
+    /// Returns the number of mapped songs.
+    pub async fn load_edition_numbers(
+        &self,
+        songbook_code: &str,
+        edition: &str,
+        path: impl AsRef<Path>
+    ) -> AppResult<usize> {
+        let songbook_id = self.songbook_id(songbook_code).await?;
+        let edition_id = PgSongbookRead::new(self.pool.clone())
+            .find_edition_id(songbook_id, edition)
+            .await?;
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path.as_ref())
+            .map_err(|e| AppError::internal(format!("Failed to read numbering: {e}")))?;
+
+        let songs = PgSongRead::new(self.pool.clone());
+        let mut numbers = Vec::new();
+
+        for row in reader.deserialize::<EditionNumberRow>() {
+            let row =
+                row.map_err(|e| AppError::internal(format!("Invalid numbering row: {e}")))?;
+
+            match songs.find_song_id(songbook_id, row.number).await? {
+                Some(song_id) => numbers.push(EditionNumber {
+                    number: row.edition_number,
+                    song_id
+                }),
+                None => tracing::warn!("Skipping #{}: no song with this number", row.number)
+            }
+        }
+
+        if !self.dry_run {
+            PgSongbookWrite::new(self.pool.clone())
+                .set_edition_numbers(songbook_id, edition_id, &numbers)
+                .await?;
+        }
+
+        Ok(numbers.len())
+    }
+
+    async fn songbook_id(&self, code: &str) -> AppResult<Uuid> {
+        use revelation_songbook::ports::SongbookRead;
+        let songbook = PgSongbookRead::new(self.pool.clone())
+            .get_songbook_by_code(code)
+            .await?;
+        Ok(songbook.id)
+    }
+
+    async fn load_song(
+        &self,
+        mut song: CreateSong,
+        path: &Path,
+        stats: &mut ImportStats
+    ) -> AppResult<()> {
+        if song.title.is_empty() || song.content.trim().is_empty() {
+            tracing::warn!("Skipping {:?}: missing title or lyrics", path);
+            stats.skipped += 1;
+            return Ok(());
+        }
+
+        let Some(number) = song.number else {
+            tracing::warn!("Skipping {:?}: missing song number", path);
+            stats.skipped += 1;
+            return Ok(());
+        };
+
+        if let Err(error) = normalize_song_music(&mut song) {
+            tracing::warn!("Skipping {:?}: {}", path, error);
+            stats.skipped += 1;
+            return Ok(());
+        }
+
+        match self.import_song(song).await? {
+            Outcome::Created => {
+                tracing::info!("Created #{} from {:?}", number, path);
+                stats.created += 1;
+            }
+            Outcome::Updated => {
+                tracing::info!("Updated #{} from {:?}", number, path);
+                stats.updated += 1;
+            }
+            Outcome::Skipped => {
+                tracing::debug!("Unchanged #{} from {:?}", number, path);
+                stats.skipped += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Songs keep their stated key; a key is detected from the chords only
+    /// when neither the import nor the existing song has one
+    async fn import_song(&self, mut song: CreateSong) -> AppResult<Outcome> {
+        let (Some(songbook_id), Some(number)) = (song.songbook_id, song.number) else {
+            return Ok(Outcome::Skipped);
+        };
+
+        let reader = PgSongRead::new(self.pool.clone());
+        let writer = PgSongWrite::new(self.pool.clone());
+
+        let Some(id) = reader.find_song_id(songbook_id, number).await? else {
+            fill_missing_key(&mut song);
+            if !self.dry_run {
+                writer.create_song(song).await?;
+            }
+            return Ok(Outcome::Created);
+        };
+
+        let existing = reader.load_song(id, None).await?;
+        let mut merged = merge(&existing, song);
+        fill_missing_key(&mut merged);
+
+        if is_unchanged(&existing, &merged) {
+            return Ok(Outcome::Skipped);
+        }
+
+        if !self.dry_run {
+            writer.replace_song(id, &merged).await?;
+        }
+
+        Ok(Outcome::Updated)
+    }
+}
+
+/// Statistics from songbook import operation
+#[derive(Debug, Default)]
+pub struct ImportStats {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize
+}
+
+impl std::fmt::Display for ImportStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Created {} songs, updated {}, skipped {}",
+            self.created, self.updated, self.skipped
+        )
+    }
+}
+
+fn is_song_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        SONG_EXTENSIONS
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known))
+    })
+}
+
+fn read_song_file(path: &Path) -> AppResult<String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::internal(format!("Failed to read {}: {e}", path.display())))?;
+
+    // Strip UTF-8 BOM if present
+    Ok(content
+        .strip_prefix('\u{feff}')
+        .map(str::to_string)
+        .unwrap_or(content))
+}
+
+/// Split a leading song number off a file name (`012 - Title` -> 12, `Title`)
+fn split_number(stem: &str) -> (Option<i32>, &str) {
+    let digits = stem.len() - stem.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let number = stem[..digits].parse().ok();
+    let name = stem[digits..]
+        .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '.' | '-' | '_' | ')'));
+
+    (number, name.trim())
+}
+
+/// Build song data from ChordPro content and its metadata directives
+fn song_from_chordpro(content: String, songbook_id: Uuid) -> CreateSong {
+    let doc = ChordProDocument::parse(&content);
+
+    CreateSong {
+        songbook_id: Some(songbook_id),
+        number: None,
+        title: doc.title.unwrap_or_default(),
+        title_alt: doc.subtitle,
+        author_lyrics: doc.lyricist.or(doc.artist),
+        author_music: doc.composer,
+        translator: None,
+        year_written: doc.year.and_then(|y| y.trim().parse().ok()),
+        copyright: doc.copyright,
+        original_key: doc.key,
+        tempo: doc.tempo.and_then(|t| t.trim().parse().ok()),
+        time_signature: doc.time,
+        content,
+        source_url: None,
+        categories: Vec::new(),
+        tag_ids: Vec::new()
+    }
+}
+
+fn apply_manifest_row(song: &mut CreateSong, row: ManifestRow) -> AppResult<()> {
+    song.number = row.number;
+    song.title = row
+        .title
+        .filter(|t| !t.is_empty())
+        .unwrap_or(std::mem::take(&mut song.title));
+
+    let fields = [
+        (&mut song.title_alt, row.title_alt),
+        (&mut song.author_lyrics, row.author_lyrics),
+        (&mut song.author_music, row.author_music),
+        (&mut song.translator, row.translator),
+        (&mut song.copyright, row.copyright),
+        (&mut song.original_key, row.original_key),
+        (&mut song.time_signature, row.time_signature)
+    ];
+    for (field, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            *field = Some(value);
+        }
+    }
+
+    song.year_written = row.year_written.or(song.year_written);
+    song.tempo = row.tempo.or(song.tempo);
+
+    if let Some(categories) = row.categories {
+        song.categories = categories
+            .split(';')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(|c| {
+                serde_json::from_value::<SongCategory>(serde_json::Value::String(c.to_string()))
+                    .map_err(|_| AppError::validation(format!("Unknown song category: {c}")))
+            })
+            .collect::<AppResult<_>>()?;
+    }
+
+    Ok(())
+}
+
+/// Fill metadata missing from the imported song with existing values
+fn merge(existing: &Song, song: CreateSong) -> CreateSong {
+    CreateSong {
+        songbook_id:    song.songbook_id,
+        number:         song.number,
+        title:          song.title,
+        title_alt:      song.title_alt.or_else(|| existing.title_alt.clone()),
+        author_lyrics:  song
+            .author_lyrics
+            .or_else(|| existing.author_lyrics.clone()),
+        author_music:   song.author_music.or_else(|| existing.author_music.clone()),
+        translator:     song.translator.or_else(|| existing.translator.clone()),
+        year_written:   song.year_written.or(existing.year_written),
+        copyright:      song.copyright.or_else(|| existing.copyright.clone()),
+        original_key:   song.original_key.or_else(|| existing.original_key.clone()),
+        tempo:          song.tempo.or(existing.tempo),
+        time_signature: song
+            .time_signature
+            .or_else(|| existing.time_signature.clone()),
+        content:        song.content,
+        source_url:     song.source_url,
+        categories:     song.categories,
+        tag_ids:        song.tag_ids
+    }
+}
+
+fn is_unchanged(existing: &Song, song: &CreateSong) -> bool {
+    existing.title == song.title
+        && existing.title_alt == song.title_alt
+        && existing.author_lyrics == song.author_lyrics
+        && existing.author_music == song.author_music
+        && existing.translator == song.translator
+        && existing.year_written == song.year_written
+        && existing.copyright == song.copyright
+        && existing.original_key == song.original_key
+        && existing.tempo == song.tempo
+        && existing.time_signature == song.time_signature
+        && existing.content == song.content
+        && (song.categories.is_empty() || same_categories(&existing.categories, &song.categories))
+}
+
+fn same_categories(a: &[SongCategory], b: &[SongCategory]) -> bool {
+    a.len() == b.len() && a.iter().all(|c| b.contains(c))
+}
@@ -1,19 +1,36 @@
-use revelation_server::{BibleService, SongbookService};
+use std::sync::Arc;
+
+use revelation_server::{
+    BibleService, LiveHub, NotificationService, SongbookService,
+    adapters::storage::AttachmentStorage
+};
 use sqlx::PgPool;
 
+use crate::middleware::ViewerKeys;
+
 /// Application state shared across all handlers
 #[derive(Clone)]
 pub struct AppState {
-    pub pool:  PgPool,
-    pub bible: BibleService,
-    pub songs: SongbookService
+    pub pool:          PgPool,
+    pub bible:         BibleService,
+    pub songs:         SongbookService,
+    pub live:          LiveHub,
+    pub notifications: NotificationService,
+    pub viewer_keys:   ViewerKeys
 }
 
 impl AppState {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(
+        pool: PgPool,
+        viewer_keys: ViewerKeys,
+        attachments: Arc<dyn AttachmentStorage>
+    ) -> Self {
         Self {
             bible: BibleService::new(pool.clone()),
-            songs: SongbookService::new(pool.clone()),
+            songs: SongbookService::new(pool.clone(), attachments),
+            live: LiveHub::new(pool.clone()),
+            notifications: NotificationService::new(pool.clone()),
+            viewer_keys,
             pool
         }
     }
@@ -0,0 +1,172 @@
+//! Strong's lexicon and tagged text loader.
+
+use std::{collections::HashMap, path::Path};
+
+use masterror::prelude::*;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    adapters::postgres::PgStrongs,
+    domain::{OriginalLanguage, StrongsEntry, StrongsNumber, parse_tagged_verse}
+};
+
+/// Lexicon entry in the format of the public-domain Open Scriptures Strong's
+/// dictionaries
+#[derive(Debug, Deserialize)]
+struct LexiconEntry {
+    lemma:       String,
+    xlit:        Option<String>,
+    translit:    Option<String>,
+    pron:        Option<String>,
+    strongs_def: Option<String>,
+    kjv_def:     Option<String>
+}
+
+/// Loads Strong's lexicon entries and word tags into the database
+pub struct StrongsLoader {
+    pool: PgPool
+}
+
+impl StrongsLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool
+        }
+    }
+
+    /// Load a lexicon: a JSON object of entries keyed by Strong's number, as
+    /// in `strongs-hebrew-dictionary.js` and `strongs-greek-dictionary.js`.
+    /// A JavaScript wrapper around the object is skipped.
+    pub async fn load_lexicon(&self, path: impl AsRef<Path>) -> AppResult<StrongsStats> {
+        let content = read_file(path.as_ref())?;
+        let json = match (content.find('{'), content.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &content[start..=end],
+            _ => return Err(AppError::internal("Lexicon file has no JSON object"))
+        };
+        let lexicon: HashMap<String, LexiconEntry> = serde_json::from_str(json)
+            .map_err(|e| AppError::internal(format!("Failed to parse JSON: {e}")))?;
+
+        let mut stats = StrongsStats::default();
+        let mut entries = Vec::with_capacity(lexicon.len());
+        for (number, entry) in lexicon {
+            let number = match number.parse::<StrongsNumber>() {
+                Ok(number) => number,
+                Err(e) => {
+                    tracing::warn!("Skipping lexicon entry {number}: {e}");
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+            entries.push(StrongsEntry {
+                number:          number.to_string(),
+                language:        number.language(),
+                lemma:           entry.lemma,
+                transliteration: entry.xlit.or(entry.translit),
+                pronunciation:   entry.pron,
+                definition:      entry.strongs_def.unwrap_or_default().trim().to_string(),
+                usage:           entry.kjv_def.map(|usage| usage.trim().to_string())
+            });
+        }
+
+        stats.entries = PgStrongs::new(self.pool.clone())
+            .upsert_entries(&entries)
+            .await?;
+        Ok(stats)
+    }
+
+    /// Load word tags from a tab-separated file of `book_id<TAB>chapter<TAB>
+    /// verse<TAB>tagged text` lines, replacing the tags of every verse in it.
+    /// See [`parse_tagged_verse`] for the tag syntax.
+    pub async fn load_tagged_text(&self, path: impl AsRef<Path>) -> AppResult<StrongsStats> {
+        let content = read_file(path.as_ref())?;
+        let strongs = PgStrongs::new(self.pool.clone());
+        let mut stats = StrongsStats::default();
+
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((book_id, chapter, verse, text)) = parse_line(line) else {
+                tracing::warn!(
+                    "Skipping line {}: expected book_id, chapter, verse and text",
+                    line_no + 1
+                );
+                stats.skipped += 1;
+                continue;
+            };
+            let tags = match parse_tagged_verse(text, OriginalLanguage::of_book(book_id)) {
+                Ok(tags) => tags,
+                Err(e) => {
+                    tracing::warn!("Skipping line {}: {e}", line_no + 1);
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+
+            stats.tags += strongs
+                .replace_verse_tags(book_id, chapter, verse, &tags)
+                .await?;
+            stats.verses += 1;
+        }
+        Ok(stats)
+    }
+}
+
+fn read_file(path: &Path) -> AppResult<String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::internal(format!("Failed to read file: {e}")))?;
+    // Strip UTF-8 BOM if present
+    Ok(content
+        .strip_prefix('\u{feff}')
+        .map(str::to_string)
+        .unwrap_or(content))
+}
+
+fn parse_line(line: &str) -> Option<(i16, i16, i16, &str)> {
+    let mut fields = line.splitn(4, '\t');
+    let book_id = fields.next()?.trim().parse().ok()?;
+    let chapter = fields.next()?.trim().parse().ok()?;
+    let verse = fields.next()?.trim().parse().ok()?;
+    Some((book_id, chapter, verse, fields.next()?))
+}
+
+/// Statistics from a Strong's import
+#[derive(Debug, Default)]
+pub struct StrongsStats {
+    pub entries: u64,
+    pub verses:  usize,
+    pub tags:    u64,
+    pub skipped: usize
+}
+
+impl std::fmt::Display for StrongsStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Loaded {} lexicon entries, {} tags in {} verses ({} skipped)",
+            self.entries, self.tags, self.verses, self.skipped
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tab_separated_lines() {
+        assert_eq!(
+            parse_line("1\t1\t1\tВ начале<H7225> сотворил<H1254>"),
+            Some((1, 1, 1, "В начале<H7225> сотворил<H1254>"))
+        );
+        assert_eq!(
+            parse_line(" 43 \t3\t16\tИбо так<G3779>\tвозлюбил"),
+            Some((43, 3, 16, "Ибо так<G3779>\tвозлюбил"))
+        );
+        for line in ["1\t1\tВ начале", "Быт\t1\t1\tВ начале", "1 1 1 В начале"]
+        {
+            assert_eq!(parse_line(line), None, "{line:?}");
+        }
+    }
+}